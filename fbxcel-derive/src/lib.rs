@@ -0,0 +1,594 @@
+//! Derive macros for [`fbxcel`](https://crates.io/crates/fbxcel).
+//!
+//! Use these macros through `fbxcel` with `derive` feature enabled, rather
+//! than depending on this crate directly.
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+#![warn(clippy::missing_docs_in_private_items)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    ext::IdentExt, parse::ParseStream, parse_macro_input, spanned::Spanned, Attribute, Data,
+    DeriveInput, Fields, Ident, LitBool, LitInt, LitStr, Path, Token,
+};
+
+/// Derives an attribute loader for the type.
+///
+/// The derive macro generates a loader type named `{Type}Loader`, which
+/// implements `fbxcel::pull_parser::v7400::LoadAttribute` with the annotated
+/// type as `Output`.
+///
+/// # Supported types
+///
+/// * Newtype structs (tuple structs with a single field).
+///     + `#[fbxcel(...)]` on the type lists the accepted attribute types.
+/// * Enums with newtype variants and unit variants.
+///     + `#[fbxcel(...)]` on a newtype variant lists the accepted attribute
+///       types.
+///     + `#[fbxcel(...)]` on a unit variant lists the accepted attribute
+///       values, such as `string = "ByPolygon"` or `i32 = 0`.
+///     + Variants without `#[fbxcel(...)]` are never loaded.
+///
+/// Accepted attribute types are: `bool`, `i16`, `i32`, `i64`, `f32`, `f64`,
+/// `seq_bool`, `seq_i32`, `seq_i64`, `seq_f32`, `seq_f64`, `binary`, and
+/// `string`.
+/// Each of them implements `load_{type}` method of the loader.
+/// If `binary` is accepted but `string` is not, the loader also implements
+/// `expects_binary()` to return `true`, so that base64 strings in FBX ASCII
+/// are loaded as binaries.
+/// Values are converted into the field type by `From::from()`, and arrays,
+/// binaries, and strings are passed as `Vec<_>`, `Vec<u8>`, and `String`.
+///
+/// If an enum has both unit variants and a newtype variant for the same
+/// attribute type, the newtype variant receives values which do not match
+/// any of the unit variants.
+///
+/// # Type attributes
+///
+/// * `#[fbxcel(loader = "Name")]` changes the name of the loader type.
+/// * `#[fbxcel(expecting = "description")]` overrides the return value of
+///   `LoadAttribute::expecting()`.
+/// * `#[fbxcel(crate = "path")]` changes the path to `fbxcel` crate.
+///
+/// # Examples
+///
+/// ```
+/// use fbxcel::pull_parser::v7400::LoadAttribute;
+///
+/// #[derive(Debug, PartialEq, LoadAttribute)]
+/// enum MappingMode {
+///     #[fbxcel(string = "ByPolygonVertex")]
+///     ByPolygonVertex,
+///     #[fbxcel(string = "ByVertex", string = "ByVertice")]
+///     ByVertex,
+///     #[fbxcel(string)]
+///     Other(String),
+/// }
+///
+/// #[derive(Debug, PartialEq, LoadAttribute)]
+/// #[fbxcel(i32, i64)]
+/// struct ObjectId(i64);
+///
+/// # fn main() -> Result<(), fbxcel::pull_parser::Error> {
+/// assert_eq!(
+///     MappingModeLoader.load_string(&b"ByVertice"[..], 9)?,
+///     MappingMode::ByVertex
+/// );
+/// assert_eq!(
+///     MappingModeLoader.load_string(&b"AllSame"[..], 7)?,
+///     MappingMode::Other("AllSame".to_owned())
+/// );
+/// assert!(MappingModeLoader.load_i32(0).is_err());
+/// assert_eq!(ObjectIdLoader.load_i32(42)?, ObjectId(42));
+/// # Ok(())
+/// # }
+/// ```
+#[proc_macro_derive(LoadAttribute, attributes(fbxcel))]
+pub fn derive_load_attribute(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Attribute types accepted by the loader.
+const ATTRIBUTE_TYPES: &[AttributeKind] = &[
+    AttributeKind::Primitive("bool", "boolean"),
+    AttributeKind::Primitive("i16", "i16"),
+    AttributeKind::Primitive("i32", "i32"),
+    AttributeKind::Primitive("i64", "i64"),
+    AttributeKind::Primitive("f32", "f32"),
+    AttributeKind::Primitive("f64", "f64"),
+    AttributeKind::Array("bool", "boolean array"),
+    AttributeKind::Array("i32", "i32 array"),
+    AttributeKind::Array("i64", "i64 array"),
+    AttributeKind::Array("f32", "f32 array"),
+    AttributeKind::Array("f64", "f64 array"),
+    AttributeKind::Binary,
+    AttributeKind::String,
+];
+
+/// Attribute type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AttributeKind {
+    /// Primitive type with the type name and the description.
+    Primitive(&'static str, &'static str),
+    /// Array type with the element type name and the description.
+    Array(&'static str, &'static str),
+    /// Binary.
+    Binary,
+    /// String.
+    String,
+}
+
+impl AttributeKind {
+    /// Returns the attribute type for the keyword.
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        ATTRIBUTE_TYPES
+            .iter()
+            .copied()
+            .find(|kind| kind.keyword() == keyword)
+    }
+
+    /// Returns the keyword in `#[fbxcel(...)]`.
+    fn keyword(self) -> String {
+        match self {
+            AttributeKind::Primitive(ty, _) => ty.to_owned(),
+            AttributeKind::Array(ty, _) => format!("seq_{}", ty),
+            AttributeKind::Binary => "binary".to_owned(),
+            AttributeKind::String => "string".to_owned(),
+        }
+    }
+
+    /// Returns the description used in error messages.
+    fn description(self) -> &'static str {
+        match self {
+            AttributeKind::Primitive(_, desc) | AttributeKind::Array(_, desc) => desc,
+            AttributeKind::Binary => "binary",
+            AttributeKind::String => "string",
+        }
+    }
+
+    /// Returns whether unit variants can be matched to values of the type.
+    fn is_matchable(self) -> bool {
+        match self {
+            AttributeKind::Primitive(ty, _) => !ty.starts_with('f'),
+            AttributeKind::String => true,
+            AttributeKind::Array(..) | AttributeKind::Binary => false,
+        }
+    }
+}
+
+/// Options on the type.
+struct TypeOptions {
+    /// Loader type name.
+    loader: Option<Ident>,
+    /// Description of the expected value.
+    expecting: Option<LitStr>,
+    /// Path to `fbxcel` crate.
+    krate: Option<Path>,
+    /// Accepted types for newtype structs.
+    types: Vec<(AttributeKind, Span)>,
+}
+
+/// Accepted value of a unit variant.
+struct UnitValue {
+    /// Attribute type.
+    kind: AttributeKind,
+    /// Pattern to match.
+    pattern: TokenStream2,
+    /// Description of the value.
+    description: String,
+}
+
+/// Loadable target.
+enum Target {
+    /// Newtype struct or newtype variant with accepted types.
+    Newtype {
+        /// Constructor path.
+        ctor: TokenStream2,
+        /// Accepted types.
+        types: Vec<(AttributeKind, Span)>,
+    },
+    /// Unit variant with accepted values.
+    Unit {
+        /// Constructor path.
+        ctor: TokenStream2,
+        /// Accepted values.
+        values: Vec<UnitValue>,
+    },
+}
+
+/// Generates the loader type and the implementation.
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() || input.generics.where_clause.is_some() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "`LoadAttribute` cannot be derived for generic types",
+        ));
+    }
+
+    let options = parse_type_options(&input.attrs)?;
+    let ty = &input.ident;
+    let targets = collect_targets(input, &options)?;
+    let krate = options
+        .krate
+        .as_ref()
+        .map_or_else(|| quote!(::fbxcel), |path| quote!(#path));
+
+    let mut methods = Vec::new();
+    let mut descriptions = Vec::new();
+    let mut accepts_binary = false;
+    let mut accepts_string = false;
+    for &kind in ATTRIBUTE_TYPES {
+        let newtype = newtype_for_kind(&targets, kind)?;
+        let units = targets
+            .iter()
+            .filter_map(|target| match target {
+                Target::Unit { ctor, values } => Some(
+                    values
+                        .iter()
+                        .filter(|value| value.kind == kind)
+                        .map(move |value| (ctor, value)),
+                ),
+                Target::Newtype { .. } => None,
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        check_duplicate_values(&units)?;
+        if newtype.is_none() && units.is_empty() {
+            continue;
+        }
+        if newtype.is_some() {
+            descriptions.push(kind.description().to_owned());
+        } else {
+            descriptions.extend(units.iter().map(|(_, value)| value.description.clone()));
+        }
+        match kind {
+            AttributeKind::Binary => accepts_binary = true,
+            AttributeKind::String => accepts_string = true,
+            _ => {}
+        }
+        methods.push(generate_method(&krate, kind, newtype, &units));
+    }
+    // Base64 strings in FBX ASCII are decoded only if strings are not
+    // accepted.
+    if accepts_binary && !accepts_string {
+        methods.push(quote! {
+            fn expects_binary(&self) -> bool {
+                true
+            }
+        });
+    }
+    if methods.is_empty() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "no attribute types are accepted; specify them by `#[fbxcel(...)]`",
+        ));
+    }
+
+    let expecting = match &options.expecting {
+        Some(expecting) => expecting.value(),
+        None => join_descriptions(&descriptions),
+    };
+    let loader = options
+        .loader
+        .unwrap_or_else(|| format_ident!("{}Loader", ty));
+    let vis = &input.vis;
+    let loader_doc = format!("Attribute loader for [`{}`].", ty);
+
+    Ok(quote! {
+        #[doc = #loader_doc]
+        #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #vis struct #loader;
+
+        impl #krate::pull_parser::v7400::LoadAttribute for #loader {
+            type Output = #ty;
+
+            fn expecting(&self) -> ::std::string::String {
+                ::std::string::String::from(#expecting)
+            }
+
+            #(#methods)*
+        }
+    })
+}
+
+/// Parses `#[fbxcel(...)]` on the type.
+fn parse_type_options(attrs: &[Attribute]) -> syn::Result<TypeOptions> {
+    let mut options = TypeOptions {
+        loader: None,
+        expecting: None,
+        krate: None,
+        types: Vec::new(),
+    };
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("fbxcel")) {
+        parse_nested(attr, |name, input| {
+            if name == "loader" {
+                let name: LitStr = parse_value(input)?;
+                options.loader = Some(name.parse()?);
+            } else if name == "expecting" {
+                options.expecting = Some(parse_value(input)?);
+            } else if name == "crate" {
+                let path: LitStr = parse_value(input)?;
+                options.krate = Some(path.parse()?);
+            } else {
+                options.types.push(parse_type_keyword(name, input)?);
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+/// Parses comma-separated `name` or `name = value` items in the attribute.
+///
+/// The callback receives the name, and parses `= value` from the input if
+/// necessary.
+fn parse_nested<F>(attr: &Attribute, mut f: F) -> syn::Result<()>
+where
+    F: FnMut(&Ident, ParseStream<'_>) -> syn::Result<()>,
+{
+    attr.parse_args_with(|input: ParseStream<'_>| {
+        while !input.is_empty() {
+            // Keywords such as `crate` are also allowed as names.
+            let name = Ident::parse_any(input)?;
+            f(&name, input)?;
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(())
+    })
+}
+
+/// Parses `= value`.
+fn parse_value<T: syn::parse::Parse>(input: ParseStream<'_>) -> syn::Result<T> {
+    input.parse::<Token![=]>()?;
+    input.parse()
+}
+
+/// Parses an attribute type keyword.
+fn parse_type_keyword(name: &Ident, input: ParseStream<'_>) -> syn::Result<(AttributeKind, Span)> {
+    let kind = AttributeKind::from_keyword(&name.to_string())
+        .ok_or_else(|| syn::Error::new(name.span(), "unknown attribute type"))?;
+    if input.peek(Token![=]) {
+        return Err(syn::Error::new(
+            name.span(),
+            "values are only allowed for unit variants",
+        ));
+    }
+    Ok((kind, name.span()))
+}
+
+/// Collects loadable structs and variants.
+fn collect_targets(input: &DeriveInput, options: &TypeOptions) -> syn::Result<Vec<Target>> {
+    let ty = &input.ident;
+    match &input.data {
+        Data::Struct(data) => {
+            if !matches!(&data.fields, Fields::Unnamed(fields) if fields.unnamed.len() == 1) {
+                return Err(syn::Error::new(
+                    ty.span(),
+                    "`LoadAttribute` can be derived only for newtype structs and enums",
+                ));
+            }
+            Ok(vec![Target::Newtype {
+                ctor: quote!(#ty),
+                types: options.types.clone(),
+            }])
+        }
+        Data::Enum(data) => {
+            if let Some((_, span)) = options.types.first() {
+                return Err(syn::Error::new(
+                    *span,
+                    "attribute types of enums should be specified on variants",
+                ));
+            }
+            let mut targets = Vec::new();
+            for variant in &data.variants {
+                let attrs = variant
+                    .attrs
+                    .iter()
+                    .filter(|attr| attr.path.is_ident("fbxcel"))
+                    .collect::<Vec<_>>();
+                if attrs.is_empty() {
+                    continue;
+                }
+                let var = &variant.ident;
+                let ctor = quote!(#ty::#var);
+                match &variant.fields {
+                    Fields::Unit => {
+                        let mut values = Vec::new();
+                        for attr in attrs {
+                            parse_nested(attr, |name, input| {
+                                values.push(parse_unit_value(name, input)?);
+                                Ok(())
+                            })?;
+                        }
+                        targets.push(Target::Unit { ctor, values });
+                    }
+                    Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                        let mut types = Vec::new();
+                        for attr in attrs {
+                            parse_nested(attr, |name, input| {
+                                types.push(parse_type_keyword(name, input)?);
+                                Ok(())
+                            })?;
+                        }
+                        targets.push(Target::Newtype { ctor, types });
+                    }
+                    _ => {
+                        return Err(syn::Error::new(
+                            var.span(),
+                            "only unit variants and newtype variants can be loaded",
+                        ))
+                    }
+                }
+            }
+            Ok(targets)
+        }
+        Data::Union(_) => Err(syn::Error::new(
+            ty.span(),
+            "`LoadAttribute` cannot be derived for unions",
+        )),
+    }
+}
+
+/// Parses an accepted value of a unit variant.
+fn parse_unit_value(name: &Ident, input: ParseStream<'_>) -> syn::Result<UnitValue> {
+    let kind = AttributeKind::from_keyword(&name.to_string())
+        .filter(|kind| kind.is_matchable())
+        .ok_or_else(|| {
+            syn::Error::new(
+                name.span(),
+                "expected one of `bool`, `i16`, `i32`, `i64`, or `string` with a value",
+            )
+        })?;
+    input.parse::<Token![=]>()?;
+    let (pattern, value) = match kind {
+        AttributeKind::String => {
+            let lit: LitStr = input.parse()?;
+            (quote!(#lit), format!("{:?}", lit.value()))
+        }
+        AttributeKind::Primitive("bool", _) => {
+            let lit: LitBool = input.parse()?;
+            (quote!(#lit), lit.value.to_string())
+        }
+        _ => {
+            let neg: Option<Token![-]> = input.parse()?;
+            let lit: LitInt = input.parse()?;
+            let sign = if neg.is_some() { "-" } else { "" };
+            (
+                quote!(#neg #lit),
+                format!("{}{}", sign, lit.base10_digits()),
+            )
+        }
+    };
+    Ok(UnitValue {
+        kind,
+        pattern,
+        description: format!("{} {}", kind.description(), value),
+    })
+}
+
+/// Returns the newtype target which accepts the attribute type, if exists.
+fn newtype_for_kind(targets: &[Target], kind: AttributeKind) -> syn::Result<Option<&TokenStream2>> {
+    let mut found = None;
+    for target in targets {
+        if let Target::Newtype { ctor, types } = target {
+            if let Some((_, span)) = types.iter().find(|(ty, _)| *ty == kind) {
+                if found.is_some() {
+                    return Err(syn::Error::new(
+                        *span,
+                        format!("`{}` is accepted by multiple variants", kind.keyword()),
+                    ));
+                }
+                found = Some(ctor);
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Checks that no values are accepted by multiple variants.
+fn check_duplicate_values(units: &[(&TokenStream2, &UnitValue)]) -> syn::Result<()> {
+    for (i, (_, value)) in units.iter().enumerate() {
+        if units[..i]
+            .iter()
+            .any(|(_, prev)| prev.description == value.description)
+        {
+            return Err(syn::Error::new(
+                value.pattern.span(),
+                format!("{} is accepted by multiple variants", value.description),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Joins descriptions as "a, b, or c".
+fn join_descriptions(descriptions: &[String]) -> String {
+    match descriptions {
+        [] => String::new(),
+        [single] => single.clone(),
+        [first, second] => format!("{} or {}", first, second),
+        [init @ .., last] => format!("{}, or {}", init.join(", "), last),
+    }
+}
+
+/// Generates a `load_*` method.
+fn generate_method(
+    krate: &TokenStream2,
+    kind: AttributeKind,
+    newtype: Option<&TokenStream2>,
+    units: &[(&TokenStream2, &UnitValue)],
+) -> TokenStream2 {
+    let method = format_ident!("load_{}", kind.keyword());
+    let result = quote!(#krate::pull_parser::Result<Self::Output>);
+    let arms = units.iter().map(|(ctor, value)| {
+        let pattern = &value.pattern;
+        quote!(#pattern => ::std::result::Result::Ok(#ctor),)
+    });
+    let description = kind.description();
+    let fallback = match newtype {
+        Some(ctor) => quote! {
+            ::std::result::Result::Ok(#ctor(::std::convert::From::from(v)))
+        },
+        None => quote! {
+            ::std::result::Result::Err(
+                #krate::pull_parser::error::DataError::UnexpectedAttribute(
+                    #krate::pull_parser::v7400::LoadAttribute::expecting(&self),
+                    ::std::format!("{} {:?}", #description, v),
+                )
+                .into(),
+            )
+        },
+    };
+
+    match kind {
+        AttributeKind::Primitive(ty, _) => {
+            let ty = Ident::new(ty, Span::call_site());
+            quote! {
+                fn #method(self, v: #ty) -> #result {
+                    match v {
+                        #(#arms)*
+                        _ => #fallback,
+                    }
+                }
+            }
+        }
+        AttributeKind::Array(ty, _) => {
+            let ty = Ident::new(ty, Span::call_site());
+            quote! {
+                fn #method(
+                    self,
+                    iter: impl ::std::iter::Iterator<Item = #krate::pull_parser::Result<#ty>>,
+                    _: usize,
+                ) -> #result {
+                    let v = iter.collect::<#krate::pull_parser::Result<::std::vec::Vec<#ty>>>()?;
+                    #fallback
+                }
+            }
+        }
+        AttributeKind::Binary => quote! {
+            fn #method(self, mut reader: impl ::std::io::Read, len: u64) -> #result {
+                let mut v = ::std::vec::Vec::with_capacity(len as usize);
+                ::std::io::Read::read_to_end(&mut reader, &mut v)?;
+                #fallback
+            }
+        },
+        AttributeKind::String => quote! {
+            fn #method(self, mut reader: impl ::std::io::Read, len: u64) -> #result {
+                let mut v = ::std::string::String::with_capacity(len as usize);
+                ::std::io::Read::read_to_string(&mut reader, &mut v)?;
+                match v.as_str() {
+                    #(#arms)*
+                    _ => #fallback,
+                }
+            }
+        },
+    }
+}
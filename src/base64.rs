@@ -0,0 +1,93 @@
+//! Base64 encoding (RFC 4648, with padding).
+
+/// Base64 alphabet.
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes the given bytes into base64 string.
+#[cfg(any(feature = "tree", feature = "writer"))]
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity((bytes.len() + 2) / 3 * 4);
+    for chunk in bytes.chunks(3) {
+        let b0 = u32::from(chunk[0]);
+        let b1 = chunk.get(1).copied().map_or(0, u32::from);
+        let b2 = chunk.get(2).copied().map_or(0, u32::from);
+        let v = (b0 << 16) | (b1 << 8) | b2;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (v >> (18 - 6 * i)) & 0x3f;
+                s.push(char::from(ALPHABET[index as usize]));
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+/// Decodes the given base64 string.
+///
+/// Returns `None` if the string is not a valid padded base64 string.
+pub(crate) fn decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.as_bytes();
+    if s.len() % 4 != 0 {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(s.len() / 4 * 3);
+    for (chunk_index, chunk) in s.chunks(4).enumerate() {
+        let is_last = (chunk_index + 1) * 4 == s.len();
+        let num_padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if num_padding > 2 || (num_padding != 0 && !is_last) {
+            return None;
+        }
+        let mut v = 0u32;
+        for &c in &chunk[..(4 - num_padding)] {
+            let index = ALPHABET.iter().position(|&a| a == c)?;
+            v = (v << 6) | index as u32;
+        }
+        v <<= 6 * num_padding as u32;
+        let decoded = [(v >> 16) as u8, (v >> 8) as u8, v as u8];
+        bytes.extend_from_slice(&decoded[..(3 - num_padding)]);
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(feature = "tree", feature = "writer"))]
+    #[test]
+    fn encode_rfc4648() {
+        let cases: &[(&[u8], &str)] = &[
+            (b"", ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"foobar", "Zm9vYmFy"),
+        ];
+        for &(input, expected) in cases {
+            assert_eq!(encode(input), expected);
+        }
+    }
+
+    #[test]
+    fn decode_rfc4648() {
+        let cases: &[(&str, &[u8])] = &[
+            ("", b""),
+            ("Zg==", b"f"),
+            ("Zm8=", b"fo"),
+            ("Zm9v", b"foo"),
+            ("Zm9vYg==", b"foob"),
+            ("Zm9vYmE=", b"fooba"),
+            ("Zm9vYmFy", b"foobar"),
+        ];
+        for &(input, expected) in cases {
+            assert_eq!(decode(input).as_deref(), Some(expected));
+        }
+        for &invalid in &["Zg", "Zg=a", "Z===", "Zg==Zg==", "Zm9*"] {
+            assert_eq!(decode(invalid), None, "input={:?}", invalid);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Command line arguments.
+
+use std::{ffi::OsString, str::FromStr};
+
+use crate::{Result, UsageError};
+
+/// Command line argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Arg {
+    /// Option (such as `--max-depth`), without leading `--`.
+    Option(String),
+    /// Positional argument.
+    Positional(OsString),
+}
+
+/// Command line arguments.
+#[derive(Debug)]
+pub(crate) struct Args {
+    /// Rest of the arguments.
+    args: std::vec::IntoIter<OsString>,
+    /// Value given to the last option in `--name=value` form.
+    inline_value: Option<OsString>,
+    /// Whether `--` is already seen.
+    options_end: bool,
+}
+
+impl Args {
+    /// Creates a new `Args` from the command line arguments of the process.
+    pub(crate) fn from_env() -> Self {
+        Self {
+            args: std::env::args_os().skip(1).collect::<Vec<_>>().into_iter(),
+            inline_value: None,
+            options_end: false,
+        }
+    }
+
+    /// Returns the next argument.
+    pub(crate) fn next(&mut self) -> Result<Option<Arg>> {
+        if let Some(value) = self.inline_value.take() {
+            return Err(UsageError::new(format!("Unexpected value {:?}", value)).into());
+        }
+        let arg = match self.args.next() {
+            Some(arg) => arg,
+            None => return Ok(None),
+        };
+        if self.options_end {
+            return Ok(Some(Arg::Positional(arg)));
+        }
+        let s = match arg.to_str() {
+            Some(s) => s,
+            None => return Ok(Some(Arg::Positional(arg))),
+        };
+        if s == "--" {
+            self.options_end = true;
+            return self.next();
+        }
+        if s == "-h" {
+            return Ok(Some(Arg::Option("help".to_owned())));
+        }
+        match s.strip_prefix("--") {
+            Some(option) => match option.split_once('=') {
+                Some((name, value)) => {
+                    self.inline_value = Some(value.into());
+                    Ok(Some(Arg::Option(name.to_owned())))
+                }
+                None => Ok(Some(Arg::Option(option.to_owned()))),
+            },
+            None if s.starts_with('-') && s != "-" => {
+                Err(UsageError::new(format!("Unknown option {:?}", s)).into())
+            }
+            None => Ok(Some(Arg::Positional(arg))),
+        }
+    }
+
+    /// Returns the next positional argument.
+    ///
+    /// Returns an error if the next argument is an option.
+    pub(crate) fn next_positional(&mut self) -> Result<Option<OsString>> {
+        match self.next()? {
+            Some(Arg::Positional(arg)) => Ok(Some(arg)),
+            Some(Arg::Option(name)) if name == "help" => Ok(Some("--help".into())),
+            Some(Arg::Option(name)) => {
+                Err(UsageError::new(format!("Unexpected option `--{}`", name)).into())
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the value of the option.
+    pub(crate) fn value(&mut self, name: &str) -> Result<OsString> {
+        self.inline_value
+            .take()
+            .or_else(|| self.args.next())
+            .ok_or_else(|| UsageError::new(format!("Option `--{}` requires a value", name)).into())
+    }
+
+    /// Parses the value of the option.
+    pub(crate) fn parse_value<T: FromStr>(&mut self, name: &str) -> Result<T> {
+        let value = self.value(name)?;
+        value.to_str().and_then(|s| s.parse().ok()).ok_or_else(|| {
+            UsageError::new(format!("Invalid value {:?} for option `--{}`", value, name)).into()
+        })
+    }
+}
+
+/// Returns an error for the unknown option.
+pub(crate) fn unknown_option<T>(name: &str) -> Result<T> {
+    Err(UsageError::new(format!("Unknown option `--{}`", name)).into())
+}
+
+/// Returns an error for the unexpected positional argument.
+pub(crate) fn unexpected_positional<T>(arg: &OsString) -> Result<T> {
+    Err(UsageError::new(format!("Unexpected argument {:?}", arg)).into())
+}
+
+/// Returns an error for the missing positional argument.
+pub(crate) fn missing_positional<T>(name: &str) -> Result<T> {
+    Err(UsageError::new(format!("Missing argument <{}>", name)).into())
+}
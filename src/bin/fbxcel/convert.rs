@@ -0,0 +1,218 @@
+//! `convert` command.
+
+use std::{
+    convert::TryFrom,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use fbxcel::{
+    low::{v7400::AttributeValue, FbxVersion},
+    tree::v7400::{json, NodeHandle},
+    writer::v7400::{ascii, binary},
+};
+use log::warn;
+
+use crate::{
+    args::{self, Arg, Args},
+    input::{self, format_version, Format},
+    Result, UsageError, EXIT_SUCCESS,
+};
+
+/// Usage of the command.
+const USAGE: &str = "\
+Usage: fbxcel convert [OPTIONS] <INPUT> <OUTPUT>
+
+Convert FBX binary, FBX ASCII, or JSON file into another format or version.
+<OUTPUT> can be `-` (standard output).
+
+FBX ASCII has no binary type, and binaries are written as base64 strings.
+Since the base64 strings in FBX ASCII input are read as strings, conversion
+from FBX ASCII is lossy: binaries (such as `Content` of embedded media) are
+written as strings into FBX binary or JSON.
+
+Options:
+    --to <FORMAT>            Output format: `binary`, `ascii`, or `json`
+                             (default: `json` if <OUTPUT> ends with `.json`,
+                             `binary` otherwise)
+    --fbx-version <VERSION>  FBX version of the output: `7.4` or `7.5`
+                             (default: version of the input, or 7.4 for JSON;
+                             7.4 output is upgraded to 7.5 if it is too large)
+    --compress <ELEMENTS>    Compress arrays with <ELEMENTS> elements or more
+                             in FBX binary output (default: never)
+                             Arrays from FBX binary input keep their original
+                             encodings regardless of this option.
+    -h, --help               Print this help";
+
+/// Runs `convert` command.
+pub(crate) fn run(mut args: Args) -> Result<i32> {
+    let mut to = None;
+    let mut fbx_version = None;
+    let mut compression = binary::CompressionPolicy::never();
+    let mut paths = Vec::new();
+    while let Some(arg) = args.next()? {
+        match arg {
+            Arg::Option(name) => match name.as_str() {
+                "to" => to = Some(parse_format(&args.value(&name)?.to_string_lossy())?),
+                "fbx-version" => {
+                    fbx_version = Some(parse_version(&args.value(&name)?.to_string_lossy())?)
+                }
+                "compress" => {
+                    compression = parse_compression(&args.value(&name)?.to_string_lossy())?
+                }
+                "help" => {
+                    println!("{}", USAGE);
+                    return Ok(EXIT_SUCCESS);
+                }
+                _ => return args::unknown_option(&name),
+            },
+            Arg::Positional(arg) if paths.len() < 2 => paths.push(PathBuf::from(arg)),
+            Arg::Positional(arg) => return args::unexpected_positional(&arg),
+        }
+    }
+    let (input, output) = match <[PathBuf; 2]>::try_from(paths) {
+        Ok([input, output]) => (input, output),
+        Err(paths) if paths.is_empty() => return args::missing_positional("INPUT"),
+        Err(_) => return args::missing_positional("OUTPUT"),
+    };
+    let to = to.unwrap_or_else(|| match output.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("json") => Format::Json,
+        _ => Format::Binary,
+    });
+
+    // Upgrade the version only if it is not explicitly specified.
+    let version_overflow = match fbx_version {
+        Some(_) => binary::VersionOverflow::Error,
+        None => binary::VersionOverflow::Upgrade,
+    };
+    let loaded = input::load_tree(&input)?;
+    if loaded.format == Format::Ascii && to != Format::Ascii {
+        let count = count_content_strings(loaded.tree.root());
+        if count > 0 {
+            warn!(
+                "{} string attribute(s) of `Content` nodes in FBX ASCII input are \
+                 possibly base64 binaries, but they are converted as strings",
+                count
+            );
+        }
+    }
+    let mut fbx_version = fbx_version
+        .or(loaded.fbx_version)
+        .unwrap_or(FbxVersion::V7_4);
+
+    match to {
+        Format::Binary => {
+            // Keep the unknown fields of the original footer.
+            let footer = match &loaded.footer {
+                Some(footer) => binary::FbxFooter {
+                    unknown1: Some(&footer.unknown1),
+                    unknown2: Some(footer.unknown2),
+                    unknown3: Some(&footer.unknown3),
+                    ..Default::default()
+                },
+                None => Default::default(),
+            };
+            if is_stdout(&output) {
+                let mut writer = binary::StreamWriter::new(open_output(&output)?, fbx_version)?;
+                writer.set_compression_policy(compression);
+                writer.set_version_overflow(version_overflow);
+                writer.write_tree(&loaded.tree)?;
+                fbx_version = writer.fbx_version();
+                writer.finalize_and_flush(&footer)?;
+            } else {
+                let sink = BufWriter::new(File::create(&output)?);
+                let mut writer = binary::Writer::new(sink, fbx_version)?;
+                writer.set_compression_policy(compression);
+                writer.set_version_overflow(version_overflow);
+                writer.write_tree(&loaded.tree)?;
+                fbx_version = writer.fbx_version();
+                writer.finalize_and_flush(&footer)?;
+            }
+        }
+        Format::Ascii => {
+            let mut writer = ascii::Writer::new(open_output(&output)?, fbx_version)?;
+            writer.write_tree(&loaded.tree)?;
+            writer.finalize_and_flush()?;
+        }
+        Format::Json => {
+            let mut sink = open_output(&output)?;
+            json::to_writer(&loaded.tree, &mut sink)?;
+            sink.flush()?;
+        }
+    }
+    log::info!(
+        "Converted {} ({}) into {} ({}, FBX {})",
+        input.display(),
+        loaded.format.name(),
+        output.display(),
+        to.name(),
+        format_version(fbx_version)
+    );
+
+    Ok(EXIT_SUCCESS)
+}
+
+/// Parses the output format.
+fn parse_format(s: &str) -> Result<Format> {
+    match s {
+        "binary" => Ok(Format::Binary),
+        "ascii" => Ok(Format::Ascii),
+        "json" => Ok(Format::Json),
+        _ => Err(UsageError::new(format!("Unknown output format {:?}", s)).into()),
+    }
+}
+
+/// Parses the FBX version.
+fn parse_version(s: &str) -> Result<FbxVersion> {
+    match s {
+        "7.4" | "7400" => Ok(FbxVersion::V7_4),
+        "7.5" | "7500" => Ok(FbxVersion::V7_5),
+        _ => Err(UsageError::new(format!("Unsupported FBX version {:?}", s)).into()),
+    }
+}
+
+/// Parses the compression threshold.
+fn parse_compression(s: &str) -> Result<binary::CompressionPolicy> {
+    let count = s
+        .parse()
+        .map_err(|_| UsageError::new(format!("Invalid number of elements {:?}", s)))?;
+    Ok(binary::CompressionPolicy::new(
+        binary::CompressionThreshold::ElementsCount(count),
+        binary::CompressionLevel::Default,
+    ))
+}
+
+/// Returns the number of string attributes of `Content` nodes under the node.
+///
+/// FBX binary has binaries (such as embedded media) in `Content` nodes, and
+/// they become base64 strings in FBX ASCII.
+fn count_content_strings(root: NodeHandle<'_>) -> usize {
+    let mut count = 0;
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.name() == "Content" {
+            count += node
+                .attributes()
+                .iter()
+                .filter(|attr| matches!(attr, AttributeValue::String(_)))
+                .count();
+        }
+        stack.extend(node.children());
+    }
+    count
+}
+
+/// Returns whether the path means the standard output.
+fn is_stdout(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+/// Opens the output.
+fn open_output(path: &Path) -> io::Result<Box<dyn Write>> {
+    if is_stdout(path) {
+        Ok(Box::new(BufWriter::new(io::stdout())))
+    } else {
+        Ok(Box::new(BufWriter::new(File::create(path)?)))
+    }
+}
@@ -0,0 +1,205 @@
+//! `dump` command.
+
+use std::{
+    fmt::Write as _,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use fbxcel::{low::v7400::AttributeValue, tree::v7400::NodeHandle};
+
+use crate::{
+    args::{self, Arg, Args},
+    input::{self, format_version},
+    Result, EXIT_SUCCESS,
+};
+
+/// Usage of the command.
+const USAGE: &str = "\
+Usage: fbxcel dump [OPTIONS] <FILE>
+
+Print the node tree of FBX binary, FBX ASCII, or JSON file.
+
+Options:
+    --max-depth <N>         Do not print nodes deeper than N
+    --max-children <N>      Print at most N children for each node
+    --max-array-len <N>     Print at most N elements of arrays and binaries
+                            (default: 8)
+    --max-string-len <N>    Print at most N characters of strings (default: 64)
+    --full                  Print everything without truncation
+    -h, --help              Print this help";
+
+/// Truncation options.
+#[derive(Debug, Clone, Copy)]
+struct Limits {
+    /// Maximum depth of nodes.
+    max_depth: Option<usize>,
+    /// Maximum number of children for each node.
+    max_children: Option<usize>,
+    /// Maximum number of array elements.
+    max_array_len: Option<usize>,
+    /// Maximum number of characters of strings.
+    max_string_len: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            max_children: None,
+            max_array_len: Some(8),
+            max_string_len: Some(64),
+        }
+    }
+}
+
+/// Runs `dump` command.
+pub(crate) fn run(mut args: Args) -> Result<i32> {
+    let mut limits = Limits::default();
+    let mut path = None;
+    while let Some(arg) = args.next()? {
+        match arg {
+            Arg::Option(name) => match name.as_str() {
+                "max-depth" => limits.max_depth = Some(args.parse_value(&name)?),
+                "max-children" => limits.max_children = Some(args.parse_value(&name)?),
+                "max-array-len" => limits.max_array_len = Some(args.parse_value(&name)?),
+                "max-string-len" => limits.max_string_len = Some(args.parse_value(&name)?),
+                "full" => {
+                    limits = Limits {
+                        max_depth: None,
+                        max_children: None,
+                        max_array_len: None,
+                        max_string_len: None,
+                    }
+                }
+                "help" => {
+                    println!("{}", USAGE);
+                    return Ok(EXIT_SUCCESS);
+                }
+                _ => return args::unknown_option(&name),
+            },
+            Arg::Positional(arg) if path.is_none() => path = Some(PathBuf::from(arg)),
+            Arg::Positional(arg) => return args::unexpected_positional(&arg),
+        }
+    }
+    let path = match path {
+        Some(path) => path,
+        None => return args::missing_positional("FILE"),
+    };
+
+    let loaded = input::load_tree(&path)?;
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    match loaded.fbx_version {
+        Some(version) => writeln!(
+            out,
+            "; FBX {} ({})",
+            format_version(version),
+            loaded.format.name()
+        )?,
+        None => writeln!(out, "; {}", loaded.format.name())?,
+    }
+    dump_children(&mut out, loaded.tree.root(), 0, &limits)?;
+    out.flush()?;
+
+    Ok(EXIT_SUCCESS)
+}
+
+/// Prints the children of the node.
+fn dump_children(
+    out: &mut impl Write,
+    parent: NodeHandle<'_>,
+    depth: usize,
+    limits: &Limits,
+) -> io::Result<()> {
+    let num_children = parent.children().count();
+    if num_children == 0 {
+        return Ok(());
+    }
+    if limits.max_depth.map_or(false, |max| depth >= max) {
+        return writeln!(
+            out,
+            "{:indent$}... ({} children)",
+            "",
+            num_children,
+            indent = depth * 4
+        );
+    }
+    let max_children = limits.max_children.unwrap_or(usize::MAX);
+    for child in parent.children().take(max_children) {
+        write!(out, "{:indent$}{}", "", child.name(), indent = depth * 4)?;
+        if !child.attributes().is_empty() {
+            let attrs = child
+                .attributes()
+                .iter()
+                .map(|attr| format_attribute(attr, limits))
+                .collect::<Vec<_>>();
+            write!(out, ": {}", attrs.join(", "))?;
+        }
+        writeln!(out)?;
+        dump_children(out, child, depth + 1, limits)?;
+    }
+    if num_children > max_children {
+        writeln!(
+            out,
+            "{:indent$}... ({} more nodes)",
+            "",
+            num_children - max_children,
+            indent = depth * 4
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns a human-readable representation of the attribute.
+fn format_attribute(attr: &AttributeValue, limits: &Limits) -> String {
+    match attr {
+        AttributeValue::Bool(v) => format!("bool {}", v),
+        AttributeValue::I16(v) => format!("i16 {}", v),
+        AttributeValue::I32(v) => format!("i32 {}", v),
+        AttributeValue::I64(v) => format!("i64 {}", v),
+        AttributeValue::F32(v) => format!("f32 {:?}", v),
+        AttributeValue::F64(v) => format!("f64 {:?}", v),
+        AttributeValue::ArrBool(v) => format_array("arr_bool", v, limits, |v| v.to_string()),
+        AttributeValue::ArrI32(v) => format_array("arr_i32", v, limits, |v| v.to_string()),
+        AttributeValue::ArrI64(v) => format_array("arr_i64", v, limits, |v| v.to_string()),
+        AttributeValue::ArrF32(v) => format_array("arr_f32", v, limits, |v| format!("{:?}", v)),
+        AttributeValue::ArrF64(v) => format_array("arr_f64", v, limits, |v| format!("{:?}", v)),
+        AttributeValue::Binary(v) => format_array("binary", v, limits, |v| format!("{:02x}", v)),
+        AttributeValue::String(v) => {
+            let len = v.chars().count();
+            match limits.max_string_len {
+                Some(max) if len > max => {
+                    let truncated = v.chars().take(max).collect::<String>();
+                    format!("string {:?}... ({} chars)", truncated, len)
+                }
+                _ => format!("string {:?}", v),
+            }
+        }
+    }
+}
+
+/// Returns a human-readable representation of the array.
+fn format_array<T>(
+    tag: &str,
+    values: &[T],
+    limits: &Limits,
+    mut format: impl FnMut(&T) -> String,
+) -> String {
+    let mut s = format!("{}({}) [", tag, values.len());
+    let max = limits.max_array_len.unwrap_or(usize::MAX);
+    for (i, v) in values.iter().take(max).enumerate() {
+        if i != 0 {
+            s.push_str(", ");
+        }
+        s.push_str(&format(v));
+    }
+    if values.len() > max {
+        if max != 0 {
+            s.push_str(", ");
+        }
+        write!(s, "... ({} more)", values.len() - max).expect("Should never fail");
+    }
+    s.push(']');
+    s
+}
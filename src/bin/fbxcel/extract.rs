@@ -0,0 +1,198 @@
+//! `extract` command.
+
+use std::{fs, io, path::PathBuf};
+
+use fbxcel::pull_parser::{
+    self,
+    v7400::{attribute::loaders::BinaryLoader, Event, LoadAttribute},
+};
+use log::warn;
+
+use crate::{
+    args::{self, Arg, Args},
+    input::{self, format_position},
+    Result, EXIT_SUCCESS,
+};
+
+/// Usage of the command.
+const USAGE: &str = "\
+Usage: fbxcel extract [OPTIONS] <FILE>
+
+Write binary attributes (such as embedded textures) of FBX binary or FBX ASCII
+file out to files.
+
+Files are named `{SEQ}-{NODE_NAME}-{ATTRIBUTE_INDEX}.bin`, where `SEQ` is the
+sequential number of the node in the file (starting from 0).  Characters
+unsafe for file names in `NODE_NAME` are replaced with `_`.
+
+FBX ASCII has no binary type, and binaries are written as base64 strings.
+For FBX ASCII, every string attribute which is valid base64 is extracted as a
+binary, so short strings (such as `Mesh`) can also be extracted.  Use `--node`
+(for example, `--node Content` for embedded media) to narrow down the targets.
+
+Options:
+    --node <NAME>           Extract only attributes of the nodes with the name
+    --output-dir <DIR>      Directory to write files into (default: `.`)
+    -h, --help              Print this help";
+
+/// Runs `extract` command.
+pub(crate) fn run(mut args: Args) -> Result<i32> {
+    let mut node_name = None;
+    let mut output_dir = PathBuf::from(".");
+    let mut path = None;
+    while let Some(arg) = args.next()? {
+        match arg {
+            Arg::Option(name) => match name.as_str() {
+                "node" => node_name = Some(args.value(&name)?.to_string_lossy().into_owned()),
+                "output-dir" => output_dir = PathBuf::from(args.value(&name)?),
+                "help" => {
+                    println!("{}", USAGE);
+                    return Ok(EXIT_SUCCESS);
+                }
+                _ => return args::unknown_option(&name),
+            },
+            Arg::Positional(arg) if path.is_none() => path = Some(PathBuf::from(arg)),
+            Arg::Positional(arg) => return args::unexpected_positional(&arg),
+        }
+    }
+    let path = match path {
+        Some(path) => path,
+        None => return args::missing_positional("FILE"),
+    };
+
+    let (_, mut parser) = input::open_parser(&path)?;
+    parser.set_warning_handler(|w, pos| {
+        warn!("WARNING: {} (pos={})", w, format_position(pos));
+        Ok(())
+    });
+    fs::create_dir_all(&output_dir)?;
+
+    let mut seq = 0usize;
+    loop {
+        match parser.next_event()? {
+            Event::StartNode(start) => {
+                let current_seq = seq;
+                seq += 1;
+                if node_name
+                    .as_ref()
+                    .map_or(false, |name| name != start.name())
+                {
+                    continue;
+                }
+                let is_content = start.name() == "Content";
+                let file_name_base = format!("{}-{}", current_seq, sanitize(start.name()));
+                let mut attrs = start.attributes();
+                let mut index = 0;
+                while let Some(attr) = attrs.load_next(ExtractLoader)? {
+                    match attr {
+                        Extracted::Binary(data) => {
+                            let file_path =
+                                output_dir.join(format!("{}-{}.bin", file_name_base, index));
+                            fs::write(&file_path, &data)?;
+                            println!("{}: {} bytes", file_path.display(), data.len());
+                        }
+                        // `Content` nodes have binaries such as embedded media.
+                        Extracted::String if is_content => warn!(
+                            "Attribute {} of the node {} (`Content`) is a string which is \
+                             not a binary nor valid base64, and is not extracted",
+                            index, current_seq
+                        ),
+                        Extracted::String | Extracted::Other => {}
+                    }
+                    index += 1;
+                }
+            }
+            Event::EndNode => {}
+            Event::EndFbx(_) => break,
+        }
+    }
+
+    Ok(EXIT_SUCCESS)
+}
+
+/// Attribute loaded by [`ExtractLoader`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Extracted {
+    /// Binary.
+    Binary(Vec<u8>),
+    /// String which is not a binary.
+    String,
+    /// Value of other types.
+    Other,
+}
+
+/// Loader which loads only binaries, and ignores values of other types.
+///
+/// This expects binaries, so that binaries in FBX ASCII (written as base64
+/// strings) are loaded as binaries.
+#[derive(Debug, Clone, Copy)]
+struct ExtractLoader;
+
+/// Implements `load_*` methods which ignore the values.
+macro_rules! impl_ignore_single {
+    ($($method:ident($ty:ty);)*) => {$(
+        fn $method(self, _: $ty) -> pull_parser::Result<Self::Output> {
+            Ok(Extracted::Other)
+        }
+    )*}
+}
+
+/// Implements `load_seq_*` methods which ignore the values.
+macro_rules! impl_ignore_seq {
+    ($($method:ident($ty:ty);)*) => {$(
+        fn $method(
+            self,
+            _: impl Iterator<Item = pull_parser::Result<$ty>>,
+            _: usize,
+        ) -> pull_parser::Result<Self::Output> {
+            Ok(Extracted::Other)
+        }
+    )*}
+}
+
+impl LoadAttribute for ExtractLoader {
+    type Output = Extracted;
+
+    fn expecting(&self) -> String {
+        "any type".into()
+    }
+
+    fn expects_binary(&self) -> bool {
+        true
+    }
+
+    impl_ignore_single! {
+        load_bool(bool);
+        load_i16(i16);
+        load_i32(i32);
+        load_i64(i64);
+        load_f32(f32);
+        load_f64(f64);
+    }
+
+    impl_ignore_seq! {
+        load_seq_bool(bool);
+        load_seq_i32(i32);
+        load_seq_i64(i64);
+        load_seq_f32(f32);
+        load_seq_f64(f64);
+    }
+
+    fn load_binary(self, reader: impl io::Read, len: u64) -> pull_parser::Result<Self::Output> {
+        BinaryLoader.load_binary(reader, len).map(Extracted::Binary)
+    }
+
+    fn load_string(self, _: impl io::Read, _: u64) -> pull_parser::Result<Self::Output> {
+        Ok(Extracted::String)
+    }
+}
+
+/// Replaces characters which are not safe for file names with `_`.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+            _ => '_',
+        })
+        .collect()
+}
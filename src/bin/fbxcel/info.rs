@@ -0,0 +1,126 @@
+//! `info` command.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use fbxcel::pull_parser::v7400::{attribute::loaders::TypeLoader, Event};
+use log::warn;
+
+use crate::{
+    args::{self, Arg, Args},
+    input::{self, format_position, format_version},
+    Result, EXIT_SUCCESS,
+};
+
+/// Usage of the command.
+const USAGE: &str = "\
+Usage: fbxcel info [OPTIONS] <FILE>
+
+Print the FBX version, the footer, and node statistics of FBX binary or
+FBX ASCII file.
+
+Options:
+    --top <N>     Print N most frequent node names (default: 10)
+    -h, --help    Print this help";
+
+/// Runs `info` command.
+pub(crate) fn run(mut args: Args) -> Result<i32> {
+    let mut top = 10;
+    let mut path = None;
+    while let Some(arg) = args.next()? {
+        match arg {
+            Arg::Option(name) => match name.as_str() {
+                "top" => top = args.parse_value(&name)?,
+                "help" => {
+                    println!("{}", USAGE);
+                    return Ok(EXIT_SUCCESS);
+                }
+                _ => return args::unknown_option(&name),
+            },
+            Arg::Positional(arg) if path.is_none() => path = Some(PathBuf::from(arg)),
+            Arg::Positional(arg) => return args::unexpected_positional(&arg),
+        }
+    }
+    let path = match path {
+        Some(path) => path,
+        None => return args::missing_positional("FILE"),
+    };
+
+    let (format, mut parser) = input::open_parser(&path)?;
+    println!("File: {}", path.display());
+    println!("Format: {}", format.name());
+    println!("FBX version: {}", format_version(parser.fbx_version()));
+    println!("Parser version: {:?}", parser.parser_version());
+
+    parser.set_warning_handler(|w, pos| {
+        warn!("WARNING: {} (pos={})", w, format_position(pos));
+        Ok(())
+    });
+
+    let mut num_nodes = 0usize;
+    let mut max_depth = 0usize;
+    let mut depth = 0usize;
+    let mut toplevels = Vec::new();
+    let mut names = HashMap::<String, usize>::new();
+    let mut attribute_types = BTreeMap::new();
+    let mut num_attributes = 0usize;
+    let footer = loop {
+        match parser.next_event()? {
+            Event::StartNode(start) => {
+                num_nodes += 1;
+                depth += 1;
+                max_depth = max_depth.max(depth);
+                if depth == 1 {
+                    toplevels.push(start.name().to_owned());
+                }
+                *names.entry(start.name().to_owned()).or_default() += 1;
+                let mut attrs = start.attributes();
+                while let Some(type_) = attrs.load_next(TypeLoader)? {
+                    num_attributes += 1;
+                    *attribute_types
+                        .entry(format!("{:?}", type_))
+                        .or_insert(0usize) += 1;
+                }
+            }
+            Event::EndNode => depth -= 1,
+            Event::EndFbx(footer) => break footer,
+        }
+    };
+
+    match footer {
+        Ok(footer) => {
+            println!("Footer:");
+            println!("    FBX version: {}", format_version(footer.fbx_version));
+            println!("    Padding length: {}", footer.padding_len);
+            println!("    Unknown field 1: {}", hex(&footer.unknown1));
+            println!("    Unknown field 2: {}", hex(&footer.unknown2));
+            println!("    Unknown field 3: {}", hex(&footer.unknown3));
+        }
+        Err(e) => println!("Footer: unavailable ({})", e),
+    }
+    println!("Nodes: {} (max depth: {})", num_nodes, max_depth);
+    println!("Top-level nodes: {}", toplevels.join(", "));
+    println!("Attributes: {}", num_attributes);
+    for (type_, count) in &attribute_types {
+        println!("    {}: {}", type_, count);
+    }
+    let mut names = names.into_iter().collect::<Vec<_>>();
+    names.sort_by(|(lname, lcount), (rname, rcount)| {
+        rcount.cmp(lcount).then_with(|| lname.cmp(rname))
+    });
+    if top != 0 && !names.is_empty() {
+        println!("Most frequent node names:");
+        for (name, count) in names.iter().take(top) {
+            println!("    {}: {}", name, count);
+        }
+    }
+
+    Ok(EXIT_SUCCESS)
+}
+
+/// Returns the bytes as a hexadecimal string.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,156 @@
+//! Input files.
+
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use fbxcel::{
+    low::{v7400::FbxFooter, FbxHeader, FbxVersion},
+    pull_parser::{
+        any::{from_seekable_reader, AnyParser},
+        reader::SeekableSource,
+        v7400::{ascii, Parser},
+        SyntacticPosition,
+    },
+    tree::v7400::{json, ArrayPreservation, Loader, Tree},
+};
+use log::warn;
+
+use crate::Result;
+
+/// Parser type for input files.
+pub(crate) type FileParser = Parser<SeekableSource<BufReader<File>>>;
+
+/// Input file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    /// FBX binary.
+    Binary,
+    /// FBX ASCII.
+    Ascii,
+    /// JSON exported by `fbxcel convert --to=json`.
+    Json,
+}
+
+impl Format {
+    /// Returns the name of the format.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Format::Binary => "binary",
+            Format::Ascii => "ascii",
+            Format::Json => "json",
+        }
+    }
+}
+
+/// Opens the file and detects its format.
+pub(crate) fn open(path: &Path) -> Result<(Format, BufReader<File>)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let format = if FbxHeader::load(&mut reader).is_ok() {
+        Format::Binary
+    } else {
+        reader.seek(SeekFrom::Start(0))?;
+        let mut head = Vec::new();
+        (&mut reader).take(64).read_to_end(&mut head)?;
+        match head.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'[') => Format::Json,
+            _ => Format::Ascii,
+        }
+    };
+    reader.seek(SeekFrom::Start(0))?;
+    Ok((format, reader))
+}
+
+/// Creates a parser for the FBX file.
+pub(crate) fn open_parser(path: &Path) -> Result<(Format, FileParser)> {
+    let (format, reader) = open(path)?;
+    let parser = create_parser(format, reader)?;
+    Ok((format, parser))
+}
+
+/// Creates a parser for the opened FBX file.
+fn create_parser(format: Format, reader: BufReader<File>) -> Result<FileParser> {
+    match format {
+        Format::Binary => match from_seekable_reader(reader)? {
+            AnyParser::V6100(parser) | AnyParser::V7400(parser) => Ok(parser),
+            parser => Err(format!("Unsupported FBX version: {:?}", parser.fbx_version()).into()),
+        },
+        Format::Ascii => Ok(ascii::from_seekable_reader(reader)?),
+        Format::Json => Err("JSON input is not supported by this command".into()),
+    }
+}
+
+/// Loaded tree.
+#[derive(Debug)]
+pub(crate) struct LoadedTree {
+    /// Input format.
+    pub(crate) format: Format,
+    /// FBX version, if available.
+    pub(crate) fbx_version: Option<FbxVersion>,
+    /// Tree.
+    pub(crate) tree: Tree,
+    /// Footer, if available.
+    pub(crate) footer: Option<Box<FbxFooter>>,
+}
+
+/// Loads a tree from the file.
+///
+/// Encodings of array attributes in FBX binary are kept in the tree, so that
+/// compressed arrays are written compressed again.
+pub(crate) fn load_tree(path: &Path) -> Result<LoadedTree> {
+    let (format, reader) = open(path)?;
+    if format == Format::Json {
+        return Ok(LoadedTree {
+            format,
+            fbx_version: None,
+            tree: json::from_reader(reader)?,
+            footer: None,
+        });
+    }
+
+    let mut parser = create_parser(format, reader)?;
+    parser.set_warning_handler(|w, pos| {
+        warn!("WARNING: {} (pos={})", w, format_position(pos));
+        Ok(())
+    });
+    let (tree, footer) = Loader::new()
+        .with_array_preservation(ArrayPreservation::Encoding)
+        .load(&mut parser)?;
+    let footer = match footer {
+        Ok(footer) => Some(footer),
+        // FBX ASCII has no footer.
+        Err(_) if format == Format::Ascii => None,
+        Err(e) => {
+            warn!("Failed to load the footer: {}", e);
+            None
+        }
+    };
+    Ok(LoadedTree {
+        format,
+        fbx_version: Some(parser.fbx_version()),
+        tree,
+        footer,
+    })
+}
+
+/// Returns a human-readable representation of the position.
+pub(crate) fn format_position(pos: &SyntacticPosition) -> String {
+    let mut s = format!("byte {}, at /", pos.byte_pos());
+    let path = pos
+        .node_path()
+        .iter()
+        .map(|(index, name)| format!("{}[{}]", name, index))
+        .collect::<Vec<_>>();
+    s.push_str(&path.join("/"));
+    if let Some(index) = pos.attribute_index() {
+        s.push_str(&format!(", attribute #{}", index));
+    }
+    s
+}
+
+/// Returns a human-readable representation of the FBX version.
+pub(crate) fn format_version(version: FbxVersion) -> String {
+    format!("{}.{}", version.major(), version.minor())
+}
@@ -0,0 +1,99 @@
+//! `fbxcel` command-line tool for inspecting and converting FBX files.
+//!
+//! Enabled by `cli` feature.
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+#![warn(clippy::missing_docs_in_private_items)]
+
+use std::{error, fmt, process};
+
+use self::args::Args;
+
+mod args;
+mod convert;
+mod dump;
+mod extract;
+mod info;
+mod input;
+mod validate;
+
+/// Result type of the commands.
+type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
+
+/// Usage of the command.
+const USAGE: &str = "\
+Usage: fbxcel <COMMAND> [OPTIONS] <FILE>...
+
+Commands:
+    dump       Print the node tree
+    info       Print the FBX version, the footer, and node statistics
+    convert    Convert between FBX binary, FBX ASCII, and JSON
+    validate   Parse the whole file and report warnings and errors
+    extract    Write binary attributes out to files
+
+Run `fbxcel <COMMAND> --help` for the options of each command.
+
+Set `RUST_LOG` environment variable to control log messages
+(default: `warn`).";
+
+/// Exit status on success.
+const EXIT_SUCCESS: i32 = 0;
+/// Exit status when the input has problems (such as warnings on validation).
+const EXIT_PROBLEMS_FOUND: i32 = 1;
+/// Exit status on errors.
+const EXIT_FAILURE: i32 = 2;
+
+fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let status = match run(Args::from_env()) {
+        Ok(status) => status,
+        Err(e) => {
+            eprintln!("fbxcel: {}", e);
+            if e.is::<UsageError>() {
+                eprintln!("\n{}", USAGE);
+            }
+            EXIT_FAILURE
+        }
+    };
+    process::exit(status);
+}
+
+/// Runs the command and returns the exit status.
+fn run(mut args: Args) -> Result<i32> {
+    let command = match args.next_positional()? {
+        Some(command) => command,
+        None => return Err(UsageError::new("No command is specified").into()),
+    };
+    match command.to_str() {
+        Some("dump") => dump::run(args),
+        Some("info") => info::run(args),
+        Some("convert") => convert::run(args),
+        Some("validate") => validate::run(args),
+        Some("extract") => extract::run(args),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{}", USAGE);
+            Ok(EXIT_SUCCESS)
+        }
+        _ => Err(UsageError::new(format!("Unknown command {:?}", command)).into()),
+    }
+}
+
+/// Error for invalid command line.
+#[derive(Debug, Clone)]
+struct UsageError(String);
+
+impl UsageError {
+    /// Creates a new `UsageError`.
+    fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl error::Error for UsageError {}
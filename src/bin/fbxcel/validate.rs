@@ -0,0 +1,124 @@
+//! `validate` command.
+
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use fbxcel::pull_parser::v7400::{attribute::loaders::DirectLoader, validate as binary, Event};
+
+use crate::{
+    args::{self, Arg, Args},
+    input::{self, format_position, Format},
+    Result, EXIT_FAILURE, EXIT_PROBLEMS_FOUND, EXIT_SUCCESS,
+};
+
+/// Usage of the command.
+const USAGE: &str = "\
+Usage: fbxcel validate [OPTIONS] <FILE>...
+
+Parse the whole FBX binary or FBX ASCII files, including all attributes and
+the footer, and report warnings and errors.
+For FBX binary, all problems in the file are reported. For FBX ASCII, parsing
+stops at the first error.
+
+Exits with 0 if no problems are found, 1 if warnings are found, and 2 if
+errors are found.
+
+Options:
+    -h, --help    Print this help";
+
+/// Runs `validate` command.
+pub(crate) fn run(mut args: Args) -> Result<i32> {
+    let mut paths = Vec::new();
+    while let Some(arg) = args.next()? {
+        match arg {
+            Arg::Option(name) if name == "help" => {
+                println!("{}", USAGE);
+                return Ok(EXIT_SUCCESS);
+            }
+            Arg::Option(name) => return args::unknown_option(&name),
+            Arg::Positional(arg) => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        return args::missing_positional("FILE");
+    }
+
+    let mut status = EXIT_SUCCESS;
+    for path in paths {
+        let file_status = match validate(&path) {
+            Ok(0) => {
+                println!("{}: ok", path.display());
+                EXIT_SUCCESS
+            }
+            Ok(num_warnings) => {
+                println!("{}: {} warning(s)", path.display(), num_warnings);
+                EXIT_PROBLEMS_FOUND
+            }
+            Err(e) => {
+                println!("{}: error: {}", path.display(), e);
+                EXIT_FAILURE
+            }
+        };
+        status = status.max(file_status);
+    }
+
+    Ok(status)
+}
+
+/// Validates the file, and returns the number of warnings.
+fn validate(path: &std::path::Path) -> Result<usize> {
+    let (format, reader) = input::open(path)?;
+    if format == Format::Binary {
+        let report = binary::validate(reader)?;
+        for issue in report.issues() {
+            println!(
+                "{}: {}: {} ({})",
+                path.display(),
+                issue.severity(),
+                issue.kind(),
+                format_position(issue.position())
+            );
+        }
+        let num_errors = report.errors().count();
+        if num_errors != 0 {
+            return Err(format!("{} error(s)", num_errors).into());
+        }
+        return Ok(report.warnings().count());
+    }
+
+    let (_, mut parser) = input::open_parser(path)?;
+    let num_warnings = Rc::new(RefCell::new(0));
+    parser.set_warning_handler({
+        let path = path.to_owned();
+        let num_warnings = num_warnings.clone();
+        move |w, pos| {
+            *num_warnings.borrow_mut() += 1;
+            println!(
+                "{}: warning: {} ({})",
+                path.display(),
+                w,
+                format_position(pos)
+            );
+            Ok(())
+        }
+    });
+
+    let res = (|| loop {
+        match parser.next_event()? {
+            Event::StartNode(start) => {
+                let mut attrs = start.attributes();
+                while attrs.load_next(DirectLoader)?.is_some() {}
+            }
+            Event::EndNode => {}
+            Event::EndFbx(footer) => return footer.map(drop),
+        }
+    })();
+    if let Err(e) = res {
+        return Err(match e.position() {
+            Some(pos) => format!("{} ({})", e, format_position(pos)).into(),
+            None => e.into(),
+        });
+    }
+
+    let num_warnings = *num_warnings.borrow();
+    Ok(num_warnings)
+}
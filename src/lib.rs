@@ -4,7 +4,7 @@
 //! attribute value, etc.
 //!
 //! [`pull_parser`] module provides pull parser for FBX binary format.
-//! ASCII format is not supported.
+//! FBX ASCII format is also supported by [`pull_parser::v7400::ascii`].
 //!
 //! [`tree`] module provides tree types, which allow users to access FBX data as
 //! tree, not as stream of parser events.
@@ -12,11 +12,23 @@
 //!
 //! [`writer`] module provides writer types.
 //! To use `writer` module, enable `writer` feature.
+//!
+//! With `serde` feature, [`low::v7400::AttributeValue`] and `tree::v7400::Tree`
+//! implement `serde::Serialize` and `serde::Deserialize`, and nodes can be
+//! deserialized into user types by `tree::v7400::de`.
+//!
+//! With `cli` feature, `fbxcel` command-line tool is built. It dumps, inspects,
+//! converts, and validates FBX files.
 #![cfg_attr(feature = "docsrs", feature(doc_cfg))]
+// `docsrs` is not a real feature but is passed by docs.rs (see `Cargo.toml`).
+// `unexpected_cfgs` lint is unknown to older compilers.
+#![allow(unknown_lints)]
+#![allow(unexpected_cfgs)]
 #![forbid(unsafe_code)]
 #![warn(missing_docs)]
 #![warn(clippy::missing_docs_in_private_items)]
 
+mod base64;
 pub mod low;
 pub mod pull_parser;
 #[cfg(feature = "tree")]
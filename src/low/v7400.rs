@@ -1,13 +1,11 @@
 //! Low-level or primitive data types for FBX 7.4 and compatible versions.
 
+pub(crate) use self::special_attribute::SpecialAttributeHeader;
 pub use self::{
-    array_attribute::ArrayAttributeEncoding,
+    array_attribute::{ArrayAttributeEncoding, ArrayAttributeHeader},
     attribute::{type_::AttributeType, value::AttributeValue},
     fbx_footer::FbxFooter,
-};
-pub(crate) use self::{
-    array_attribute::ArrayAttributeHeader, node_header::NodeHeader,
-    special_attribute::SpecialAttributeHeader,
+    node_header::NodeHeader,
 };
 
 mod array_attribute;
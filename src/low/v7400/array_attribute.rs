@@ -62,8 +62,11 @@ impl FromReader for ArrayAttributeEncoding {
 }
 
 /// A header type for array-type attributes.
+///
+/// This is available for FBX binary through
+/// [`AttributeLayout::array_header()`][`crate::pull_parser::v7400::attribute::AttributeLayout::array_header`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub(crate) struct ArrayAttributeHeader {
+pub struct ArrayAttributeHeader {
     /// Number of elements.
     pub(crate) elements_count: u32,
     /// Encoding.
@@ -72,6 +75,23 @@ pub(crate) struct ArrayAttributeHeader {
     pub(crate) bytelen: u32,
 }
 
+impl ArrayAttributeHeader {
+    /// Returns the number of elements.
+    pub fn elements_count(&self) -> u32 {
+        self.elements_count
+    }
+
+    /// Returns the encoding of the elements.
+    pub fn encoding(&self) -> ArrayAttributeEncoding {
+        self.encoding
+    }
+
+    /// Returns the length of the (possibly compressed) elements in bytes.
+    pub fn bytelen(&self) -> u32 {
+        self.bytelen
+    }
+}
+
 impl FromReader for ArrayAttributeHeader {
     fn from_reader(reader: &mut impl io::Read) -> Result<Self, ParserError> {
         let elements_count = u32::from_reader(reader)?;
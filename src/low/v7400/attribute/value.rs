@@ -2,6 +2,9 @@
 
 use crate::low::v7400::AttributeType;
 
+#[cfg(feature = "serde")]
+mod serde_float;
+
 /// Node attribute value.
 ///
 /// To get a value of the specific type easily, use `get_*()` or
@@ -14,7 +17,15 @@ use crate::low::v7400::AttributeType;
 ///     + If a value of the expected type available, returns `Ok(_)`.
 ///     + If not, returns `Ok(ty)` where `ty` is value type (same value as
 ///       returned by [`type_`][`Self::type_()`] method.
+///
+/// With `serde` feature, this is serialized as an externally tagged enum
+/// (e.g. `{"I16": 3}` in JSON), so the exact type is kept.
+/// In human-readable formats, floating point numbers are serialized as
+/// hexadecimal bit pattern strings (e.g. `{"F32": "0x3f800000"}`), since some
+/// formats such as JSON cannot represent non-finite values or may not read
+/// decimal numbers back exactly.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AttributeValue {
     /// Single `bool`.
     Bool(bool),
@@ -25,9 +36,9 @@ pub enum AttributeValue {
     /// Single `i64`.
     I64(i64),
     /// Single `f32`.
-    F32(f32),
+    F32(#[cfg_attr(feature = "serde", serde(with = "serde_float"))] f32),
     /// Single `f64`.
-    F64(f64),
+    F64(#[cfg_attr(feature = "serde", serde(with = "serde_float"))] f64),
     /// Array of `bool`.
     ArrBool(Vec<bool>),
     /// Array of `i32`.
@@ -35,9 +46,9 @@ pub enum AttributeValue {
     /// Array of `i64`.
     ArrI64(Vec<i64>),
     /// Array of `f32`.
-    ArrF32(Vec<f32>),
+    ArrF32(#[cfg_attr(feature = "serde", serde(with = "serde_float::seq"))] Vec<f32>),
     /// Array of `f64`.
-    ArrF64(Vec<f64>),
+    ArrF64(#[cfg_attr(feature = "serde", serde(with = "serde_float::seq"))] Vec<f64>),
     /// UTF-8 string.
     String(String),
     /// Binary.
@@ -0,0 +1,180 @@
+//! Serde support for floating point attribute values.
+//!
+//! Human-readable formats such as JSON may not be able to represent
+//! non-finite values (for example, serde_json writes NaN and infinities as
+//! `null`), and may not parse decimal numbers into exactly the same value.
+//! To keep them lossless, values are serialized as hexadecimal bit patterns
+//! (such as `"0x3f800000"`) in human-readable formats, as `tree::v7400::json`
+//! does.
+//! Plain numbers are also accepted on deserialization.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, Deserializer, SeqAccess, Visitor},
+    ser::{SerializeSeq, Serializer},
+    Deserialize, Serialize,
+};
+
+/// Floating point number type.
+pub(crate) trait Float: Copy {
+    /// Type name.
+    const NAME: &'static str;
+
+    /// Returns the bit pattern as a hexadecimal string.
+    fn to_bits_string(self) -> String;
+    /// Parses a hexadecimal bit pattern (such as `0x7fc00000`).
+    fn from_bits_str(s: &str) -> Option<Self>;
+    /// Converts the deserialized `f64` value.
+    fn from_f64(v: f64) -> Self;
+    /// Serializes the value as a plain number.
+    fn serialize_plain<S: Serializer>(self, serializer: S) -> Result<S::Ok, S::Error>;
+    /// Deserializes the value as a plain number.
+    fn deserialize_plain<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+/// Implements `Float` for the given type.
+macro_rules! impl_float {
+    ($ty:ty, $ty_bits:ty, $width:expr, $serialize:ident) => {
+        impl Float for $ty {
+            const NAME: &'static str = stringify!($ty);
+
+            fn to_bits_string(self) -> String {
+                format!("0x{:0width$x}", self.to_bits(), width = $width)
+            }
+
+            fn from_bits_str(s: &str) -> Option<Self> {
+                let digits = s.strip_prefix("0x")?;
+                if digits.len() != $width {
+                    return None;
+                }
+                <$ty_bits>::from_str_radix(digits, 16)
+                    .ok()
+                    .map(<$ty>::from_bits)
+            }
+
+            fn from_f64(v: f64) -> Self {
+                v as $ty
+            }
+
+            fn serialize_plain<S: Serializer>(self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.$serialize(self)
+            }
+
+            fn deserialize_plain<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Self, D::Error> {
+                <$ty>::deserialize(deserializer)
+            }
+        }
+    };
+}
+
+impl_float! { f32, u32, 8, serialize_f32 }
+impl_float! { f64, u64, 16, serialize_f64 }
+
+/// Serializes a floating point number.
+pub(crate) fn serialize<T: Float, S: Serializer>(v: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&v.to_bits_string())
+    } else {
+        v.serialize_plain(serializer)
+    }
+}
+
+/// Deserializes a floating point number.
+pub(crate) fn deserialize<'de, T: Float, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(FloatVisitor(PhantomData))
+    } else {
+        T::deserialize_plain(deserializer)
+    }
+}
+
+/// Visitor for a floating point number or its bit pattern.
+struct FloatVisitor<T>(PhantomData<fn() -> T>);
+
+impl<T: Float> Visitor<'_> for FloatVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} number or hexadecimal bit pattern string", T::NAME)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<T, E> {
+        Ok(T::from_f64(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> {
+        Ok(T::from_f64(v as f64))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+        Ok(T::from_f64(v as f64))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        T::from_bits_str(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+}
+
+/// Floating point number wrapper using this module.
+struct Repr<T>(T);
+
+impl<T: Float> Serialize for Repr<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, T: Float> Deserialize<'de> for Repr<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(Repr)
+    }
+}
+
+/// Serde support for arrays of floating point numbers.
+pub(crate) mod seq {
+    use super::*;
+
+    /// Serializes an array of floating point numbers.
+    pub(crate) fn serialize<T: Float, S: Serializer>(
+        v: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(v.len()))?;
+        for &elem in v {
+            seq.serialize_element(&Repr(elem))?;
+        }
+        seq.end()
+    }
+
+    /// Deserializes an array of floating point numbers.
+    pub(crate) fn deserialize<'de, T: Float, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<T>, D::Error> {
+        deserializer.deserialize_seq(SeqVisitor(PhantomData))
+    }
+
+    /// Visitor for an array of floating point numbers.
+    struct SeqVisitor<T>(PhantomData<fn() -> T>);
+
+    impl<'de, T: Float> Visitor<'de> for SeqVisitor<T> {
+        type Value = Vec<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "array of {}", T::NAME)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+            // Avoid huge allocation for broken size hint.
+            let mut vec = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(0x1_0000));
+            while let Some(Repr(elem)) = seq.next_element()? {
+                vec.push(elem);
+            }
+            Ok(vec)
+        }
+    }
+}
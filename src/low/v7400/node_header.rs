@@ -1,13 +1,21 @@
 //! Node header.
 
-use crate::pull_parser::{
-    v7400::{FromParser, Parser},
-    Error as ParserError, ParserSource,
+use std::io;
+
+use crate::{
+    low::FbxVersion,
+    pull_parser::{
+        v7400::{FromParser, FromReader, Parser},
+        Error as ParserError, ParserSource,
+    },
 };
 
 /// Node header.
+///
+/// This is available for FBX binary through
+/// [`StartNode::header()`][`crate::pull_parser::v7400::StartNode::header`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub(crate) struct NodeHeader {
+pub struct NodeHeader {
     /// End offset of the node.
     pub(crate) end_offset: u64,
     /// The number of the node attributes.
@@ -19,6 +27,28 @@ pub(crate) struct NodeHeader {
 }
 
 impl NodeHeader {
+    /// Returns the end offset of the node.
+    ///
+    /// "End offset" means a next byte of the last byte of the node.
+    pub fn end_offset(&self) -> u64 {
+        self.end_offset
+    }
+
+    /// Returns the number of the node attributes.
+    pub fn num_attributes(&self) -> u64 {
+        self.num_attributes
+    }
+
+    /// Returns the length of the node attributes in bytes.
+    pub fn bytelen_attributes(&self) -> u64 {
+        self.bytelen_attributes
+    }
+
+    /// Returns the length of the node name in bytes.
+    pub fn bytelen_name(&self) -> u8 {
+        self.bytelen_name
+    }
+
     /// Checks whether the entry indicates end of a node.
     pub(crate) fn is_node_end(&self) -> bool {
         self.end_offset == 0
@@ -40,24 +70,24 @@ impl NodeHeader {
     }
 }
 
-impl FromParser for NodeHeader {
-    fn read_from_parser<R>(parser: &mut Parser<R>) -> Result<Self, ParserError>
-    where
-        R: ParserSource,
-    {
-        let (end_offset, num_attributes, bytelen_attributes) = if parser.fbx_version().raw() < 7500
-        {
-            let eo = u64::from(parser.parse::<u32>()?);
-            let na = u64::from(parser.parse::<u32>()?);
-            let ba = u64::from(parser.parse::<u32>()?);
+impl NodeHeader {
+    /// Reads a node header of the given FBX version from the given reader.
+    pub(crate) fn from_reader(
+        reader: &mut impl io::Read,
+        fbx_version: FbxVersion,
+    ) -> Result<Self, ParserError> {
+        let (end_offset, num_attributes, bytelen_attributes) = if fbx_version.raw() < 7500 {
+            let eo = u64::from(u32::from_reader(reader)?);
+            let na = u64::from(u32::from_reader(reader)?);
+            let ba = u64::from(u32::from_reader(reader)?);
             (eo, na, ba)
         } else {
-            let eo = parser.parse::<u64>()?;
-            let na = parser.parse::<u64>()?;
-            let ba = parser.parse::<u64>()?;
+            let eo = u64::from_reader(reader)?;
+            let na = u64::from_reader(reader)?;
+            let ba = u64::from_reader(reader)?;
             (eo, na, ba)
         };
-        let bytelen_name = parser.parse::<u8>()?;
+        let bytelen_name = u8::from_reader(reader)?;
 
         Ok(Self {
             end_offset,
@@ -67,3 +97,13 @@ impl FromParser for NodeHeader {
         })
     }
 }
+
+impl FromParser for NodeHeader {
+    fn read_from_parser<R>(parser: &mut Parser<R>) -> Result<Self, ParserError>
+    where
+        R: ParserSource,
+    {
+        let fbx_version = parser.fbx_version();
+        Self::from_reader(parser.reader(), fbx_version)
+    }
+}
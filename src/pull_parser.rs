@@ -1,4 +1,4 @@
-//! Pull parser for FBX binary.
+//! Pull parser for FBX binary (and ASCII).
 //!
 //! # FBX versions and types
 //!
@@ -10,6 +10,9 @@
 //! * [`AnyParser`][`any::AnyParser`] feature (defined in [`any`] module).
 //! * Parser source traits and wrappers (defined in [`reader`] module).
 //!
+//! FBX 6.x binary has the same low-level node structure as FBX 7.4, so
+//! [`v6100`] module shares the parser type with [`v7400`] module.
+//!
 //! # Using pull parser
 //!
 //! There are two ways to set up a parser: easy setup and manual setup.
@@ -34,6 +37,11 @@
 //!     AnyParser::V7400(mut parser) => {
 //!         // You got a parser! Do what you want!
 //!     },
+//!     // Use v6100 parser (implemented in `v6100` module).
+//!     AnyParser::V6100(mut parser) => {
+//!         // The parser type is same as `V7400`, but the document structure
+//!         // is different.
+//!     },
 //!     // `AnyParser` is nonexhaustive.
 //!     // You should handle new unknown parser version case.
 //!     _ => panic!("Unsupported FBX parser is required"),
@@ -81,17 +89,33 @@
 //!     ),
 //! }
 //! ```
+//!
+//! ## FBX ASCII
+//!
+//! FBX ASCII documents have no binary header, so they should be set up by
+//! [`v7400::ascii`] module.
+//! The parser emits the same events as for FBX binary.
+//!
+//! ```no_run
+//! let file = std::fs::File::open("sample.fbx").expect("Failed to open file");
+//! let reader = std::io::BufReader::new(file);
+//! let mut parser = fbxcel::pull_parser::v7400::ascii::from_seekable_reader(reader)
+//!     .expect("Failed to setup parser");
+//! ```
 
 pub use self::{
     error::{Error, Result, Warning},
     position::SyntacticPosition,
     reader::ParserSource,
     version::ParserVersion,
+    warning_policy::{WarningAction, WarningCollector, WarningPolicy},
 };
 
 pub mod any;
 pub mod error;
 mod position;
 pub mod reader;
+pub mod v6100;
 pub mod v7400;
 mod version;
+mod warning_policy;
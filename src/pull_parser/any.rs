@@ -18,6 +18,8 @@ mod error;
 /// FBX tree type with any supported version.
 #[non_exhaustive]
 pub enum AnyParser<R> {
+    /// FBX 6.x.
+    V6100(pull_parser::v6100::Parser<R>),
     /// FBX 7.4 or later.
     V7400(pull_parser::v7400::Parser<R>),
 }
@@ -26,6 +28,7 @@ impl<R: ParserSource> AnyParser<R> {
     /// Returns the parser version.
     pub fn parser_version(&self) -> ParserVersion {
         match self {
+            AnyParser::V6100(_) => ParserVersion::V6100,
             AnyParser::V7400(_) => pull_parser::v7400::Parser::<R>::PARSER_VERSION,
         }
     }
@@ -33,6 +36,7 @@ impl<R: ParserSource> AnyParser<R> {
     /// Returns the FBX version.
     pub fn fbx_version(&self) -> FbxVersion {
         match self {
+            AnyParser::V6100(parser) => parser.fbx_version(),
             AnyParser::V7400(parser) => parser.fbx_version(),
         }
     }
@@ -53,6 +57,16 @@ fn parser_version(header: FbxHeader) -> Result<ParserVersion> {
 pub fn from_reader<R: Read>(mut reader: R) -> Result<AnyParser<PlainSource<R>>> {
     let header = FbxHeader::load(&mut reader)?;
     match parser_version(header)? {
+        ParserVersion::V6100 => {
+            let parser = pull_parser::v6100::from_reader(header, reader).unwrap_or_else(|e| {
+                panic!(
+                    "Should never fail: FBX version {:?} should be supported by v6100 parser: {}",
+                    header.version(),
+                    e
+                )
+            });
+            Ok(AnyParser::V6100(parser))
+        }
         ParserVersion::V7400 => {
             let parser = pull_parser::v7400::from_reader(header, reader).unwrap_or_else(|e| {
                 panic!(
@@ -70,6 +84,17 @@ pub fn from_reader<R: Read>(mut reader: R) -> Result<AnyParser<PlainSource<R>>>
 pub fn from_seekable_reader<R: Read + Seek>(mut reader: R) -> Result<AnyParser<SeekableSource<R>>> {
     let header = FbxHeader::load(&mut reader)?;
     match parser_version(header)? {
+        ParserVersion::V6100 => {
+            let parser =
+                pull_parser::v6100::from_seekable_reader(header, reader).unwrap_or_else(|e| {
+                    panic!(
+                    "Should never fail: FBX version {:?} should be supported by v6100 parser: {}",
+                    header.version(),
+                    e
+                )
+                });
+            Ok(AnyParser::V6100(parser))
+        }
         ParserVersion::V7400 => {
             let parser =
                 pull_parser::v7400::from_seekable_reader(header, reader).unwrap_or_else(|e| {
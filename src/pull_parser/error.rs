@@ -7,9 +7,9 @@ use std::{error, fmt, io};
 use crate::pull_parser::SyntacticPosition;
 
 pub use self::{
-    data::{Compression, DataError},
+    data::{Compression, DataError, Limit},
     operation::OperationError,
-    warning::Warning,
+    warning::{Warning, WarningKind},
 };
 
 mod data;
@@ -4,6 +4,8 @@
 
 use std::{error, fmt, string::FromUtf8Error};
 
+use crate::low::HeaderError;
+
 /// Data error.
 #[derive(Debug)]
 #[non_exhaustive]
@@ -17,14 +19,32 @@ pub enum DataError {
     BrokenFbxFooter,
     /// Got an unknown array attribute encoding.
     InvalidArrayAttributeEncoding(u32),
+    /// Invalid syntax of FBX ASCII.
+    ///
+    /// The string is a description of the error.
+    InvalidAsciiSyntax(String),
     /// Invalid node attribute type code.
     ///
     /// The `u8` is the code the parser got.
     InvalidAttributeTypeCode(u8),
+    /// Invalid FBX binary header.
+    ///
+    /// This error is returned by parsers which read the header by themselves.
+    InvalidFbxHeader(HeaderError),
     /// Invalid node name encoding.
     ///
     /// This error indicates that the node name is non-valid UTF-8.
     InvalidNodeNameEncoding(FromUtf8Error),
+    /// Parser limit exceeded.
+    ///
+    /// This error indicates that the data exceeds the limit set by
+    /// [`ParserLimits`][`crate::pull_parser::v7400::ParserLimits`].
+    /// The position of the error is available from
+    /// [`Error::position()`][`crate::pull_parser::Error::position`].
+    ///
+    /// The former is the exceeded limit, the latter `u64` is the value which
+    /// exceeds the limit.
+    LimitExceeded(Limit, u64),
     /// Node attribute error.
     ///
     /// This error indicates that some error happened while reading node
@@ -50,6 +70,7 @@ impl error::Error for DataError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             DataError::BrokenCompression(_, e) => Some(e.as_ref()),
+            DataError::InvalidFbxHeader(e) => Some(e),
             DataError::InvalidNodeNameEncoding(e) => Some(e),
             _ => None,
         }
@@ -68,12 +89,19 @@ impl fmt::Display for DataError {
             DataError::InvalidArrayAttributeEncoding(encoding) => {
                 write!(f, "Unknown array attribute encoding: got {:?}", encoding)
             }
+            DataError::InvalidAsciiSyntax(desc) => {
+                write!(f, "Invalid FBX ASCII syntax: {}", desc)
+            }
             DataError::InvalidAttributeTypeCode(code) => {
                 write!(f, "Invalid node attribute type code: {:?}", code)
             }
+            DataError::InvalidFbxHeader(e) => write!(f, "Invalid FBX binary header: {}", e),
             DataError::InvalidNodeNameEncoding(e) => {
                 write!(f, "Invalid node name encoding: {:?}", e)
             }
+            DataError::LimitExceeded(limit, value) => {
+                write!(f, "Parser limit exceeded: {} is {}", limit, value)
+            }
             DataError::NodeAttributeError => {
                 write!(f, "Some error occured while reading node attributes")
             }
@@ -97,3 +125,34 @@ pub enum Compression {
     /// ZLIB compression.
     Zlib,
 }
+
+/// Resource limit of the parser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Limit {
+    /// Depth of nodes.
+    Depth,
+    /// Number of attributes of a node.
+    AttributesPerNode,
+    /// Number of elements of an array attribute.
+    ArrayElements,
+    /// Total byte length of decompressed array attributes.
+    DecompressedBytes,
+    /// Byte length of a string or binary attribute.
+    StringLength,
+    /// Number of nodes.
+    NodeCount,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Limit::Depth => "node depth",
+            Limit::AttributesPerNode => "number of node attributes",
+            Limit::ArrayElements => "number of array elements",
+            Limit::DecompressedBytes => "total decompressed length",
+            Limit::StringLength => "string or binary length",
+            Limit::NodeCount => "number of nodes",
+        })
+    }
+}
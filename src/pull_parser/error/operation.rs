@@ -12,8 +12,18 @@ pub enum OperationError {
     AlreadyAborted,
     /// Attempt to parse more data while the parsing is (successfully) finished.
     AlreadyFinished,
+    /// Attempt to read the FBX footer which does not exist.
+    ///
+    /// FBX ASCII documents have no footer, and parsers for a subtree (created
+    /// by [`NodeIndex::parser_at`][`crate::pull_parser::v7400::index::NodeIndex::parser_at`])
+    /// do not read the footer.
+    FooterUnavailable,
     /// Attempt to create a parser with unsupported FBX version.
     UnsupportedFbxVersion(ParserVersion, FbxVersion),
+    /// Attempt to read the byte layout of attributes which does not exist.
+    ///
+    /// FBX ASCII documents have no binary attribute layouts.
+    AttributeLayoutUnavailable,
 }
 
 impl error::Error for OperationError {}
@@ -28,11 +38,18 @@ impl fmt::Display for OperationError {
                 f,
                 "Attempt to parse more data while the parsing is successfully finished"
             ),
+            OperationError::FooterUnavailable => {
+                write!(f, "Attempt to read the FBX footer which does not exist")
+            }
             OperationError::UnsupportedFbxVersion(parser, fbx) => write!(
                 f,
                 "Unsupported FBX version: parser={:?}, fbx={:?}",
                 parser, fbx
             ),
+            OperationError::AttributeLayoutUnavailable => write!(
+                f,
+                "Attempt to read the byte layout of attributes which does not exist"
+            ),
         }
     }
 }
@@ -26,6 +26,20 @@ pub enum Warning {
     UnexpectedFooterFieldValue,
 }
 
+impl Warning {
+    /// Returns the kind of the warning.
+    pub fn kind(&self) -> WarningKind {
+        match self {
+            Warning::EmptyNodeName => WarningKind::EmptyNodeName,
+            Warning::ExtraNodeEndMarker => WarningKind::ExtraNodeEndMarker,
+            Warning::IncorrectBooleanRepresentation => WarningKind::IncorrectBooleanRepresentation,
+            Warning::InvalidFooterPaddingLength(..) => WarningKind::InvalidFooterPaddingLength,
+            Warning::MissingNodeEndMarker => WarningKind::MissingNodeEndMarker,
+            Warning::UnexpectedFooterFieldValue => WarningKind::UnexpectedFooterFieldValue,
+        }
+    }
+}
+
 impl error::Error for Warning {}
 
 impl fmt::Display for Warning {
@@ -46,3 +60,21 @@ impl fmt::Display for Warning {
         }
     }
 }
+
+/// Kind of a [`Warning`], without its details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// [`Warning::EmptyNodeName`].
+    EmptyNodeName,
+    /// [`Warning::ExtraNodeEndMarker`].
+    ExtraNodeEndMarker,
+    /// [`Warning::IncorrectBooleanRepresentation`].
+    IncorrectBooleanRepresentation,
+    /// [`Warning::InvalidFooterPaddingLength`].
+    InvalidFooterPaddingLength,
+    /// [`Warning::MissingNodeEndMarker`].
+    MissingNodeEndMarker,
+    /// [`Warning::UnexpectedFooterFieldValue`].
+    UnexpectedFooterFieldValue,
+}
@@ -16,7 +16,7 @@ use std::io;
 
 pub use self::{
     position_cache::PositionCacheReader,
-    source::{PlainSource, SeekableSource},
+    source::{PlainSource, SeekableSource, SliceSource},
 };
 
 mod position_cache;
@@ -93,6 +93,18 @@ pub trait ParserSource: Sized + io::Read {
             .expect("Attempt to skip backward");
         self.skip_distance(distance)
     }
+
+    /// Returns the whole data of the source, if it is in memory.
+    ///
+    /// The data should be indexed by the offsets returned by
+    /// [`position()`][`Self::position`].
+    /// Parsers use this to refer to node names in the data instead of copying
+    /// them.
+    ///
+    /// The default implementation returns `None`.
+    fn as_slice(&self) -> Option<&[u8]> {
+        None
+    }
 }
 
 impl<R: ParserSource> ParserSource for &mut R {
@@ -107,4 +119,8 @@ impl<R: ParserSource> ParserSource for &mut R {
     fn skip_to(&mut self, pos: u64) -> io::Result<()> {
         (**self).skip_to(pos)
     }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        (**self).as_slice()
+    }
 }
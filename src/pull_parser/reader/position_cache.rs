@@ -84,7 +84,7 @@ impl<R: io::Read> PositionCacheReader<R> {
         R: io::Seek,
     {
         while distance > 0 {
-            let part = std::cmp::min(distance, std::i64::MAX as u64);
+            let part = std::cmp::min(distance, i64::MAX as u64);
             self.inner.seek(SeekFrom::Current(part as i64))?;
             self.advance(part as usize);
             distance -= part;
@@ -174,7 +174,7 @@ mod tests {
         // So here use `0..size`, not `OFFSET..(OFFSET+size)`.
         assert_eq!(
             &buf[..size],
-            &(0..size as u8).into_iter().collect::<Vec<u8>>()[..],
+            &(0..size as u8).collect::<Vec<u8>>()[..],
             "Read should obtain correct data"
         );
         assert_eq!(
@@ -155,3 +155,110 @@ impl<R: io::Read + io::Seek> ParserSource for SeekableSource<R> {
         self.inner.skip_distance(distance)
     }
 }
+
+/// Source with in-memory byte slice backend.
+///
+/// The whole data is kept in memory, so the parser can borrow node names and
+/// attribute values directly from the data instead of copying them.
+/// See [`from_slice`][`crate::pull_parser::v7400::from_slice`] for usage.
+///
+/// Offsets (i.e. positions) are indices of the slice.
+#[derive(Debug, Clone, Copy)]
+pub struct SliceSource<'a> {
+    /// Whole data.
+    data: &'a [u8],
+    /// Offset of a byte which would be read next.
+    ///
+    /// `pos <= data.len()` always holds.
+    pos: usize,
+}
+
+impl<'a> SliceSource<'a> {
+    /// Creates a new `SliceSource`.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Creates a new `SliceSource` starting from the given offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset is larger than the length of the data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use fbxcel::pull_parser::reader::SliceSource;
+    /// use fbxcel::pull_parser::ParserSource;
+    ///
+    /// let msg = b"Hello, world!";
+    /// let mut reader = SliceSource::with_offset(msg, 7);
+    ///
+    /// assert_eq!(reader.position(), 7);
+    /// assert_eq!(reader.read_slice(5).expect("Should never fail"), b"world");
+    /// assert_eq!(reader.position(), 12);
+    /// ```
+    pub fn with_offset(data: &'a [u8], offset: usize) -> Self {
+        assert!(
+            offset <= data.len(),
+            "The offset should not exceed the data length"
+        );
+        Self { data, pos: offset }
+    }
+
+    /// Returns the whole data.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Reads the given length of bytes without copying them.
+    ///
+    /// Returns [`std::io::ErrorKind::UnexpectedEof`] error if the rest of the
+    /// data is shorter than the length.
+    pub fn read_slice(&mut self, len: u64) -> io::Result<&'a [u8]> {
+        let rest = &self.data[self.pos..];
+        if (rest.len() as u64) < len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let len = len as usize;
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+}
+
+impl io::Read for SliceSource<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut rest = &self.data[self.pos..];
+        let len = rest.read(buf)?;
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+impl io::BufRead for SliceSource<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(&self.data[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = self
+            .pos
+            .checked_add(amt)
+            .filter(|&pos| pos <= self.data.len())
+            .expect("Attempt to consume more bytes than available");
+    }
+}
+
+impl ParserSource for SliceSource<'_> {
+    fn position(&self) -> u64 {
+        self.pos as u64
+    }
+
+    fn skip_distance(&mut self, distance: u64) -> io::Result<()> {
+        self.read_slice(distance).map(drop)
+    }
+
+    fn as_slice(&self) -> Option<&[u8]> {
+        Some(self.data)
+    }
+}
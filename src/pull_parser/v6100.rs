@@ -0,0 +1,56 @@
+//! Parser-related stuff for FBX 6.x.
+//!
+//! FBX 6.x binary has the same low-level node structure as FBX 7.4 binary
+//! (node headers with 32-bit fields, same attribute types and array
+//! encodings, and same footer), so the parser and related types are shared with
+//! [`v7400`][`super::v7400`] module.
+//! Parsers created by this module report [`ParserVersion::V6100`] by
+//! [`Parser::parser_version()`].
+//!
+//! Note that the document structure of FBX 6.x (such as object connections
+//! and property nodes) is different from FBX 7.x.
+//! The parser does not interpret them, and emits nodes as they are.
+//!
+//! To see how to setup a parser, see module documentation of [`pull_parser`][`super`].
+
+use std::io;
+
+use crate::{
+    low::FbxHeader,
+    pull_parser::{
+        reader::{PlainSource, SeekableSource},
+        ParserVersion, Result,
+    },
+};
+
+pub use crate::pull_parser::v7400::{
+    attribute, Attributes, Event, LoadAttribute, Parser, StartNode,
+};
+
+/// Creates a new [`Parser`] for FBX 6.x from the given reader.
+///
+/// Returns an error if the given FBX version in unsupported.
+pub fn from_reader<R>(header: FbxHeader, reader: R) -> Result<Parser<PlainSource<R>>>
+where
+    R: io::Read,
+{
+    Parser::create_with_parser_version(
+        ParserVersion::V6100,
+        header.version(),
+        PlainSource::with_offset(reader, header.len()),
+    )
+}
+
+/// Creates a new [`Parser`] for FBX 6.x from the given seekable reader.
+///
+/// Returns an error if the given FBX version in unsupported.
+pub fn from_seekable_reader<R>(header: FbxHeader, reader: R) -> Result<Parser<SeekableSource<R>>>
+where
+    R: io::Read + io::Seek,
+{
+    Parser::create_with_parser_version(
+        ParserVersion::V6100,
+        header.version(),
+        SeekableSource::with_offset(reader, header.len()),
+    )
+}
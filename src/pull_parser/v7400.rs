@@ -2,14 +2,22 @@
 //!
 //! To see how to setup a parser, see module documentation of [`pull_parser`][`super`].
 
+pub(crate) use self::parser::{load_binary_header, read_node_header_and_name};
 pub(crate) use self::read::{FromParser, FromReader};
 pub use self::{
     attribute::{Attributes, LoadAttribute},
     event::{Event, StartNode},
+    limits::ParserLimits,
     parser::{from_reader, from_seekable_reader, Parser},
+    slice::{from_slice, SliceParser},
 };
 
+pub mod ascii;
 pub mod attribute;
 mod event;
+pub mod index;
+mod limits;
 mod parser;
 mod read;
+pub mod slice;
+pub mod validate;
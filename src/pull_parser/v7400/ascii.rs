@@ -0,0 +1,495 @@
+//! Parser for FBX ASCII format.
+//!
+//! FBX ASCII documents have the same node structure as FBX binary documents,
+//! so the parser for them is [`v7400::Parser`][`Parser`] which emits the same
+//! events as for FBX binary.
+//! Any [`LoadAttribute`] loaders (and tree loaders) can be used with them.
+//!
+//! ```no_run
+//! use fbxcel::pull_parser::v7400::{ascii, Event};
+//!
+//! let file = std::fs::File::open("sample.fbx").expect("Failed to open file");
+//! // The parser reads the source byte by byte, so it is recommended to use
+//! // buffered readers.
+//! let reader = std::io::BufReader::new(file);
+//! let mut parser = ascii::from_seekable_reader(reader).expect("Failed to create parser");
+//! loop {
+//!     match parser.next_event().expect("Failed to get next event") {
+//!         Event::StartNode(start) => println!("Start node: {}", start.name()),
+//!         Event::EndNode => println!("End node"),
+//!         Event::EndFbx(_) => break,
+//!     }
+//! }
+//! ```
+//!
+//! # Differences from FBX binary
+//!
+//! FBX ASCII format does not have type information of the attributes, so the
+//! parser guesses it from the literals:
+//!
+//! * Integers are reported as `i32` if they fit, as `i64` otherwise.
+//! * Reals (including `nan` and `inf`) are reported as `f64`.
+//! * Single-letter words such as `Y` and `T` are reported as booleans.
+//! * Quoted strings are reported as strings.
+//!     + Binaries are written as base64 strings in FBX ASCII.
+//!       If the loader [expects a binary][`LoadAttribute::expects_binary`],
+//!       a quoted string which is valid base64 is decoded and reported as a
+//!       binary.
+//!       Otherwise (for example, with tree loaders) it is reported as a
+//!       string as is.
+//!     + Object names in the form of `"Class::Name"` are converted to the form
+//!       used by FBX binary, i.e. `"Name\u{0}\u{1}Class"`.
+//!       Object names are the first string attributes of the nodes directly
+//!       under the top-level `Objects` node, and other strings are reported
+//!       as is.
+//! * Arrays (`*N { a: ... }`) are reported as `f64` arrays if they have reals,
+//!   as `i32` or `i64` arrays if they have only integers (using the same rule as
+//!   scalar integers), and as boolean arrays if they have only booleans.
+//!     + Empty arrays are reported as `f64` arrays.
+//!
+//! FBX ASCII documents have no footer, so the parser emits
+//! [`Event::EndFbx`][`super::Event::EndFbx`] with an error created from
+//! [`OperationError::FooterUnavailable`].
+//!
+//! The FBX version is read from the header comment (such as
+//! `; FBX 7.4.0 project file`).
+//! If the comment is missing, FBX 7.4 is assumed.
+//! FBX 6.x ASCII documents are also accepted, and the parser reports
+//! [`ParserVersion::V6100`][`crate::pull_parser::ParserVersion::V6100`] for
+//! them.
+
+use std::{collections::VecDeque, convert::TryFrom, io};
+
+use log::warn;
+
+use crate::{
+    low::{v7400::AttributeValue, FbxVersion},
+    pull_parser::{
+        error::{DataError, Limit, OperationError},
+        reader::{PlainSource, SeekableSource},
+        v7400::{LoadAttribute, Parser, ParserLimits},
+        ParserSource, Result,
+    },
+};
+
+use self::lexer::Lexer;
+pub(crate) use self::lexer::Token;
+
+mod lexer;
+
+/// Creates a new [`Parser`] for FBX ASCII from the given reader.
+///
+/// Returns an error if the FBX version declared in the document is
+/// unsupported.
+pub fn from_reader<R>(reader: R) -> Result<Parser<PlainSource<R>>>
+where
+    R: io::Read,
+{
+    create(PlainSource::new(reader))
+}
+
+/// Creates a new [`Parser`] for FBX ASCII from the given seekable reader.
+///
+/// Returns an error if the FBX version declared in the document is
+/// unsupported.
+pub fn from_seekable_reader<R>(reader: R) -> Result<Parser<SeekableSource<R>>>
+where
+    R: io::Read + io::Seek,
+{
+    create(SeekableSource::new(reader))
+}
+
+/// Reads the header comment and creates a new ASCII parser.
+fn create<R: ParserSource>(mut reader: R) -> Result<Parser<R>> {
+    let mut lexer = Lexer::new();
+    let fbx_version = match lexer.read_header_version(&mut reader)? {
+        Some(raw) => FbxVersion::new(raw),
+        None => {
+            warn!("FBX ASCII header comment not found, assuming FBX 7.4");
+            FbxVersion::V7_4
+        }
+    };
+    Parser::create_ascii(fbx_version, reader, AsciiState::new(lexer))
+}
+
+/// Node attribute read from FBX ASCII.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AsciiAttribute {
+    /// Start offset of the attribute.
+    pub(crate) offset: u64,
+    /// Attribute value.
+    pub(crate) value: AttributeValue,
+    /// Whether the attribute has incorrect boolean representation.
+    pub(crate) has_incorrect_boolean: bool,
+}
+
+/// ASCII-specific parser state.
+#[derive(Debug, Clone)]
+pub(crate) struct AsciiState {
+    /// Lexer.
+    lexer: Lexer,
+    /// Whether the current node has no children block and ends right after
+    /// the attributes.
+    pub(crate) pending_end: bool,
+    /// Unread attributes of the current node.
+    pub(crate) attributes: VecDeque<AsciiAttribute>,
+}
+
+impl AsciiState {
+    /// Creates a new `AsciiState`.
+    fn new(lexer: Lexer) -> Self {
+        Self {
+            lexer,
+            pending_end: false,
+            attributes: VecDeque::new(),
+        }
+    }
+
+    /// Returns the offset of the next token.
+    pub(crate) fn position<R: ParserSource>(&self, reader: &R) -> u64 {
+        self.lexer.position(reader)
+    }
+
+    /// Returns the next token and its offset.
+    pub(crate) fn next_token<R: ParserSource>(&mut self, reader: &mut R) -> Result<(u64, Token)> {
+        self.lexer.next_token(reader)
+    }
+
+    /// Reads the attributes of a node whose name is already read, and stores
+    /// them to `self.attributes`.
+    ///
+    /// The limits for attributes are checked while the attributes are read.
+    ///
+    /// If `is_object` is true, the first string attribute is read as an
+    /// object name.
+    ///
+    /// Returns `true` if the node has a children block (`{ ... }`).
+    pub(crate) fn read_attributes<R: ParserSource>(
+        &mut self,
+        reader: &mut R,
+        limits: &ParserLimits,
+        is_object: bool,
+    ) -> Result<bool> {
+        self.attributes.clear();
+        self.lexer.max_string_len = limits.max_string_len;
+
+        let (mut pos, mut token) = self.lexer.next_token(reader)?;
+        // FBX SDK emits a leading comma for some attributes (such as
+        // `Content: , "..."`).
+        let leading_comma = token == Token::Comma;
+        if leading_comma {
+            let next = self.lexer.next_token(reader)?;
+            pos = next.0;
+            token = next.1;
+        }
+        if token.is_value_start() {
+            let mut object_name_pending = is_object;
+            loop {
+                limits.check(Limit::AttributesPerNode, self.attributes.len() as u64 + 1)?;
+                let is_object_name = object_name_pending && matches!(token, Token::String(_));
+                if is_object_name {
+                    object_name_pending = false;
+                }
+                let attr = self.read_value(reader, pos, token, limits, is_object_name)?;
+                self.attributes.push_back(attr);
+                let (next_pos, next) = self.lexer.next_token(reader)?;
+                if next != Token::Comma {
+                    pos = next_pos;
+                    token = next;
+                    break;
+                }
+                let (next_pos, next) = self.lexer.next_token(reader)?;
+                if !next.is_value_start() {
+                    return Err(unexpected(next_pos, &next, "attribute value"));
+                }
+                pos = next_pos;
+                token = next;
+            }
+        } else if leading_comma {
+            return Err(unexpected(pos, &token, "attribute value"));
+        }
+
+        if token == Token::OpenBrace {
+            return Ok(true);
+        }
+        self.lexer.push_back(pos, token);
+        Ok(false)
+    }
+
+    /// Reads an attribute value starting with the given token.
+    ///
+    /// If `is_object_name` is true, a string is converted to the object name
+    /// form used by FBX binary.
+    fn read_value<R: ParserSource>(
+        &mut self,
+        reader: &mut R,
+        offset: u64,
+        token: Token,
+        limits: &ParserLimits,
+        is_object_name: bool,
+    ) -> Result<AsciiAttribute> {
+        let (value, has_incorrect_boolean) = match token {
+            Token::String(s) if is_object_name => {
+                (AttributeValue::String(object_name_to_binary(s)), false)
+            }
+            Token::String(s) => (AttributeValue::String(s), false),
+            Token::ArrayLength(len) => self.read_array(reader, offset, len, limits)?,
+            token => match scalar(offset, &token)? {
+                Scalar::Bool(v, incorrect) => (AttributeValue::Bool(v), incorrect),
+                Scalar::Int(v) => (int_value(v), false),
+                Scalar::Real(v) => (AttributeValue::F64(v), false),
+            },
+        };
+
+        Ok(AsciiAttribute {
+            offset,
+            value,
+            has_incorrect_boolean,
+        })
+    }
+
+    /// Reads an array block (`{ a: ... }`) after `*N`.
+    ///
+    /// Both the declared length and the actual number of elements are
+    /// checked against the limit.
+    fn read_array<R: ParserSource>(
+        &mut self,
+        reader: &mut R,
+        offset: u64,
+        len: u64,
+        limits: &ParserLimits,
+    ) -> Result<(AttributeValue, bool)> {
+        limits.check(Limit::ArrayElements, len)?;
+        match self.lexer.next_token(reader)? {
+            (_, Token::OpenBrace) => {}
+            (pos, token) => return Err(unexpected(pos, &token, "`{`")),
+        }
+        match self.lexer.next_token(reader)? {
+            (_, Token::Name(_)) => {}
+            (_, Token::CloseBrace) if len == 0 => {
+                return Ok((AttributeValue::ArrF64(Vec::new()), false))
+            }
+            (pos, token) => return Err(unexpected(pos, &token, "array content (`a:`)")),
+        }
+
+        // Avoid huge allocation for broken length.
+        let mut elements = Vec::with_capacity(len.min(0x1_0000) as usize);
+        loop {
+            let (pos, token) = self.lexer.next_token(reader)?;
+            if token == Token::CloseBrace {
+                // Empty array, or a trailing comma.
+                break;
+            }
+            limits.check(Limit::ArrayElements, elements.len() as u64 + 1)?;
+            elements.push(scalar(pos, &token)?);
+            match self.lexer.next_token(reader)? {
+                (_, Token::Comma) => {}
+                (_, Token::CloseBrace) => break,
+                (pos, token) => return Err(unexpected(pos, &token, "`,` or `}`")),
+            }
+        }
+
+        if elements.len() as u64 != len {
+            return Err(DataError::InvalidAsciiSyntax(format!(
+                "array length mismatch at offset {}: declared {}, got {}",
+                offset,
+                len,
+                elements.len()
+            ))
+            .into());
+        }
+
+        array_value(offset, elements)
+    }
+}
+
+/// Scalar literal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scalar {
+    /// Boolean, and whether the representation is incorrect.
+    Bool(bool, bool),
+    /// Integer.
+    Int(i64),
+    /// Real.
+    Real(f64),
+}
+
+/// Interprets the given token as a scalar literal.
+fn scalar(offset: u64, token: &Token) -> Result<Scalar> {
+    match token {
+        Token::Number(s) => {
+            if let Ok(v) = s.parse::<i64>() {
+                return Ok(Scalar::Int(v));
+            }
+            s.parse::<f64>().map(Scalar::Real).map_err(|_| {
+                DataError::InvalidAsciiSyntax(format!(
+                    "invalid number {:?} at offset {}",
+                    s, offset
+                ))
+                .into()
+            })
+        }
+        Token::Word(s) if s.len() == 1 => {
+            let raw = s.as_bytes()[0];
+            Ok(Scalar::Bool((raw & 1) != 0, raw != b'T' && raw != b'Y'))
+        }
+        Token::Word(s) => s.parse::<f64>().map(Scalar::Real).map_err(|_| {
+            DataError::InvalidAsciiSyntax(format!("invalid value {:?} at offset {}", s, offset))
+                .into()
+        }),
+        token => Err(unexpected(offset, token, "scalar value")),
+    }
+}
+
+/// Creates an integer attribute value.
+fn int_value(v: i64) -> AttributeValue {
+    match i32::try_from(v) {
+        Ok(v) => AttributeValue::I32(v),
+        Err(_) => AttributeValue::I64(v),
+    }
+}
+
+/// Creates an array attribute value from the given elements.
+fn array_value(offset: u64, elements: Vec<Scalar>) -> Result<(AttributeValue, bool)> {
+    let mut has_bool = false;
+    let mut has_int = false;
+    let mut has_real = false;
+    let mut has_i64 = false;
+    let mut has_incorrect_boolean = false;
+    for elem in &elements {
+        match *elem {
+            Scalar::Bool(_, incorrect) => {
+                has_bool = true;
+                has_incorrect_boolean |= incorrect;
+            }
+            Scalar::Int(v) => {
+                has_int = true;
+                has_i64 |= i32::try_from(v).is_err();
+            }
+            Scalar::Real(_) => has_real = true,
+        }
+    }
+    if has_bool && (has_int || has_real) {
+        return Err(DataError::InvalidAsciiSyntax(format!(
+            "array at offset {} has both booleans and numbers",
+            offset
+        ))
+        .into());
+    }
+
+    let value = if has_bool {
+        AttributeValue::ArrBool(
+            elements
+                .into_iter()
+                .map(|v| matches!(v, Scalar::Bool(true, _)))
+                .collect(),
+        )
+    } else if has_real || elements.is_empty() {
+        AttributeValue::ArrF64(
+            elements
+                .into_iter()
+                .map(|v| match v {
+                    Scalar::Int(v) => v as f64,
+                    Scalar::Real(v) => v,
+                    Scalar::Bool(..) => unreachable!("Should never happen: checked above"),
+                })
+                .collect(),
+        )
+    } else {
+        let ints = elements.into_iter().map(|v| match v {
+            Scalar::Int(v) => v,
+            _ => unreachable!("Should never happen: checked above"),
+        });
+        if has_i64 {
+            AttributeValue::ArrI64(ints.collect())
+        } else {
+            AttributeValue::ArrI32(ints.map(|v| v as i32).collect())
+        }
+    };
+
+    Ok((value, has_incorrect_boolean))
+}
+
+/// Creates an error for the unexpected token.
+fn unexpected(offset: u64, token: &Token, expected: &str) -> crate::pull_parser::Error {
+    DataError::InvalidAsciiSyntax(format!(
+        "expected {} but got {} at offset {}",
+        expected, token, offset
+    ))
+    .into()
+}
+
+/// Converts object name of the form `Class::Name` into the FBX binary form
+/// `Name\u{0}\u{1}Class`.
+///
+/// Strings without `::` are returned as is.
+fn object_name_to_binary(s: String) -> String {
+    match s.find("::") {
+        Some(sep) => format!("{}\u{0}\u{1}{}", &s[(sep + 2)..], &s[..sep]),
+        None => s,
+    }
+}
+
+/// Lets the loader load the given attribute value.
+pub(crate) fn load_value<V: LoadAttribute>(
+    value: AttributeValue,
+    loader: V,
+    buffered: bool,
+) -> Result<V::Output> {
+    match value {
+        AttributeValue::Bool(v) => loader.load_bool(v),
+        AttributeValue::I16(v) => loader.load_i16(v),
+        AttributeValue::I32(v) => loader.load_i32(v),
+        AttributeValue::I64(v) => loader.load_i64(v),
+        AttributeValue::F32(v) => loader.load_f32(v),
+        AttributeValue::F64(v) => loader.load_f64(v),
+        AttributeValue::ArrBool(v) => {
+            let len = v.len();
+            loader.load_seq_bool(v.into_iter().map(Ok), len)
+        }
+        AttributeValue::ArrI32(v) => {
+            let len = v.len();
+            loader.load_seq_i32(v.into_iter().map(Ok), len)
+        }
+        AttributeValue::ArrI64(v) => {
+            let len = v.len();
+            loader.load_seq_i64(v.into_iter().map(Ok), len)
+        }
+        AttributeValue::ArrF32(v) => {
+            let len = v.len();
+            loader.load_seq_f32(v.into_iter().map(Ok), len)
+        }
+        AttributeValue::ArrF64(v) => {
+            let len = v.len();
+            loader.load_seq_f64(v.into_iter().map(Ok), len)
+        }
+        AttributeValue::String(v) => {
+            if loader.expects_binary() {
+                if let Some(binary) = crate::base64::decode(&v) {
+                    return load_value(AttributeValue::Binary(binary), loader, buffered);
+                }
+            }
+            let len = v.len() as u64;
+            let reader = io::Cursor::new(v.into_bytes());
+            if buffered {
+                loader.load_string_buffered(reader, len)
+            } else {
+                loader.load_string(reader, len)
+            }
+        }
+        AttributeValue::Binary(v) => {
+            let len = v.len() as u64;
+            let reader = io::Cursor::new(v);
+            if buffered {
+                loader.load_binary_buffered(reader, len)
+            } else {
+                loader.load_binary(reader, len)
+            }
+        }
+    }
+}
+
+/// Returns an error indicating that FBX ASCII has no footer.
+pub(crate) fn footer_unavailable() -> crate::pull_parser::Error {
+    OperationError::FooterUnavailable.into()
+}
@@ -0,0 +1,422 @@
+//! Tokenizer for FBX ASCII format.
+
+use std::{fmt, io};
+
+use crate::pull_parser::{
+    error::{DataError, Limit},
+    ParserSource, Result,
+};
+
+/// Token of FBX ASCII format.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Token {
+    /// Node name followed by a colon (such as `Vertices:`).
+    Name(String),
+    /// Bare word (such as `Y`, `T`, or `nan`).
+    Word(String),
+    /// Number literal.
+    Number(String),
+    /// Quoted string (already unescaped).
+    String(String),
+    /// Array length prefix (such as `*42`).
+    ArrayLength(u64),
+    /// Comma.
+    Comma,
+    /// Opening brace.
+    OpenBrace,
+    /// Closing brace.
+    CloseBrace,
+    /// End of the stream.
+    Eof,
+}
+
+impl Token {
+    /// Returns `true` if the token starts an attribute value.
+    pub(crate) fn is_value_start(&self) -> bool {
+        matches!(
+            self,
+            Token::Word(_) | Token::Number(_) | Token::String(_) | Token::ArrayLength(_)
+        )
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Name(v) => write!(f, "node name {:?}", v),
+            Token::Word(v) => write!(f, "word {:?}", v),
+            Token::Number(v) => write!(f, "number {:?}", v),
+            Token::String(v) => write!(f, "string {:?}", v),
+            Token::ArrayLength(v) => write!(f, "array length `*{}`", v),
+            Token::Comma => f.write_str("`,`"),
+            Token::OpenBrace => f.write_str("`{`"),
+            Token::CloseBrace => f.write_str("`}`"),
+            Token::Eof => f.write_str("end of stream"),
+        }
+    }
+}
+
+/// Tokenizer for FBX ASCII format.
+///
+/// The lexer reads the source byte by byte, so it is recommended to use
+/// buffered readers as the source.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Lexer {
+    /// Byte read from the source but not yet consumed.
+    peeked_byte: Option<u8>,
+    /// Token read from the source but not yet consumed, and its offset.
+    peeked_token: Option<(u64, Token)>,
+    /// Maximum length of (unescaped) quoted strings.
+    pub(crate) max_string_len: Option<u64>,
+}
+
+impl Lexer {
+    /// Creates a new `Lexer`.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the offset of the next token (or byte) to be consumed.
+    pub(crate) fn position<R: ParserSource>(&self, reader: &R) -> u64 {
+        match &self.peeked_token {
+            Some((pos, _)) => *pos,
+            None => self.byte_position(reader),
+        }
+    }
+
+    /// Returns the offset of the next byte to be consumed.
+    fn byte_position<R: ParserSource>(&self, reader: &R) -> u64 {
+        reader.position() - u64::from(self.peeked_byte.is_some())
+    }
+
+    /// Returns the next byte without consuming it.
+    fn peek_byte<R: ParserSource>(&mut self, reader: &mut R) -> io::Result<Option<u8>> {
+        if self.peeked_byte.is_none() {
+            let mut buf = [0_u8];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) => return Ok(None),
+                    Ok(_) => break,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            self.peeked_byte = Some(buf[0]);
+        }
+        Ok(self.peeked_byte)
+    }
+
+    /// Consumes the peeked byte.
+    fn bump(&mut self) {
+        debug_assert!(self.peeked_byte.is_some(), "No bytes are peeked");
+        self.peeked_byte = None;
+    }
+
+    /// Returns the next byte, or an error if the stream ends.
+    fn next_byte_or_eof<R: ParserSource>(&mut self, reader: &mut R) -> io::Result<u8> {
+        match self.peek_byte(reader)? {
+            Some(b) => {
+                self.bump();
+                Ok(b)
+            }
+            None => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+
+    /// Skips whitespaces and comments.
+    fn skip_trivia<R: ParserSource>(&mut self, reader: &mut R) -> io::Result<()> {
+        while let Some(b) = self.peek_byte(reader)? {
+            match b {
+                b' ' | b'\t' | b'\r' | b'\n' => self.bump(),
+                b';' => {
+                    self.read_line(reader)?;
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads bytes until the end of the line, and returns them (without the
+    /// line terminator).
+    fn read_line<R: ParserSource>(&mut self, reader: &mut R) -> io::Result<Vec<u8>> {
+        let mut line = Vec::new();
+        while let Some(b) = self.peek_byte(reader)? {
+            self.bump();
+            if b == b'\n' {
+                break;
+            }
+            line.push(b);
+        }
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        Ok(line)
+    }
+
+    /// Reads the header comments and returns the raw FBX version declared
+    /// there (such as `7400` for `; FBX 7.4.0 project file`), if found.
+    ///
+    /// This should be called before any tokens are read.
+    pub(crate) fn read_header_version<R: ParserSource>(
+        &mut self,
+        reader: &mut R,
+    ) -> io::Result<Option<u32>> {
+        // Skip UTF-8 BOM.
+        if self.peek_byte(reader)? == Some(0xEF) {
+            self.bump();
+            for &expected in &[0xBB, 0xBF] {
+                if self.next_byte_or_eof(reader)? != expected {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Broken UTF-8 BOM at the beginning of FBX ASCII document",
+                    ));
+                }
+            }
+        }
+
+        let mut version = None;
+        loop {
+            match self.peek_byte(reader)? {
+                Some(b' ') | Some(b'\t') | Some(b'\r') | Some(b'\n') => self.bump(),
+                Some(b';') => {
+                    let line = self.read_line(reader)?;
+                    if version.is_none() {
+                        version = parse_version_comment(&String::from_utf8_lossy(&line));
+                    }
+                }
+                _ => return Ok(version),
+            }
+        }
+    }
+
+    /// Returns the next token and its offset.
+    pub(crate) fn next_token<R: ParserSource>(&mut self, reader: &mut R) -> Result<(u64, Token)> {
+        if let Some(token) = self.peeked_token.take() {
+            return Ok(token);
+        }
+
+        self.skip_trivia(reader)?;
+        let pos = self.byte_position(reader);
+        let first = match self.peek_byte(reader)? {
+            Some(b) => b,
+            None => return Ok((pos, Token::Eof)),
+        };
+        let token = match first {
+            b',' => {
+                self.bump();
+                Token::Comma
+            }
+            b'{' => {
+                self.bump();
+                Token::OpenBrace
+            }
+            b'}' => {
+                self.bump();
+                Token::CloseBrace
+            }
+            b'"' => {
+                self.bump();
+                Token::String(self.read_string(reader)?)
+            }
+            b'*' => {
+                self.bump();
+                let digits = self.read_while(reader, |b| b.is_ascii_digit())?;
+                let len = digits.parse::<u64>().map_err(|_| {
+                    DataError::InvalidAsciiSyntax(format!(
+                        "invalid array length `*{}` at offset {}",
+                        digits, pos
+                    ))
+                })?;
+                Token::ArrayLength(len)
+            }
+            b'0'..=b'9' | b'-' | b'+' | b'.' => Token::Number(self.read_while(reader, |b| {
+                b.is_ascii_alphanumeric() || b == b'.' || b == b'-' || b == b'+'
+            })?),
+            b if b.is_ascii_alphabetic() || b == b'_' => {
+                let ident = self.read_while(reader, |b| b.is_ascii_alphanumeric() || b == b'_')?;
+                while let Some(b' ') | Some(b'\t') = self.peek_byte(reader)? {
+                    self.bump();
+                }
+                if self.peek_byte(reader)? == Some(b':') {
+                    self.bump();
+                    Token::Name(ident)
+                } else {
+                    Token::Word(ident)
+                }
+            }
+            b => {
+                return Err(DataError::InvalidAsciiSyntax(format!(
+                    "unexpected character {:?} at offset {}",
+                    char::from(b),
+                    pos
+                ))
+                .into())
+            }
+        };
+
+        Ok((pos, token))
+    }
+
+    /// Pushes back the given token, so that it is returned by the next
+    /// `next_token()` call.
+    pub(crate) fn push_back(&mut self, pos: u64, token: Token) {
+        assert!(
+            self.peeked_token.is_none(),
+            "Only one token can be pushed back"
+        );
+        self.peeked_token = Some((pos, token));
+    }
+
+    /// Reads ASCII characters while the given predicate returns `true`.
+    fn read_while<R, F>(&mut self, reader: &mut R, pred: F) -> io::Result<String>
+    where
+        R: ParserSource,
+        F: Fn(u8) -> bool,
+    {
+        let mut s = String::new();
+        while let Some(b) = self.peek_byte(reader)? {
+            if !pred(b) {
+                break;
+            }
+            self.bump();
+            s.push(char::from(b));
+        }
+        Ok(s)
+    }
+
+    /// Reads the rest of a quoted string, and unescapes it.
+    ///
+    /// The opening double quote should be already consumed.
+    ///
+    /// If `max_string_len` is set, the escaped string is read up to
+    /// [`MAX_ESCAPE_LEN`] times the limit, and then the unescaped length is
+    /// checked.
+    fn read_string<R: ParserSource>(&mut self, reader: &mut R) -> Result<String> {
+        let start = self.byte_position(reader);
+        let max_raw_len = self
+            .max_string_len
+            .map(|max| max.saturating_mul(MAX_ESCAPE_LEN));
+        let mut bytes = Vec::new();
+        loop {
+            match self.next_byte_or_eof(reader)? {
+                b'"' => break,
+                b => bytes.push(b),
+            }
+            if let Some(max_raw_len) = max_raw_len {
+                if bytes.len() as u64 > max_raw_len {
+                    return Err(
+                        DataError::LimitExceeded(Limit::StringLength, bytes.len() as u64).into(),
+                    );
+                }
+            }
+        }
+        let raw = String::from_utf8(bytes).map_err(|_| {
+            DataError::InvalidAsciiSyntax(format!(
+                "non-UTF-8 string at offset {}",
+                start.saturating_sub(1)
+            ))
+        })?;
+        let s = unescape(&raw);
+        if let Some(max) = self.max_string_len {
+            if s.len() as u64 > max {
+                return Err(DataError::LimitExceeded(Limit::StringLength, s.len() as u64).into());
+            }
+        }
+        Ok(s)
+    }
+}
+
+/// Length of the longest escape sequence (`&quot;`) in FBX ASCII strings.
+const MAX_ESCAPE_LEN: u64 = 6;
+
+/// Unescapes the string content of FBX ASCII format.
+///
+/// FBX ASCII format does not allow double quotes and line breaks in strings,
+/// and they are written as `&quot;`, `&cr;`, and `&lf;`.
+fn unescape(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_owned();
+    }
+    s.replace("&quot;", "\"")
+        .replace("&cr;", "\r")
+        .replace("&lf;", "\n")
+}
+
+/// Parses the version from the header comment such as `FBX 7.4.0 project file`.
+fn parse_version_comment(line: &str) -> Option<u32> {
+    let rest = &line[(line.find("FBX ")? + 4)..];
+    let version = rest.split_whitespace().next()?;
+    let mut parts = version.split('.').map(str::parse::<u32>);
+    let major = parts.next()?.ok()?;
+    let minor = parts.next()?.ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    if minor >= 10 || patch >= 10 {
+        return None;
+    }
+    Some(major * 1000 + minor * 100 + patch * 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pull_parser::reader::PlainSource;
+
+    fn tokens(src: &str) -> Vec<Token> {
+        let mut reader = PlainSource::new(src.as_bytes());
+        let mut lexer = Lexer::new();
+        let mut tokens = Vec::new();
+        loop {
+            let (_, token) = lexer.next_token(&mut reader).expect("Should never fail");
+            if token == Token::Eof {
+                return tokens;
+            }
+            tokens.push(token);
+        }
+    }
+
+    #[test]
+    fn tokenize() {
+        let src =
+            "; comment\nNode : 1, -2.5e-3,\"a&quot;b\" ,Y {\n\tArr: *2 {\n\t\ta: 1,2\n\t}\n}\n";
+        assert_eq!(
+            tokens(src),
+            vec![
+                Token::Name("Node".into()),
+                Token::Number("1".into()),
+                Token::Comma,
+                Token::Number("-2.5e-3".into()),
+                Token::Comma,
+                Token::String("a\"b".into()),
+                Token::Comma,
+                Token::Word("Y".into()),
+                Token::OpenBrace,
+                Token::Name("Arr".into()),
+                Token::ArrayLength(2),
+                Token::OpenBrace,
+                Token::Name("a".into()),
+                Token::Number("1".into()),
+                Token::Comma,
+                Token::Number("2".into()),
+                Token::CloseBrace,
+                Token::CloseBrace,
+            ]
+        );
+    }
+
+    #[test]
+    fn header_version() {
+        let src = "\u{FEFF}; FBX 7.5.0 project file\n; ----\n\nFBXHeaderExtension:  {\n}";
+        let mut reader = PlainSource::new(src.as_bytes());
+        let mut lexer = Lexer::new();
+        let version = lexer
+            .read_header_version(&mut reader)
+            .expect("Should never fail");
+        assert_eq!(version, Some(7500));
+        assert_eq!(
+            lexer.next_token(&mut reader).expect("Should never fail").1,
+            Token::Name("FBXHeaderExtension".into())
+        );
+    }
+}
@@ -5,20 +5,86 @@ use std::io;
 use crate::{
     low::v7400::{ArrayAttributeHeader, AttributeType, SpecialAttributeHeader},
     pull_parser::{
-        error::DataError,
-        v7400::{FromReader, Parser},
+        error::{DataError, Limit, OperationError},
+        reader::SliceSource,
+        v7400::{ascii, slice::SliceAttribute, FromReader, Parser},
         ParserSource, Result, SyntacticPosition, Warning,
     },
 };
 
-use self::array::{ArrayAttributeValues, AttributeStreamDecoder, BooleanArrayAttributeValues};
 pub use self::loader::LoadAttribute;
+use self::{
+    array::{ArrayAttributeValues, AttributeStreamDecoder, BooleanArrayAttributeValues},
+    loaders::DirectLoader,
+};
+#[cfg(feature = "derive")]
+#[cfg_attr(feature = "docsrs", doc(cfg(feature = "derive")))]
+pub use fbxcel_derive::LoadAttribute;
 
 mod array;
 pub mod iter;
 mod loader;
 pub mod loaders;
 
+/// Array attribute header and raw elements captured during loading.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct ArrayCapture {
+    /// Whether to keep the raw elements.
+    keep_raw: bool,
+    /// Array attribute header.
+    pub(crate) header: Option<ArrayAttributeHeader>,
+    /// Raw (possibly compressed) elements.
+    pub(crate) raw: Option<Vec<u8>>,
+}
+
+/// Byte layout of a node attribute in FBX binary.
+///
+/// This is returned by [`Attributes::next_layout()`] and
+/// [`Attributes::load_next_with_layout()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AttributeLayout {
+    /// Start offset of the attribute (i.e. the offset of the type code).
+    start_offset: u64,
+    /// End offset of the attribute.
+    end_offset: u64,
+    /// Attribute type.
+    type_: AttributeType,
+    /// Array attribute header.
+    array_header: Option<ArrayAttributeHeader>,
+}
+
+impl AttributeLayout {
+    /// Returns the start offset of the attribute (i.e. the offset of the type
+    /// code).
+    pub fn start_offset(&self) -> u64 {
+        self.start_offset
+    }
+
+    /// Returns the end offset of the attribute.
+    ///
+    /// "End offset" means a next byte of the last byte of the attribute.
+    pub fn end_offset(&self) -> u64 {
+        self.end_offset
+    }
+
+    /// Returns the attribute type.
+    pub fn type_(&self) -> AttributeType {
+        self.type_
+    }
+
+    /// Returns the raw type code of the attribute.
+    pub fn type_code(&self) -> u8 {
+        self.type_.type_code()
+    }
+
+    /// Returns the array attribute header.
+    ///
+    /// Returns `None` if the attribute is not an array.
+    pub fn array_header(&self) -> Option<ArrayAttributeHeader> {
+        self.array_header
+    }
+}
+
 /// Node attributes reader.
 #[derive(Debug)]
 pub struct Attributes<'a, R> {
@@ -39,7 +105,10 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
     /// Creates a new `Attributes`.
     pub(crate) fn from_parser(parser: &'a mut Parser<R>) -> Self {
         let total_count = parser.current_attributes_count();
-        let pos = parser.reader().position();
+        let pos = match parser.next_ascii_attribute_offset() {
+            Some(pos) => pos,
+            None => parser.reader().position(),
+        };
         Self {
             total_count,
             rest_count: total_count,
@@ -48,6 +117,12 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
         }
     }
 
+    /// Returns `Ok(())` if the parser can continue parsing.
+    #[cfg(feature = "tree")]
+    pub(crate) fn ensure_continuable(&self) -> Result<()> {
+        self.parser.ensure_continuable()
+    }
+
     /// Returns the total number of attributes.
     pub fn total_count(&self) -> u64 {
         self.total_count
@@ -83,8 +158,14 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
             Ok(v) => Ok(v),
             Err(e) => {
                 let err_pos = self.position(start_pos, attr_index);
-                self.parser.set_aborted(err_pos.clone());
-                Err(e.and_position(err_pos))
+                let e = e.and_position(err_pos.clone());
+                if self.parser.recover(&e) {
+                    // The rest attributes are skipped with the node.
+                    self.rest_count = 0;
+                } else {
+                    self.parser.set_aborted(err_pos);
+                }
+                Err(e)
             }
         }
     }
@@ -110,18 +191,122 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
         Ok(Some(attr_type))
     }
 
+    /// Reads the byte layout of the next node attribute, and skips its value.
+    ///
+    /// Only the type code and the array attribute header (if any) are read,
+    /// so the value is neither loaded nor decompressed.
+    /// Use this instead of [`load_next`][`Self::load_next`] to inspect the
+    /// attribute; the skipped value cannot be loaded afterwards.
+    ///
+    /// Returns [`OperationError::AttributeLayoutUnavailable`] error for FBX
+    /// ASCII.
+    pub fn next_layout(&mut self) -> Result<Option<AttributeLayout>> {
+        if self.parser.is_ascii() {
+            return Err(OperationError::AttributeLayoutUnavailable.into());
+        }
+        self.do_with_health_check(|this, start_pos, _| {
+            let type_ = match this.read_next_attr_type()? {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+            let array_header = match type_ {
+                AttributeType::Bool => {
+                    this.update_next_attr_start_offset(1);
+                    None
+                }
+                AttributeType::I16 => {
+                    this.update_next_attr_start_offset(2);
+                    None
+                }
+                AttributeType::I32 | AttributeType::F32 => {
+                    this.update_next_attr_start_offset(4);
+                    None
+                }
+                AttributeType::I64 | AttributeType::F64 => {
+                    this.update_next_attr_start_offset(8);
+                    None
+                }
+                AttributeType::ArrBool
+                | AttributeType::ArrI32
+                | AttributeType::ArrI64
+                | AttributeType::ArrF32
+                | AttributeType::ArrF64 => {
+                    let header = ArrayAttributeHeader::from_reader(this.parser.reader())?;
+                    this.update_next_attr_start_offset(u64::from(header.bytelen));
+                    Some(header)
+                }
+                AttributeType::Binary | AttributeType::String => {
+                    let header = this.parser.parse::<SpecialAttributeHeader>()?;
+                    this.update_next_attr_start_offset(u64::from(header.bytelen));
+                    None
+                }
+            };
+            Ok(Some(AttributeLayout {
+                start_offset: start_pos,
+                end_offset: this.next_attr_start_offset,
+                type_,
+                array_header,
+            }))
+        })
+    }
+
     /// Lets loader load the next node attribute.
     pub fn load_next<V>(&mut self, loader: V) -> Result<Option<V::Output>>
     where
         V: LoadAttribute,
     {
+        if self.parser.is_ascii() {
+            return self.load_next_ascii(loader, false);
+        }
         self.do_with_health_check(|this, start_pos, attr_index| {
             let attr_type = match this.read_next_attr_type()? {
                 Some(v) => v,
                 None => return Ok(None),
             };
-            this.load_next_impl(attr_type, loader, start_pos, attr_index)
-                .map(Some)
+            this.load_next_impl(
+                attr_type,
+                loader,
+                start_pos,
+                attr_index,
+                &mut ArrayCapture::default(),
+            )
+            .map(Some)
+        })
+    }
+
+    /// Lets loader load the next node attribute, and returns it with the byte
+    /// layout of the attribute.
+    ///
+    /// This is same as [`load_next`][`Self::load_next`] except that the
+    /// layout (as [`next_layout`][`Self::next_layout`] returns) is also
+    /// returned.
+    ///
+    /// Returns [`OperationError::AttributeLayoutUnavailable`] error for FBX
+    /// ASCII.
+    pub fn load_next_with_layout<V>(
+        &mut self,
+        loader: V,
+    ) -> Result<Option<(V::Output, AttributeLayout)>>
+    where
+        V: LoadAttribute,
+    {
+        if self.parser.is_ascii() {
+            return Err(OperationError::AttributeLayoutUnavailable.into());
+        }
+        self.do_with_health_check(|this, start_pos, attr_index| {
+            let type_ = match this.read_next_attr_type()? {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+            let mut capture = ArrayCapture::default();
+            let v = this.load_next_impl(type_, loader, start_pos, attr_index, &mut capture)?;
+            let layout = AttributeLayout {
+                start_offset: start_pos,
+                end_offset: this.next_attr_start_offset,
+                type_,
+                array_header: capture.header,
+            };
+            Ok(Some((v, layout)))
         })
     }
 
@@ -134,6 +319,9 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
         R: io::BufRead,
         V: LoadAttribute,
     {
+        if self.parser.is_ascii() {
+            return self.load_next_ascii(loader, true);
+        }
         self.do_with_health_check(|this, start_pos, attr_index| {
             let attr_type = match this.read_next_attr_type()? {
                 Some(v) => v,
@@ -144,13 +332,130 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
         })
     }
 
+    /// Lets loader load the next node attribute, and returns it with the
+    /// array attribute header and the raw (possibly compressed) elements.
+    ///
+    /// Raw elements are kept only if `keep_raw` is true.
+    /// For FBX ASCII and non-array attributes, the returned capture is empty.
+    #[cfg(feature = "tree")]
+    pub(crate) fn load_next_with_capture<V>(
+        &mut self,
+        loader: V,
+        keep_raw: bool,
+    ) -> Result<Option<(V::Output, ArrayCapture)>>
+    where
+        V: LoadAttribute,
+    {
+        if self.parser.is_ascii() {
+            return Ok(self
+                .load_next_ascii(loader, false)?
+                .map(|v| (v, ArrayCapture::default())));
+        }
+        self.do_with_health_check(|this, start_pos, attr_index| {
+            let attr_type = match this.read_next_attr_type()? {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+            let mut capture = ArrayCapture {
+                keep_raw,
+                ..Default::default()
+            };
+            let v = this.load_next_impl(attr_type, loader, start_pos, attr_index, &mut capture)?;
+            Ok(Some((v, capture)))
+        })
+    }
+
+    /// Lets loader load the next node attribute, or lets `raw_array` handle
+    /// the raw elements of an array attribute.
+    ///
+    /// For array attributes of FBX binary, `raw_array` is called with the
+    /// loader, the array attribute header, and the reader of the raw
+    /// (possibly compressed) elements, and the elements are never loaded into
+    /// memory by this method.
+    /// The rest of the raw elements not read by `raw_array` are skipped.
+    #[cfg(feature = "writer")]
+    pub(crate) fn load_next_or_raw_array<V, F>(
+        &mut self,
+        loader: V,
+        raw_array: F,
+    ) -> Result<Option<V::Output>>
+    where
+        V: LoadAttribute,
+        F: FnOnce(V, AttributeType, &ArrayAttributeHeader, &mut dyn io::Read) -> V::Output,
+    {
+        if self.parser.is_ascii() {
+            return self.load_next_ascii(loader, false);
+        }
+        self.do_with_health_check(|this, start_pos, attr_index| {
+            let attr_type = match this.read_next_attr_type()? {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+            match attr_type {
+                AttributeType::ArrBool
+                | AttributeType::ArrI32
+                | AttributeType::ArrI64
+                | AttributeType::ArrF32
+                | AttributeType::ArrF64 => {
+                    let header = ArrayAttributeHeader::from_reader(this.parser.reader())?;
+                    this.update_next_attr_start_offset(u64::from(header.bytelen));
+                    this.parser.check_array_limits(attr_type, &header)?;
+                    let mut reader =
+                        io::Read::take(this.parser.reader(), u64::from(header.bytelen));
+                    Ok(Some(raw_array(loader, attr_type, &header, &mut reader)))
+                }
+                _ => this
+                    .load_next_impl(
+                        attr_type,
+                        loader,
+                        start_pos,
+                        attr_index,
+                        &mut ArrayCapture::default(),
+                    )
+                    .map(Some),
+            }
+        })
+    }
+
+    /// Lets loader load the next node attribute of FBX ASCII.
+    ///
+    /// If `buffered` is true, `V::load_{binary,string}_buffered` is preferred.
+    fn load_next_ascii<V>(&mut self, loader: V, buffered: bool) -> Result<Option<V::Output>>
+    where
+        V: LoadAttribute,
+    {
+        if let Some(offset) = self.parser.next_ascii_attribute_offset() {
+            self.next_attr_start_offset = offset;
+        }
+        self.do_with_health_check(|this, start_pos, attr_index| {
+            let attr = match this.parser.next_ascii_attribute() {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+            // This never overflows because the parser has the same number of
+            // attributes as `rest_count`.
+            this.rest_count -= 1;
+            if attr.has_incorrect_boolean {
+                this.parser.warn(
+                    Warning::IncorrectBooleanRepresentation,
+                    this.position(start_pos, attr_index),
+                )?;
+            }
+            ascii::load_value(attr.value, loader, buffered).map(Some)
+        })
+    }
+
     /// Internal implementation of `load_next`.
+    ///
+    /// The array attribute header (and the raw elements if requested) is
+    /// stored to `capture`.
     fn load_next_impl<V>(
         &mut self,
         attr_type: AttributeType,
         loader: V,
         start_pos: u64,
         attr_index: usize,
+        capture: &mut ArrayCapture,
     ) -> Result<V::Output>
     where
         V: LoadAttribute,
@@ -193,79 +498,40 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
                 self.update_next_attr_start_offset(0);
                 loader.load_f64(value)
             }
-            AttributeType::ArrBool => {
+            AttributeType::ArrBool
+            | AttributeType::ArrI32
+            | AttributeType::ArrI64
+            | AttributeType::ArrF32
+            | AttributeType::ArrF64 => {
                 let header = ArrayAttributeHeader::from_reader(self.parser.reader())?;
                 self.update_next_attr_start_offset(u64::from(header.bytelen));
-                let reader = AttributeStreamDecoder::create(header.encoding, self.parser.reader())?;
-                let count = header.elements_count;
-                let mut iter = BooleanArrayAttributeValues::new(reader, count);
-                let res = loader.load_seq_bool(&mut iter, count as usize)?;
-                // Save `has_error` to make `iter` discardable before
-                // `self.parser.warn()` call.
-                let has_error = iter.has_error();
-                if iter.has_incorrect_boolean_value() {
+                self.parser.check_array_limits(attr_type, &header)?;
+                let (res, has_incorrect_boolean) = if capture.keep_raw {
+                    let mut raw = Vec::new();
+                    io::Read::read_to_end(
+                        &mut io::Read::take(self.parser.reader(), u64::from(header.bytelen)),
+                        &mut raw,
+                    )?;
+                    let res = load_array(attr_type, &header, &raw[..], loader);
+                    capture.raw = Some(raw);
+                    res
+                } else {
+                    load_array(attr_type, &header, self.parser.reader(), loader)
+                };
+                capture.header = Some(header);
+                if has_incorrect_boolean {
                     self.parser.warn(
                         Warning::IncorrectBooleanRepresentation,
                         self.position(start_pos, attr_index),
                     )?;
                 }
-                if has_error {
-                    return Err(DataError::NodeAttributeError.into());
-                }
-                Ok(res)
-            }
-            AttributeType::ArrI32 => {
-                let header = ArrayAttributeHeader::from_reader(self.parser.reader())?;
-                self.update_next_attr_start_offset(u64::from(header.bytelen));
-                let reader = AttributeStreamDecoder::create(header.encoding, self.parser.reader())?;
-                let count = header.elements_count;
-                let mut iter = ArrayAttributeValues::<_, i32>::new(reader, count);
-                let res = loader.load_seq_i32(&mut iter, count as usize)?;
-                if iter.has_error() {
-                    return Err(DataError::NodeAttributeError.into());
-                }
-                Ok(res)
-            }
-            AttributeType::ArrI64 => {
-                let header = ArrayAttributeHeader::from_reader(self.parser.reader())?;
-                self.update_next_attr_start_offset(u64::from(header.bytelen));
-                let reader = AttributeStreamDecoder::create(header.encoding, self.parser.reader())?;
-                let count = header.elements_count;
-                let mut iter = ArrayAttributeValues::<_, i64>::new(reader, count);
-                let res = loader.load_seq_i64(&mut iter, count as usize)?;
-                if iter.has_error() {
-                    return Err(DataError::NodeAttributeError.into());
-                }
-                Ok(res)
-            }
-            AttributeType::ArrF32 => {
-                let header = ArrayAttributeHeader::from_reader(self.parser.reader())?;
-                self.update_next_attr_start_offset(u64::from(header.bytelen));
-                let reader = AttributeStreamDecoder::create(header.encoding, self.parser.reader())?;
-                let count = header.elements_count;
-                let mut iter = ArrayAttributeValues::<_, f32>::new(reader, count);
-                let res = loader.load_seq_f32(&mut iter, count as usize)?;
-                if iter.has_error() {
-                    return Err(DataError::NodeAttributeError.into());
-                }
-                Ok(res)
-            }
-            AttributeType::ArrF64 => {
-                let header = ArrayAttributeHeader::from_reader(self.parser.reader())?;
-                self.update_next_attr_start_offset(u64::from(header.bytelen));
-                let reader = AttributeStreamDecoder::create(header.encoding, self.parser.reader())?;
-                let count = header.elements_count;
-                let mut iter = ArrayAttributeValues::<_, f64>::new(reader, count);
-                let res = loader.load_seq_f64(&mut iter, count as usize)?;
-                if iter.has_error() {
-                    return Err(DataError::NodeAttributeError.into());
-                }
-                Ok(res)
+                res
             }
             AttributeType::Binary => {
                 let header = self.parser.parse::<SpecialAttributeHeader>()?;
                 let bytelen = u64::from(header.bytelen);
                 self.update_next_attr_start_offset(bytelen);
+                self.parser.check_limit(Limit::StringLength, bytelen)?;
                 // `self.parser.reader().by_ref().take(bytelen)` is rejected by
                 // borrowck (of rustc 1.31.0-beta.15 (4b3a1d911 2018-11-20)).
                 let reader = io::Read::take(self.parser.reader(), bytelen);
@@ -275,6 +541,7 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
                 let header = self.parser.parse::<SpecialAttributeHeader>()?;
                 let bytelen = u64::from(header.bytelen);
                 self.update_next_attr_start_offset(bytelen);
+                self.parser.check_limit(Limit::StringLength, bytelen)?;
                 // `self.parser.reader().by_ref().take(bytelen)` is rejected by
                 // borrowck (of rustc 1.31.0-beta.15 (4b3a1d911 2018-11-20)).
                 let reader = io::Read::take(self.parser.reader(), bytelen);
@@ -300,6 +567,7 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
                 let header = self.parser.parse::<SpecialAttributeHeader>()?;
                 let bytelen = u64::from(header.bytelen);
                 self.update_next_attr_start_offset(bytelen);
+                self.parser.check_limit(Limit::StringLength, bytelen)?;
                 // `self.parser.reader().by_ref().take(bytelen)` is rejected by
                 // borrowck (of rustc 1.31.0-beta.15 (4b3a1d911 2018-11-20)).
                 let reader = io::Read::take(self.parser.reader(), bytelen);
@@ -309,12 +577,19 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
                 let header = self.parser.parse::<SpecialAttributeHeader>()?;
                 let bytelen = u64::from(header.bytelen);
                 self.update_next_attr_start_offset(bytelen);
+                self.parser.check_limit(Limit::StringLength, bytelen)?;
                 // `self.parser.reader().by_ref().take(bytelen)` is rejected by
                 // borrowck (of rustc 1.31.0-beta.15 (4b3a1d911 2018-11-20)).
                 let reader = io::Read::take(self.parser.reader(), bytelen);
                 loader.load_string_buffered(reader, bytelen)
             }
-            _ => self.load_next_impl(attr_type, loader, start_pos, attr_index),
+            _ => self.load_next_impl(
+                attr_type,
+                loader,
+                start_pos,
+                attr_index,
+                &mut ArrayCapture::default(),
+            ),
         }
     }
 
@@ -368,3 +643,130 @@ impl<'a, R: 'a + ParserSource> Attributes<'a, R> {
         iter::OwnedIterBuffered::new(self, loaders.into_iter())
     }
 }
+
+impl<'a> Attributes<'_, SliceSource<'a>> {
+    /// Reads the next node attribute, borrowing the input data.
+    ///
+    /// Strings, binaries, and array elements are not copied nor
+    /// decompressed.
+    /// See [`slice`][`super::slice`] module for detail.
+    pub fn next_borrowed(&mut self) -> Result<Option<SliceAttribute<'a>>> {
+        self.do_with_health_check(|this, start_pos, attr_index| {
+            let attr_type = match this.read_next_attr_type()? {
+                Some(v) => v,
+                None => return Ok(None),
+            };
+            this.next_borrowed_impl(attr_type, start_pos, attr_index)
+                .map(Some)
+        })
+    }
+
+    /// Internal implementation of `next_borrowed`.
+    fn next_borrowed_impl(
+        &mut self,
+        attr_type: AttributeType,
+        start_pos: u64,
+        attr_index: usize,
+    ) -> Result<SliceAttribute<'a>> {
+        match attr_type {
+            AttributeType::ArrBool
+            | AttributeType::ArrI32
+            | AttributeType::ArrI64
+            | AttributeType::ArrF32
+            | AttributeType::ArrF64 => {
+                let header = ArrayAttributeHeader::from_reader(self.parser.reader())?;
+                self.update_next_attr_start_offset(u64::from(header.bytelen));
+                self.parser.check_array_limits(attr_type, &header)?;
+                let raw = self.parser.reader().read_slice(u64::from(header.bytelen))?;
+                let attr = SliceAttribute::from_array(attr_type, &header, raw)?;
+                // Compressed arrays are not checked here, because it requires
+                // decompression.
+                if let SliceAttribute::ArrBool(view) = attr {
+                    let has_incorrect_boolean = view
+                        .as_le_bytes()
+                        .map_or(false, |bytes| bytes.iter().any(|&v| v != b'T' && v != b'Y'));
+                    if has_incorrect_boolean {
+                        self.parser.warn(
+                            Warning::IncorrectBooleanRepresentation,
+                            self.position(start_pos, attr_index),
+                        )?;
+                    }
+                }
+                Ok(attr)
+            }
+            AttributeType::Binary | AttributeType::String => {
+                let header = self.parser.parse::<SpecialAttributeHeader>()?;
+                let bytelen = u64::from(header.bytelen);
+                self.update_next_attr_start_offset(bytelen);
+                self.parser.check_limit(Limit::StringLength, bytelen)?;
+                let bytes = self.parser.reader().read_slice(bytelen)?;
+                if attr_type == AttributeType::Binary {
+                    return Ok(SliceAttribute::Binary(bytes));
+                }
+                std::str::from_utf8(bytes)
+                    .map(SliceAttribute::String)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e).into())
+            }
+            _ => {
+                let value = self.load_next_impl(
+                    attr_type,
+                    DirectLoader,
+                    start_pos,
+                    attr_index,
+                    &mut ArrayCapture::default(),
+                )?;
+                Ok(SliceAttribute::from_scalar(value))
+            }
+        }
+    }
+}
+
+/// Loads the array attribute from the given reader.
+///
+/// Returns the result and whether an incorrect boolean value is found.
+fn load_array<V: LoadAttribute>(
+    attr_type: AttributeType,
+    header: &ArrayAttributeHeader,
+    reader: impl io::Read,
+    loader: V,
+) -> (Result<V::Output>, bool) {
+    let reader = match AttributeStreamDecoder::create(header.encoding, reader) {
+        Ok(v) => v,
+        Err(e) => return (Err(e), false),
+    };
+    let count = header.elements_count;
+    let (res, has_error, has_incorrect_boolean) = match attr_type {
+        AttributeType::ArrBool => {
+            let mut iter = BooleanArrayAttributeValues::new(reader, count);
+            let res = loader.load_seq_bool(&mut iter, count as usize);
+            (res, iter.has_error(), iter.has_incorrect_boolean_value())
+        }
+        AttributeType::ArrI32 => {
+            let mut iter = ArrayAttributeValues::<_, i32>::new(reader, count);
+            let res = loader.load_seq_i32(&mut iter, count as usize);
+            (res, iter.has_error(), false)
+        }
+        AttributeType::ArrI64 => {
+            let mut iter = ArrayAttributeValues::<_, i64>::new(reader, count);
+            let res = loader.load_seq_i64(&mut iter, count as usize);
+            (res, iter.has_error(), false)
+        }
+        AttributeType::ArrF32 => {
+            let mut iter = ArrayAttributeValues::<_, f32>::new(reader, count);
+            let res = loader.load_seq_f32(&mut iter, count as usize);
+            (res, iter.has_error(), false)
+        }
+        AttributeType::ArrF64 => {
+            let mut iter = ArrayAttributeValues::<_, f64>::new(reader, count);
+            let res = loader.load_seq_f64(&mut iter, count as usize);
+            (res, iter.has_error(), false)
+        }
+        _ => unreachable!("Should never fail: `attr_type` should be an array type"),
+    };
+    let res = match res {
+        Ok(_) if has_error => Err(DataError::NodeAttributeError.into()),
+        res => res,
+    };
+
+    (res, has_incorrect_boolean)
+}
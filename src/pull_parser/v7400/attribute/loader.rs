@@ -18,6 +18,8 @@ use crate::pull_parser::{error::DataError, Result};
 ///
 /// For simple types, [`pull_parser::v7400::attribute::loaders`][`super::loaders`] module contains
 /// useful loaders.
+/// With `derive` feature, loaders for enums and newtypes can be generated by
+/// `#[derive(LoadAttribute)]`.
 pub trait LoadAttribute: Sized + fmt::Debug {
     /// Result type on successful read.
     type Output;
@@ -25,6 +27,19 @@ pub trait LoadAttribute: Sized + fmt::Debug {
     /// Describes the expecting value.
     fn expecting(&self) -> String;
 
+    /// Returns `true` if the loader expects a binary rather than a string.
+    ///
+    /// FBX ASCII has no literal for binaries, and binaries (such as `Content`
+    /// of embedded media) are written as base64 strings.
+    /// If this returns `true`, the FBX ASCII parser decodes a quoted string
+    /// which is valid base64, and passes the decoded bytes to `load_binary`
+    /// instead of passing the string to `load_string`.
+    ///
+    /// This is not used for FBX binary.
+    fn expects_binary(&self) -> bool {
+        false
+    }
+
     /// Loads boolean value.
     fn load_bool(self, _: bool) -> Result<Self::Output> {
         Err(DataError::UnexpectedAttribute(self.expecting(), "boolean".into()).into())
@@ -78,6 +78,10 @@ impl LoadAttribute for BinaryLoader {
         "binary".into()
     }
 
+    fn expects_binary(&self) -> bool {
+        true
+    }
+
     fn load_binary(self, mut reader: impl io::Read, len: u64) -> Result<Self::Output> {
         let mut buf = Vec::with_capacity(len as usize);
         reader.read_to_end(&mut buf)?;
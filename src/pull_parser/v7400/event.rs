@@ -1,8 +1,9 @@
 //! Parser event.
 
 use crate::{
-    low::v7400::FbxFooter,
+    low::v7400::{FbxFooter, NodeHeader},
     pull_parser::{
+        reader::SliceSource,
         v7400::{Attributes, Parser},
         ParserSource, Result,
     },
@@ -40,8 +41,42 @@ impl<'a, R: 'a + ParserSource> StartNode<'a, R> {
         self.parser.current_node_name()
     }
 
+    /// Returns the start offset of the node.
+    ///
+    /// For FBX binary, this is the offset of the node header.
+    /// For FBX ASCII, this is the offset of the node name.
+    pub fn start_offset(&self) -> u64 {
+        self.parser.current_node_start_offset()
+    }
+
+    /// Returns the node header.
+    ///
+    /// This is the raw header read from the FBX binary, and does not require
+    /// the attributes to be loaded.
+    /// Returns `None` for FBX ASCII, since it has no node headers.
+    pub fn header(&self) -> Option<NodeHeader> {
+        self.parser.current_node_header()
+    }
+
     /// Returns node attributes reader.
     pub fn attributes(self) -> Attributes<'a, R> {
         Attributes::from_parser(self.parser)
     }
+
+    /// Returns the parser.
+    #[cfg(all(feature = "tree", feature = "serde"))]
+    pub(crate) fn into_parser(self) -> &'a mut Parser<R> {
+        self.parser
+    }
+}
+
+impl<'a> StartNode<'_, SliceSource<'a>> {
+    /// Returns the node name, borrowing the input data.
+    ///
+    /// Unlike [`name()`][`Self::name`], the returned string borrows the data
+    /// given to [`from_slice`][`crate::pull_parser::v7400::from_slice`], not
+    /// the parser.
+    pub fn borrowed_name(&self) -> &'a str {
+        self.parser.current_node_name_in_data()
+    }
 }
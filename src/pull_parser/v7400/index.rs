@@ -0,0 +1,304 @@
+//! Node offset index.
+//!
+//! [`NodeIndex`] maps the whole node hierarchy of FBX binary by reading only
+//! node headers and names.
+//! Attributes are not read nor decoded, so building an index is fast even for
+//! very large files.
+//!
+//! Parsers for any indexed node can be created by
+//! [`NodeIndex::parser_at`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use fbxcel::pull_parser::v7400::{index::NodeIndex, Event};
+//!
+//! let file = std::fs::File::open("sample.fbx").expect("Failed to open file");
+//! let mut reader = std::io::BufReader::new(file);
+//!
+//! let index = NodeIndex::build(&mut reader).expect("Failed to build index");
+//! for entry in index.entries() {
+//!     println!(
+//!         "{:indent$}{} ({} bytes)",
+//!         "",
+//!         entry.name(),
+//!         entry.end_offset() - entry.start_offset(),
+//!         indent = (entry.depth() - 1) * 4
+//!     );
+//! }
+//!
+//! // Parse the `Objects` node.
+//! if let Some(objects) = index
+//!     .entries()
+//!     .iter()
+//!     .position(|entry| entry.depth() == 1 && entry.name() == "Objects")
+//! {
+//!     let mut parser = index.parser_at(&mut reader, objects).expect("Failed to setup parser");
+//!     match parser.next_event().expect("Failed to parse") {
+//!         Event::StartNode(start) => assert_eq!(start.name(), "Objects"),
+//!         _ => unreachable!("The parser starts at the `Objects` node"),
+//!     }
+//! }
+//! ```
+
+use std::{io, ops::Range};
+
+use crate::{
+    low::FbxVersion,
+    pull_parser::{
+        error::DataError,
+        reader::SeekableSource,
+        v7400::{load_binary_header, read_node_header_and_name, Parser},
+        ParserSource, ParserVersion, Result,
+    },
+};
+
+/// Node offset index of FBX binary.
+///
+/// Entries are stored in document order (i.e. depth-first pre-order), so the
+/// descendants of a node immediately follow the node.
+///
+/// See [module documentation][`self`] for usage.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeIndex {
+    /// Parser version.
+    parser_version: ParserVersion,
+    /// FBX version.
+    fbx_version: FbxVersion,
+    /// Stream position of the beginning of the FBX data.
+    base_position: u64,
+    /// Node entries.
+    entries: Vec<NodeEntry>,
+}
+
+impl NodeIndex {
+    /// Builds a node index by reading node headers from the given reader.
+    ///
+    /// The reader should be positioned at the beginning of the FBX binary
+    /// (i.e. at the FBX header).
+    /// The reader is used only for reading and seeking forward.
+    pub fn build<R: io::Read + io::Seek>(mut reader: R) -> Result<Self> {
+        let base_position = reader.stream_position()?;
+        let (header, parser_version) = load_binary_header(&mut reader)?;
+        let fbx_version = header.version();
+        let mut source = SeekableSource::with_offset(reader, header.len());
+
+        let mut entries: Vec<NodeEntry> = Vec::new();
+        // Indices of open nodes.
+        let mut open_nodes: Vec<usize> = Vec::new();
+        loop {
+            let node_start_offset = source.position();
+
+            // Close the nodes ending here without node end marker.
+            if let Some(&current) = open_nodes.last() {
+                let current_end_offset = entries[current].end_offset;
+                if current_end_offset < node_start_offset {
+                    return Err(DataError::NodeLengthMismatch(current_end_offset, None).into());
+                }
+                if current_end_offset == node_start_offset {
+                    open_nodes.pop();
+                    continue;
+                }
+            }
+
+            let (node_header, name) = match read_node_header_and_name(&mut source, fbx_version)? {
+                Some(v) => v,
+                None => match open_nodes.pop() {
+                    Some(closing) => {
+                        let end_offset = entries[closing].end_offset;
+                        if end_offset != source.position() {
+                            return Err(DataError::NodeLengthMismatch(
+                                end_offset,
+                                Some(source.position()),
+                            )
+                            .into());
+                        }
+                        continue;
+                    }
+                    // End of the implicit root node.
+                    None => break,
+                },
+            };
+            let attributes_start_offset = source.position();
+            source.skip_distance(node_header.bytelen_attributes)?;
+
+            entries.push(NodeEntry {
+                name,
+                depth: open_nodes.len() + 1,
+                parent: open_nodes.last().copied(),
+                start_offset: node_start_offset,
+                end_offset: node_header.end_offset,
+                num_attributes: node_header.num_attributes,
+                attributes_start_offset,
+                bytelen_attributes: node_header.bytelen_attributes,
+            });
+            open_nodes.push(entries.len() - 1);
+        }
+
+        Ok(Self {
+            parser_version,
+            fbx_version,
+            base_position,
+            entries,
+        })
+    }
+
+    /// Returns the parser version.
+    pub fn parser_version(&self) -> ParserVersion {
+        self.parser_version
+    }
+
+    /// Returns the FBX version.
+    pub fn fbx_version(&self) -> FbxVersion {
+        self.fbx_version
+    }
+
+    /// Returns the node entries in document order.
+    pub fn entries(&self) -> &[NodeEntry] {
+        &self.entries
+    }
+
+    /// Returns the node entry at the given index.
+    pub fn get(&self, index: usize) -> Option<&NodeEntry> {
+        self.entries.get(index)
+    }
+
+    /// Returns the number of the indexed nodes.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the document has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator of indices of the children of the given node.
+    ///
+    /// If `parent` is `None`, this returns the indices of the top-level nodes.
+    pub fn children(&self, parent: Option<usize>) -> impl Iterator<Item = usize> + '_ {
+        let (start, depth) = match parent {
+            Some(parent) => (parent + 1, self.entries[parent].depth + 1),
+            None => (0, 1),
+        };
+        self.entries[start..]
+            .iter()
+            .take_while(move |entry| entry.depth >= depth)
+            .enumerate()
+            .filter(move |(_, entry)| entry.depth == depth)
+            .map(move |(i, _)| start + i)
+    }
+
+    /// Creates a new [`Parser`] which reads the node at the given index and
+    /// its descendants.
+    ///
+    /// The reader should be the same data source as the one used to build the
+    /// index.
+    /// It will be seeked to the beginning of the node.
+    ///
+    /// The parser emits [`Event::StartNode`][`super::Event::StartNode`] for
+    /// the node first, and [`Event::EndFbx`][`super::Event::EndFbx`] right
+    /// after the node ends.
+    /// `EndFbx` event will have [`OperationError::FooterUnavailable`] error
+    /// instead of the footer.
+    ///
+    /// Note that [syntactic position]s reported by the parser have node paths
+    /// relative to the parent of the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of range.
+    ///
+    /// [syntactic position]: `crate::pull_parser::SyntacticPosition`
+    /// [`OperationError::FooterUnavailable`]: `crate::pull_parser::error::OperationError::FooterUnavailable`
+    pub fn parser_at<R>(&self, mut reader: R, index: usize) -> Result<Parser<SeekableSource<R>>>
+    where
+        R: io::Read + io::Seek,
+    {
+        let entry = &self.entries[index];
+        reader.seek(io::SeekFrom::Start(self.base_position + entry.start_offset))?;
+        let source = SeekableSource::with_offset(reader, entry.start_offset as usize);
+        Parser::create_for_subtree(
+            self.parser_version,
+            self.fbx_version,
+            source,
+            entry.end_offset,
+        )
+    }
+}
+
+/// Node entry of the node index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeEntry {
+    /// Node name.
+    name: String,
+    /// Node depth.
+    depth: usize,
+    /// Index of the parent node.
+    parent: Option<usize>,
+    /// Start offset of the node.
+    start_offset: u64,
+    /// End offset of the node.
+    end_offset: u64,
+    /// Number of the node attributes.
+    num_attributes: u64,
+    /// Start offset of the node attributes.
+    attributes_start_offset: u64,
+    /// Length of the node attributes in bytes.
+    bytelen_attributes: u64,
+}
+
+impl NodeEntry {
+    /// Returns the node name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the node depth.
+    ///
+    /// Implicit root node is considered to be depth 0, so top-level nodes
+    /// have depth 1.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the index of the parent node.
+    ///
+    /// Returns `None` for top-level nodes.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Returns the start offset of the node (i.e. the offset of the node
+    /// header).
+    pub fn start_offset(&self) -> u64 {
+        self.start_offset
+    }
+
+    /// Returns the end offset of the node.
+    ///
+    /// "End offset" means a next byte of the last byte of the node.
+    pub fn end_offset(&self) -> u64 {
+        self.end_offset
+    }
+
+    /// Returns the byte range of the node, including its descendants.
+    pub fn byte_range(&self) -> Range<u64> {
+        self.start_offset..self.end_offset
+    }
+
+    /// Returns the number of the node attributes.
+    pub fn num_attributes(&self) -> u64 {
+        self.num_attributes
+    }
+
+    /// Returns the length of the node attributes in bytes.
+    pub fn bytelen_attributes(&self) -> u64 {
+        self.bytelen_attributes
+    }
+
+    /// Returns the byte range of the node attributes.
+    pub fn attributes_range(&self) -> Range<u64> {
+        self.attributes_start_offset..(self.attributes_start_offset + self.bytelen_attributes)
+    }
+}
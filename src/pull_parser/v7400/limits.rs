@@ -0,0 +1,83 @@
+//! Resource limits of the parser.
+
+use crate::pull_parser::{
+    error::{DataError, Limit},
+    Result,
+};
+
+/// Resource limits of [`Parser`][`super::Parser`].
+///
+/// Each limit is checked before the parser (or the attribute loader)
+/// allocates memory for the data, so that small but malicious data (such as
+/// a compressed array with `u32::MAX` elements, or deeply nested nodes) is
+/// rejected early.
+/// For FBX ASCII, the limits for attributes are checked while the array
+/// elements and the strings are read, before the whole data is materialized.
+/// Exceeding a limit is reported as [`DataError::LimitExceeded`] error.
+///
+/// `None` means no limit, and nothing is limited by default.
+///
+/// # Examples
+///
+/// ```
+/// use fbxcel::pull_parser::v7400::ParserLimits;
+///
+/// let limits = ParserLimits {
+///     max_depth: Some(64),
+///     max_array_elements: Some(16 * 1024 * 1024),
+///     max_decompressed_bytes: Some(512 * 1024 * 1024),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParserLimits {
+    /// Maximum depth of nodes.
+    ///
+    /// Top-level nodes have depth 1.
+    pub max_depth: Option<u64>,
+    /// Maximum number of attributes of a node.
+    pub max_attributes_per_node: Option<u64>,
+    /// Maximum number of elements of an array attribute.
+    pub max_array_elements: Option<u64>,
+    /// Maximum total byte length of decompressed array attributes.
+    ///
+    /// Only compressed arrays are counted.
+    pub max_decompressed_bytes: Option<u64>,
+    /// Maximum byte length of a string or binary attribute.
+    ///
+    /// For FBX ASCII, a quoted string is read up to six times this length
+    /// (as the longest escape sequence `&quot;` has six bytes), and then
+    /// its unescaped length is checked.
+    /// If the quoted string is longer than that, the number of bytes read
+    /// is reported as the value in the error.
+    pub max_string_len: Option<u64>,
+    /// Maximum number of nodes.
+    pub max_node_count: Option<u64>,
+}
+
+impl ParserLimits {
+    /// Creates a new `ParserLimits` without any limits.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Returns the maximum value of the given limit.
+    pub fn max(&self, limit: Limit) -> Option<u64> {
+        match limit {
+            Limit::Depth => self.max_depth,
+            Limit::AttributesPerNode => self.max_attributes_per_node,
+            Limit::ArrayElements => self.max_array_elements,
+            Limit::DecompressedBytes => self.max_decompressed_bytes,
+            Limit::StringLength => self.max_string_len,
+            Limit::NodeCount => self.max_node_count,
+        }
+    }
+
+    /// Returns an error if the value exceeds the limit.
+    pub(crate) fn check(&self, limit: Limit, value: u64) -> Result<()> {
+        match self.max(limit) {
+            Some(max) if value > max => Err(DataError::LimitExceeded(limit, value).into()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -4,19 +4,25 @@ use std::{fmt, io};
 
 use crate::{
     low::{
-        v7400::{FbxFooter, NodeHeader},
+        v7400::{
+            ArrayAttributeEncoding, ArrayAttributeHeader, AttributeType, FbxFooter, NodeHeader,
+        },
         FbxHeader, FbxVersion,
     },
     pull_parser::{
-        error::{DataError, OperationError},
-        reader::{PlainSource, SeekableSource},
-        v7400::{Event, FromParser, StartNode},
-        Error, ParserSource, ParserVersion, Result, SyntacticPosition, Warning,
+        error::{DataError, ErrorKind, Limit, OperationError},
+        reader::{PlainSource, SeekableSource, SliceSource},
+        v7400::{
+            ascii::{self, AsciiAttribute, AsciiState, Token},
+            limits::ParserLimits,
+            Event, FromParser, StartNode,
+        },
+        Error, ParserSource, ParserVersion, Result, SyntacticPosition, Warning, WarningPolicy,
     },
 };
 
 /// Warning handler type.
-type WarningHandler = Box<dyn FnMut(Warning, &SyntacticPosition) -> Result<()>>;
+pub(crate) type WarningHandler = Box<dyn FnMut(Warning, &SyntacticPosition) -> Result<()>>;
 
 /// Creates a new [`Parser`] from the given reader.
 ///
@@ -44,37 +50,136 @@ where
     )
 }
 
+/// Loads the FBX binary header, and returns it with the parser version.
+///
+/// Returns an error if the header is broken or the FBX version is unsupported
+/// by [`Parser`].
+pub(crate) fn load_binary_header(reader: impl io::Read) -> Result<(FbxHeader, ParserVersion)> {
+    let header = FbxHeader::load(reader).map_err(DataError::InvalidFbxHeader)?;
+    match header.parser_version() {
+        Some(v @ ParserVersion::V6100) | Some(v @ ParserVersion::V7400) => Ok((header, v)),
+        _ => Err(
+            OperationError::UnsupportedFbxVersion(ParserVersion::V7400, header.version()).into(),
+        ),
+    }
+}
+
+/// Reads a node header and the node name from the given source.
+///
+/// Returns `Ok(None)` if the header is a node end marker.
+pub(crate) fn read_node_header_and_name<R: ParserSource>(
+    source: &mut R,
+    fbx_version: FbxVersion,
+) -> Result<Option<(NodeHeader, String)>> {
+    let node_header = NodeHeader::from_reader(source, fbx_version)?;
+    if node_header.is_node_end() {
+        return Ok(None);
+    }
+    let mut vec = vec![0; node_header.bytelen_name as usize];
+    source.read_exact(&mut vec)?;
+    let name = String::from_utf8(vec).map_err(DataError::InvalidNodeNameEncoding)?;
+
+    Ok(Some((node_header, name)))
+}
+
 /// Pull parser for FBX 7.4 binary or compatible later versions.
+///
+/// This also parses FBX ASCII documents, if created by the functions in
+/// [`ascii`][`super::ascii`] module.
 pub struct Parser<R> {
     /// Parser state.
     state: State,
     /// Reader.
     reader: R,
+    /// FBX ASCII specific state.
+    ///
+    /// This is `None` if the parser is reading FBX binary.
+    ascii: Option<AsciiState>,
     /// Warning handler.
     warning_handler: Option<WarningHandler>,
+    /// Recovery mode state.
+    ///
+    /// This is `None` if the recovery mode is disabled.
+    recovery: Option<Recovery>,
+    /// Resource limits.
+    limits: ParserLimits,
 }
 
 impl<R: ParserSource> Parser<R> {
     /// Parser version.
+    ///
+    /// Note that this parser is also used for FBX 6.x (as
+    /// [`ParserVersion::V6100`]) if created by the functions in
+    /// [`pull_parser::v6100`][`crate::pull_parser::v6100`] module.
+    /// To get the parser version of the parser instance, use
+    /// [`parser_version()`][`Self::parser_version`].
     pub const PARSER_VERSION: ParserVersion = ParserVersion::V7400;
 
     /// Creates a new `Parser`.
     ///
     /// Returns an error if the given FBX version in unsupported.
     pub(crate) fn create(fbx_version: FbxVersion, reader: R) -> Result<Self> {
-        if ParserVersion::from_fbx_version(fbx_version) != Some(Self::PARSER_VERSION) {
-            return Err(
-                OperationError::UnsupportedFbxVersion(Self::PARSER_VERSION, fbx_version).into(),
-            );
+        Self::create_with_parser_version(Self::PARSER_VERSION, fbx_version, reader)
+    }
+
+    /// Creates a new `Parser` which behaves as the given parser version.
+    ///
+    /// Returns an error if the given FBX version in unsupported by the given
+    /// parser version, or the parser version is not supported by this parser.
+    pub(crate) fn create_with_parser_version(
+        parser_version: ParserVersion,
+        fbx_version: FbxVersion,
+        reader: R,
+    ) -> Result<Self> {
+        let is_compatible = matches!(parser_version, ParserVersion::V6100 | ParserVersion::V7400);
+        if !is_compatible || ParserVersion::from_fbx_version(fbx_version) != Some(parser_version) {
+            return Err(OperationError::UnsupportedFbxVersion(parser_version, fbx_version).into());
         }
 
         Ok(Self {
-            state: State::new(fbx_version),
+            state: State::new(parser_version, fbx_version),
             reader,
+            ascii: None,
             warning_handler: None,
+            recovery: None,
+            limits: ParserLimits::default(),
         })
     }
 
+    /// Creates a new `Parser` which reads only a node and its descendants.
+    ///
+    /// The reader should be positioned at the start of the node, and
+    /// `node_end_offset` should be the end offset of the node.
+    /// The parser emits [`Event::EndFbx`] right after the node ends, with
+    /// [`OperationError::FooterUnavailable`] error.
+    pub(crate) fn create_for_subtree(
+        parser_version: ParserVersion,
+        fbx_version: FbxVersion,
+        reader: R,
+        node_end_offset: u64,
+    ) -> Result<Self> {
+        let mut parser = Self::create_with_parser_version(parser_version, fbx_version, reader)?;
+        parser.state.subtree_end_offset = Some(node_end_offset);
+        Ok(parser)
+    }
+
+    /// Creates a new `Parser` for FBX ASCII.
+    ///
+    /// Returns an error if the given FBX version in unsupported.
+    pub(crate) fn create_ascii(
+        fbx_version: FbxVersion,
+        reader: R,
+        ascii_state: AsciiState,
+    ) -> Result<Self> {
+        let parser_version = match ParserVersion::from_fbx_version(fbx_version) {
+            Some(v @ ParserVersion::V6100) => v,
+            _ => Self::PARSER_VERSION,
+        };
+        let mut parser = Self::create_with_parser_version(parser_version, fbx_version, reader)?;
+        parser.ascii = Some(ascii_state);
+        Ok(parser)
+    }
+
     /// Sets the warning handler.
     ///
     /// The warning handler will receive warnings and their [syntactic
@@ -111,27 +216,274 @@ impl<R: ParserSource> Parser<R> {
         self.warning_handler = Some(Box::new(warning_handler));
     }
 
+    /// Sets the warning handler which handles warnings according to the
+    /// given policy.
+    ///
+    /// This replaces the warning handler set by
+    /// [`set_warning_handler()`][`Self::set_warning_handler`].
+    /// See [`WarningPolicy`] for examples.
+    pub fn set_warning_policy(&mut self, policy: WarningPolicy) {
+        self.set_warning_handler(move |warning, pos| policy.handle(warning, pos));
+    }
+
+    /// Enables or disables the recovery mode.
+    ///
+    /// By default, the parser is aborted on the first error.
+    /// In the recovery mode, the parser tries to continue parsing after
+    /// errors in FBX binary data:
+    ///
+    /// * On an error in a node, the parser records the error as a
+    ///   diagnostic, and skips to the end of the innermost open node (i.e.
+    ///   the parent of the broken node) using its `end_offset`.
+    ///   Then the parser emits [`Event::EndNode`] for the skipped node and
+    ///   its open descendants, and continues parsing the following nodes.
+    /// * On an error in a top-level node, or on premature end of the data,
+    ///   the parser records the error as a diagnostic, closes all open nodes
+    ///   by emitting [`Event::EndNode`]s, and then emits [`Event::EndFbx`]
+    ///   with an error created from [`OperationError::FooterUnavailable`].
+    /// * On an error in node attributes, the attributes reader returns the
+    ///   error and the rest attributes of the node are skipped, but the
+    ///   parser is not aborted.
+    ///
+    /// Critical warnings (i.e. warnings rejected by the warning handler) and
+    /// invalid operations still abort the parser.
+    /// The recovery mode has no effect for FBX ASCII.
+    ///
+    /// This should be set before the parser emits any events.
+    /// Recovered errors can be retrieved by
+    /// [`take_diagnostics()`][`Self::take_diagnostics`].
+    /// Disabling the recovery mode discards the diagnostics not yet taken.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use fbxcel::low::FbxHeader;
+    /// use fbxcel::pull_parser::v7400::Event;
+    /// # let reader = std::io::empty();
+    /// # let header: FbxHeader = unimplemented!();
+    /// let mut parser = fbxcel::pull_parser::v7400::from_reader(header, reader)
+    ///     .expect("Failed to create parser");
+    /// parser.set_recovery_mode(true);
+    /// loop {
+    ///     let event = parser.next_event().expect("Failed to parse");
+    ///     let is_end = matches!(event, Event::EndFbx(_));
+    ///     for e in parser.take_diagnostics() {
+    ///         eprintln!("Recovered from an error: {}", e);
+    ///     }
+    ///     if is_end {
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    pub fn set_recovery_mode(&mut self, enabled: bool) {
+        self.recovery = if enabled {
+            Some(self.recovery.take().unwrap_or_default())
+        } else {
+            None
+        };
+    }
+
+    /// Returns `true` if the recovery mode is enabled.
+    ///
+    /// See [`set_recovery_mode()`][`Self::set_recovery_mode`].
+    pub fn is_recovery_mode(&self) -> bool {
+        self.recovery.is_some()
+    }
+
+    /// Returns the errors the parser has recovered from, which are not taken
+    /// yet.
+    ///
+    /// This is always empty if the recovery mode is disabled.
+    /// See [`set_recovery_mode()`][`Self::set_recovery_mode`].
+    pub fn diagnostics(&self) -> &[Error] {
+        self.recovery
+            .as_ref()
+            .map_or(&[], |recovery| &recovery.diagnostics)
+    }
+
+    /// Takes the errors the parser has recovered from.
+    ///
+    /// The errors are returned in the order they happened, and they will not
+    /// be returned again by the later calls.
+    /// This always returns an empty vector if the recovery mode is disabled.
+    /// See [`set_recovery_mode()`][`Self::set_recovery_mode`].
+    pub fn take_diagnostics(&mut self) -> Vec<Error> {
+        self.recovery
+            .as_mut()
+            .map(|recovery| std::mem::take(&mut recovery.diagnostics))
+            .unwrap_or_default()
+    }
+
+    /// Takes the first error the parser has recovered from after the first
+    /// `start` diagnostics.
+    ///
+    /// The later diagnostics are discarded.
+    #[cfg(any(feature = "tree", feature = "writer"))]
+    pub(crate) fn take_diagnostic_since(&mut self, start: usize) -> Option<Error> {
+        let diagnostics = &mut self.recovery.as_mut()?.diagnostics;
+        if diagnostics.len() <= start {
+            return None;
+        }
+        diagnostics.drain(start..).next()
+    }
+
+    /// Sets the resource limits.
+    ///
+    /// By default, nothing is limited.
+    /// Limits are checked for each node and attribute the parser reads after
+    /// this call.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use fbxcel::low::FbxHeader;
+    /// use fbxcel::pull_parser::v7400::ParserLimits;
+    /// # let reader = std::io::empty();
+    /// # let header: FbxHeader = unimplemented!();
+    /// let mut parser = fbxcel::pull_parser::v7400::from_reader(header, reader)
+    ///     .expect("Failed to create parser");
+    /// parser.set_limits(ParserLimits {
+    ///     max_depth: Some(64),
+    ///     max_decompressed_bytes: Some(512 * 1024 * 1024),
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn set_limits(&mut self, limits: ParserLimits) {
+        self.limits = limits;
+    }
+
+    /// Returns the resource limits.
+    pub fn limits(&self) -> &ParserLimits {
+        &self.limits
+    }
+
+    /// Returns an error if the value exceeds the limit.
+    pub(crate) fn check_limit(&self, limit: Limit, value: u64) -> Result<()> {
+        self.limits.check(limit, value)
+    }
+
+    /// Checks the limits for an array attribute to be loaded.
+    pub(crate) fn check_array_limits(
+        &mut self,
+        attr_type: AttributeType,
+        header: &ArrayAttributeHeader,
+    ) -> Result<()> {
+        let elements_count = u64::from(header.elements_count);
+        self.check_limit(Limit::ArrayElements, elements_count)?;
+        if header.encoding == ArrayAttributeEncoding::Zlib {
+            let elem_size = match attr_type {
+                AttributeType::ArrBool => 1,
+                AttributeType::ArrI32 | AttributeType::ArrF32 => 4,
+                _ => 8,
+            };
+            let total = self
+                .state
+                .decompressed_bytes
+                .saturating_add(elements_count * elem_size);
+            self.check_limit(Limit::DecompressedBytes, total)?;
+            self.state.decompressed_bytes = total;
+        }
+        Ok(())
+    }
+
+    /// Checks the limits for a node to be started, and counts the node.
+    fn check_node_limits(&mut self, attributes_count: u64) -> Result<()> {
+        let depth = self.state.started_nodes.len() as u64 + 1;
+        self.check_limit(Limit::Depth, depth)?;
+        self.check_limit(Limit::AttributesPerNode, attributes_count)?;
+        let node_count = self.state.node_count + 1;
+        self.check_limit(Limit::NodeCount, node_count)?;
+        self.state.node_count = node_count;
+        Ok(())
+    }
+
     /// Returns a mutable reference to the inner reader.
     pub(crate) fn reader(&mut self) -> &mut R {
         &mut self.reader
     }
 
+    /// Returns the parser version the parser behaves as.
+    ///
+    /// This returns [`ParserVersion::V6100`] for FBX 6.x, and
+    /// [`ParserVersion::V7400`] for FBX 7.x.
+    pub fn parser_version(&self) -> ParserVersion {
+        self.state.parser_version
+    }
+
     /// Returns FBX version.
     pub fn fbx_version(&self) -> FbxVersion {
         self.state.fbx_version
     }
 
+    /// Returns `true` if the parser is reading FBX ASCII.
+    pub fn is_ascii(&self) -> bool {
+        self.ascii.is_some()
+    }
+
+    /// Returns the start offset of the next unread attribute of FBX ASCII.
+    ///
+    /// Returns `None` if the parser is reading FBX binary or there are no
+    /// unread attributes.
+    pub(crate) fn next_ascii_attribute_offset(&self) -> Option<u64> {
+        self.ascii
+            .as_ref()?
+            .attributes
+            .front()
+            .map(|attr| attr.offset)
+    }
+
+    /// Takes the next unread attribute of FBX ASCII.
+    ///
+    /// Returns `None` if the parser is reading FBX binary or there are no
+    /// unread attributes.
+    pub(crate) fn next_ascii_attribute(&mut self) -> Option<AsciiAttribute> {
+        self.ascii.as_mut()?.attributes.pop_front()
+    }
+
     /// Returns the name of the current node.
     ///
     /// # Panics
     ///
     /// This panics if there are no open nodes.
     pub fn current_node_name(&self) -> &str {
+        let node = self
+            .state
+            .current_node()
+            .expect("Implicit top-level node has no name");
+        self.node_name(node)
+    }
+
+    /// Returns the name of the given node.
+    fn node_name<'s>(&'s self, node: &'s StartedNode) -> &'s str {
+        match node.name {
+            NodeName::Owned(ref name) => name,
+            NodeName::InSource(start, end) => {
+                let data = self
+                    .reader
+                    .as_slice()
+                    .expect("Should never fail: the source has the data in memory");
+                std::str::from_utf8(&data[(start as usize)..(end as usize)])
+                    .expect("Should never fail: the name is already validated")
+            }
+        }
+    }
+
+    /// Returns the start offset of the current node.
+    pub(crate) fn current_node_start_offset(&self) -> u64 {
         self.state
             .current_node()
-            .expect("Implicit top-level node has no name")
-            .name
-            .as_str()
+            .expect("Implicit top-level node has no start offset")
+            .node_start_offset
+    }
+
+    /// Returns the node header of the current node.
+    ///
+    /// Returns `None` for FBX ASCII.
+    pub(crate) fn current_node_header(&self) -> Option<NodeHeader> {
+        self.state
+            .current_node()
+            .expect("Implicit top-level node has no header")
+            .header
     }
 
     /// Returns the number of attributes of the current node.
@@ -184,18 +536,32 @@ impl<R: ParserSource> Parser<R> {
     /// If you call `next_event()` with failed parser, error created from
     /// [`OperationError::AlreadyAborted`] will be returned.
     pub fn next_event(&mut self) -> Result<Event<'_, R>> {
-        let previous_depth = self.current_depth();
+        let mut previous_depth = self.current_depth();
 
         // Precondition: Health should be `Health::Running`.
         self.ensure_continuable()?;
 
         // Update health.
-        let event_kind = match self.next_event_impl() {
-            Ok(v) => v,
-            Err(e) => {
-                let err_pos = self.position();
-                self.set_aborted(err_pos.clone());
-                return Err(e.and_position(err_pos));
+        let event_kind = loop {
+            match self.next_event_impl() {
+                Ok(v) => break v,
+                Err(e) => {
+                    let err_pos = self.position();
+                    let e = e.and_position(err_pos.clone());
+                    if self.recover(&e) {
+                        // Record the error, and emit the event to close the
+                        // skipped nodes (or to finish parsing) instead.
+                        self.recovery
+                            .as_mut()
+                            .expect("Should never fail: the recovery mode is enabled")
+                            .diagnostics
+                            .push(e);
+                        previous_depth = self.current_depth();
+                        continue;
+                    }
+                    self.set_aborted(err_pos);
+                    return Err(e);
+                }
             }
         };
         if event_kind == EventKind::EndFbx {
@@ -246,7 +612,15 @@ impl<R: ParserSource> Parser<R> {
             EventKind::StartNode => Event::StartNode(StartNode::new(self)),
             EventKind::EndNode => Event::EndNode,
             EventKind::EndFbx => {
-                let footer_res = FbxFooter::read_from_parser(self).map(Box::new);
+                let footer_res = if self.is_ascii() {
+                    Err(ascii::footer_unavailable())
+                } else if self.state.subtree_end_offset.is_some()
+                    || self.recovery.as_ref().map_or(false, |v| v.finishing)
+                {
+                    Err(OperationError::FooterUnavailable.into())
+                } else {
+                    FbxFooter::read_from_parser(self).map(Box::new)
+                };
                 Event::EndFbx(footer_res)
             }
         })
@@ -258,11 +632,39 @@ impl<R: ParserSource> Parser<R> {
         assert_eq!(self.state.health(), &Health::Running);
         assert_ne!(self.state.last_event_kind(), Some(EventKind::EndFbx));
 
+        // Close the nodes skipped by the recovery.
+        if let Some(recovery) = &mut self.recovery {
+            if recovery.pending_end_nodes > 0 {
+                recovery.pending_end_nodes -= 1;
+                self.state.started_nodes.pop();
+                return Ok(EventKind::EndNode);
+            }
+            if recovery.finishing {
+                return Ok(EventKind::EndFbx);
+            }
+        }
+
+        if self.ascii.is_some() {
+            return self.next_event_impl_ascii();
+        }
+
         // Skip unread attribute of previous node, if exists.
         self.skip_unread_attributes()?;
 
         let event_start_offset = self.reader().position();
 
+        // Check if the subtree ends here.
+        if let Some(subtree_end_offset) = self.state.subtree_end_offset {
+            if self.state.started_nodes.is_empty() {
+                if subtree_end_offset < event_start_offset {
+                    return Err(DataError::NodeLengthMismatch(subtree_end_offset, None).into());
+                }
+                if subtree_end_offset == event_start_offset {
+                    return Ok(EventKind::EndFbx);
+                }
+            }
+        }
+
         // Check if the current node ends here (without any marker).
         // A node end marker (all-zero node header, which indicates end of the
         // current node) is omitted if and only if:
@@ -305,26 +707,32 @@ impl<R: ParserSource> Parser<R> {
         // Check if a node or a document ends here (with explicit marker).
         if node_header.is_node_end() {
             // The current node explicitly ends here.
-            return match self.state.started_nodes.pop() {
-                Some(closing) => {
-                    if closing.node_end_offset != header_end_offset {
-                        return Err(DataError::NodeLengthMismatch(
-                            closing.node_end_offset,
-                            Some(header_end_offset),
-                        )
-                        .into());
-                    }
-                    if closing.attributes_count != 0 && closing.known_children_count == 0 {
-                        // It's odd, the current node should not have a node end
-                        // marker at the ending, but found.
-                        self.warn(Warning::ExtraNodeEndMarker, self.position())?;
-                    }
-                    Ok(EventKind::EndNode)
-                }
-                None => Ok(EventKind::EndFbx),
+            let closing = match self.state.current_node() {
+                Some(v) => v,
+                None => return Ok(EventKind::EndFbx),
             };
+            // Check before closing the node, so that the node is still open
+            // on error.
+            if closing.node_end_offset != header_end_offset {
+                return Err(DataError::NodeLengthMismatch(
+                    closing.node_end_offset,
+                    Some(header_end_offset),
+                )
+                .into());
+            }
+            let has_extra_marker =
+                closing.attributes_count != 0 && closing.known_children_count == 0;
+            self.state.started_nodes.pop();
+            if has_extra_marker {
+                // It's odd, the current node should not have a node end
+                // marker at the ending, but found.
+                self.warn(Warning::ExtraNodeEndMarker, self.position())?;
+            }
+            return Ok(EventKind::EndNode);
         }
 
+        self.check_node_limits(node_header.num_attributes)?;
+
         if node_header.bytelen_name == 0 {
             let mut pos = self.position();
             // Need to modify position, because the currently reading node is
@@ -343,10 +751,29 @@ impl<R: ParserSource> Parser<R> {
         }
 
         // Read the node name.
-        let name = {
-            let mut vec = vec![0; node_header.bytelen_name as usize];
-            self.reader.read_exact(&mut vec[..])?;
-            String::from_utf8(vec).map_err(DataError::InvalidNodeNameEncoding)?
+        // If the source has the data in memory, refer to the name in the data
+        // instead of copying it.
+        let name = match self.reader.as_slice() {
+            Some(data) => {
+                let start = self.reader.position();
+                let end = start + u64::from(node_header.bytelen_name);
+                let bytes = data
+                    .get((start as usize)..(end as usize))
+                    .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+                if std::str::from_utf8(bytes).is_err() {
+                    // Use the same error type as the non-slice source.
+                    let e = String::from_utf8(bytes.to_vec())
+                        .expect_err("Should never fail: the name is known to be invalid");
+                    return Err(DataError::InvalidNodeNameEncoding(e).into());
+                }
+                self.reader.skip_to(end)?;
+                NodeName::InSource(start, end)
+            }
+            None => {
+                let mut vec = vec![0; node_header.bytelen_name as usize];
+                self.reader.read_exact(&mut vec[..])?;
+                NodeName::Owned(String::from_utf8(vec).map_err(DataError::InvalidNodeNameEncoding)?)
+            }
         };
         let current_offset = self.reader().position();
         let starting = StartedNode {
@@ -356,6 +783,7 @@ impl<R: ParserSource> Parser<R> {
             attributes_end_offset: current_offset + node_header.bytelen_attributes,
             name,
             known_children_count: 0,
+            header: Some(node_header),
         };
 
         // Update parser status.
@@ -367,6 +795,68 @@ impl<R: ParserSource> Parser<R> {
         Ok(EventKind::StartNode)
     }
 
+    /// Reads the next node of FBX ASCII and changes the parser state (except
+    /// for parser health and the last event kind).
+    fn next_event_impl_ascii(&mut self) -> Result<EventKind> {
+        // Objects are the nodes directly under the top-level `Objects` node.
+        let is_object = matches!(
+            &self.state.started_nodes[..],
+            [StartedNode { name: NodeName::Owned(name), .. }] if name == "Objects"
+        );
+        let ascii = self
+            .ascii
+            .as_mut()
+            .expect("Should never fail: the parser is reading FBX ASCII");
+
+        // Discard unread attributes of the previous node.
+        ascii.attributes.clear();
+
+        // The current node has no children block.
+        if ascii.pending_end {
+            ascii.pending_end = false;
+            self.state.started_nodes.pop();
+            return Ok(EventKind::EndNode);
+        }
+
+        let (event_start_offset, token) = ascii.next_token(&mut self.reader)?;
+        match token {
+            Token::Name(name) => {
+                let has_children_block =
+                    ascii.read_attributes(&mut self.reader, &self.limits, is_object)?;
+                ascii.pending_end = !has_children_block;
+                let starting = StartedNode {
+                    node_start_offset: event_start_offset,
+                    node_end_offset: u64::MAX,
+                    attributes_count: ascii.attributes.len() as u64,
+                    attributes_end_offset: ascii.position(&self.reader),
+                    name: NodeName::Owned(name),
+                    known_children_count: 0,
+                    header: None,
+                };
+                self.check_node_limits(starting.attributes_count)?;
+
+                // Update parser status.
+                match self.state.started_nodes.last_mut() {
+                    Some(parent) => parent.known_children_count += 1,
+                    None => self.state.known_toplevel_nodes_count += 1,
+                }
+                self.state.started_nodes.push(starting);
+                Ok(EventKind::StartNode)
+            }
+            Token::CloseBrace if !self.state.started_nodes.is_empty() => {
+                self.state.started_nodes.pop();
+                Ok(EventKind::EndNode)
+            }
+            Token::Eof if self.state.started_nodes.is_empty() => Ok(EventKind::EndFbx),
+            Token::Eof => Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            token => Err(DataError::InvalidAsciiSyntax(format!(
+                "unexpected {} at offset {}",
+                token, event_start_offset
+            ))
+            .into()),
+        }
+    }
+
     /// Skips unread attribute of the current node, if remains.
     ///
     /// If there are no unread attributes, this method simply do nothing.
@@ -388,6 +878,52 @@ impl<R: ParserSource> Parser<R> {
         self.state.health = Health::Aborted(pos);
     }
 
+    /// Prepares to continue parsing after the given error, if possible.
+    ///
+    /// Returns `true` if the parser can continue in the recovery mode.
+    pub(crate) fn recover(&mut self, e: &Error) -> bool {
+        if self.recovery.is_none() || self.is_ascii() {
+            return false;
+        }
+        let is_data_error = match e.kind() {
+            ErrorKind::Data => true,
+            ErrorKind::Io => false,
+            _ => return false,
+        };
+
+        // Skip to the end of the innermost node which has not ended yet.
+        // I/O errors (including premature EOF) mean the rest of the data
+        // cannot be read.
+        let pos = self.reader.position();
+        let resync_index = if is_data_error {
+            self.state
+                .started_nodes
+                .iter()
+                .rposition(|node| node.node_end_offset >= pos)
+        } else {
+            None
+        };
+        let depth = self.state.started_nodes.len();
+        let (pending_end_nodes, finishing) = match resync_index {
+            Some(index) => {
+                let end_offset = self.state.started_nodes[index].node_end_offset;
+                match self.reader.skip_to(end_offset) {
+                    Ok(()) => (depth - index, false),
+                    Err(_) => (depth, true),
+                }
+            }
+            None => (depth, true),
+        };
+
+        let recovery = self
+            .recovery
+            .as_mut()
+            .expect("Should never fail: the recovery mode is enabled");
+        recovery.pending_end_nodes = pending_end_nodes;
+        recovery.finishing = finishing;
+        true
+    }
+
     /// Ignores events until the current node closes.
     ///
     /// This discards parser events until the [`EndNode`] event for the current
@@ -396,6 +932,8 @@ impl<R: ParserSource> Parser<R> {
     ///
     /// This method seeks to the node end position without any additional
     /// parsing, since the parser already knows the node end position.
+    /// (For FBX ASCII, the node end position is unknown, so this method
+    /// internally reads events until the node ends.)
     /// Because of this, some errors can be overlooked, or detected at the
     /// different position from the true error position.
     ///
@@ -442,6 +980,15 @@ impl<R: ParserSource> Parser<R> {
     ///
     /// [`EndNode`]: `Event::EndNode`
     pub fn skip_current_node(&mut self) -> Result<()> {
+        if self.is_ascii() {
+            let depth = self.current_depth();
+            assert!(depth > 0, "Attempt to skip implicit top-level node");
+            while self.current_depth() >= depth {
+                self.next_event()?;
+            }
+            return Ok(());
+        }
+
         let end_pos = self
             .state
             .started_nodes
@@ -449,15 +996,39 @@ impl<R: ParserSource> Parser<R> {
             .expect("Attempt to skip implicit top-level node")
             .node_end_offset;
         self.state.last_event_kind = Some(EventKind::EndNode);
+        if let Some(recovery) = &mut self.recovery {
+            if recovery.pending_end_nodes > 0 {
+                // The reader is already at the end of the skipped nodes.
+                recovery.pending_end_nodes -= 1;
+                return Ok(());
+            }
+            if let Err(e) = self.reader.skip_to(end_pos) {
+                let e = Error::from(e).and_position(self.position());
+                if self.recover(&e) {
+                    self.recovery
+                        .as_mut()
+                        .expect("Should never fail: the recovery mode is enabled")
+                        .diagnostics
+                        .push(e);
+                    return Ok(());
+                }
+                return Err(e);
+            }
+            return Ok(());
+        }
         self.reader.skip_to(end_pos)?;
 
         Ok(())
     }
+
     /// Returns the syntactic position of the current node.
     ///
     /// Note that this allocates memory.
     pub fn position(&self) -> SyntacticPosition {
-        let byte_pos = self.reader.position();
+        let byte_pos = match &self.ascii {
+            Some(ascii) => ascii.position(&self.reader),
+            None => self.reader.position(),
+        };
         if self.state.current_node().is_none() {
             // Reading implicit root node.
             return SyntacticPosition {
@@ -485,7 +1056,11 @@ impl<R: ParserSource> Parser<R> {
             .iter()
             .map(|v| v.known_children_count.saturating_sub(1));
         let node_indices = std::iter::once(toplevel_index).chain(trailing_indices);
-        let node_names = self.state.started_nodes.iter().map(|v| v.name.clone());
+        let node_names = self
+            .state
+            .started_nodes
+            .iter()
+            .map(|v| self.node_name(v).to_owned());
         let node_path = node_indices.zip(node_names).collect();
 
         SyntacticPosition {
@@ -527,22 +1102,48 @@ impl<R: ParserSource> Parser<R> {
     }
 }
 
+impl<'a> Parser<SliceSource<'a>> {
+    /// Returns the name of the current node, borrowing the input data.
+    ///
+    /// # Panics
+    ///
+    /// This panics if there are no open nodes.
+    pub(crate) fn current_node_name_in_data(&self) -> &'a str {
+        let node = self
+            .state
+            .current_node()
+            .expect("Implicit top-level node has no name");
+        match node.name {
+            NodeName::InSource(start, end) => {
+                std::str::from_utf8(&self.reader.data()[(start as usize)..(end as usize)])
+                    .expect("Should never fail: the name is already validated")
+            }
+            NodeName::Owned(_) => {
+                unreachable!("Should never fail: names are always in the data of slice sources")
+            }
+        }
+    }
+}
+
 impl<R: fmt::Debug> fmt::Debug for Parser<R> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Parser")
             .field("state", &self.state)
             .field("reader", &self.reader)
+            .field("ascii", &self.ascii)
             .field(
                 "warning_handler",
                 &self.warning_handler.as_ref().map(|v| v as *const _),
             )
+            .field("recovery", &self.recovery)
+            .field("limits", &self.limits)
             .finish()
     }
 }
 
 /// Health of a parser.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-enum Health {
+pub(crate) enum Health {
     /// Ready or already started, but not yet finished, and no critical errors.
     Running,
     /// Successfully finished.
@@ -551,12 +1152,26 @@ enum Health {
     Aborted(SyntacticPosition),
 }
 
+/// Recovery mode state.
+#[derive(Debug, Default)]
+struct Recovery {
+    /// Number of open nodes to be closed by [`Event::EndNode`]s without
+    /// reading data.
+    pending_end_nodes: usize,
+    /// Whether the parser finishes after closing all open nodes.
+    finishing: bool,
+    /// Errors the parser has recovered from, which are not taken yet.
+    diagnostics: Vec<Error>,
+}
+
 /// Parser state.
 ///
 /// This type contains parser state especially which are independent of parser
 /// source type.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct State {
+    /// Parser version the parser behaves as.
+    parser_version: ParserVersion,
     /// Target FBX version.
     fbx_version: FbxVersion,
     /// Health of the parser.
@@ -571,17 +1186,28 @@ struct State {
     ///
     /// This is here because [`StartedNode`] is not used for implicit root node.
     known_toplevel_nodes_count: usize,
+    /// End offset of the subtree, if the parser reads only a subtree.
+    subtree_end_offset: Option<u64>,
+    /// Number of nodes started so far.
+    node_count: u64,
+    /// Total byte length of compressed arrays after decompression, read so
+    /// far.
+    decompressed_bytes: u64,
 }
 
 impl State {
-    /// Creates a new `State` for the given FBX version.
-    fn new(fbx_version: FbxVersion) -> Self {
+    /// Creates a new `State` for the given parser and FBX version.
+    fn new(parser_version: ParserVersion, fbx_version: FbxVersion) -> Self {
         Self {
+            parser_version,
             fbx_version,
             health: Health::Running,
             started_nodes: Vec::new(),
             last_event_kind: None,
             known_toplevel_nodes_count: 0,
+            subtree_end_offset: None,
+            node_count: 0,
+            decompressed_bytes: 0,
         }
     }
 
@@ -603,7 +1229,7 @@ impl State {
 
 /// Event kind.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum EventKind {
+pub(crate) enum EventKind {
     /// Node start.
     StartNode,
     /// Node end.
@@ -620,6 +1246,9 @@ struct StartedNode {
     /// End offset of the node.
     ///
     /// "End offset" means a next byte of the last byte of the last node.
+    ///
+    /// This is `u64::MAX` for FBX ASCII, since it is unknown until the node
+    /// ends.
     node_end_offset: u64,
     /// Number of node attributes.
     attributes_count: u64,
@@ -628,7 +1257,23 @@ struct StartedNode {
     /// "End offset" means a next byte of the last byte of the last attribute.
     attributes_end_offset: u64,
     /// Node name.
-    name: String,
+    name: NodeName,
     /// Number of known children.
     known_children_count: usize,
+    /// Node header.
+    ///
+    /// This is `None` for FBX ASCII.
+    header: Option<NodeHeader>,
+}
+
+/// Node name of a started node.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum NodeName {
+    /// Name copied from the source.
+    Owned(String),
+    /// Name in the source data, as the start and end offsets.
+    ///
+    /// This is used if the source has the data in memory (see
+    /// [`ParserSource::as_slice`]).
+    InSource(u64, u64),
 }
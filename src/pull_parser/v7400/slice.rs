@@ -0,0 +1,325 @@
+//! Zero-copy parsing for FBX binary in memory.
+//!
+//! If the whole FBX binary is already in memory (for example, memory-mapped
+//! files or asset packs), [`from_slice`] can be used to create a
+//! [`Parser`] reading the data through [`SliceSource`].
+//! The parser works in the same way as the parser created from readers
+//! (including the recovery mode, resource limits, and attribute layouts),
+//! but it does not copy node names, and the attributes can be read by
+//! [`Attributes::next_borrowed`][`super::Attributes::next_borrowed`] without
+//! copying them:
+//!
+//! * Node names are available as `&'a str` through
+//!   [`StartNode::borrowed_name`][`super::StartNode::borrowed_name`].
+//! * Strings and binaries borrow the input data.
+//! * Array attributes are exposed as [`ArrayView`]s, which borrow
+//!   little-endian elements if the array is not compressed.
+//!
+//! FBX 7.x binary and FBX 6.x binary are supported (see
+//! [`Parser::parser_version`]), but FBX ASCII is not supported.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use fbxcel::pull_parser::v7400::{from_slice, slice::SliceAttribute, Event};
+//!
+//! let data = std::fs::read("sample.fbx").expect("Failed to read file");
+//! let mut parser = from_slice(&data).expect("Failed to setup parser");
+//! loop {
+//!     match parser.next_event().expect("Failed to parse") {
+//!         Event::StartNode(node) => {
+//!             // The name borrows `data`, not the parser.
+//!             let name: &str = node.borrowed_name();
+//!             let mut attrs = node.attributes();
+//!             while let Some(attr) = attrs.next_borrowed().expect("Failed to read attribute") {
+//!                 match attr {
+//!                     SliceAttribute::String(s) => println!("{}: {:?}", name, s),
+//!                     SliceAttribute::ArrF64(arr) => {
+//!                         // Little-endian elements, if not compressed.
+//!                         let _bytes: Option<&[u8]> = arr.as_le_bytes();
+//!                     }
+//!                     _ => {}
+//!                 }
+//!             }
+//!         }
+//!         Event::EndNode => {}
+//!         Event::EndFbx(_) => break,
+//!     }
+//! }
+//! ```
+
+use std::{
+    borrow::Cow,
+    io::{self, Read},
+    marker::PhantomData,
+};
+
+use byteorder::{ByteOrder, LittleEndian};
+use libflate::zlib::Decoder as ZlibDecoder;
+
+use crate::{
+    low::v7400::{ArrayAttributeEncoding, ArrayAttributeHeader, AttributeType, AttributeValue},
+    pull_parser::{
+        error::{Compression, DataError},
+        reader::SliceSource,
+        v7400::{load_binary_header, Parser},
+        Result,
+    },
+};
+
+/// Parser for FBX binary in memory.
+pub type SliceParser<'a> = Parser<SliceSource<'a>>;
+
+/// Creates a new [`Parser`] from the given FBX binary in memory.
+///
+/// The slice should contain the whole FBX binary, including the header.
+/// The parser behaves as [`ParserVersion::V6100`][`crate::pull_parser::ParserVersion::V6100`]
+/// for FBX 6.x, and as [`ParserVersion::V7400`][`crate::pull_parser::ParserVersion::V7400`]
+/// for FBX 7.x.
+///
+/// Returns an error if the header is broken or the FBX version is
+/// unsupported.
+pub fn from_slice(data: &[u8]) -> Result<SliceParser<'_>> {
+    let (header, parser_version) = load_binary_header(data)?;
+    Parser::create_with_parser_version(
+        parser_version,
+        header.version(),
+        SliceSource::with_offset(data, header.len()),
+    )
+}
+
+/// Node attribute borrowing the input data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SliceAttribute<'a> {
+    /// Single `bool`.
+    Bool(bool),
+    /// Single `i16`.
+    I16(i16),
+    /// Single `i32`.
+    I32(i32),
+    /// Single `i64`.
+    I64(i64),
+    /// Single `f32`.
+    F32(f32),
+    /// Single `f64`.
+    F64(f64),
+    /// Array of `bool`.
+    ArrBool(ArrayView<'a, bool>),
+    /// Array of `i32`.
+    ArrI32(ArrayView<'a, i32>),
+    /// Array of `i64`.
+    ArrI64(ArrayView<'a, i64>),
+    /// Array of `f32`.
+    ArrF32(ArrayView<'a, f32>),
+    /// Array of `f64`.
+    ArrF64(ArrayView<'a, f64>),
+    /// Binary.
+    Binary(&'a [u8]),
+    /// UTF-8 string.
+    String(&'a str),
+}
+
+impl<'a> SliceAttribute<'a> {
+    /// Creates an array attribute from the header and the raw elements.
+    pub(crate) fn from_array(
+        attr_type: AttributeType,
+        header: &ArrayAttributeHeader,
+        raw: &'a [u8],
+    ) -> Result<Self> {
+        Ok(match attr_type {
+            AttributeType::ArrBool => SliceAttribute::ArrBool(ArrayView::new(header, raw, 1)?),
+            AttributeType::ArrI32 => SliceAttribute::ArrI32(ArrayView::new(header, raw, 4)?),
+            AttributeType::ArrI64 => SliceAttribute::ArrI64(ArrayView::new(header, raw, 8)?),
+            AttributeType::ArrF32 => SliceAttribute::ArrF32(ArrayView::new(header, raw, 4)?),
+            AttributeType::ArrF64 => SliceAttribute::ArrF64(ArrayView::new(header, raw, 8)?),
+            _ => unreachable!("Should never fail: `attr_type` should be an array type"),
+        })
+    }
+
+    /// Creates a single value attribute from the loaded value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not a single value.
+    pub(crate) fn from_scalar(value: AttributeValue) -> Self {
+        match value {
+            AttributeValue::Bool(v) => SliceAttribute::Bool(v),
+            AttributeValue::I16(v) => SliceAttribute::I16(v),
+            AttributeValue::I32(v) => SliceAttribute::I32(v),
+            AttributeValue::I64(v) => SliceAttribute::I64(v),
+            AttributeValue::F32(v) => SliceAttribute::F32(v),
+            AttributeValue::F64(v) => SliceAttribute::F64(v),
+            v => unreachable!(
+                "Should never fail: {:?} should be a single value",
+                v.type_()
+            ),
+        }
+    }
+
+    /// Returns the value type.
+    pub fn type_(&self) -> AttributeType {
+        match self {
+            SliceAttribute::Bool(_) => AttributeType::Bool,
+            SliceAttribute::I16(_) => AttributeType::I16,
+            SliceAttribute::I32(_) => AttributeType::I32,
+            SliceAttribute::I64(_) => AttributeType::I64,
+            SliceAttribute::F32(_) => AttributeType::F32,
+            SliceAttribute::F64(_) => AttributeType::F64,
+            SliceAttribute::ArrBool(_) => AttributeType::ArrBool,
+            SliceAttribute::ArrI32(_) => AttributeType::ArrI32,
+            SliceAttribute::ArrI64(_) => AttributeType::ArrI64,
+            SliceAttribute::ArrF32(_) => AttributeType::ArrF32,
+            SliceAttribute::ArrF64(_) => AttributeType::ArrF64,
+            SliceAttribute::Binary(_) => AttributeType::Binary,
+            SliceAttribute::String(_) => AttributeType::String,
+        }
+    }
+
+    /// Creates an owned [`AttributeValue`].
+    ///
+    /// This allocates memory, and decompresses arrays if necessary.
+    pub fn to_value(&self) -> Result<AttributeValue> {
+        Ok(match *self {
+            SliceAttribute::Bool(v) => AttributeValue::Bool(v),
+            SliceAttribute::I16(v) => AttributeValue::I16(v),
+            SliceAttribute::I32(v) => AttributeValue::I32(v),
+            SliceAttribute::I64(v) => AttributeValue::I64(v),
+            SliceAttribute::F32(v) => AttributeValue::F32(v),
+            SliceAttribute::F64(v) => AttributeValue::F64(v),
+            SliceAttribute::ArrBool(v) => AttributeValue::ArrBool(v.to_vec()?),
+            SliceAttribute::ArrI32(v) => AttributeValue::ArrI32(v.to_vec()?),
+            SliceAttribute::ArrI64(v) => AttributeValue::ArrI64(v.to_vec()?),
+            SliceAttribute::ArrF32(v) => AttributeValue::ArrF32(v.to_vec()?),
+            SliceAttribute::ArrF64(v) => AttributeValue::ArrF64(v.to_vec()?),
+            SliceAttribute::Binary(v) => AttributeValue::Binary(v.to_owned()),
+            SliceAttribute::String(v) => AttributeValue::String(v.to_owned()),
+        })
+    }
+}
+
+/// View of an array attribute borrowing the input data.
+///
+/// Elements are stored in little endian.
+/// Boolean elements are stored as a byte for each, and any bytes with the
+/// lowest bit set are considered as `true`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArrayView<'a, T> {
+    /// Number of elements.
+    elements_count: u32,
+    /// Encoding.
+    encoding: ArrayAttributeEncoding,
+    /// Raw (possibly compressed) data.
+    raw: &'a [u8],
+    /// Length of the decoded elements in bytes.
+    bytelen_elements: usize,
+    /// Element type.
+    _element: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> ArrayView<'a, T> {
+    /// Creates a new `ArrayView` from the header and the raw elements.
+    fn new(header: &ArrayAttributeHeader, raw: &'a [u8], elem_size: usize) -> Result<Self> {
+        let bytelen_elements = (header.elements_count as usize)
+            .checked_mul(elem_size)
+            .ok_or(DataError::NodeAttributeError)?;
+        if header.encoding == ArrayAttributeEncoding::Direct && raw.len() < bytelen_elements {
+            return Err(DataError::NodeAttributeError.into());
+        }
+
+        Ok(Self {
+            elements_count: header.elements_count,
+            encoding: header.encoding,
+            raw,
+            bytelen_elements,
+            _element: PhantomData,
+        })
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.elements_count as usize
+    }
+
+    /// Returns `true` if the array has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.elements_count == 0
+    }
+
+    /// Returns the array encoding.
+    pub fn encoding(&self) -> ArrayAttributeEncoding {
+        self.encoding
+    }
+
+    /// Returns the raw data as stored in the input, possibly compressed.
+    pub fn raw_bytes(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Returns the little-endian elements if the array is not compressed.
+    ///
+    /// Returns `None` if the array is compressed.
+    pub fn as_le_bytes(&self) -> Option<&'a [u8]> {
+        match self.encoding {
+            ArrayAttributeEncoding::Direct => Some(&self.raw[..self.bytelen_elements]),
+            ArrayAttributeEncoding::Zlib => None,
+        }
+    }
+
+    /// Returns the little-endian elements, decompressing them if necessary.
+    ///
+    /// This borrows the input if the array is not compressed.
+    pub fn le_bytes(&self) -> Result<Cow<'a, [u8]>> {
+        match self.encoding {
+            ArrayAttributeEncoding::Direct => Ok(Cow::Borrowed(&self.raw[..self.bytelen_elements])),
+            ArrayAttributeEncoding::Zlib => {
+                // The elements count comes from the input and may be broken,
+                // so let the buffer grow as the elements are decompressed.
+                let mut buf = Vec::new();
+                ZlibDecoder::new(self.raw)
+                    .and_then(|decoder| {
+                        decoder
+                            .take(self.bytelen_elements as u64)
+                            .read_to_end(&mut buf)
+                    })
+                    .and_then(|len| {
+                        if len == self.bytelen_elements {
+                            Ok(())
+                        } else {
+                            Err(io::ErrorKind::UnexpectedEof.into())
+                        }
+                    })
+                    .map_err(|e| DataError::BrokenCompression(Compression::Zlib, e.into()))?;
+                Ok(Cow::Owned(buf))
+            }
+        }
+    }
+}
+
+impl ArrayView<'_, bool> {
+    /// Returns the elements as a vector, decompressing them if necessary.
+    pub fn to_vec(&self) -> Result<Vec<bool>> {
+        Ok(self.le_bytes()?.iter().map(|&raw| (raw & 1) != 0).collect())
+    }
+}
+
+/// Implements `to_vec()` for `ArrayView` of the given element type.
+macro_rules! impl_array_view_to_vec {
+    ($ty_elem:ty, $size:expr, $read_elem:ident) => {
+        impl ArrayView<'_, $ty_elem> {
+            /// Returns the elements as a vector, decompressing them if
+            /// necessary.
+            pub fn to_vec(&self) -> Result<Vec<$ty_elem>> {
+                Ok(self
+                    .le_bytes()?
+                    .chunks_exact($size)
+                    .map(LittleEndian::$read_elem)
+                    .collect())
+            }
+        }
+    };
+}
+
+impl_array_view_to_vec! { i32, 4, read_i32 }
+impl_array_view_to_vec! { i64, 8, read_i64 }
+impl_array_view_to_vec! { f32, 4, read_f32 }
+impl_array_view_to_vec! { f64, 8, read_f64 }
@@ -0,0 +1,916 @@
+//! Validator of FBX binary.
+//!
+//! [`Parser`] stops at the first error, and the warning handler can see only
+//! the problems found before the error.
+//! [`validate`] scans the whole FBX binary instead, and reports every
+//! structural problem it finds with its syntactic position.
+//!
+//! After an error, the validator resynchronizes using the node header fields
+//! (such as `end_offset` and `bytelen_attributes`) and node end markers, so
+//! the problems in the following nodes are also reported.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use fbxcel::pull_parser::v7400::validate::{validate, Severity};
+//!
+//! let file = std::fs::File::open("sample.fbx").expect("Failed to open file");
+//! let reader = std::io::BufReader::new(file);
+//!
+//! let report = validate(reader).expect("Failed to read the file");
+//! for issue in report.issues() {
+//!     let severity = match issue.severity() {
+//!         Severity::Error => "error",
+//!         _ => "warning",
+//!     };
+//!     println!(
+//!         "{}: {} (byte {})",
+//!         severity,
+//!         issue.kind(),
+//!         issue.position().byte_pos()
+//!     );
+//! }
+//! if report.has_errors() {
+//!     eprintln!("The file is broken");
+//! }
+//! ```
+
+use std::{fmt, io};
+
+use libflate::zlib::Decoder as ZlibDecoder;
+
+use crate::{
+    low::{
+        v7400::{ArrayAttributeEncoding, AttributeType, FbxFooter, NodeHeader},
+        FbxVersion,
+    },
+    pull_parser::{
+        error::{DataError, ErrorKind},
+        reader::SeekableSource,
+        v7400::{load_binary_header, FromParser, FromReader, Parser},
+        ParserSource, ParserVersion, Result, SyntacticPosition, Warning, WarningPolicy,
+    },
+};
+
+/// Validates the FBX binary read from the given reader.
+///
+/// The reader should be positioned at the beginning of the FBX binary (i.e.
+/// at the FBX header).
+/// Byte positions in the report are relative to that position.
+/// Use of buffered reader (such as [`std::io::BufReader`]) is recommended.
+///
+/// Problems in the FBX data are reported in the returned [`Report`].
+/// This returns an error only if the FBX header is broken, the FBX version
+/// is unsupported, or an I/O error happened.
+pub fn validate<R: io::Read + io::Seek>(mut reader: R) -> Result<Report> {
+    let base_position = reader.stream_position()?;
+    let stream_len = reader.seek(io::SeekFrom::End(0))?;
+    reader.seek(io::SeekFrom::Start(base_position))?;
+
+    let (header, parser_version) = load_binary_header(&mut reader)?;
+    let mut validator = Validator {
+        source: SeekableSource::with_offset(reader, header.len()),
+        parser_version,
+        fbx_version: header.version(),
+        file_len: stream_len.saturating_sub(base_position),
+        open_nodes: Vec::new(),
+        toplevel_count: 0,
+        issues: Vec::new(),
+    };
+    if validator.scan_nodes()? {
+        validator.scan_footer()?;
+    }
+
+    Ok(Report {
+        fbx_version: validator.fbx_version,
+        issues: validator.issues,
+    })
+}
+
+/// Validation report.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Report {
+    /// FBX version.
+    fbx_version: FbxVersion,
+    /// Found issues, in document order.
+    issues: Vec<Issue>,
+}
+
+impl Report {
+    /// Returns the FBX version.
+    pub fn fbx_version(&self) -> FbxVersion {
+        self.fbx_version
+    }
+
+    /// Returns the found issues in document order.
+    pub fn issues(&self) -> &[Issue] {
+        &self.issues
+    }
+
+    /// Returns the found issues.
+    pub fn into_issues(self) -> Vec<Issue> {
+        self.issues
+    }
+
+    /// Returns an iterator of the errors.
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity() == Severity::Error)
+    }
+
+    /// Returns an iterator of the warnings.
+    pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity() == Severity::Warning)
+    }
+
+    /// Returns `true` if some errors are found.
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Returns `true` if no problems (including warnings) are found.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// A problem found by the validator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Issue {
+    /// Kind of the problem.
+    kind: IssueKind,
+    /// Syntactic position of the problem.
+    position: SyntacticPosition,
+}
+
+impl Issue {
+    /// Returns the kind of the problem.
+    pub fn kind(&self) -> &IssueKind {
+        &self.kind
+    }
+
+    /// Returns the severity of the problem.
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+
+    /// Returns the syntactic position of the problem.
+    pub fn position(&self) -> &SyntacticPosition {
+        &self.position
+    }
+}
+
+/// Severity of a problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Warning.
+    ///
+    /// [`Parser`] reports this through the warning handler, and can continue
+    /// parsing.
+    Warning,
+    /// Error.
+    ///
+    /// The data is broken, and [`Parser`] may fail or read wrong values.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Kind of a problem found by the validator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum IssueKind {
+    /// `end_offset` of the node points past the end of the file.
+    EndOffsetPastEof {
+        /// `end_offset` of the node.
+        end_offset: u64,
+        /// Length of the FBX data.
+        file_len: u64,
+    },
+    /// `end_offset` of the node points past the end of the parent node.
+    EndOffsetPastParent {
+        /// `end_offset` of the node.
+        end_offset: u64,
+        /// `end_offset` of the parent node.
+        parent_end_offset: u64,
+    },
+    /// The node does not end at `end_offset`.
+    NodeLengthMismatch {
+        /// `end_offset` of the node.
+        expected: u64,
+        /// Actual end offset of the node, if known.
+        actual: Option<u64>,
+    },
+    /// Length of the node attributes differs from `bytelen_attributes`.
+    AttributesLengthMismatch {
+        /// `bytelen_attributes` of the node.
+        expected: u64,
+        /// Actual length of the node attributes.
+        actual: u64,
+    },
+    /// A node attribute extends past the end of the node.
+    AttributePastNodeEnd,
+    /// A node end marker is missing where the marker is expected.
+    MissingNodeEndMarker,
+    /// An extra (unexpected) node end marker is found.
+    ExtraNodeEndMarker,
+    /// Node name is empty.
+    EmptyNodeName,
+    /// Node name is not valid UTF-8 string.
+    InvalidNodeNameEncoding,
+    /// Invalid node attribute type code.
+    InvalidAttributeTypeCode(u8),
+    /// Invalid array attribute encoding.
+    InvalidArrayAttributeEncoding(u32),
+    /// Length of the (decompressed) array elements differs from the length
+    /// expected from the elements count.
+    ArrayLengthMismatch {
+        /// Expected length in bytes.
+        expected: u64,
+        /// Actual length in bytes.
+        ///
+        /// Compressed elements are decompressed only up to `expected + 1`
+        /// bytes, so this is `expected + 1` for any longer compressed array.
+        actual: u64,
+    },
+    /// Compressed array elements are broken.
+    ///
+    /// The value is a description of the decompression error.
+    BrokenCompression(String),
+    /// Incorrect boolean representation.
+    ///
+    /// See [`Warning::IncorrectBooleanRepresentation`].
+    IncorrectBooleanRepresentation,
+    /// Unexpected end of the file.
+    UnexpectedEof,
+    /// FBX footer is broken or missing.
+    BrokenFbxFooter,
+    /// Footer padding length is invalid.
+    InvalidFooterPaddingLength {
+        /// Expected padding length.
+        expected: usize,
+        /// Actual padding length.
+        actual: usize,
+    },
+    /// Unexpected value for footer fields (mainly for unknown fields).
+    UnexpectedFooterFieldValue,
+    /// Extra data after the FBX footer.
+    TrailingData {
+        /// Length of the extra data in bytes.
+        len: u64,
+    },
+}
+
+impl IssueKind {
+    /// Returns the severity of the problem.
+    pub fn severity(&self) -> Severity {
+        match self {
+            IssueKind::MissingNodeEndMarker
+            | IssueKind::ExtraNodeEndMarker
+            | IssueKind::EmptyNodeName
+            | IssueKind::IncorrectBooleanRepresentation
+            | IssueKind::InvalidFooterPaddingLength { .. }
+            | IssueKind::UnexpectedFooterFieldValue
+            | IssueKind::TrailingData { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueKind::EndOffsetPastEof {
+                end_offset,
+                file_len,
+            } => write!(
+                f,
+                "Node end offset {} is past the end of the file (length {})",
+                end_offset, file_len
+            ),
+            IssueKind::EndOffsetPastParent {
+                end_offset,
+                parent_end_offset,
+            } => write!(
+                f,
+                "Node end offset {} is past the end of the parent node ({})",
+                end_offset, parent_end_offset
+            ),
+            IssueKind::NodeLengthMismatch {
+                expected,
+                actual: Some(actual),
+            } => write!(
+                f,
+                "Node length mismatch: expected the node to end at {}, but ends at {}",
+                expected, actual
+            ),
+            IssueKind::NodeLengthMismatch {
+                expected,
+                actual: None,
+            } => write!(
+                f,
+                "Node length mismatch: expected the node to end at {}, but it does not",
+                expected
+            ),
+            IssueKind::AttributesLengthMismatch { expected, actual } => write!(
+                f,
+                "Node attributes length mismatch: expected {} bytes, got {} bytes",
+                expected, actual
+            ),
+            IssueKind::AttributePastNodeEnd => {
+                write!(f, "Node attribute extends past the end of the node")
+            }
+            IssueKind::MissingNodeEndMarker => write!(f, "Missing node end marker"),
+            IssueKind::ExtraNodeEndMarker => write!(f, "Extra (unexpected) node end marker found"),
+            IssueKind::EmptyNodeName => write!(f, "Node name is empty"),
+            IssueKind::InvalidNodeNameEncoding => write!(f, "Invalid node name encoding"),
+            IssueKind::InvalidAttributeTypeCode(code) => {
+                write!(f, "Invalid node attribute type code: {:?}", code)
+            }
+            IssueKind::InvalidArrayAttributeEncoding(encoding) => {
+                write!(f, "Invalid array attribute encoding: {:?}", encoding)
+            }
+            IssueKind::ArrayLengthMismatch { expected, actual } => write!(
+                f,
+                "Array elements length mismatch: expected {} bytes, got {} bytes",
+                expected, actual
+            ),
+            IssueKind::BrokenCompression(e) => {
+                write!(f, "Data with broken compression (codec=zlib): {}", e)
+            }
+            IssueKind::IncorrectBooleanRepresentation => {
+                write!(f, "Incorrect boolean representation")
+            }
+            IssueKind::UnexpectedEof => write!(f, "Unexpected end of the file"),
+            IssueKind::BrokenFbxFooter => write!(f, "Broken FBX footer"),
+            IssueKind::InvalidFooterPaddingLength { expected, actual } => write!(
+                f,
+                "Invalid footer padding length: expected {} bytes, got {} bytes",
+                expected, actual
+            ),
+            IssueKind::UnexpectedFooterFieldValue => write!(f, "Unexpected footer field value"),
+            IssueKind::TrailingData { len } => {
+                write!(f, "Extra {} bytes after the FBX footer", len)
+            }
+        }
+    }
+}
+
+/// Node currently being validated.
+#[derive(Debug, Clone)]
+struct OpenNode {
+    /// Node name.
+    name: String,
+    /// Index of the node in its siblings.
+    index: usize,
+    /// Start offset of the node.
+    start_offset: u64,
+    /// End offset of the node.
+    ///
+    /// If `end_offset` in the node header is invalid, this is the end offset
+    /// of the attributes (if the node has attributes), or the end offset of
+    /// the parent node (or the length of the file).
+    end_offset: u64,
+    /// Whether `end_offset` is the valid value in the node header.
+    has_valid_end_offset: bool,
+    /// Number of the node attributes.
+    num_attributes: u64,
+    /// Number of the children found so far.
+    children_count: usize,
+}
+
+/// Validator state.
+struct Validator<R> {
+    /// Reader.
+    source: SeekableSource<R>,
+    /// Parser version.
+    parser_version: ParserVersion,
+    /// FBX version.
+    fbx_version: FbxVersion,
+    /// Length of the FBX data.
+    file_len: u64,
+    /// Nodes currently open.
+    open_nodes: Vec<OpenNode>,
+    /// Number of the toplevel nodes found so far.
+    toplevel_count: usize,
+    /// Found issues.
+    issues: Vec<Issue>,
+}
+
+impl<R: io::Read + io::Seek> Validator<R> {
+    /// Returns the syntactic position.
+    fn position(
+        &self,
+        byte_pos: u64,
+        component_byte_pos: u64,
+        attribute_index: Option<usize>,
+    ) -> SyntacticPosition {
+        SyntacticPosition {
+            byte_pos,
+            component_byte_pos,
+            node_path: self
+                .open_nodes
+                .iter()
+                .map(|node| (node.index, node.name.clone()))
+                .collect(),
+            attribute_index,
+        }
+    }
+
+    /// Adds an issue at the given position in the current node.
+    fn report(&mut self, kind: IssueKind, byte_pos: u64) {
+        let component_byte_pos = self.open_nodes.last().map_or(0, |node| node.start_offset);
+        let position = self.position(byte_pos, component_byte_pos, None);
+        self.issues.push(Issue { kind, position });
+    }
+
+    /// Adds an issue at the given position in the attribute.
+    fn report_attribute(&mut self, kind: IssueKind, byte_pos: u64, start: u64, index: usize) {
+        let position = self.position(byte_pos, start, Some(index));
+        self.issues.push(Issue { kind, position });
+    }
+
+    /// Returns `true` if `len` bytes from the current position fit before
+    /// `limit`.
+    fn fits(&self, len: u64, limit: u64) -> bool {
+        self.source
+            .position()
+            .checked_add(len)
+            .map_or(false, |end| end <= limit)
+    }
+
+    /// Scans the nodes.
+    ///
+    /// Returns `true` if the end of the implicit root node is found.
+    fn scan_nodes(&mut self) -> Result<bool> {
+        let header_len: u64 = if self.fbx_version.raw() < 7500 {
+            13
+        } else {
+            25
+        };
+
+        loop {
+            let node_start_offset = self.source.position();
+
+            // Close the node ending here without node end marker.
+            if let Some(current) = self.open_nodes.last() {
+                if current.end_offset <= node_start_offset {
+                    if current.end_offset < node_start_offset {
+                        if current.has_valid_end_offset {
+                            let expected = current.end_offset;
+                            self.report(
+                                IssueKind::NodeLengthMismatch {
+                                    expected,
+                                    actual: None,
+                                },
+                                node_start_offset,
+                            );
+                        }
+                    } else if current.has_valid_end_offset
+                        && (current.children_count != 0 || current.num_attributes == 0)
+                    {
+                        self.report(IssueKind::MissingNodeEndMarker, node_start_offset);
+                    }
+                    self.open_nodes.pop();
+                    continue;
+                }
+            }
+
+            if !self.fits(header_len, self.file_len) {
+                self.report(IssueKind::UnexpectedEof, node_start_offset);
+                return Ok(false);
+            }
+            let node_header = NodeHeader::from_reader(&mut self.source, self.fbx_version)?;
+            let header_end_offset = self.source.position();
+
+            if node_header.is_node_end() {
+                let (has_valid_end_offset, end_offset, has_attributes, has_children) =
+                    match self.open_nodes.last() {
+                        Some(v) => (
+                            v.has_valid_end_offset,
+                            v.end_offset,
+                            v.num_attributes != 0,
+                            v.children_count != 0,
+                        ),
+                        // End of the implicit root node.
+                        None => return Ok(true),
+                    };
+                if has_valid_end_offset && end_offset != header_end_offset {
+                    self.report(
+                        IssueKind::NodeLengthMismatch {
+                            expected: end_offset,
+                            actual: Some(header_end_offset),
+                        },
+                        node_start_offset,
+                    );
+                }
+                if has_attributes && !has_children {
+                    self.report(IssueKind::ExtraNodeEndMarker, node_start_offset);
+                }
+                self.open_nodes.pop();
+                continue;
+            }
+
+            if !self.scan_node(node_start_offset, node_header)? {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Scans the node name and the attributes of a node.
+    ///
+    /// The node header is already read.
+    /// Returns `false` if the file ends in the node name.
+    fn scan_node(&mut self, start_offset: u64, header: NodeHeader) -> Result<bool> {
+        let (index, parent_end_offset) = match self.open_nodes.last_mut() {
+            Some(parent) => {
+                parent.children_count += 1;
+                (parent.children_count - 1, Some(parent.end_offset))
+            }
+            None => {
+                self.toplevel_count += 1;
+                (self.toplevel_count - 1, None)
+            }
+        };
+        // Limit of the node end offset.
+        let end_limit = parent_end_offset.unwrap_or(self.file_len);
+        let name_offset = self.source.position();
+
+        // Read the node name.
+        let name_len = u64::from(header.bytelen_name);
+        let name = if self.fits(name_len, self.file_len) {
+            let mut buf = vec![0; usize::from(header.bytelen_name)];
+            io::Read::read_exact(&mut self.source, &mut buf)?;
+            Some(String::from_utf8(buf))
+        } else {
+            None
+        };
+        self.open_nodes.push(OpenNode {
+            name: match &name {
+                Some(Ok(name)) => name.clone(),
+                Some(Err(e)) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+                None => String::new(),
+            },
+            index,
+            start_offset,
+            end_offset: end_limit,
+            has_valid_end_offset: false,
+            num_attributes: header.num_attributes,
+            children_count: 0,
+        });
+        match name {
+            Some(Ok(_)) => {}
+            Some(Err(_)) => self.report(IssueKind::InvalidNodeNameEncoding, name_offset),
+            None => {
+                self.report(IssueKind::UnexpectedEof, name_offset);
+                return Ok(false);
+            }
+        }
+        if header.bytelen_name == 0 {
+            self.report(IssueKind::EmptyNodeName, name_offset);
+        }
+
+        // Check the node end offset.
+        let attributes_start_offset = self.source.position();
+        let attributes_end_offset =
+            attributes_start_offset.saturating_add(header.bytelen_attributes);
+        if header.end_offset > self.file_len {
+            self.report(
+                IssueKind::EndOffsetPastEof {
+                    end_offset: header.end_offset,
+                    file_len: self.file_len,
+                },
+                start_offset,
+            );
+        } else if let Some(parent_end_offset) = parent_end_offset.filter(|&v| header.end_offset > v)
+        {
+            self.report(
+                IssueKind::EndOffsetPastParent {
+                    end_offset: header.end_offset,
+                    parent_end_offset,
+                },
+                start_offset,
+            );
+        } else {
+            let node = self
+                .open_nodes
+                .last_mut()
+                .expect("Should never fail: the node is pushed above");
+            node.end_offset = header.end_offset;
+            node.has_valid_end_offset = true;
+        }
+        let node_end_offset = self
+            .open_nodes
+            .last()
+            .expect("Should never fail: the node is pushed above")
+            .end_offset;
+
+        // Scan the attributes.
+        let actual_end_offset = self.scan_attributes(header.num_attributes, node_end_offset)?;
+        if let Some(actual_end_offset) = actual_end_offset {
+            if actual_end_offset != attributes_end_offset {
+                self.report(
+                    IssueKind::AttributesLengthMismatch {
+                        expected: header.bytelen_attributes,
+                        actual: actual_end_offset - attributes_start_offset,
+                    },
+                    attributes_start_offset,
+                );
+            }
+        }
+
+        // Resynchronize with the declared attributes length if possible.
+        let next_offset = if attributes_end_offset <= node_end_offset {
+            attributes_end_offset
+        } else {
+            actual_end_offset.unwrap_or(node_end_offset)
+        };
+        let next_offset = next_offset.max(self.source.position());
+        self.source.skip_to(next_offset)?;
+
+        let node = self
+            .open_nodes
+            .last_mut()
+            .expect("Should never fail: the node is pushed above");
+        if !node.has_valid_end_offset && node.num_attributes != 0 {
+            // Most nodes with attributes have no children, so assume that
+            // the node ends here.
+            node.end_offset = next_offset;
+        }
+
+        Ok(true)
+    }
+
+    /// Scans the node attributes.
+    ///
+    /// Returns the end offset of the attributes, or `None` if the attributes
+    /// cannot be scanned to the end.
+    fn scan_attributes(&mut self, num_attributes: u64, limit: u64) -> Result<Option<u64>> {
+        let mut index = 0;
+        while index < num_attributes {
+            // `num_attributes` might be too large, but each attribute has
+            // at least 1 byte, so the loop stops at `limit`.
+            if !self.scan_attribute(index as usize, limit)? {
+                return Ok(None);
+            }
+            index += 1;
+        }
+
+        Ok(Some(self.source.position()))
+    }
+
+    /// Scans a node attribute.
+    ///
+    /// Returns `false` if the rest attributes cannot be scanned.
+    fn scan_attribute(&mut self, index: usize, limit: u64) -> Result<bool> {
+        let start = self.source.position();
+        if !self.fits(1, limit) {
+            self.report_attribute(IssueKind::AttributePastNodeEnd, start, start, index);
+            return Ok(false);
+        }
+        let type_code = u8::from_reader(&mut self.source)?;
+        let attr_type = match AttributeType::from_type_code(type_code) {
+            Some(v) => v,
+            None => {
+                self.report_attribute(
+                    IssueKind::InvalidAttributeTypeCode(type_code),
+                    start,
+                    start,
+                    index,
+                );
+                return Ok(false);
+            }
+        };
+
+        let value_len = match attr_type {
+            AttributeType::Bool => {
+                if !self.fits(1, limit) {
+                    self.report_attribute(IssueKind::AttributePastNodeEnd, start, start, index);
+                    return Ok(false);
+                }
+                let raw = u8::from_reader(&mut self.source)?;
+                if raw != b'T' && raw != b'Y' {
+                    self.report_attribute(
+                        IssueKind::IncorrectBooleanRepresentation,
+                        start + 1,
+                        start,
+                        index,
+                    );
+                }
+                return Ok(true);
+            }
+            AttributeType::I16 => 2,
+            AttributeType::I32 | AttributeType::F32 => 4,
+            AttributeType::I64 | AttributeType::F64 => 8,
+            AttributeType::ArrBool
+            | AttributeType::ArrI32
+            | AttributeType::ArrI64
+            | AttributeType::ArrF32
+            | AttributeType::ArrF64 => return self.scan_array(attr_type, start, index, limit),
+            AttributeType::Binary | AttributeType::String => {
+                if !self.fits(4, limit) {
+                    self.report_attribute(IssueKind::AttributePastNodeEnd, start, start, index);
+                    return Ok(false);
+                }
+                u64::from(u32::from_reader(&mut self.source)?)
+            }
+        };
+        if !self.fits(value_len, limit) {
+            let pos = self.source.position();
+            self.report_attribute(IssueKind::AttributePastNodeEnd, pos, start, index);
+            return Ok(false);
+        }
+        self.source.skip_distance(value_len)?;
+
+        Ok(true)
+    }
+
+    /// Scans an array attribute.
+    ///
+    /// The type code is already read.
+    /// Returns `false` if the rest attributes cannot be scanned.
+    fn scan_array(
+        &mut self,
+        attr_type: AttributeType,
+        start: u64,
+        index: usize,
+        limit: u64,
+    ) -> Result<bool> {
+        let header_pos = self.source.position();
+        if !self.fits(12, limit) {
+            self.report_attribute(IssueKind::AttributePastNodeEnd, header_pos, start, index);
+            return Ok(false);
+        }
+        let elements_count = u32::from_reader(&mut self.source)?;
+        let raw_encoding = u32::from_reader(&mut self.source)?;
+        let bytelen = u64::from(u32::from_reader(&mut self.source)?);
+        let data_pos = self.source.position();
+        if !self.fits(bytelen, limit) {
+            self.report_attribute(IssueKind::AttributePastNodeEnd, data_pos, start, index);
+            return Ok(false);
+        }
+
+        let is_bool = attr_type == AttributeType::ArrBool;
+        let element_len = match attr_type {
+            AttributeType::ArrBool => 1,
+            AttributeType::ArrI32 | AttributeType::ArrF32 => 4,
+            _ => 8,
+        };
+        let expected_len = u64::from(elements_count) * element_len;
+        let data = io::Read::take(&mut self.source, bytelen);
+        let decoded = match ArrayAttributeEncoding::from_u32(raw_encoding) {
+            None => {
+                self.report_attribute(
+                    IssueKind::InvalidArrayAttributeEncoding(raw_encoding),
+                    header_pos + 4,
+                    start,
+                    index,
+                );
+                None
+            }
+            Some(ArrayAttributeEncoding::Direct) if is_bool => Some(scan_elements(data, true)?),
+            Some(ArrayAttributeEncoding::Direct) => Some((bytelen, false)),
+            // Any error of the limited reader is a decompression error,
+            // since the reader is known to have enough bytes.
+            // Decompression stops one byte past the expected length, so that
+            // a small stream cannot inflate to an unbounded length.
+            Some(ArrayAttributeEncoding::Zlib) => {
+                match ZlibDecoder::new(data).and_then(|decoder| {
+                    scan_elements(io::Read::take(decoder, expected_len + 1), is_bool)
+                }) {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        self.report_attribute(
+                            IssueKind::BrokenCompression(e.to_string()),
+                            data_pos,
+                            start,
+                            index,
+                        );
+                        None
+                    }
+                }
+            }
+        };
+        if let Some((decoded_len, has_incorrect_boolean)) = decoded {
+            if decoded_len != expected_len {
+                self.report_attribute(
+                    IssueKind::ArrayLengthMismatch {
+                        expected: expected_len,
+                        actual: decoded_len,
+                    },
+                    data_pos,
+                    start,
+                    index,
+                );
+            }
+            if has_incorrect_boolean {
+                self.report_attribute(
+                    IssueKind::IncorrectBooleanRepresentation,
+                    data_pos,
+                    start,
+                    index,
+                );
+            }
+        }
+        self.source.skip_to(data_pos + bytelen)?;
+
+        Ok(true)
+    }
+
+    /// Scans the FBX footer.
+    fn scan_footer(&mut self) -> Result<()> {
+        let footer_pos = self.source.position();
+        let mut parser = Parser::create_with_parser_version(
+            self.parser_version,
+            self.fbx_version,
+            &mut self.source,
+        )?;
+        let policy = WarningPolicy::collecting();
+        parser.set_warning_policy(policy.clone());
+        let footer = FbxFooter::read_from_parser(&mut parser);
+        drop(parser);
+
+        for (warning, position) in policy.collector().take() {
+            let kind = match warning {
+                Warning::InvalidFooterPaddingLength(expected, actual) => {
+                    IssueKind::InvalidFooterPaddingLength { expected, actual }
+                }
+                Warning::UnexpectedFooterFieldValue => IssueKind::UnexpectedFooterFieldValue,
+                warning => unreachable!("Unexpected warning for the footer: {:?}", warning),
+            };
+            self.issues.push(Issue { kind, position });
+        }
+        match footer {
+            Ok(_) => {}
+            Err(e) => {
+                let is_broken = match e.kind() {
+                    ErrorKind::Data => e.downcast_ref::<DataError>().is_some(),
+                    ErrorKind::Io => e
+                        .downcast_ref::<io::Error>()
+                        .map_or(false, |e| e.kind() == io::ErrorKind::UnexpectedEof),
+                    _ => false,
+                };
+                if !is_broken {
+                    return Err(e);
+                }
+                let position = e
+                    .position()
+                    .cloned()
+                    .unwrap_or_else(|| self.position(footer_pos, footer_pos, None));
+                self.issues.push(Issue {
+                    kind: IssueKind::BrokenFbxFooter,
+                    position,
+                });
+                return Ok(());
+            }
+        }
+
+        let footer_end = self.source.position();
+        if footer_end < self.file_len {
+            self.report(
+                IssueKind::TrailingData {
+                    len: self.file_len - footer_end,
+                },
+                footer_end,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the array elements to the end.
+///
+/// Returns the length of the elements, and whether an incorrect boolean
+/// value is found (if `is_bool` is true).
+fn scan_elements(mut reader: impl io::Read, is_bool: bool) -> io::Result<(u64, bool)> {
+    let mut buf = [0u8; 4096];
+    let mut len = 0;
+    let mut has_incorrect_boolean = false;
+    loop {
+        let read_len = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(v) => v,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        len += read_len as u64;
+        if is_bool && !has_incorrect_boolean {
+            has_incorrect_boolean = buf[..read_len]
+                .iter()
+                .any(|&raw| raw != b'T' && raw != b'Y');
+        }
+    }
+
+    Ok((len, has_incorrect_boolean))
+}
@@ -11,6 +11,8 @@ use crate::low::FbxVersion;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[non_exhaustive]
 pub enum ParserVersion {
+    /// FBX 6.x (6.1 is explicitly supported).
+    V6100,
     /// FBX 7.4 and 7.5.
     V7400,
 }
@@ -20,6 +22,12 @@ impl ParserVersion {
     pub fn from_fbx_version(fbx_version: FbxVersion) -> Option<Self> {
         let raw = fbx_version.raw();
         match raw {
+            6000..=6999 => {
+                if raw != 6100 {
+                    info!("FBX-6.x (except for 6.1) might be successfully read, but unsupported");
+                }
+                Some(ParserVersion::V6100)
+            }
             7000..=7999 => {
                 if raw < 7400 {
                     info!("<FBX-7.4 might be successfully read, but unsupported");
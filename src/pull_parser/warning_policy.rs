@@ -0,0 +1,199 @@
+//! Ready-made warning handlers.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use crate::pull_parser::{error::WarningKind, Result, SyntacticPosition, Warning};
+
+/// Action for a warning, decided by [`WarningPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarningAction {
+    /// Ignores the warning and continues parsing.
+    Ignore,
+    /// Stores the warning to the collector of the policy, and continues
+    /// parsing.
+    Collect,
+    /// Treats the warning as a critical error, and aborts parsing.
+    Fatal,
+}
+
+impl Default for WarningAction {
+    fn default() -> Self {
+        WarningAction::Ignore
+    }
+}
+
+/// Warning policy.
+///
+/// This decides the [`WarningAction`] for each kind of warnings, and can be
+/// set to parsers by
+/// [`Parser::set_warning_policy()`][`crate::pull_parser::v7400::Parser::set_warning_policy`].
+///
+/// Cloned policies share the same collector, so the collected warnings can
+/// be read back from the policy after the parser (or the loader) which took
+/// the policy has finished.
+///
+/// The default policy is [`lenient`][`Self::lenient`], which is the same
+/// behavior as parsers without warning handlers.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use fbxcel::low::FbxHeader;
+/// use fbxcel::pull_parser::{error::WarningKind, WarningAction, WarningPolicy};
+/// # let reader = std::io::empty();
+/// # let header: FbxHeader = unimplemented!();
+///
+/// // Reject broken node structures, and collect other warnings.
+/// let policy = WarningPolicy::new(WarningAction::Collect)
+///     .with_action(WarningKind::MissingNodeEndMarker, WarningAction::Fatal)
+///     .with_action(WarningKind::ExtraNodeEndMarker, WarningAction::Fatal);
+/// let mut parser = fbxcel::pull_parser::v7400::from_reader(header, reader)
+///     .expect("Failed to create parser");
+/// parser.set_warning_policy(policy.clone());
+///
+/// // ... parse ...
+///
+/// for (warning, pos) in policy.collector().take() {
+///     eprintln!("WARNING: {} (pos={:?})", warning, pos);
+/// }
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct WarningPolicy {
+    /// Action for warnings without specific actions.
+    default_action: WarningAction,
+    /// Actions for each warning kind.
+    actions: HashMap<WarningKind, WarningAction>,
+    /// Collector for warnings with [`WarningAction::Collect`].
+    collector: WarningCollector,
+}
+
+impl WarningPolicy {
+    /// Creates a new policy which applies the given action to all warnings.
+    pub fn new(default_action: WarningAction) -> Self {
+        Self {
+            default_action,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a new policy which treats all warnings as critical errors.
+    pub fn strict() -> Self {
+        Self::new(WarningAction::Fatal)
+    }
+
+    /// Creates a new policy which ignores all warnings.
+    pub fn lenient() -> Self {
+        Self::new(WarningAction::Ignore)
+    }
+
+    /// Creates a new policy which collects all warnings.
+    pub fn collecting() -> Self {
+        Self::new(WarningAction::Collect)
+    }
+
+    /// Sets the action for the given kind of warnings, and returns the policy.
+    pub fn with_action(mut self, kind: WarningKind, action: WarningAction) -> Self {
+        self.actions.insert(kind, action);
+        self
+    }
+
+    /// Returns the action for the given kind of warnings.
+    pub fn action(&self, kind: WarningKind) -> WarningAction {
+        self.actions
+            .get(&kind)
+            .copied()
+            .unwrap_or(self.default_action)
+    }
+
+    /// Returns the collector for warnings with [`WarningAction::Collect`].
+    pub fn collector(&self) -> &WarningCollector {
+        &self.collector
+    }
+
+    /// Handles the warning according to the policy.
+    ///
+    /// This can be called from custom warning handlers, for example to log
+    /// warnings before applying the policy.
+    pub fn handle(&self, warning: Warning, pos: &SyntacticPosition) -> Result<()> {
+        match self.action(warning.kind()) {
+            WarningAction::Ignore => Ok(()),
+            WarningAction::Collect => {
+                self.collector.push(warning, pos.clone());
+                Ok(())
+            }
+            WarningAction::Fatal => Err(warning.into()),
+        }
+    }
+}
+
+/// Collecting warning sink.
+///
+/// Cloned collectors share the same storage.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use fbxcel::low::FbxHeader;
+/// use fbxcel::pull_parser::WarningCollector;
+/// # let reader = std::io::empty();
+/// # let header: FbxHeader = unimplemented!();
+///
+/// let collector = WarningCollector::new();
+/// let mut parser = fbxcel::pull_parser::v7400::from_reader(header, reader)
+///     .expect("Failed to create parser");
+/// let sink = collector.clone();
+/// parser.set_warning_handler(move |warning, pos| {
+///     sink.push(warning, pos.clone());
+///     Ok(())
+/// });
+///
+/// // ... parse ...
+///
+/// let warnings = collector.take();
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct WarningCollector {
+    /// Collected warnings.
+    warnings: Arc<Mutex<Vec<(Warning, SyntacticPosition)>>>,
+}
+
+impl WarningCollector {
+    /// Creates a new empty collector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the warning to the collector.
+    pub fn push(&self, warning: Warning, pos: SyntacticPosition) {
+        self.lock().push((warning, pos));
+    }
+
+    /// Returns the number of the collected warnings.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if no warnings are collected.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Takes the collected warnings out of the collector.
+    ///
+    /// The collector will be empty after this call.
+    pub fn take(&self) -> Vec<(Warning, SyntacticPosition)> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// Locks the storage.
+    fn lock(&self) -> MutexGuard<'_, Vec<(Warning, SyntacticPosition)>> {
+        // Warnings are only pushed, so the storage is valid even if another
+        // thread panicked with the lock.
+        self.warnings
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
@@ -7,7 +7,7 @@ use log::warn;
 pub use self::error::{Error, Result};
 use crate::{
     low::{self, FbxVersion},
-    pull_parser::{self, any::AnyParser},
+    pull_parser::{self, any::AnyParser, ParserSource, WarningPolicy},
     tree,
 };
 
@@ -16,6 +16,15 @@ mod error;
 /// FBX tree type with any supported version.
 #[non_exhaustive]
 pub enum AnyTree {
+    /// FBX 6.x.
+    ///
+    /// FBX 6.x has the same low-level node structure as FBX 7.4, so the tree
+    /// and footer types are shared with `V7400`.
+    V6100(
+        FbxVersion,
+        tree::v7400::Tree,
+        std::result::Result<Box<low::v7400::FbxFooter>, pull_parser::Error>,
+    ),
     /// FBX 7.4 or later.
     V7400(
         FbxVersion,
@@ -30,32 +39,74 @@ impl AnyTree {
     /// This works for seekable readers (which implement [`std::io::Seek`]), but
     /// [`from_seekable_reader`][`Self::from_seekable_reader`] should be used for them, because it is more
     /// efficent.
+    ///
+    /// Warnings are logged and ignored.
     pub fn from_reader(reader: impl Read) -> Result<Self> {
-        match pull_parser::any::from_reader(reader)? {
-            AnyParser::V7400(mut parser) => {
-                let fbx_version = parser.fbx_version();
-                parser.set_warning_handler(|w, pos| {
-                    warn!("WARNING: {} (pos={:?})", w, pos);
-                    Ok(())
-                });
-                let tree_loader = tree::v7400::Loader::new();
-                let (tree, footer) = tree_loader.load(&mut parser)?;
-                Ok(AnyTree::V7400(fbx_version, tree, footer))
-            }
-        }
+        Self::from_parser(pull_parser::any::from_reader(reader)?, None)
     }
 
     /// Loads a tree from the given seekable reader.
+    ///
+    /// Warnings are logged and ignored.
     pub fn from_seekable_reader(reader: impl Read + Seek) -> Result<Self> {
-        match pull_parser::any::from_seekable_reader(reader)? {
+        Self::from_parser(pull_parser::any::from_seekable_reader(reader)?, None)
+    }
+
+    /// Loads a tree from the given reader, handling warnings by the given
+    /// policy.
+    ///
+    /// See [`from_reader`][`Self::from_reader`] for detail.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use fbxcel::{pull_parser::WarningPolicy, tree::any::AnyTree};
+    ///
+    /// let file = std::fs::File::open("sample.fbx").expect("Failed to open file");
+    /// let reader = std::io::BufReader::new(file);
+    /// let policy = WarningPolicy::collecting();
+    /// let tree = AnyTree::from_reader_with_warning_policy(reader, policy.clone())
+    ///     .expect("Failed to load tree");
+    /// for (warning, pos) in policy.collector().take() {
+    ///     eprintln!("WARNING: {} (pos={:?})", warning, pos);
+    /// }
+    /// ```
+    pub fn from_reader_with_warning_policy(
+        reader: impl Read,
+        policy: WarningPolicy,
+    ) -> Result<Self> {
+        Self::from_parser(pull_parser::any::from_reader(reader)?, Some(policy))
+    }
+
+    /// Loads a tree from the given seekable reader, handling warnings by the
+    /// given policy.
+    ///
+    /// See [`from_reader_with_warning_policy`][`Self::from_reader_with_warning_policy`]
+    /// for detail.
+    pub fn from_seekable_reader_with_warning_policy(
+        reader: impl Read + Seek,
+        policy: WarningPolicy,
+    ) -> Result<Self> {
+        Self::from_parser(
+            pull_parser::any::from_seekable_reader(reader)?,
+            Some(policy),
+        )
+    }
+
+    /// Loads a tree from the given parser.
+    ///
+    /// If the policy is not given, warnings are logged and ignored.
+    fn from_parser<R: ParserSource>(
+        parser: AnyParser<R>,
+        policy: Option<WarningPolicy>,
+    ) -> Result<Self> {
+        match parser {
+            AnyParser::V6100(mut parser) => {
+                let (fbx_version, tree, footer) = load_v7400_tree(&mut parser, policy)?;
+                Ok(AnyTree::V6100(fbx_version, tree, footer))
+            }
             AnyParser::V7400(mut parser) => {
-                let fbx_version = parser.fbx_version();
-                parser.set_warning_handler(|w, pos| {
-                    warn!("WARNING: {} (pos={:?})", w, pos);
-                    Ok(())
-                });
-                let tree_loader = tree::v7400::Loader::new();
-                let (tree, footer) = tree_loader.load(&mut parser)?;
+                let (fbx_version, tree, footer) = load_v7400_tree(&mut parser, policy)?;
                 Ok(AnyTree::V7400(fbx_version, tree, footer))
             }
         }
@@ -64,7 +115,35 @@ impl AnyTree {
     /// Returns the FBX version of the document the tree came from.
     pub fn fbx_version(&self) -> FbxVersion {
         match self {
+            Self::V6100(ver, _, _) => *ver,
             Self::V7400(ver, _, _) => *ver,
         }
     }
 }
+
+/// Loaded tree and footer for FBX 7.4 (or compatible) parser.
+type V7400TreeAndFooter = (
+    FbxVersion,
+    tree::v7400::Tree,
+    std::result::Result<Box<low::v7400::FbxFooter>, pull_parser::Error>,
+);
+
+/// Loads a tree using the FBX 7.4 (or compatible) parser.
+///
+/// If the policy is not given, warnings are logged and ignored.
+fn load_v7400_tree<R: ParserSource>(
+    parser: &mut pull_parser::v7400::Parser<R>,
+    policy: Option<WarningPolicy>,
+) -> Result<V7400TreeAndFooter> {
+    let fbx_version = parser.fbx_version();
+    let mut tree_loader = tree::v7400::Loader::new();
+    match policy {
+        Some(policy) => tree_loader = tree_loader.with_warning_policy(policy),
+        None => parser.set_warning_handler(|w, pos| {
+            warn!("WARNING: {} (pos={:?})", w, pos);
+            Ok(())
+        }),
+    }
+    let (tree, footer) = tree_loader.load(parser)?;
+    Ok((fbx_version, tree, footer))
+}
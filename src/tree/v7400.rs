@@ -1,29 +1,43 @@
 //! FBX data tree for v7.4 or later.
 
-use std::fmt;
+use std::{collections::HashMap, fmt};
 
 use indextree::Arena;
 use string_interner::StringInterner;
 
-use crate::low::v7400::AttributeValue;
+use crate::low::v7400::{ArrayAttributeEncoding, AttributeValue};
 
-use self::node::{NodeData, NodeNameSym};
+use self::node::{ArrayRepr, NodeData, NodeNameSym};
 pub use self::{
     error::LoadError,
-    loader::Loader,
+    lazy::{
+        LazyChildren, LazyChildrenByName, LazyNodeHandle, LazyTree, DEFAULT_ARRAY_CACHE_CAPACITY,
+    },
+    loader::{ArrayPreservation, FilterAction, Loader},
     node::{
-        handle::{Children, ChildrenByName, NodeHandle},
+        handle::{Children, ChildrenByName, NodeHandle, NodeHandleMut},
         NodeId,
     },
 };
 
 mod macros;
 
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "docsrs", doc(cfg(feature = "serde")))]
+pub mod de;
+pub mod diff;
 mod error;
+pub mod json;
+mod lazy;
 mod loader;
 mod node;
+#[cfg(feature = "serde")]
+mod serde_impls;
 
 /// FBX data tree.
+///
+/// With `serde` feature, this implements `Serialize` and `Deserialize` as a
+/// sequence of nested nodes.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Tree {
     /// Tree data.
@@ -40,6 +54,21 @@ impl Tree {
         NodeHandle::new(self, self.root_id)
     }
 
+    /// Returns the mutable root node.
+    pub fn root_mut(&mut self) -> NodeHandleMut<'_> {
+        let root_id = self.root_id;
+        NodeHandleMut::new(self, root_id)
+    }
+
+    /// Returns the mutable handle of the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node ID is not used in the tree.
+    pub fn node_mut(&mut self, node_id: NodeId) -> NodeHandleMut<'_> {
+        NodeHandleMut::new(self, node_id)
+    }
+
     /// Creates a new `Tree`.
     fn new(
         arena: Arena<NodeData>,
@@ -83,6 +112,24 @@ impl Tree {
         self.node_names.get(name)
     }
 
+    /// Returns internally managed node data of a non-root node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node ID is invalid (i.e. not used or root node).
+    pub(crate) fn non_root_node_data_mut(&mut self, node_id: NodeId) -> &mut NodeData {
+        assert_ne!(node_id, self.root_id, "Root node should not be modified");
+        self.arena
+            .get_mut(node_id.raw())
+            .unwrap_or_else(|| {
+                panic!(
+                    "The given node ID is not used in the tree: node_id={:?}",
+                    node_id
+                )
+            })
+            .get_mut()
+    }
+
     /// Checks whether or not the given node ID is used in the tree.
     pub(crate) fn contains_node(&self, node_id: NodeId) -> bool {
         self.arena.get(node_id.raw()).is_some()
@@ -142,7 +189,190 @@ impl Tree {
         NodeId::new(new_child)
     }
 
-    /// Creates a new node and inserts before the given sibling node.
+    /// Removes the node and its descendants from the tree.
+    ///
+    /// Node IDs of the removed nodes become invalid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node ID is invalid (i.e. not used or root node).
+    pub fn remove(&mut self, node_id: NodeId) {
+        assert_ne!(node_id, self.root_id, "Root node should not be removed");
+        assert!(
+            self.contains_node(node_id),
+            "The given node ID is not used in the tree: node_id={:?}",
+            node_id
+        );
+        node_id.raw().remove_subtree(&mut self.arena);
+    }
+
+    /// Detaches the node and its descendants from the parent.
+    ///
+    /// Detached nodes are kept in the tree, but they are not reachable from
+    /// the root node until they are attached again by
+    /// [`append_existing`][`Self::append_existing`] or other methods.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node ID is invalid (i.e. not used or root node).
+    pub fn detach(&mut self, node_id: NodeId) {
+        assert_ne!(node_id, self.root_id, "Root node should not be detached");
+        assert!(
+            self.contains_node(node_id),
+            "The given node ID is not used in the tree: node_id={:?}",
+            node_id
+        );
+        node_id.raw().detach(&mut self.arena);
+    }
+
+    /// Moves the existing node (and its descendants) to the last child of the
+    /// given parent node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node IDs are invalid (i.e. not used, or `node` is
+    /// root node), or `node` is `parent` or its ancestor.
+    pub fn append_existing(&mut self, parent: NodeId, node: NodeId) {
+        assert_ne!(node, self.root_id, "Root node should not be moved");
+        parent
+            .raw()
+            .checked_append(node.raw(), &mut self.arena)
+            .unwrap_or_else(|e| panic!("Failed to move the node {:?}: {}", node, e));
+    }
+
+    /// Moves the existing node (and its descendants) to the first child of
+    /// the given parent node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node IDs are invalid (i.e. not used, or `node` is
+    /// root node), or `node` is `parent` or its ancestor.
+    pub fn prepend_existing(&mut self, parent: NodeId, node: NodeId) {
+        assert_ne!(node, self.root_id, "Root node should not be moved");
+        parent
+            .raw()
+            .checked_prepend(node.raw(), &mut self.arena)
+            .unwrap_or_else(|e| panic!("Failed to move the node {:?}: {}", node, e));
+    }
+
+    /// Moves the existing node (and its descendants) after the given sibling
+    /// node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node IDs are invalid (i.e. not used or root node),
+    /// or `node` is `sibling` or its ancestor.
+    pub fn insert_existing_after(&mut self, sibling: NodeId, node: NodeId) {
+        assert_ne!(sibling, self.root_id, "Root node should have no siblings");
+        assert_ne!(node, self.root_id, "Root node should not be moved");
+        sibling
+            .raw()
+            .checked_insert_after(node.raw(), &mut self.arena)
+            .unwrap_or_else(|e| panic!("Failed to move the node {:?}: {}", node, e));
+    }
+
+    /// Moves the existing node (and its descendants) before the given sibling
+    /// node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node IDs are invalid (i.e. not used or root node),
+    /// or `node` is `sibling` or its ancestor.
+    pub fn insert_existing_before(&mut self, sibling: NodeId, node: NodeId) {
+        assert_ne!(sibling, self.root_id, "Root node should have no siblings");
+        assert_ne!(node, self.root_id, "Root node should not be moved");
+        sibling
+            .raw()
+            .checked_insert_before(node.raw(), &mut self.arena)
+            .unwrap_or_else(|e| panic!("Failed to move the node {:?}: {}", node, e));
+    }
+
+    /// Renames the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node ID is invalid (i.e. not used or root node).
+    pub fn rename(&mut self, node_id: NodeId, name: &str) {
+        let name_sym = self.node_names.get_or_intern(name);
+        self.non_root_node_data_mut(node_id).set_name_sym(name_sym);
+    }
+
+    /// Deep-copies the subtree of another tree, and appends it to the given
+    /// parent node.
+    ///
+    /// Returns the node ID of the copy of `src`.
+    /// Node names are interned again in this tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given parent node ID is not used in the tree, or `src`
+    /// is the root node of its tree.
+    pub fn import_subtree(&mut self, parent: NodeId, src: NodeHandle<'_>) -> NodeId {
+        self.import_subtree_impl(parent, src, |_, _| {})
+    }
+
+    /// Deep-copies the subtree of another tree, and appends it to the given
+    /// parent node.
+    ///
+    /// Returns the node ID of the copy of `src`, and the map from node IDs in
+    /// the source tree to the corresponding node IDs in this tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given parent node ID is not used in the tree, or `src`
+    /// is the root node of its tree.
+    pub fn import_subtree_with_mapping(
+        &mut self,
+        parent: NodeId,
+        src: NodeHandle<'_>,
+    ) -> (NodeId, HashMap<NodeId, NodeId>) {
+        let mut mapping = HashMap::new();
+        let new_id = self.import_subtree_impl(parent, src, |old, new| {
+            mapping.insert(old, new);
+        });
+        (new_id, mapping)
+    }
+
+    /// Internal implementation of `import_subtree` and
+    /// `import_subtree_with_mapping`.
+    ///
+    /// `on_copy` is called with the source node ID and the new node ID for
+    /// each copied node.
+    fn import_subtree_impl(
+        &mut self,
+        parent: NodeId,
+        src: NodeHandle<'_>,
+        mut on_copy: impl FnMut(NodeId, NodeId),
+    ) -> NodeId {
+        assert_ne!(
+            src.node_id(),
+            src.tree().root_id,
+            "Root node should not be imported"
+        );
+
+        let mut copy_node = |tree: &mut Self, parent: NodeId, src: NodeHandle<'_>| {
+            let new_id = tree.append_new(parent, src.name());
+            tree.non_root_node_data_mut(new_id)
+                .extend_attributes_from(src.node().get());
+            on_copy(src.node_id(), new_id);
+            new_id
+        };
+
+        let new_root = copy_node(self, parent, src);
+        // Pairs of a source node and the new parent for it.
+        let mut stack: Vec<_> = src.children().map(|child| (child, new_root)).collect();
+        stack.reverse();
+        while let Some((src, parent)) = stack.pop() {
+            let new_id = copy_node(self, parent, src);
+            let children_start = stack.len();
+            stack.extend(src.children().map(|child| (child, new_id)));
+            stack[children_start..].reverse();
+        }
+
+        new_root
+    }
+
+    /// Appends the attribute to the node.
     ///
     /// # Panics
     ///
@@ -162,6 +392,98 @@ impl Tree {
         node.get_mut().append_attribute(v)
     }
 
+    /// Replaces the attribute at the given index, and returns the old value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node ID is invalid (i.e. not used or root node),
+    /// or the index is out of range.
+    pub fn set_attribute(
+        &mut self,
+        node_id: NodeId,
+        index: usize,
+        v: impl Into<AttributeValue>,
+    ) -> AttributeValue {
+        self.non_root_node_data_mut(node_id)
+            .set_attribute(index, v.into())
+    }
+
+    /// Inserts the attribute at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node ID is invalid (i.e. not used or root node),
+    /// or `index` is greater than the number of the attributes.
+    pub fn insert_attribute(
+        &mut self,
+        node_id: NodeId,
+        index: usize,
+        v: impl Into<AttributeValue>,
+    ) {
+        self.non_root_node_data_mut(node_id)
+            .insert_attribute(index, v.into());
+    }
+
+    /// Removes the attribute at the given index, and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node ID is invalid (i.e. not used or root node),
+    /// or the index is out of range.
+    pub fn remove_attribute(&mut self, node_id: NodeId, index: usize) -> AttributeValue {
+        self.non_root_node_data_mut(node_id).remove_attribute(index)
+    }
+
+    /// Removes all attributes of the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node ID is invalid (i.e. not used or root node).
+    pub fn clear_attributes(&mut self, node_id: NodeId) {
+        self.non_root_node_data_mut(node_id).clear_attributes();
+    }
+
+    /// Sets the encoding of the array attribute at the given index, which is
+    /// used when the tree is written as FBX binary.
+    ///
+    /// If `encoding` is `None`, the attribute is written with the compression
+    /// policy of the writer.
+    /// Raw array elements kept by the loader are discarded unless the encoding
+    /// is unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node ID is invalid (i.e. not used or root node),
+    /// the index is out of range, or the attribute is not an array.
+    pub fn set_array_encoding(
+        &mut self,
+        node_id: NodeId,
+        index: usize,
+        encoding: Option<ArrayAttributeEncoding>,
+    ) {
+        let data = self.non_root_node_data_mut(node_id);
+        assert!(
+            matches!(
+                data.attributes()[index],
+                AttributeValue::ArrBool(_)
+                    | AttributeValue::ArrI32(_)
+                    | AttributeValue::ArrI64(_)
+                    | AttributeValue::ArrF32(_)
+                    | AttributeValue::ArrF64(_)
+            ),
+            "The attribute is not an array: index={:?}",
+            index
+        );
+        let repr = match (encoding, data.array_repr(index)) {
+            (Some(encoding), Some(old)) if old.encoding == encoding => Some(old.clone()),
+            (encoding, _) => encoding.map(|encoding| ArrayRepr {
+                encoding,
+                raw: None,
+            }),
+        };
+        data.set_array_repr(index, repr);
+    }
+
     /// Compares trees strictly.
     ///
     /// Returns `true` if the two trees are same.
@@ -170,6 +492,7 @@ impl Tree {
     ///
     /// Note that this method compares tree data, not internal states of the
     /// objects.
+    /// Preserved encodings of array attributes are not compared.
     pub fn strict_eq(&self, other: &Self) -> bool {
         self.root().strict_eq(&other.root())
     }
@@ -0,0 +1,798 @@
+//! Deserialization of nodes into user types by `serde`.
+//!
+//! [`NodeDeserializer`] maps a node to user types as below:
+//!
+//! * Structs are deserialized from child nodes. Fields are matched to the
+//!   children by their names.
+//!     + `Vec<_>` fields receive all children with the name, and other fields
+//!       receive the first child with the name.
+//!     + Fields are missing if the node has no such child, so `Option<_>`
+//!       fields become `None`, and fields with `#[serde(default)]` get the
+//!       default value.
+//!       Use `#[serde(default)]` for `Vec<_>` fields which can be empty.
+//!     + A field named `$attributes` receives the node attributes as a
+//!       sequence, and a field named `$name` receives the node name.
+//! * Tuples and sequences are deserialized from the node attributes.
+//!     + Array attributes are deserialized as sequences.
+//! * Maps are deserialized from child nodes, with child names as keys.
+//! * Primitives, strings, and enums (unit variants only) are deserialized
+//!   from the single attribute of the node.
+//!
+//! Errors have the path to the node where the error happened.
+//!
+//! # Examples
+//!
+//! ```
+//! use fbxcel::{tree::v7400::de::from_node, tree_v7400};
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! #[allow(non_snake_case)]
+//! struct Geometry {
+//!     #[serde(rename = "$attributes")]
+//!     id_and_name: (i64, String),
+//!     Vertices: (Vec<f64>,),
+//!     #[serde(default)]
+//!     Layer: Vec<(i32,)>,
+//!     Version: Option<i32>,
+//! }
+//!
+//! let tree = tree_v7400! {
+//!     Geometry: [42i64, "Geometry::Cube"] {
+//!         Vertices: [vec![0.0f64, 1.0, 2.0]] {},
+//!         Layer: [0i32] {},
+//!         Layer: [1i32] {},
+//!     },
+//! };
+//! let node = tree.root().first_child().expect("Should have a child");
+//! let geometry: Geometry = from_node(node).expect("Failed to deserialize");
+//! assert_eq!(geometry.id_and_name, (42, "Geometry::Cube".to_owned()));
+//! assert_eq!(geometry.Vertices.0, [0.0, 1.0, 2.0]);
+//! assert_eq!(geometry.Layer, [(0,), (1,)]);
+//! assert_eq!(geometry.Version, None);
+//! ```
+
+use std::{error, fmt};
+
+use serde::de::{
+    self,
+    value::{BorrowedStrDeserializer, SeqDeserializer},
+    DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor,
+};
+
+use crate::{
+    low::v7400::AttributeValue,
+    pull_parser::{
+        v7400::{attribute::loaders::DirectLoader, Attributes, Event, StartNode},
+        Error as ParserError, ParserSource,
+    },
+    tree::v7400::{NodeHandle, NodeId, Tree},
+};
+
+/// Node path.
+///
+/// This is a vector of pairs of node indices in siblings and node names, as
+/// [`SyntacticPosition::node_path`][`crate::pull_parser::SyntacticPosition::node_path`].
+type NodePath = Vec<(usize, String)>;
+
+/// Deserializes a value from the node.
+pub fn from_node<'de, T: de::Deserialize<'de>>(node: NodeHandle<'de>) -> Result<T, Error> {
+    T::deserialize(NodeDeserializer::new(node))
+}
+
+/// Deserializes a value from the node being read by the pull parser.
+///
+/// The node and its descendants are loaded by the parser, so the parser
+/// will be at the end of the node after this function returns.
+///
+/// Node paths in errors are absolute, i.e. they are based on
+/// [`Parser::position`][`crate::pull_parser::v7400::Parser::position`].
+pub fn from_start_node<T, R>(start: StartNode<'_, R>) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+    R: ParserSource,
+{
+    let parser = start.into_parser();
+    // Errors recovered before this node are not related to the node.
+    let diagnostics_count = parser.diagnostics().len();
+    let base_path = parser.position().node_path().to_vec();
+
+    let mut tree = Tree::default();
+    let root_id = tree.root().node_id();
+    let node_id = tree.append_new(root_id, parser.current_node_name());
+    load_attributes(&mut tree, node_id, Attributes::from_parser(parser))?;
+    let mut open_nodes = vec![node_id];
+    while let Some(&parent) = open_nodes.last() {
+        match parser.next_event()? {
+            Event::StartNode(start) => {
+                let child = tree.append_new(parent, start.name());
+                load_attributes(&mut tree, child, start.attributes())?;
+                open_nodes.push(child);
+            }
+            Event::EndNode => {
+                open_nodes.pop();
+            }
+            Event::EndFbx(_) => {
+                return Err(Error::new(ErrorKind::Custom(
+                    "Unexpected end of the FBX document".to_owned(),
+                )))
+            }
+        }
+        if let Some(e) = parser.take_diagnostic_since(diagnostics_count) {
+            return Err(e.into());
+        }
+    }
+
+    T::deserialize(NodeDeserializer::with_path(
+        node_id.to_handle(&tree),
+        base_path,
+    ))
+}
+
+/// Loads the node attributes into the tree.
+fn load_attributes<R: ParserSource>(
+    tree: &mut Tree,
+    node_id: NodeId,
+    attributes: Attributes<'_, R>,
+) -> Result<(), Error> {
+    let values = attributes
+        .into_iter(std::iter::repeat(DirectLoader))
+        .collect::<Result<Vec<_>, _>>()?;
+    *tree.non_root_node_data_mut(node_id).attributes_mut() = values;
+    Ok(())
+}
+
+/// Deserialization error.
+#[derive(Debug)]
+pub struct Error {
+    /// Error kind.
+    kind: ErrorKind,
+    /// Node path, if available.
+    node_path: Option<NodePath>,
+    /// Attribute index, if available.
+    attribute_index: Option<usize>,
+}
+
+impl Error {
+    /// Creates a new error without position.
+    fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            node_path: None,
+            attribute_index: None,
+        }
+    }
+
+    /// Sets the position if it is not set yet, and returns the error.
+    fn at(mut self, node_path: &[(usize, String)], attribute_index: Option<usize>) -> Self {
+        if self.node_path.is_none() {
+            self.node_path = Some(node_path.to_vec());
+            self.attribute_index = attribute_index;
+        }
+        self
+    }
+
+    /// Returns the path to the node where the error happened.
+    ///
+    /// This is a vector of pairs of node indices in siblings and node names.
+    /// Paths are relative to the node passed to the deserializer, unless the
+    /// error is returned by [`from_start_node`].
+    pub fn node_path(&self) -> Option<&[(usize, String)]> {
+        self.node_path.as_deref()
+    }
+
+    /// Returns the index of the attribute where the error happened.
+    pub fn attribute_index(&self) -> Option<usize> {
+        self.attribute_index
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::Custom(msg) => f.write_str(msg)?,
+            ErrorKind::Parser(e) => write!(f, "Parser error: {}", e)?,
+        }
+        if let Some(node_path) = &self.node_path {
+            f.write_str(" (at ")?;
+            if node_path.is_empty() {
+                f.write_str("/")?;
+            }
+            for (index, name) in node_path {
+                write!(f, "/{}[{}]", name, index)?;
+            }
+            if let Some(index) = self.attribute_index {
+                write!(f, ", attribute #{}", index)?;
+            }
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Parser(e) => Some(e),
+            ErrorKind::Custom(_) => None,
+        }
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::new(ErrorKind::Custom(msg.to_string()))
+    }
+}
+
+impl From<ParserError> for Error {
+    fn from(e: ParserError) -> Self {
+        Self::new(ErrorKind::Parser(e))
+    }
+}
+
+/// Deserialization error kind.
+#[derive(Debug)]
+enum ErrorKind {
+    /// Error with a message.
+    Custom(String),
+    /// Parser error.
+    Parser(ParserError),
+}
+
+/// Deserializer for a node.
+///
+/// See [module documentation][`self`] for the mapping.
+#[derive(Debug, Clone)]
+pub struct NodeDeserializer<'de> {
+    /// Node.
+    node: NodeHandle<'de>,
+    /// Path to the node.
+    path: NodePath,
+}
+
+impl<'de> NodeDeserializer<'de> {
+    /// Creates a new `NodeDeserializer`.
+    pub fn new(node: NodeHandle<'de>) -> Self {
+        Self::with_path(node, Vec::new())
+    }
+
+    /// Creates a new `NodeDeserializer` with the given node path.
+    fn with_path(node: NodeHandle<'de>, path: NodePath) -> Self {
+        Self { node, path }
+    }
+
+    /// Sets the node path to the error.
+    fn attach_path<T>(&self, res: Result<T, Error>) -> Result<T, Error> {
+        res.map_err(|e| e.at(&self.path, None))
+    }
+
+    /// Returns the deserializer for the single attribute.
+    fn single_attribute(&self) -> Result<AttributeDeserializer<'de>, Error> {
+        match self.node.attributes() {
+            [value] => Ok(AttributeDeserializer { value }),
+            attrs => Err(de::Error::invalid_length(
+                attrs.len(),
+                &"a node with single attribute",
+            )),
+        }
+    }
+
+    /// Returns the children with their indices, grouped by names.
+    fn children_by_names(&self) -> Vec<(&'de str, Vec<(usize, NodeHandle<'de>)>)> {
+        let mut groups: Vec<(&'de str, Vec<(usize, NodeHandle<'de>)>)> = Vec::new();
+        for (index, child) in self.node.children().enumerate() {
+            match groups.iter_mut().find(|(name, _)| *name == child.name()) {
+                Some((_, group)) => group.push((index, child)),
+                None => groups.push((child.name(), vec![(index, child)])),
+            }
+        }
+        groups
+    }
+
+    /// Returns the children with the given name and their indices.
+    fn children_with_name(&self, name: &str) -> Vec<(usize, NodeHandle<'de>)> {
+        self.node
+            .children()
+            .enumerate()
+            .filter(|(_, child)| child.name() == name)
+            .collect()
+    }
+}
+
+/// Implements deserializer methods which use the single attribute.
+macro_rules! forward_to_single_attribute {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let res = self
+                    .single_attribute()
+                    .and_then(|attr| attr.deserialize_any(visitor).map_err(|e| e.at(&self.path, Some(0))));
+                self.attach_path(res)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for NodeDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.node.first_child().is_some() {
+            self.deserialize_map(visitor)
+        } else if self.node.attributes().len() == 1 {
+            self.deserialize_bool(visitor)
+        } else {
+            self.deserialize_seq(visitor)
+        }
+    }
+
+    forward_to_single_attribute! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_identifier
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let res = visitor.visit_seq(AttributesAccess::new(self.node, &self.path));
+        self.attach_path(res)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let access = ChildrenMapAccess {
+            groups: self.children_by_names().into_iter(),
+            value: None,
+            path: &self.path,
+        };
+        let res = visitor.visit_map(access);
+        self.attach_path(res)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let access = StructAccess {
+            deserializer: &self,
+            fields: fields.iter(),
+            value: None,
+        };
+        let res = visitor.visit_map(access);
+        self.attach_path(res)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let res = self.single_attribute().and_then(|attr| {
+            attr.deserialize_enum(name, variants, visitor)
+                .map_err(|e| e.at(&self.path, Some(0)))
+        });
+        self.attach_path(res)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+/// Map access for struct fields.
+struct StructAccess<'a, 'de> {
+    /// Node deserializer.
+    deserializer: &'a NodeDeserializer<'de>,
+    /// Rest of the fields.
+    fields: std::slice::Iter<'static, &'static str>,
+    /// Value for the last key.
+    value: Option<FieldValue<'de>>,
+}
+
+/// Value of a struct field.
+enum FieldValue<'de> {
+    /// Node name.
+    Name,
+    /// Node attributes.
+    Attributes,
+    /// Children with the field name.
+    Children(Vec<(usize, NodeHandle<'de>)>),
+}
+
+impl<'de> de::MapAccess<'de> for StructAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        for &field in &mut self.fields {
+            let value = match field {
+                "$name" => FieldValue::Name,
+                "$attributes" => FieldValue::Attributes,
+                _ => {
+                    let children = self.deserializer.children_with_name(field);
+                    if children.is_empty() {
+                        continue;
+                    }
+                    FieldValue::Children(children)
+                }
+            };
+            self.value = Some(value);
+            return seed
+                .deserialize(BorrowedStrDeserializer::new(field))
+                .map(Some);
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let node = self.deserializer.node;
+        let path = &self.deserializer.path;
+        match self
+            .value
+            .take()
+            .expect("`next_value_seed` should be called after `next_key_seed`")
+        {
+            FieldValue::Name => seed.deserialize(BorrowedStrDeserializer::new(node.name())),
+            FieldValue::Attributes => seed.deserialize(AttributesDeserializer { node, path }),
+            FieldValue::Children(nodes) => seed.deserialize(ChildrenDeserializer { nodes, path }),
+        }
+    }
+}
+
+/// Map access for children grouped by names.
+struct ChildrenMapAccess<'a, 'de> {
+    /// Rest of the groups.
+    groups: std::vec::IntoIter<(&'de str, Vec<(usize, NodeHandle<'de>)>)>,
+    /// Value for the last key.
+    value: Option<Vec<(usize, NodeHandle<'de>)>>,
+    /// Path to the parent node.
+    path: &'a [(usize, String)],
+}
+
+impl<'de> de::MapAccess<'de> for ChildrenMapAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let (name, nodes) = match self.groups.next() {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        self.value = Some(nodes);
+        seed.deserialize(BorrowedStrDeserializer::new(name))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let nodes = self
+            .value
+            .take()
+            .expect("`next_value_seed` should be called after `next_key_seed`");
+        seed.deserialize(ChildrenDeserializer {
+            nodes,
+            path: self.path,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.groups.len())
+    }
+}
+
+/// Deserializer for children with the same name.
+///
+/// Sequences are deserialized from all children, and others are deserialized
+/// from the first child.
+struct ChildrenDeserializer<'a, 'de> {
+    /// Children and their indices.
+    nodes: Vec<(usize, NodeHandle<'de>)>,
+    /// Path to the parent node.
+    path: &'a [(usize, String)],
+}
+
+impl<'de> ChildrenDeserializer<'_, 'de> {
+    /// Returns the deserializer for the first child.
+    fn first(self) -> NodeDeserializer<'de> {
+        let (index, node) = self.nodes[0];
+        child_deserializer(self.path, index, node)
+    }
+}
+
+/// Creates a node deserializer for the child.
+fn child_deserializer<'de>(
+    parent_path: &[(usize, String)],
+    index: usize,
+    node: NodeHandle<'de>,
+) -> NodeDeserializer<'de> {
+    let mut path = parent_path.to_vec();
+    path.push((index, node.name().to_owned()));
+    NodeDeserializer::with_path(node, path)
+}
+
+/// Implements deserializer methods which use the first child.
+macro_rules! forward_to_first_child {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.first().$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ChildrenDeserializer<'_, 'de> {
+    type Error = Error;
+
+    forward_to_first_child! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_unit deserialize_map
+        deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.first().deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let path = self.path;
+        let iter = self
+            .nodes
+            .into_iter()
+            .map(|(index, node)| child_deserializer(path, index, node));
+        let mut access = SeqDeserializer::new(iter);
+        let value = visitor.visit_seq(&mut access)?;
+        access.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.first().deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.first().deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.first().deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.first().deserialize_enum(name, variants, visitor)
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for NodeDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Deserializer for node attributes as a sequence.
+struct AttributesDeserializer<'a, 'de> {
+    /// Node.
+    node: NodeHandle<'de>,
+    /// Path to the node.
+    path: &'a [(usize, String)],
+}
+
+impl<'de> de::Deserializer<'de> for AttributesDeserializer<'_, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(AttributesAccess::new(self.node, self.path))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Sequence access for node attributes.
+struct AttributesAccess<'a, 'de> {
+    /// Attributes.
+    attributes: std::iter::Enumerate<std::slice::Iter<'de, AttributeValue>>,
+    /// Path to the node.
+    path: &'a [(usize, String)],
+}
+
+impl<'a, 'de> AttributesAccess<'a, 'de> {
+    /// Creates a new `AttributesAccess`.
+    fn new(node: NodeHandle<'de>, path: &'a [(usize, String)]) -> Self {
+        Self {
+            attributes: node.attributes().iter().enumerate(),
+            path,
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for AttributesAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        let (index, value) = match self.attributes.next() {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        seed.deserialize(AttributeDeserializer { value })
+            .map(Some)
+            .map_err(|e| e.at(self.path, Some(index)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.attributes.len())
+    }
+}
+
+/// Deserializer for a node attribute.
+struct AttributeDeserializer<'de> {
+    /// Attribute value.
+    value: &'de AttributeValue,
+}
+
+impl<'de> de::Deserializer<'de> for AttributeDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        /// Visits the array as a sequence.
+        fn visit_array<'de, T, V>(values: &'de [T], visitor: V) -> Result<V::Value, Error>
+        where
+            T: Copy + IntoDeserializer<'de, Error>,
+            V: Visitor<'de>,
+        {
+            let mut access = SeqDeserializer::new(values.iter().copied());
+            let value = visitor.visit_seq(&mut access)?;
+            access.end()?;
+            Ok(value)
+        }
+
+        match self.value {
+            AttributeValue::Bool(v) => visitor.visit_bool(*v),
+            AttributeValue::I16(v) => visitor.visit_i16(*v),
+            AttributeValue::I32(v) => visitor.visit_i32(*v),
+            AttributeValue::I64(v) => visitor.visit_i64(*v),
+            AttributeValue::F32(v) => visitor.visit_f32(*v),
+            AttributeValue::F64(v) => visitor.visit_f64(*v),
+            AttributeValue::ArrBool(v) => visit_array(v, visitor),
+            AttributeValue::ArrI32(v) => visit_array(v, visitor),
+            AttributeValue::ArrI64(v) => visit_array(v, visitor),
+            AttributeValue::ArrF32(v) => visit_array(v, visitor),
+            AttributeValue::ArrF64(v) => visit_array(v, visitor),
+            AttributeValue::String(v) => visitor.visit_borrowed_str(v),
+            AttributeValue::Binary(v) => visitor.visit_borrowed_bytes(v),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value {
+            AttributeValue::String(v) => {
+                visitor.visit_enum(BorrowedStrDeserializer::<Error>::new(v))
+            }
+            v => Err(de::Error::invalid_type(
+                de::Unexpected::Other(&format!("{:?} attribute", v.type_())),
+                &"a string attribute",
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
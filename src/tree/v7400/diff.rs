@@ -0,0 +1,448 @@
+//! Structural diff between trees.
+//!
+//! [`Differ`] walks two trees in parallel, and reports every difference with
+//! the path to the node.
+//! Children are matched by their positions, so a node inserted in the middle
+//! of siblings is reported as renamed or changed following siblings.
+//!
+//! # Examples
+//!
+//! ```
+//! use fbxcel::tree::v7400::diff::{Differ, DifferenceKind};
+//! use fbxcel::tree_v7400;
+//!
+//! let left = tree_v7400! {
+//!     Objects: {
+//!         Model: ["Cube", 1.0f64] {},
+//!     },
+//! };
+//! let right = tree_v7400! {
+//!     Objects: {
+//!         Model: ["Cube", 1.0000001f64] {},
+//!         Model: ["Sphere"] {},
+//!     },
+//! };
+//!
+//! let diffs = Differ::new().diff_trees(&left, &right);
+//! assert_eq!(diffs.len(), 2);
+//! assert_eq!(diffs[0].path().to_string(), "/Objects[0]/Model[0]");
+//! assert!(matches!(diffs[0].kind(), DifferenceKind::ValueChanged { index: 1, .. }));
+//! assert_eq!(diffs[1].path().to_string(), "/Objects[0]/Model[1]");
+//! assert_eq!(*diffs[1].kind(), DifferenceKind::NodeAdded);
+//!
+//! // Small float differences can be ignored.
+//! let diffs = Differ::new().float_tolerance(1e-3).diff_trees(&left, &right);
+//! assert_eq!(diffs.len(), 1);
+//! ```
+
+use std::fmt;
+
+use crate::{
+    low::v7400::{AttributeType, AttributeValue},
+    tree::v7400::{NodeHandle, Tree},
+};
+
+/// Tree differ.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct Differ {
+    /// Tolerance for float values.
+    float_tolerance: Option<f64>,
+}
+
+impl Differ {
+    /// Creates a new `Differ`.
+    ///
+    /// By default, float values are compared bitwise as
+    /// [`AttributeValue::strict_eq`] does.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the absolute tolerance for `f32` and `f64` values, and returns
+    /// the differ.
+    ///
+    /// Float values are considered same if they are bitwise same, or the
+    /// absolute difference is not greater than the tolerance.
+    pub fn float_tolerance(mut self, tolerance: f64) -> Self {
+        self.float_tolerance = Some(tolerance);
+        self
+    }
+
+    /// Compares the trees and returns the differences in document order.
+    pub fn diff_trees(&self, left: &Tree, right: &Tree) -> Vec<Difference> {
+        self.diff_nodes(left.root(), right.root())
+    }
+
+    /// Compares the nodes and their descendants, and returns the differences
+    /// in document order.
+    ///
+    /// Paths in the differences are relative to the given nodes.
+    pub fn diff_nodes(&self, left: NodeHandle<'_>, right: NodeHandle<'_>) -> Vec<Difference> {
+        let mut diffs = Vec::new();
+        self.diff_nodes_impl(left, right, &mut NodePath::default(), &mut diffs);
+        diffs
+    }
+
+    /// Compares the nodes recursively.
+    fn diff_nodes_impl(
+        &self,
+        left: NodeHandle<'_>,
+        right: NodeHandle<'_>,
+        path: &mut NodePath,
+        diffs: &mut Vec<Difference>,
+    ) {
+        let mut push = |kind| {
+            diffs.push(Difference {
+                path: path.clone(),
+                kind,
+            })
+        };
+
+        if left.name() != right.name() {
+            push(DifferenceKind::NodeRenamed {
+                left: left.name().to_owned(),
+                right: right.name().to_owned(),
+            });
+        }
+
+        let (left_attrs, right_attrs) = (left.attributes(), right.attributes());
+        for (index, (l, r)) in left_attrs.iter().zip(right_attrs).enumerate() {
+            self.diff_attributes(index, l, r, &mut push);
+        }
+        for (index, attr) in left_attrs.iter().enumerate().skip(right_attrs.len()) {
+            push(DifferenceKind::AttributeRemoved {
+                index,
+                type_: attr.type_(),
+            });
+        }
+        for (index, attr) in right_attrs.iter().enumerate().skip(left_attrs.len()) {
+            push(DifferenceKind::AttributeAdded {
+                index,
+                type_: attr.type_(),
+            });
+        }
+
+        let mut left_children = left.children();
+        let mut right_children = right.children();
+        for index in 0.. {
+            let (node, kind) = match (left_children.next(), right_children.next()) {
+                (Some(l), Some(r)) => {
+                    path.components.push(PathComponent::new(index, l.name()));
+                    self.diff_nodes_impl(l, r, path, diffs);
+                    path.components.pop();
+                    continue;
+                }
+                (Some(l), None) => (l, DifferenceKind::NodeRemoved),
+                (None, Some(r)) => (r, DifferenceKind::NodeAdded),
+                (None, None) => break,
+            };
+            path.components.push(PathComponent::new(index, node.name()));
+            diffs.push(Difference {
+                path: path.clone(),
+                kind,
+            });
+            path.components.pop();
+        }
+    }
+
+    /// Compares the attributes at the same index.
+    fn diff_attributes(
+        &self,
+        index: usize,
+        left: &AttributeValue,
+        right: &AttributeValue,
+        push: &mut impl FnMut(DifferenceKind),
+    ) {
+        use AttributeValue::*;
+
+        match (left, right) {
+            (ArrBool(l), ArrBool(r)) => diff_arrays(index, l, r, |l, r| l == r, Bool, push),
+            (ArrI32(l), ArrI32(r)) => diff_arrays(index, l, r, |l, r| l == r, I32, push),
+            (ArrI64(l), ArrI64(r)) => diff_arrays(index, l, r, |l, r| l == r, I64, push),
+            (ArrF32(l), ArrF32(r)) => diff_arrays(
+                index,
+                l,
+                r,
+                |l, r| l.to_bits() == r.to_bits() || self.within_tolerance(l.into(), r.into()),
+                F32,
+                push,
+            ),
+            (ArrF64(l), ArrF64(r)) => diff_arrays(
+                index,
+                l,
+                r,
+                |l, r| l.to_bits() == r.to_bits() || self.within_tolerance(l, r),
+                F64,
+                push,
+            ),
+            _ if left.type_() != right.type_() => push(DifferenceKind::AttributeTypeChanged {
+                index,
+                left: left.type_(),
+                right: right.type_(),
+            }),
+            _ => {
+                let same = match (left, right) {
+                    (F32(l), F32(r)) => self.within_tolerance((*l).into(), (*r).into()),
+                    (F64(l), F64(r)) => self.within_tolerance(*l, *r),
+                    _ => false,
+                };
+                if !same && !left.strict_eq(right) {
+                    push(DifferenceKind::ValueChanged {
+                        index,
+                        left: left.clone(),
+                        right: right.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the difference of the values are within the
+    /// tolerance.
+    fn within_tolerance(&self, left: f64, right: f64) -> bool {
+        match self.float_tolerance {
+            Some(tolerance) => (left - right).abs() <= tolerance,
+            None => false,
+        }
+    }
+}
+
+/// Compares the array attributes.
+fn diff_arrays<T: Copy>(
+    index: usize,
+    left: &[T],
+    right: &[T],
+    eq: impl Fn(T, T) -> bool,
+    to_value: impl Fn(T) -> AttributeValue,
+    push: &mut impl FnMut(DifferenceKind),
+) {
+    if left.len() != right.len() {
+        push(DifferenceKind::ArrayLengthChanged {
+            index,
+            left: left.len(),
+            right: right.len(),
+        });
+    }
+    for (element, (&l, &r)) in left.iter().zip(right).enumerate() {
+        if !eq(l, r) {
+            push(DifferenceKind::ArrayElementChanged {
+                index,
+                element,
+                left: to_value(l),
+                right: to_value(r),
+            });
+        }
+    }
+}
+
+/// Compares the trees with the default differ.
+///
+/// This is same as `Differ::new().diff_trees(left, right)`.
+pub fn diff_trees(left: &Tree, right: &Tree) -> Vec<Difference> {
+    Differ::new().diff_trees(left, right)
+}
+
+/// Difference between trees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// Path to the node.
+    path: NodePath,
+    /// Kind of the difference.
+    kind: DifferenceKind,
+}
+
+impl Difference {
+    /// Returns the path to the node.
+    pub fn path(&self) -> &NodePath {
+        &self.path
+    }
+
+    /// Returns the kind of the difference.
+    pub fn kind(&self) -> &DifferenceKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+/// Kind of a difference.
+///
+/// "Left" and "right" are the first and the second trees passed to the
+/// differ.
+/// `index` fields are attribute indices.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum DifferenceKind {
+    /// The node exists only in the right tree.
+    NodeAdded,
+    /// The node exists only in the left tree.
+    NodeRemoved,
+    /// The node has different names.
+    NodeRenamed {
+        /// Name in the left tree.
+        left: String,
+        /// Name in the right tree.
+        right: String,
+    },
+    /// The attribute exists only in the right tree.
+    AttributeAdded {
+        /// Attribute index.
+        index: usize,
+        /// Attribute type.
+        type_: AttributeType,
+    },
+    /// The attribute exists only in the left tree.
+    AttributeRemoved {
+        /// Attribute index.
+        index: usize,
+        /// Attribute type.
+        type_: AttributeType,
+    },
+    /// The attribute has different types.
+    AttributeTypeChanged {
+        /// Attribute index.
+        index: usize,
+        /// Type in the left tree.
+        left: AttributeType,
+        /// Type in the right tree.
+        right: AttributeType,
+    },
+    /// The non-array attribute has different values.
+    ValueChanged {
+        /// Attribute index.
+        index: usize,
+        /// Value in the left tree.
+        left: AttributeValue,
+        /// Value in the right tree.
+        right: AttributeValue,
+    },
+    /// The array attribute has different lengths.
+    ArrayLengthChanged {
+        /// Attribute index.
+        index: usize,
+        /// Length in the left tree.
+        left: usize,
+        /// Length in the right tree.
+        right: usize,
+    },
+    /// The array attribute has different elements at the same position.
+    ///
+    /// Elements are reported as non-array values.
+    ArrayElementChanged {
+        /// Attribute index.
+        index: usize,
+        /// Element index.
+        element: usize,
+        /// Element in the left tree.
+        left: AttributeValue,
+        /// Element in the right tree.
+        right: AttributeValue,
+    },
+}
+
+impl fmt::Display for DifferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DifferenceKind::NodeAdded => f.write_str("Node added"),
+            DifferenceKind::NodeRemoved => f.write_str("Node removed"),
+            DifferenceKind::NodeRenamed { left, right } => {
+                write!(f, "Node renamed: {:?} -> {:?}", left, right)
+            }
+            DifferenceKind::AttributeAdded { index, type_ } => {
+                write!(f, "Attribute #{} added: type={:?}", index, type_)
+            }
+            DifferenceKind::AttributeRemoved { index, type_ } => {
+                write!(f, "Attribute #{} removed: type={:?}", index, type_)
+            }
+            DifferenceKind::AttributeTypeChanged { index, left, right } => write!(
+                f,
+                "Attribute #{} type changed: {:?} -> {:?}",
+                index, left, right
+            ),
+            DifferenceKind::ValueChanged { index, left, right } => write!(
+                f,
+                "Attribute #{} value changed: {:?} -> {:?}",
+                index, left, right
+            ),
+            DifferenceKind::ArrayLengthChanged { index, left, right } => write!(
+                f,
+                "Attribute #{} array length changed: {} -> {}",
+                index, left, right
+            ),
+            DifferenceKind::ArrayElementChanged {
+                index,
+                element,
+                left,
+                right,
+            } => write!(
+                f,
+                "Attribute #{} array element [{}] changed: {:?} -> {:?}",
+                index, element, left, right
+            ),
+        }
+    }
+}
+
+/// Path to a node from the root node (or the node where the diff started).
+///
+/// Node names in the path are taken from the left tree, except for nodes
+/// which exist only in the right tree.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodePath {
+    /// Path components.
+    components: Vec<PathComponent>,
+}
+
+impl NodePath {
+    /// Returns the path components, from the outermost node.
+    ///
+    /// Returns an empty slice for the root node.
+    pub fn components(&self) -> &[PathComponent] {
+        &self.components
+    }
+}
+
+impl fmt::Display for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.components.is_empty() {
+            return f.write_str("/");
+        }
+        for component in &self.components {
+            write!(f, "/{}[{}]", component.name, component.index)?;
+        }
+        Ok(())
+    }
+}
+
+/// Node path component.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PathComponent {
+    /// Child index in the parent.
+    index: usize,
+    /// Node name.
+    name: String,
+}
+
+impl PathComponent {
+    /// Creates a new `PathComponent`.
+    fn new(index: usize, name: &str) -> Self {
+        Self {
+            index,
+            name: name.to_owned(),
+        }
+    }
+
+    /// Returns the child index in the parent node.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the node name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
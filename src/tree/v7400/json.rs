@@ -0,0 +1,722 @@
+//! JSON import and export of trees.
+//!
+//! Trees are converted from and into JSON without losing the exact attribute
+//! types, so `json::from_str(&json::to_string(&tree))` is always
+//! [`strict_eq`][`Tree::strict_eq`] to the original tree.
+//!
+//! # Format
+//!
+//! A tree is a JSON array of the top-level nodes, and a node is an object
+//! with `name`, `attributes`, and `children` fields.
+//! `attributes` and `children` can be omitted on import if they are empty.
+//!
+//! An attribute is an object with a single field, whose key is the type and
+//! whose value is the attribute value:
+//!
+//! | Type          | Example                        |
+//! |---------------|--------------------------------|
+//! | `bool`        | `{"bool": true}`               |
+//! | `i16`         | `{"i16": 3}`                   |
+//! | `i32`         | `{"i32": 3}`                   |
+//! | `i64`         | `{"i64": 3}`                   |
+//! | `f32`         | `{"f32": "0x3f800000"}`        |
+//! | `f64`         | `{"f64": "0x3ff0000000000000"}`|
+//! | `arr_bool`    | `{"arr_bool": [true, false]}`  |
+//! | `arr_i32`     | `{"arr_i32": [1, 2]}`          |
+//! | `arr_i64`     | `{"arr_i64": [1, 2]}`          |
+//! | `arr_f32`     | `{"arr_f32": ["0x3f800000"]}`  |
+//! | `arr_f64`     | `{"arr_f64": ["0x3ff0000000000000"]}` |
+//! | `string`      | `{"string": "foo"}`            |
+//! | `binary`      | `{"binary": "Zm9v"}`           |
+//!
+//! Floating point values are written as hexadecimal bit patterns, so that
+//! NaN payloads and signed zeros are kept.
+//! On import, floating point values can also be written as JSON numbers.
+//! Binaries are written as base64 strings (RFC 4648, with padding).
+//!
+//! # Examples
+//!
+//! ```
+//! use fbxcel::{tree::v7400::json, tree_v7400};
+//!
+//! let tree = tree_v7400! {
+//!     Node: [1i16, 1.0f32, "foo"] {
+//!         Child: [vec![1u8, 2, 3]] {},
+//!     },
+//! };
+//! let json = json::to_string(&tree);
+//! assert_eq!(
+//!     json,
+//!     r#"[
+//!   {"name": "Node", "attributes": [{"i16": 1}, {"f32": "0x3f800000"}, {"string": "foo"}], "children": [
+//!     {"name": "Child", "attributes": [{"binary": "AQID"}], "children": []}
+//!   ]}
+//! ]
+//! "#
+//! );
+//!
+//! let imported = json::from_str(&json).expect("Should be valid JSON");
+//! assert!(imported.strict_eq(&tree));
+//! ```
+
+use std::{error, fmt, io, iter::Peekable, str::FromStr};
+
+use crate::{
+    low::v7400::AttributeValue,
+    tree::v7400::{Children, NodeHandle, NodeId, Tree},
+};
+
+/// Writes the tree as JSON.
+pub fn to_writer(tree: &Tree, mut writer: impl io::Write) -> io::Result<()> {
+    writer.write_all(to_string(tree).as_bytes())
+}
+
+/// Converts the tree into a JSON string.
+///
+/// Each node is written in a line, for readable diffs.
+pub fn to_string(tree: &Tree) -> String {
+    let mut out = String::new();
+    write_nodes(&mut out, tree.root());
+    out.push('\n');
+    out
+}
+
+/// Writes the descendants of the node as nested JSON arrays.
+///
+/// This does not recurse, so that deep trees do not overflow the stack.
+fn write_nodes(out: &mut String, root: NodeHandle<'_>) {
+    /// Writes the start of the children array, and returns the children if
+    /// any.
+    fn start_children<'a>(
+        out: &mut String,
+        parent: NodeHandle<'a>,
+    ) -> Option<Peekable<Children<'a>>> {
+        let mut children = parent.children().peekable();
+        if children.peek().is_none() {
+            out.push_str("[]");
+            return None;
+        }
+        out.push_str("[\n");
+        Some(children)
+    }
+
+    /// Writes the end of the node, which is an element of the array.
+    fn end_node(out: &mut String, siblings: &mut Peekable<Children<'_>>) {
+        out.push('}');
+        if siblings.peek().is_some() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+
+    // Children iterators of the open arrays.
+    let mut stack: Vec<Peekable<Children<'_>>> = start_children(out, root).into_iter().collect();
+    while let Some(children) = stack.last_mut() {
+        match children.next() {
+            Some(child) => {
+                push_indent(out, stack.len());
+                out.push_str("{\"name\": ");
+                write_string(out, child.name());
+                out.push_str(", \"attributes\": [");
+                for (i, attr) in child.attributes().iter().enumerate() {
+                    if i != 0 {
+                        out.push_str(", ");
+                    }
+                    write_attribute(out, attr);
+                }
+                out.push_str("], \"children\": ");
+                match start_children(out, child) {
+                    Some(grandchildren) => stack.push(grandchildren),
+                    None => end_node(out, stack.last_mut().expect("Should never fail: non-empty")),
+                }
+            }
+            None => {
+                stack.pop();
+                push_indent(out, stack.len());
+                out.push(']');
+                if let Some(siblings) = stack.last_mut() {
+                    end_node(out, siblings);
+                }
+            }
+        }
+    }
+}
+
+/// Writes the indent.
+fn push_indent(out: &mut String, depth: usize) {
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+}
+
+/// Writes the attribute as a JSON object.
+fn write_attribute(out: &mut String, attr: &AttributeValue) {
+    /// Writes the array.
+    fn write_array<T>(out: &mut String, values: &[T], mut f: impl FnMut(&mut String, &T)) {
+        out.push('[');
+        for (i, v) in values.iter().enumerate() {
+            if i != 0 {
+                out.push_str(", ");
+            }
+            f(out, v);
+        }
+        out.push(']');
+    }
+
+    out.push_str("{\"");
+    out.push_str(type_tag(attr));
+    out.push_str("\": ");
+    match attr {
+        AttributeValue::Bool(v) => out.push_str(&v.to_string()),
+        AttributeValue::I16(v) => out.push_str(&v.to_string()),
+        AttributeValue::I32(v) => out.push_str(&v.to_string()),
+        AttributeValue::I64(v) => out.push_str(&v.to_string()),
+        AttributeValue::F32(v) => write_f32(out, *v),
+        AttributeValue::F64(v) => write_f64(out, *v),
+        AttributeValue::ArrBool(v) => write_array(out, v, |out, v| out.push_str(&v.to_string())),
+        AttributeValue::ArrI32(v) => write_array(out, v, |out, v| out.push_str(&v.to_string())),
+        AttributeValue::ArrI64(v) => write_array(out, v, |out, v| out.push_str(&v.to_string())),
+        AttributeValue::ArrF32(v) => write_array(out, v, |out, v| write_f32(out, *v)),
+        AttributeValue::ArrF64(v) => write_array(out, v, |out, v| write_f64(out, *v)),
+        AttributeValue::String(v) => write_string(out, v),
+        AttributeValue::Binary(v) => write_string(out, &crate::base64::encode(v)),
+    }
+    out.push('}');
+}
+
+/// Returns the type tag of the attribute.
+fn type_tag(attr: &AttributeValue) -> &'static str {
+    match attr {
+        AttributeValue::Bool(_) => "bool",
+        AttributeValue::I16(_) => "i16",
+        AttributeValue::I32(_) => "i32",
+        AttributeValue::I64(_) => "i64",
+        AttributeValue::F32(_) => "f32",
+        AttributeValue::F64(_) => "f64",
+        AttributeValue::ArrBool(_) => "arr_bool",
+        AttributeValue::ArrI32(_) => "arr_i32",
+        AttributeValue::ArrI64(_) => "arr_i64",
+        AttributeValue::ArrF32(_) => "arr_f32",
+        AttributeValue::ArrF64(_) => "arr_f64",
+        AttributeValue::String(_) => "string",
+        AttributeValue::Binary(_) => "binary",
+    }
+}
+
+/// Writes the `f32` value as a hexadecimal bit pattern.
+fn write_f32(out: &mut String, v: f32) {
+    out.push_str(&format!("\"0x{:08x}\"", v.to_bits()));
+}
+
+/// Writes the `f64` value as a hexadecimal bit pattern.
+fn write_f64(out: &mut String, v: f64) {
+    out.push_str(&format!("\"0x{:016x}\"", v.to_bits()));
+}
+
+/// Writes the string as a JSON string.
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => out.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Reads a tree from the JSON reader.
+pub fn from_reader(mut reader: impl io::Read) -> Result<Tree, Error> {
+    let mut s = String::new();
+    reader.read_to_string(&mut s).map_err(Error::Io)?;
+    from_str(&s)
+}
+
+/// Reads a tree from the JSON string.
+pub fn from_str(s: &str) -> Result<Tree, Error> {
+    let mut parser = JsonParser { src: s, pos: 0 };
+    let tree = parser.parse_tree()?;
+    parser.skip_whitespaces();
+    if parser.pos != s.len() {
+        return Err(parser.error("Trailing characters after the tree"));
+    }
+
+    Ok(tree)
+}
+
+/// Node being parsed.
+struct OpenNode {
+    /// Node ID in the tree being built.
+    id: NodeId,
+    /// Start position of the node object.
+    pos: usize,
+    /// Whether `name` field is already read.
+    has_name: bool,
+    /// Whether `attributes` field is already read.
+    has_attributes: bool,
+    /// Whether `children` field is already read (or being read).
+    has_children: bool,
+}
+
+/// Next syntactic element to be parsed by [`JsonParser::parse_tree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Next {
+    /// An element of an array of nodes, or the end of the array.
+    Node {
+        /// Whether this is the first element.
+        first: bool,
+    },
+    /// A field of a node, or the end of the node.
+    Field {
+        /// Whether this is the first field.
+        first: bool,
+    },
+    /// The delimiter after the field of a node.
+    AfterField,
+    /// The delimiter after the node in an array of nodes.
+    AfterNode,
+}
+
+/// JSON import error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// I/O error.
+    Io(io::Error),
+    /// Invalid JSON syntax or unexpected value.
+    Syntax {
+        /// Line number (1-based).
+        line: usize,
+        /// Column number in characters (1-based).
+        column: usize,
+        /// Error message.
+        message: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Syntax {
+                line,
+                column,
+                message,
+            } => write!(f, "{} at line {}, column {}", message, line, column),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Syntax { .. } => None,
+        }
+    }
+}
+
+/// JSON parser for trees.
+struct JsonParser<'a> {
+    /// Source string.
+    src: &'a str,
+    /// Current byte position.
+    pos: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    /// Creates a syntax error at the current position.
+    fn error(&self, message: impl Into<String>) -> Error {
+        self.error_at(self.pos, message)
+    }
+
+    /// Creates a syntax error at the given position.
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> Error {
+        let before = &self.src[..pos];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        Error::Syntax {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Skips whitespaces.
+    fn skip_whitespaces(&mut self) {
+        let rest = &self.src[self.pos..];
+        let trimmed = rest.trim_start_matches(&[' ', '\t', '\n', '\r'][..]);
+        self.pos += rest.len() - trimmed.len();
+    }
+
+    /// Skips whitespaces and returns the next byte without consuming it.
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespaces();
+        self.src.as_bytes().get(self.pos).copied()
+    }
+
+    /// Consumes the given byte if it is the next one.
+    fn consume_if(&mut self, expected: u8) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the given byte, or returns an error.
+    fn expect(&mut self, expected: u8) -> Result<(), Error> {
+        if self.consume_if(expected) {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected `{}`", char::from(expected))))
+        }
+    }
+
+    /// Parses a JSON array, calling the function for each element.
+    fn parse_array(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        self.expect(b'[')?;
+        if self.consume_if(b']') {
+            return Ok(());
+        }
+        loop {
+            f(self)?;
+            if self.consume_if(b']') {
+                return Ok(());
+            }
+            self.expect(b',')?;
+        }
+    }
+
+    /// Parses a JSON object, calling the function for each key.
+    fn parse_object(
+        &mut self,
+        mut f: impl FnMut(&mut Self, String, usize) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        self.expect(b'{')?;
+        if self.consume_if(b'}') {
+            return Ok(());
+        }
+        loop {
+            self.skip_whitespaces();
+            let key_pos = self.pos;
+            let key = self.parse_string()?;
+            self.expect(b':')?;
+            f(self, key, key_pos)?;
+            if self.consume_if(b'}') {
+                return Ok(());
+            }
+            self.expect(b',')?;
+        }
+    }
+
+    /// Parses the array of the top-level nodes into a tree.
+    ///
+    /// Nested nodes are parsed with an explicit stack instead of recursion,
+    /// so that deeply nested input does not overflow the stack.
+    fn parse_tree(&mut self) -> Result<Tree, Error> {
+        let mut tree = Tree::default();
+        let root_id = tree.root().node_id();
+        let mut open_nodes: Vec<OpenNode> = Vec::new();
+
+        self.expect(b'[')?;
+        let mut next = Next::Node { first: true };
+        loop {
+            next = match next {
+                Next::Node { first } => {
+                    if first && self.consume_if(b']') {
+                        Next::AfterField
+                    } else {
+                        self.skip_whitespaces();
+                        let pos = self.pos;
+                        self.expect(b'{')?;
+                        let parent = open_nodes.last().map_or(root_id, |node| node.id);
+                        // The name is set when `name` field is read.
+                        let id = tree.append_new(parent, "");
+                        open_nodes.push(OpenNode {
+                            id,
+                            pos,
+                            has_name: false,
+                            has_attributes: false,
+                            has_children: false,
+                        });
+                        Next::Field { first: true }
+                    }
+                }
+                Next::Field { first } => {
+                    let node = open_nodes
+                        .last_mut()
+                        .expect("Should never fail: a node should be open");
+                    if first && self.consume_if(b'}') {
+                        Next::AfterNode
+                    } else {
+                        self.skip_whitespaces();
+                        let key_pos = self.pos;
+                        let key = self.parse_string()?;
+                        self.expect(b':')?;
+                        match key.as_str() {
+                            "name" if !node.has_name => {
+                                node.has_name = true;
+                                let name = self.parse_string()?;
+                                tree.rename(node.id, &name);
+                                Next::AfterField
+                            }
+                            "attributes" if !node.has_attributes => {
+                                node.has_attributes = true;
+                                let attributes = self.parse_vec(Self::parse_attribute)?;
+                                *tree.non_root_node_data_mut(node.id).attributes_mut() = attributes;
+                                Next::AfterField
+                            }
+                            "children" if !node.has_children => {
+                                node.has_children = true;
+                                self.expect(b'[')?;
+                                Next::Node { first: true }
+                            }
+                            "name" | "attributes" | "children" => {
+                                return Err(
+                                    self.error_at(key_pos, format!("Duplicate field `{}`", key))
+                                )
+                            }
+                            _ => {
+                                return Err(
+                                    self.error_at(key_pos, format!("Unknown field `{}`", key))
+                                )
+                            }
+                        }
+                    }
+                }
+                Next::AfterField => {
+                    // Here, a field or the top-level array has been read.
+                    if open_nodes.is_empty() {
+                        return Ok(tree);
+                    }
+                    if self.consume_if(b'}') {
+                        Next::AfterNode
+                    } else {
+                        self.expect(b',')?;
+                        Next::Field { first: false }
+                    }
+                }
+                Next::AfterNode => {
+                    let node = open_nodes
+                        .pop()
+                        .expect("Should never fail: a node should be open");
+                    if !node.has_name {
+                        return Err(self.error_at(node.pos, "Missing field `name`"));
+                    }
+                    if self.consume_if(b']') {
+                        // The array of nodes (i.e. `children` field of the
+                        // parent or the top-level array) has been read.
+                        Next::AfterField
+                    } else {
+                        self.expect(b',')?;
+                        Next::Node { first: false }
+                    }
+                }
+            };
+        }
+    }
+
+    /// Parses an attribute.
+    fn parse_attribute(&mut self) -> Result<AttributeValue, Error> {
+        self.skip_whitespaces();
+        let attr_pos = self.pos;
+        let mut value = None;
+        self.parse_object(|this, tag, tag_pos| {
+            if value.is_some() {
+                return Err(this.error_at(tag_pos, "Attribute should have only one field"));
+            }
+            value = Some(match tag.as_str() {
+                "bool" => AttributeValue::Bool(this.parse_bool()?),
+                "i16" => AttributeValue::I16(this.parse_integer()?),
+                "i32" => AttributeValue::I32(this.parse_integer()?),
+                "i64" => AttributeValue::I64(this.parse_integer()?),
+                "f32" => AttributeValue::F32(this.parse_f32()?),
+                "f64" => AttributeValue::F64(this.parse_f64()?),
+                "arr_bool" => AttributeValue::ArrBool(this.parse_vec(Self::parse_bool)?),
+                "arr_i32" => AttributeValue::ArrI32(this.parse_vec(Self::parse_integer)?),
+                "arr_i64" => AttributeValue::ArrI64(this.parse_vec(Self::parse_integer)?),
+                "arr_f32" => AttributeValue::ArrF32(this.parse_vec(Self::parse_f32)?),
+                "arr_f64" => AttributeValue::ArrF64(this.parse_vec(Self::parse_f64)?),
+                "string" => AttributeValue::String(this.parse_string()?),
+                "binary" => {
+                    this.skip_whitespaces();
+                    let pos = this.pos;
+                    let s = this.parse_string()?;
+                    let binary = crate::base64::decode(&s)
+                        .ok_or_else(|| this.error_at(pos, "Invalid base64 string"))?;
+                    AttributeValue::Binary(binary)
+                }
+                _ => {
+                    return Err(this.error_at(tag_pos, format!("Unknown attribute type `{}`", tag)))
+                }
+            });
+            Ok(())
+        })?;
+        value.ok_or_else(|| self.error_at(attr_pos, "Attribute should have a type field"))
+    }
+
+    /// Parses a JSON array of values.
+    fn parse_vec<T>(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<T, Error>,
+    ) -> Result<Vec<T>, Error> {
+        let mut values = Vec::new();
+        self.parse_array(|this| {
+            values.push(f(this)?);
+            Ok(())
+        })?;
+        Ok(values)
+    }
+
+    /// Parses a boolean.
+    fn parse_bool(&mut self) -> Result<bool, Error> {
+        self.skip_whitespaces();
+        let rest = &self.src[self.pos..];
+        if rest.starts_with("true") {
+            self.pos += 4;
+            Ok(true)
+        } else if rest.starts_with("false") {
+            self.pos += 5;
+            Ok(false)
+        } else {
+            Err(self.error("Expected boolean"))
+        }
+    }
+
+    /// Reads the text of a JSON number.
+    fn number_text(&mut self) -> Result<(&'a str, usize), Error> {
+        self.skip_whitespaces();
+        let start = self.pos;
+        let len = self.src[start..]
+            .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+            .unwrap_or(self.src.len() - start);
+        if len == 0 {
+            return Err(self.error("Expected number"));
+        }
+        self.pos += len;
+        let src = self.src;
+        Ok((&src[start..self.pos], start))
+    }
+
+    /// Parses an integer.
+    fn parse_integer<T: FromStr>(&mut self) -> Result<T, Error> {
+        let (text, start) = self.number_text()?;
+        text.parse()
+            .map_err(|_| self.error_at(start, format!("Invalid integer `{}`", text)))
+    }
+
+    /// Parses an `f32` value, from a bit pattern string or a number.
+    fn parse_f32(&mut self) -> Result<f32, Error> {
+        self.parse_float(8, |bits| f32::from_bits(bits as u32))
+    }
+
+    /// Parses an `f64` value, from a bit pattern string or a number.
+    fn parse_f64(&mut self) -> Result<f64, Error> {
+        self.parse_float(16, f64::from_bits)
+    }
+
+    /// Parses a floating point value, from a bit pattern string or a number.
+    fn parse_float<T: FromStr>(
+        &mut self,
+        max_digits: usize,
+        from_bits: impl FnOnce(u64) -> T,
+    ) -> Result<T, Error> {
+        if self.peek() != Some(b'"') {
+            let (text, start) = self.number_text()?;
+            return text
+                .parse()
+                .map_err(|_| self.error_at(start, format!("Invalid number `{}`", text)));
+        }
+        let start = self.pos;
+        let s = self.parse_string()?;
+        s.strip_prefix("0x")
+            .filter(|digits| !digits.is_empty() && digits.len() <= max_digits)
+            .and_then(|digits| u64::from_str_radix(digits, 16).ok())
+            .map(from_bits)
+            .ok_or_else(|| self.error_at(start, format!("Invalid float bit pattern {:?}", s)))
+    }
+
+    /// Parses a JSON string.
+    fn parse_string(&mut self) -> Result<String, Error> {
+        self.expect(b'"')?;
+        let mut s = String::new();
+        loop {
+            let rest = &self.src[self.pos..];
+            let end = rest
+                .find(|c: char| c == '"' || c == '\\' || u32::from(c) < 0x20)
+                .ok_or_else(|| self.error("Unterminated string"))?;
+            s.push_str(&rest[..end]);
+            self.pos += end;
+            match self.src.as_bytes()[self.pos] {
+                b'"' => {
+                    self.pos += 1;
+                    return Ok(s);
+                }
+                b'\\' => {
+                    self.pos += 1;
+                    s.push(self.parse_escape()?);
+                }
+                _ => return Err(self.error("Control character in string")),
+            }
+        }
+    }
+
+    /// Parses an escape sequence after a backslash.
+    fn parse_escape(&mut self) -> Result<char, Error> {
+        let start = self.pos;
+        let c = match self.src.as_bytes().get(self.pos) {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                self.pos += 1;
+                let high = self.parse_hex4()?;
+                let code = if (0xd800..0xdc00).contains(&high) {
+                    if !self.src[self.pos..].starts_with("\\u") {
+                        return Err(self.error_at(start, "Unpaired surrogate"));
+                    }
+                    self.pos += 2;
+                    let low = self.parse_hex4()?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(self.error_at(start, "Unpaired surrogate"));
+                    }
+                    0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                } else {
+                    high
+                };
+                return char::from_u32(code)
+                    .ok_or_else(|| self.error_at(start, "Invalid unicode escape"));
+            }
+            _ => return Err(self.error("Invalid escape sequence")),
+        };
+        self.pos += 1;
+        Ok(c)
+    }
+
+    /// Parses four hexadecimal digits.
+    fn parse_hex4(&mut self) -> Result<u32, Error> {
+        let digits = self
+            .src
+            .get(self.pos..(self.pos + 4))
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("Expected four hexadecimal digits"))?;
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).expect("Should be valid hexadecimal digits"))
+    }
+}
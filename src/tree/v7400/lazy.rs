@@ -0,0 +1,733 @@
+//! Lazily loaded FBX data tree.
+
+use std::{cell::RefCell, collections::HashMap, fmt, io, rc::Rc};
+
+use log::{debug, warn};
+
+use crate::{
+    low::{v7400::AttributeValue, FbxVersion},
+    pull_parser::{
+        error::DataError,
+        reader::SeekableSource,
+        v7400::{
+            attribute::loaders::DirectLoader, load_binary_header, read_node_header_and_name,
+            Attributes, Event, LoadAttribute, Parser,
+        },
+        Error as ParserError, ParserSource, ParserVersion, Result as ParserResult,
+    },
+    tree::v7400::LoadError,
+};
+
+/// Default capacity of the array attributes cache in bytes.
+pub const DEFAULT_ARRAY_CACHE_CAPACITY: usize = 64 * 1024 * 1024;
+
+/// Index of a node in the lazy tree.
+type LazyNodeIndex = usize;
+
+/// Index of the implicit root node.
+const ROOT_INDEX: LazyNodeIndex = 0;
+
+/// Lazily loaded FBX data tree.
+///
+/// Unlike [`Tree`][`super::Tree`], this reads node children and attributes
+/// from the underlying reader on first access, and keeps them for later use.
+/// Array attributes are decoded on demand, and kept in a cache with bounded
+/// size (see [`set_array_cache_capacity`][`Self::set_array_cache_capacity`]).
+///
+/// Navigation methods of [`LazyNodeHandle`] are named after the ones of
+/// [`NodeHandle`][`super::NodeHandle`], but return `Result`s since they may
+/// read the data.
+///
+/// Only FBX binary is supported.
+pub struct LazyTree<R> {
+    /// Parser version.
+    parser_version: ParserVersion,
+    /// FBX version.
+    fbx_version: FbxVersion,
+    /// Stream position of the beginning of the FBX data.
+    base_position: u64,
+    /// Reader.
+    reader: RefCell<R>,
+    /// Nodes.
+    nodes: RefCell<Vec<LazyNode>>,
+    /// Array attributes cache.
+    cache: RefCell<ArrayCache>,
+}
+
+impl<R: io::Read + io::Seek> LazyTree<R> {
+    /// Creates a new lazy tree from the given seekable reader.
+    ///
+    /// The reader should be positioned at the beginning of the FBX binary
+    /// (i.e. at the FBX header).
+    /// Only the header is read here.
+    pub fn from_seekable_reader(mut reader: R) -> Result<Self, LoadError> {
+        debug!("Creating lazy FBX data tree from a seekable reader");
+
+        let base_position = reader.stream_position().map_err(ParserError::from)?;
+        let (header, parser_version) = load_binary_header(&mut reader)?;
+        let root = LazyNode {
+            name: "".into(),
+            parent: None,
+            start_offset: 0,
+            end_offset: u64::MAX,
+            num_attributes: 0,
+            attributes_end_offset: header.len() as u64,
+            children: None,
+            attributes: Some(Rc::new([])),
+        };
+
+        Ok(Self {
+            parser_version,
+            fbx_version: header.version(),
+            base_position,
+            reader: RefCell::new(reader),
+            nodes: RefCell::new(vec![root]),
+            cache: RefCell::new(ArrayCache::new(DEFAULT_ARRAY_CACHE_CAPACITY)),
+        })
+    }
+
+    /// Returns the root node.
+    pub fn root(&self) -> LazyNodeHandle<'_, R> {
+        LazyNodeHandle::new(self, ROOT_INDEX)
+    }
+
+    /// Returns the FBX version.
+    pub fn fbx_version(&self) -> FbxVersion {
+        self.fbx_version
+    }
+
+    /// Returns the capacity of the array attributes cache in bytes.
+    pub fn array_cache_capacity(&self) -> usize {
+        self.cache.borrow().capacity
+    }
+
+    /// Sets the capacity of the array attributes cache in bytes.
+    ///
+    /// Least recently used arrays are evicted if the cache exceeds the new
+    /// capacity.
+    /// Arrays larger than the capacity are never cached, so setting `0`
+    /// disables the cache.
+    pub fn set_array_cache_capacity(&mut self, capacity: usize) {
+        let cache = self.cache.get_mut();
+        cache.capacity = capacity;
+        cache.shrink_to_fit();
+    }
+
+    /// Returns the total size of the cached arrays in bytes.
+    pub fn cached_array_bytes(&self) -> usize {
+        self.cache.borrow().size
+    }
+
+    /// Removes all arrays from the cache.
+    pub fn clear_array_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Returns the name of the node.
+    fn node_name(&self, index: LazyNodeIndex) -> Rc<str> {
+        self.nodes.borrow()[index].name.clone()
+    }
+
+    /// Returns the children of the node, loading them if necessary.
+    fn children_of(&self, index: LazyNodeIndex) -> ParserResult<Rc<[LazyNodeIndex]>> {
+        if let Some(children) = &self.nodes.borrow()[index].children {
+            return Ok(children.clone());
+        }
+
+        let (start, end) = {
+            let node = &self.nodes.borrow()[index];
+            (node.attributes_end_offset, node.end_offset)
+        };
+        let mut reader = self.reader.borrow_mut();
+        reader.seek(io::SeekFrom::Start(self.base_position + start))?;
+        let mut source = SeekableSource::with_offset(&mut *reader, start as usize);
+
+        let mut new_nodes = Vec::new();
+        while source.position() != end {
+            let child_start = source.position();
+            let (node_header, name) =
+                match read_node_header_and_name(&mut source, self.fbx_version)? {
+                    Some(v) => v,
+                    None => break,
+                };
+            if node_header.end_offset > end {
+                return Err(
+                    DataError::NodeLengthMismatch(end, Some(node_header.end_offset)).into(),
+                );
+            }
+            // Attributes should end in the node, so the node does not end
+            // before the current position.
+            let attributes_end_offset = match source
+                .position()
+                .checked_add(node_header.bytelen_attributes)
+            {
+                Some(v) if v <= node_header.end_offset => v,
+                _ => return Err(DataError::NodeLengthMismatch(node_header.end_offset, None).into()),
+            };
+            new_nodes.push(LazyNode {
+                name: name.into(),
+                parent: Some(index),
+                start_offset: child_start,
+                end_offset: node_header.end_offset,
+                num_attributes: node_header.num_attributes,
+                attributes_end_offset,
+                children: None,
+                attributes: None,
+            });
+            source.skip_to(node_header.end_offset)?;
+        }
+
+        let mut nodes = self.nodes.borrow_mut();
+        let first = nodes.len();
+        nodes.extend(new_nodes);
+        let children: Rc<[LazyNodeIndex]> = (first..nodes.len()).collect();
+        nodes[index].children = Some(children.clone());
+
+        Ok(children)
+    }
+
+    /// Creates a parser for the node, and lets the attributes loaded by the
+    /// given function.
+    fn with_attributes<T>(
+        &self,
+        index: LazyNodeIndex,
+        f: impl FnOnce(&mut Attributes<'_, SeekableSource<&mut R>>) -> ParserResult<T>,
+    ) -> ParserResult<T> {
+        let (start, end) = {
+            let node = &self.nodes.borrow()[index];
+            (node.start_offset, node.end_offset)
+        };
+        let mut reader = self.reader.borrow_mut();
+        reader.seek(io::SeekFrom::Start(self.base_position + start))?;
+        let source = SeekableSource::with_offset(&mut *reader, start as usize);
+        let mut parser =
+            Parser::create_for_subtree(self.parser_version, self.fbx_version, source, end)?;
+        parser.set_warning_handler(|w, pos| {
+            warn!("WARNING: {} (pos={:?})", w, pos);
+            Ok(())
+        });
+        let mut attrs = match parser.next_event()? {
+            Event::StartNode(start) => start.attributes(),
+            _ => unreachable!("Subtree parser should emit `StartNode` event first"),
+        };
+        f(&mut attrs)
+    }
+
+    /// Returns the non-array attributes of the node, loading them if
+    /// necessary.
+    ///
+    /// Array attributes are `None`.
+    fn slots_of(&self, index: LazyNodeIndex) -> ParserResult<Rc<[AttributeSlot]>> {
+        if let Some(slots) = &self.nodes.borrow()[index].attributes {
+            return Ok(slots.clone());
+        }
+
+        let slots = self.with_attributes(index, |attrs| {
+            let mut slots = Vec::with_capacity(attrs.total_count() as usize);
+            while let Some(slot) = attrs.load_next(SlotLoader)? {
+                slots.push(slot.map(Rc::new));
+            }
+            Ok(slots)
+        })?;
+        let slots: Rc<[AttributeSlot]> = slots.into();
+        self.nodes.borrow_mut()[index].attributes = Some(slots.clone());
+
+        Ok(slots)
+    }
+
+    /// Returns the attribute of the node, loading it if necessary.
+    fn attribute_of(
+        &self,
+        index: LazyNodeIndex,
+        attr_index: usize,
+    ) -> ParserResult<Option<Rc<AttributeValue>>> {
+        let slots = self.slots_of(index)?;
+        match slots.get(attr_index) {
+            None => Ok(None),
+            Some(Some(value)) => Ok(Some(value.clone())),
+            Some(None) => self.array_attribute_of(index, attr_index).map(Some),
+        }
+    }
+
+    /// Returns the array attribute of the node, using the cache if possible.
+    fn array_attribute_of(
+        &self,
+        index: LazyNodeIndex,
+        attr_index: usize,
+    ) -> ParserResult<Rc<AttributeValue>> {
+        let key = (index, attr_index);
+        if let Some(value) = self.cache.borrow_mut().get(key) {
+            return Ok(value);
+        }
+
+        let value = self.with_attributes(index, |attrs| {
+            for _ in 0..attr_index {
+                attrs.load_next(SkipLoader)?;
+            }
+            attrs
+                .load_next(DirectLoader)?
+                .ok_or_else(|| DataError::NodeAttributeError.into())
+        })?;
+        let value = Rc::new(value);
+        self.cache.borrow_mut().insert(key, value.clone());
+
+        Ok(value)
+    }
+}
+
+impl<R> fmt::Debug for LazyTree<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyTree")
+            .field("parser_version", &self.parser_version)
+            .field("fbx_version", &self.fbx_version)
+            .field("base_position", &self.base_position)
+            .field("loaded_nodes", &self.nodes.borrow().len())
+            .field("cache", &self.cache.borrow())
+            .finish()
+    }
+}
+
+/// Node handle of [`LazyTree`].
+pub struct LazyNodeHandle<'a, R> {
+    /// The tree the node belongs to.
+    tree: &'a LazyTree<R>,
+    /// Node index.
+    index: LazyNodeIndex,
+    /// Node name.
+    name: Rc<str>,
+}
+
+impl<'a, R: io::Read + io::Seek> LazyNodeHandle<'a, R> {
+    /// Creates a new `LazyNodeHandle`.
+    fn new(tree: &'a LazyTree<R>, index: LazyNodeIndex) -> Self {
+        Self {
+            tree,
+            index,
+            name: tree.node_name(index),
+        }
+    }
+
+    /// Returns a reference to the tree.
+    pub fn tree(&self) -> &'a LazyTree<R> {
+        self.tree
+    }
+
+    /// Returns the node name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of the node attributes.
+    ///
+    /// This does not load the attributes.
+    pub fn num_attributes(&self) -> u64 {
+        self.tree.nodes.borrow()[self.index].num_attributes
+    }
+
+    /// Returns the node attributes.
+    ///
+    /// Attributes are loaded on the first call, and array attributes are
+    /// loaded through the cache.
+    pub fn attributes(&self) -> Result<Vec<Rc<AttributeValue>>, LoadError> {
+        let len = self.tree.slots_of(self.index)?.len();
+        (0..len)
+            .map(|i| {
+                self.tree
+                    .attribute_of(self.index, i)?
+                    .ok_or_else(|| DataError::NodeAttributeError.into())
+            })
+            .collect::<ParserResult<_>>()
+            .map_err(Into::into)
+    }
+
+    /// Returns the node attribute at the given index.
+    ///
+    /// Arrays other than the requested one are not decoded.
+    pub fn attribute(&self, index: usize) -> Result<Option<Rc<AttributeValue>>, LoadError> {
+        Ok(self.tree.attribute_of(self.index, index)?)
+    }
+
+    /// Returns the parent node.
+    ///
+    /// Returns `None` for the root node.
+    pub fn parent(&self) -> Option<Self> {
+        let parent = self.tree.nodes.borrow()[self.index].parent?;
+        Some(Self::new(self.tree, parent))
+    }
+
+    /// Returns an iterator of children.
+    ///
+    /// Children are loaded on the first call.
+    pub fn children(&self) -> Result<LazyChildren<'a, R>, LoadError> {
+        Ok(LazyChildren {
+            tree: self.tree,
+            children: self.tree.children_of(self.index)?,
+            next: 0,
+        })
+    }
+
+    /// Returns an iterator of children with the given name.
+    ///
+    /// Children are loaded on the first call.
+    pub fn children_by_name<'b>(
+        &self,
+        name: &'b str,
+    ) -> Result<LazyChildrenByName<'a, 'b, R>, LoadError> {
+        Ok(LazyChildrenByName {
+            name,
+            children_iter: self.children()?,
+        })
+    }
+
+    /// Returns the first child with the given name.
+    ///
+    /// Children are loaded on the first call.
+    pub fn first_child_by_name(&self, name: &str) -> Result<Option<Self>, LoadError> {
+        Ok(self.children_by_name(name)?.next())
+    }
+}
+
+impl<R> Clone for LazyNodeHandle<'_, R> {
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree,
+            index: self.index,
+            name: self.name.clone(),
+        }
+    }
+}
+
+impl<R> fmt::Debug for LazyNodeHandle<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyNodeHandle")
+            .field("index", &self.index)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// An iterator of children of a lazy node.
+pub struct LazyChildren<'a, R> {
+    /// Tree.
+    tree: &'a LazyTree<R>,
+    /// Child node indices.
+    children: Rc<[LazyNodeIndex]>,
+    /// Position of the next child in `children`.
+    next: usize,
+}
+
+impl<'a, R: io::Read + io::Seek> Iterator for LazyChildren<'a, R> {
+    type Item = LazyNodeHandle<'a, R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let child = *self.children.get(self.next)?;
+        self.next += 1;
+        Some(LazyNodeHandle::new(self.tree, child))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.children.len() - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<R: io::Read + io::Seek> ExactSizeIterator for LazyChildren<'_, R> {}
+
+impl<R: io::Read + io::Seek> std::iter::FusedIterator for LazyChildren<'_, R> {}
+
+impl<R> Clone for LazyChildren<'_, R> {
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree,
+            children: self.children.clone(),
+            next: self.next,
+        }
+    }
+}
+
+impl<R> fmt::Debug for LazyChildren<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyChildren").finish()
+    }
+}
+
+/// An iterator of children of a lazy node, with a specific name.
+pub struct LazyChildrenByName<'a, 'b, R> {
+    /// Name.
+    name: &'b str,
+    /// Children node iterator.
+    children_iter: LazyChildren<'a, R>,
+}
+
+impl<'a, R: io::Read + io::Seek> Iterator for LazyChildrenByName<'a, '_, R> {
+    type Item = LazyNodeHandle<'a, R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let name = self.name;
+        self.children_iter.find(|child| child.name() == name)
+    }
+}
+
+impl<R: io::Read + io::Seek> std::iter::FusedIterator for LazyChildrenByName<'_, '_, R> {}
+
+impl<R> Clone for LazyChildrenByName<'_, '_, R> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name,
+            children_iter: self.children_iter.clone(),
+        }
+    }
+}
+
+impl<R> fmt::Debug for LazyChildrenByName<'_, '_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyChildrenByName")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+/// Attribute of a lazy node.
+///
+/// `None` for array attributes, which are stored in the cache.
+type AttributeSlot = Option<Rc<AttributeValue>>;
+
+/// Node of the lazy tree.
+#[derive(Debug, Clone)]
+struct LazyNode {
+    /// Node name.
+    name: Rc<str>,
+    /// Parent node.
+    parent: Option<LazyNodeIndex>,
+    /// Start offset of the node.
+    start_offset: u64,
+    /// End offset of the node.
+    end_offset: u64,
+    /// Number of the node attributes.
+    num_attributes: u64,
+    /// End offset of the node attributes.
+    attributes_end_offset: u64,
+    /// Children, if loaded.
+    children: Option<Rc<[LazyNodeIndex]>>,
+    /// Attributes, if loaded.
+    attributes: Option<Rc<[AttributeSlot]>>,
+}
+
+/// LRU cache of array attributes, bounded by the total size of the arrays.
+#[derive(Debug, Clone)]
+struct ArrayCache {
+    /// Capacity in bytes.
+    capacity: usize,
+    /// Total size of the cached arrays in bytes.
+    size: usize,
+    /// Counter incremented on each access.
+    tick: u64,
+    /// Cached arrays with their size and last access tick.
+    entries: HashMap<(LazyNodeIndex, usize), (Rc<AttributeValue>, usize, u64)>,
+}
+
+impl ArrayCache {
+    /// Creates a new empty cache.
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            size: 0,
+            tick: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Returns the cached array and marks it as recently used.
+    fn get(&mut self, key: (LazyNodeIndex, usize)) -> Option<Rc<AttributeValue>> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(&key).map(|(value, _, last_used)| {
+            *last_used = tick;
+            value.clone()
+        })
+    }
+
+    /// Inserts the array, evicting least recently used arrays if necessary.
+    fn insert(&mut self, key: (LazyNodeIndex, usize), value: Rc<AttributeValue>) {
+        let size = array_bytelen(&value);
+        if size > self.capacity {
+            return;
+        }
+        self.tick += 1;
+        if let Some((_, old_size, _)) = self.entries.insert(key, (value, size, self.tick)) {
+            self.size -= old_size;
+        }
+        self.size += size;
+        self.shrink_to_fit();
+    }
+
+    /// Evicts least recently used arrays until the cache fits in the
+    /// capacity.
+    fn shrink_to_fit(&mut self) {
+        while self.size > self.capacity {
+            let lru = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, _, last_used))| *last_used)
+                .map(|(key, _)| *key)
+                .expect("Should never fail: non-zero size means non-empty cache");
+            let (_, size, _) = self
+                .entries
+                .remove(&lru)
+                .expect("Should never fail: the key is taken from the cache");
+            self.size -= size;
+        }
+    }
+
+    /// Removes all arrays.
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.size = 0;
+    }
+}
+
+/// Returns the size of the array elements in bytes.
+fn array_bytelen(value: &AttributeValue) -> usize {
+    match value {
+        AttributeValue::ArrBool(v) => v.len(),
+        AttributeValue::ArrI32(v) => v.len() * 4,
+        AttributeValue::ArrI64(v) => v.len() * 8,
+        AttributeValue::ArrF32(v) => v.len() * 4,
+        AttributeValue::ArrF64(v) => v.len() * 8,
+        _ => 0,
+    }
+}
+
+/// Loader for non-array attributes.
+///
+/// Arrays are not decoded, and loaded as `None`.
+#[derive(Debug, Clone, Copy)]
+struct SlotLoader;
+
+impl LoadAttribute for SlotLoader {
+    type Output = Option<AttributeValue>;
+
+    fn expecting(&self) -> String {
+        "any type".into()
+    }
+
+    fn load_bool(self, v: bool) -> ParserResult<Self::Output> {
+        DirectLoader.load_bool(v).map(Some)
+    }
+
+    fn load_i16(self, v: i16) -> ParserResult<Self::Output> {
+        DirectLoader.load_i16(v).map(Some)
+    }
+
+    fn load_i32(self, v: i32) -> ParserResult<Self::Output> {
+        DirectLoader.load_i32(v).map(Some)
+    }
+
+    fn load_i64(self, v: i64) -> ParserResult<Self::Output> {
+        DirectLoader.load_i64(v).map(Some)
+    }
+
+    fn load_f32(self, v: f32) -> ParserResult<Self::Output> {
+        DirectLoader.load_f32(v).map(Some)
+    }
+
+    fn load_f64(self, v: f64) -> ParserResult<Self::Output> {
+        DirectLoader.load_f64(v).map(Some)
+    }
+
+    fn load_seq_bool(
+        self,
+        _: impl Iterator<Item = ParserResult<bool>>,
+        _len: usize,
+    ) -> ParserResult<Self::Output> {
+        Ok(None)
+    }
+
+    fn load_seq_i32(
+        self,
+        _: impl Iterator<Item = ParserResult<i32>>,
+        _len: usize,
+    ) -> ParserResult<Self::Output> {
+        Ok(None)
+    }
+
+    fn load_seq_i64(
+        self,
+        _: impl Iterator<Item = ParserResult<i64>>,
+        _len: usize,
+    ) -> ParserResult<Self::Output> {
+        Ok(None)
+    }
+
+    fn load_seq_f32(
+        self,
+        _: impl Iterator<Item = ParserResult<f32>>,
+        _len: usize,
+    ) -> ParserResult<Self::Output> {
+        Ok(None)
+    }
+
+    fn load_seq_f64(
+        self,
+        _: impl Iterator<Item = ParserResult<f64>>,
+        _len: usize,
+    ) -> ParserResult<Self::Output> {
+        Ok(None)
+    }
+
+    fn load_binary(self, reader: impl io::Read, len: u64) -> ParserResult<Self::Output> {
+        DirectLoader.load_binary(reader, len).map(Some)
+    }
+
+    fn load_string(self, reader: impl io::Read, len: u64) -> ParserResult<Self::Output> {
+        DirectLoader.load_string(reader, len).map(Some)
+    }
+}
+
+/// Implements `LoadAttribute` methods which do nothing.
+macro_rules! impl_skip_loader {
+    ($($scalar:ident: $ty:ty),*; $($seq:ident: $elem:ty),*; $($special:ident),*) => {
+        $(
+            fn $scalar(self, _: $ty) -> ParserResult<Self::Output> {
+                Ok(())
+            }
+        )*
+        $(
+            fn $seq(
+                self,
+                _: impl Iterator<Item = ParserResult<$elem>>,
+                _len: usize,
+            ) -> ParserResult<Self::Output> {
+                Ok(())
+            }
+        )*
+        $(
+            fn $special(self, _: impl io::Read, _len: u64) -> ParserResult<Self::Output> {
+                Ok(())
+            }
+        )*
+    };
+}
+
+/// Loader which skips any attribute without reading the value.
+#[derive(Debug, Clone, Copy)]
+struct SkipLoader;
+
+impl LoadAttribute for SkipLoader {
+    type Output = ();
+
+    fn expecting(&self) -> String {
+        "any type".into()
+    }
+
+    impl_skip_loader! {
+        load_bool: bool, load_i16: i16, load_i32: i32, load_i64: i64, load_f32: f32, load_f64: f64;
+        load_seq_bool: bool, load_seq_i32: i32, load_seq_i64: i64, load_seq_f32: f32, load_seq_f64: f64;
+        load_binary, load_string
+    }
+}
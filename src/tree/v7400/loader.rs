@@ -1,5 +1,7 @@
 //! FBX data tree loader.
 
+use std::{fmt, sync::Arc};
+
 use indextree::Arena;
 use log::{debug, error, trace};
 use string_interner::StringInterner;
@@ -8,13 +10,60 @@ use crate::{
     low::v7400::FbxFooter,
     pull_parser::{
         v7400::{attribute::loaders::DirectLoader, Event, Parser, StartNode},
-        Error as ParserError, ParserSource,
+        Error as ParserError, ParserSource, WarningPolicy,
     },
-    tree::v7400::{LoadError, NodeData, NodeId, NodeNameSym, Tree},
+    tree::v7400::{ArrayRepr, LoadError, NodeData, NodeId, NodeNameSym, Tree},
 };
 
+/// Node filter function.
+type NodeFilter = Arc<dyn Fn(&[String], &str) -> FilterAction + Send + Sync>;
+
+/// Action for a node, decided by the node filter of [`Loader`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FilterAction {
+    /// Keeps the node and its attributes.
+    Keep,
+    /// Skips the node and its descendants.
+    ///
+    /// The subtree is skipped by [`Parser::skip_current_node()`], so the
+    /// attributes are never loaded.
+    Skip,
+    /// Keeps the node, but drops its attributes without loading them.
+    ///
+    /// Children of the node are still passed to the filter.
+    KeepWithoutAttributes,
+}
+
+/// How array attributes are preserved by [`Loader`].
+///
+/// Preserved encodings are used when the tree is written by the binary writer
+/// (`writer::v7400::binary::Writer::write_tree`), so that the tree is written
+/// as close to the source as possible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ArrayPreservation {
+    /// Discards the encodings of array attributes.
+    Discard,
+    /// Keeps the encodings of array attributes.
+    Encoding,
+    /// Keeps the encodings and the raw (possibly compressed) elements of array
+    /// attributes.
+    ///
+    /// Raw elements are written as is unless the attribute is modified, so the
+    /// output is byte-identical even if the source is compressed by another
+    /// implementation.
+    /// Note that this doubles the memory usage for array attributes.
+    RawBytes,
+}
+
+impl Default for ArrayPreservation {
+    fn default() -> Self {
+        ArrayPreservation::Discard
+    }
+}
+
 /// FBX data tree loader.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Loader {
     /// Tree data.
     arena: Arena<NodeData>,
@@ -22,6 +71,12 @@ pub struct Loader {
     node_names: StringInterner<NodeNameSym>,
     /// (Implicit) root node ID.
     root_id: NodeId,
+    /// Node filter.
+    filter: Option<NodeFilter>,
+    /// How array attributes are preserved.
+    array_preservation: ArrayPreservation,
+    /// Warning policy set to the parser.
+    warning_policy: Option<WarningPolicy>,
 }
 
 impl Loader {
@@ -30,6 +85,79 @@ impl Loader {
         Self::default()
     }
 
+    /// Sets the node filter, and returns the loader.
+    ///
+    /// The filter is called for each node with the names of its ancestors
+    /// (starting from a top-level node) and the name of the node, and decides
+    /// how the node is loaded.
+    /// Descendants of skipped nodes are not passed to the filter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fbxcel::tree::v7400::{FilterAction, Loader};
+    ///
+    /// // Load only `Model` nodes in `Objects`, and `Connections`.
+    /// let loader = Loader::new().with_filter(|path, name| match (path, name) {
+    ///     ([], "Objects") => FilterAction::KeepWithoutAttributes,
+    ///     ([], "Connections") => FilterAction::Keep,
+    ///     ([], _) => FilterAction::Skip,
+    ///     ([objects], "Model") if objects == "Objects" => FilterAction::Keep,
+    ///     ([objects], _) if objects == "Objects" => FilterAction::Skip,
+    ///     _ => FilterAction::Keep,
+    /// });
+    /// ```
+    pub fn with_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&[String], &str) -> FilterAction + Send + Sync + 'static,
+    {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Sets how array attributes are preserved, and returns the loader.
+    ///
+    /// By default, encodings of array attributes are discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fbxcel::tree::v7400::{ArrayPreservation, Loader};
+    ///
+    /// // Keep compressed arrays as is, to write the tree back byte-identically.
+    /// let loader = Loader::new().with_array_preservation(ArrayPreservation::RawBytes);
+    /// ```
+    pub fn with_array_preservation(mut self, preservation: ArrayPreservation) -> Self {
+        self.array_preservation = preservation;
+        self
+    }
+
+    /// Sets the warning policy, and returns the loader.
+    ///
+    /// The policy is set to the parser when the tree is loaded, replacing the
+    /// warning handler of the parser.
+    /// By default, the warning handler of the parser is used as is.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use fbxcel::pull_parser::v7400::Parser;
+    /// use fbxcel::{pull_parser::WarningPolicy, tree::v7400::Loader};
+    /// # let mut parser: Parser<fbxcel::pull_parser::reader::PlainSource<std::io::Empty>> =
+    /// #     unimplemented!();
+    ///
+    /// let policy = WarningPolicy::collecting();
+    /// let (tree, _footer) = Loader::new()
+    ///     .with_warning_policy(policy.clone())
+    ///     .load(&mut parser)
+    ///     .expect("Failed to load tree");
+    /// let warnings = policy.collector().take();
+    /// ```
+    pub fn with_warning_policy(mut self, policy: WarningPolicy) -> Self {
+        self.warning_policy = Some(policy);
+        self
+    }
+
     /// Loads a tree from the given parser, and returns the tree and FBX footer.
     ///
     /// The given parser should be brand-new, i.e. it should not have emited any
@@ -41,8 +169,60 @@ impl Loader {
     /// If the tree is successfully read but FBX footer is not,
     /// `Ok(tree, Err(parser_error))` is returned.
     pub fn load<R: ParserSource>(
+        self,
+        parser: &mut Parser<R>,
+    ) -> Result<(Tree, Result<Box<FbxFooter>, ParserError>), LoadError> {
+        self.load_impl(parser, None)
+    }
+
+    /// Loads a tree from the given parser in the recovery mode, and returns
+    /// the (possibly partial) tree, FBX footer, and the recovered errors.
+    ///
+    /// This enables the recovery mode of the parser (see
+    /// [`Parser::set_recovery_mode()`]), so that the nodes successfully read
+    /// are kept even if the FBX data is truncated or has broken nodes.
+    /// Broken nodes are kept with the attributes loaded before the error.
+    ///
+    /// If the data is truncated or broken in a top-level node, FBX footer is
+    /// not read and `Err(parser_error)` is returned as the footer.
+    /// Errors the parser cannot recover from (for example, errors of FBX
+    /// ASCII, or critical warnings) are returned as `Err(_)`.
+    ///
+    /// The given parser should be brand-new, as [`load()`][`Self::load`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use fbxcel::pull_parser::v7400::Parser;
+    /// use fbxcel::tree::v7400::Loader;
+    /// # let mut parser: Parser<fbxcel::pull_parser::reader::PlainSource<std::io::Empty>> =
+    /// #     unimplemented!();
+    /// let (tree, _footer, errors) = Loader::new()
+    ///     .load_with_recovery(&mut parser)
+    ///     .expect("Failed to load tree");
+    /// for e in &errors {
+    ///     eprintln!("Recovered from an error: {}", e);
+    /// }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn load_with_recovery<R: ParserSource>(
+        self,
+        parser: &mut Parser<R>,
+    ) -> Result<(Tree, Result<Box<FbxFooter>, ParserError>, Vec<ParserError>), LoadError> {
+        let mut errors = Vec::new();
+        parser.set_recovery_mode(true);
+        let (tree, footer) = self.load_impl(parser, Some(&mut errors))?;
+        Ok((tree, footer, errors))
+    }
+
+    /// Loads a tree from the given parser.
+    ///
+    /// If `errors` is `Some(_)`, the recovered errors are pushed to it.
+    /// Otherwise, any error is returned as `Err(_)`.
+    fn load_impl<R: ParserSource>(
         mut self,
         parser: &mut Parser<R>,
+        mut errors: Option<&mut Vec<ParserError>>,
     ) -> Result<(Tree, Result<Box<FbxFooter>, ParserError>), LoadError> {
         debug!("Loading FBX data tree from a parser");
 
@@ -50,21 +230,46 @@ impl Loader {
             error!("The given parser should be brand-new, but it has already emitted some events");
             return Err(LoadError::BadParser);
         }
+        if let Some(policy) = self.warning_policy.take() {
+            parser.set_warning_policy(policy);
+        }
 
         let mut open_nodes = vec![self.root_id];
+        // Names of the open nodes, without the root.
+        // Maintained only when the filter is set, since nobody reads it otherwise.
+        let tracks_path = self.filter.is_some();
+        let mut path: Vec<String> = Vec::new();
         let footer = loop {
             trace!("Loading next parser event: open_nodes={:?}", open_nodes);
             assert!(
                 !open_nodes.is_empty(),
                 "Open nodes stack should not be empty on loop start"
             );
+            Self::take_diagnostics(parser, errors.as_deref_mut())?;
             match parser.next_event()? {
                 Event::StartNode(start) => {
                     trace!("Got `Event::StartNode(name={:?})`", start.name());
+                    let action = match &self.filter {
+                        Some(filter) => filter(&path, start.name()),
+                        None => FilterAction::Keep,
+                    };
+                    if action == FilterAction::Skip {
+                        trace!("Skipping the node {:?}", start.name());
+                        parser.skip_current_node()?;
+                        continue;
+                    }
+                    if tracks_path {
+                        path.push(start.name().to_owned());
+                    }
                     let parent = open_nodes
                         .last_mut()
                         .expect("Should never fail: Open nodes stack should not be empty here");
-                    let current = self.add_node(*parent, start)?;
+                    let current = self.add_node(
+                        *parent,
+                        start,
+                        action != FilterAction::KeepWithoutAttributes,
+                        errors.as_deref_mut(),
+                    )?;
 
                     // Update the open nodes stack.
                     open_nodes.push(current);
@@ -74,6 +279,9 @@ impl Loader {
                     open_nodes
                         .pop()
                         .expect("Should never fail: Open nodes stack should not be empty here");
+                    if tracks_path {
+                        path.pop();
+                    }
                 }
                 Event::EndFbx(footer) => {
                     trace!("Got `Event::EndFbx(_)`");
@@ -88,17 +296,50 @@ impl Loader {
             open_nodes.is_empty(),
             "Should never fail: There should be no open nodes after `EndFbx` event is emitted"
         );
+        Self::take_diagnostics(parser, errors)?;
 
         debug!("Successfully loaded FBX data tree");
         let tree = Tree::new(self.arena, self.node_names, self.root_id);
         Ok((tree, footer))
     }
 
+    /// Takes the errors the parser has recovered from.
+    ///
+    /// If `errors` is `Some(_)`, the recovered errors are pushed to it.
+    /// Otherwise, the first recovered error is returned as `Err(_)`.
+    fn take_diagnostics<R: ParserSource>(
+        parser: &mut Parser<R>,
+        errors: Option<&mut Vec<ParserError>>,
+    ) -> Result<(), LoadError> {
+        match errors {
+            Some(errors) => {
+                for e in parser.take_diagnostics() {
+                    trace!("Got a recovered error: {:?}", e);
+                    errors.push(e);
+                }
+            }
+            None => {
+                if let Some(e) = parser.take_diagnostic_since(0) {
+                    return Err(e.into());
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Creates and adds a new node to the tree.
+    ///
+    /// If `load_attributes` is false, the node will have no attributes.
+    ///
+    /// If `errors` is `Some(_)` and the parser recovered from an error in
+    /// the attributes, the error is pushed to it and the attributes loaded
+    /// before the error are kept.
     fn add_node<R: ParserSource>(
         &mut self,
         parent: NodeId,
         start: StartNode<'_, R>,
+        load_attributes: bool,
+        errors: Option<&mut Vec<ParserError>>,
     ) -> Result<NodeId, LoadError> {
         trace!(
             "Adding a new child name={:?} to the parent {:?}",
@@ -109,12 +350,39 @@ impl Loader {
         // Create a new node.
         let current = {
             let name_sym = self.node_names.get_or_intern(start.name());
-            let attributes = start
-                .attributes()
-                .into_iter(std::iter::repeat(DirectLoader))
-                .collect::<Result<Vec<_>, _>>()?;
+            let mut attributes = Vec::new();
+            let mut array_reprs = Vec::new();
+            if load_attributes {
+                let keep_reprs = self.array_preservation != ArrayPreservation::Discard;
+                let keep_raw = self.array_preservation == ArrayPreservation::RawBytes;
+                let mut attrs = start.attributes();
+                loop {
+                    match attrs.load_next_with_capture(DirectLoader, keep_raw) {
+                        Ok(Some((attr, capture))) => {
+                            attributes.push(attr);
+                            if keep_reprs {
+                                array_reprs.push(capture.header.map(|header| ArrayRepr {
+                                    encoding: header.encoding,
+                                    raw: capture.raw,
+                                }));
+                            }
+                        }
+                        Ok(None) => break,
+                        Err(e) => match errors {
+                            // The parser is still running if it recovered
+                            // from the error.
+                            Some(errors) if attrs.ensure_continuable().is_ok() => {
+                                errors.push(e);
+                                break;
+                            }
+                            _ => return Err(e.into()),
+                        },
+                    }
+                }
+            }
+            let data = NodeData::with_array_reprs(name_sym, attributes, array_reprs);
 
-            NodeId::new(self.arena.new_node(NodeData::new(name_sym, attributes)))
+            NodeId::new(self.arena.new_node(data))
         };
 
         // Set the parent.
@@ -143,6 +411,22 @@ impl Default for Loader {
             arena,
             node_names,
             root_id,
+            filter: None,
+            array_preservation: ArrayPreservation::default(),
+            warning_policy: None,
         }
     }
 }
+
+impl fmt::Debug for Loader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Loader")
+            .field("arena", &self.arena)
+            .field("node_names", &self.node_names)
+            .field("root_id", &self.root_id)
+            .field("filter", &self.filter.as_ref().map(|_| "<filter>"))
+            .field("array_preservation", &self.array_preservation)
+            .field("warning_policy", &self.warning_policy)
+            .finish()
+    }
+}
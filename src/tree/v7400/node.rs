@@ -2,7 +2,10 @@
 
 use crate::tree::v7400::{NodeHandle, Tree};
 
-pub(crate) use self::{data::NodeData, name::NodeNameSym};
+pub(crate) use self::{
+    data::{ArrayRepr, NodeData},
+    name::NodeNameSym,
+};
 
 mod data;
 pub(crate) mod handle;
@@ -1,6 +1,18 @@
 //! Node-local data.
 
-use crate::{low::v7400::AttributeValue, tree::v7400::node::NodeNameSym};
+use crate::{
+    low::v7400::{ArrayAttributeEncoding, AttributeValue},
+    tree::v7400::node::NodeNameSym,
+};
+
+/// Array attribute representation preserved from the source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ArrayRepr {
+    /// Encoding.
+    pub(crate) encoding: ArrayAttributeEncoding,
+    /// Raw (possibly compressed) elements, as read from the source.
+    pub(crate) raw: Option<Vec<u8>>,
+}
 
 /// Node-local data in FBX data tree.
 ///
@@ -12,6 +24,11 @@ pub(crate) struct NodeData {
     name_sym: NodeNameSym,
     /// Node attributes.
     attributes: Vec<AttributeValue>,
+    /// Array representations of the attributes.
+    ///
+    /// This can be shorter than `attributes`, and the missing elements are
+    /// treated as `None`.
+    array_reprs: Vec<Option<ArrayRepr>>,
 }
 
 impl NodeData {
@@ -20,21 +37,116 @@ impl NodeData {
         self.name_sym
     }
 
+    /// Sets the node name symbol.
+    pub(crate) fn set_name_sym(&mut self, name_sym: NodeNameSym) {
+        self.name_sym = name_sym;
+    }
+
     /// Returns the reference to the attributes.
     pub(crate) fn attributes(&self) -> &[AttributeValue] {
         &self.attributes
     }
 
+    /// Returns the mutable reference to the attributes.
+    ///
+    /// Array representations are discarded, since the attributes can be
+    /// modified arbitrarily.
+    pub(crate) fn attributes_mut(&mut self) -> &mut Vec<AttributeValue> {
+        self.array_reprs.clear();
+        &mut self.attributes
+    }
+
     /// Appends the given value to the attributes.
     pub(crate) fn append_attribute(&mut self, v: AttributeValue) {
         self.attributes.push(v)
     }
 
+    /// Replaces the attribute at the given index, and returns the old value.
+    ///
+    /// The array representation of the attribute is discarded.
+    pub(crate) fn set_attribute(&mut self, index: usize, v: AttributeValue) -> AttributeValue {
+        let old = std::mem::replace(&mut self.attributes[index], v);
+        if let Some(repr) = self.array_reprs.get_mut(index) {
+            *repr = None;
+        }
+        old
+    }
+
+    /// Inserts the attribute at the given index.
+    pub(crate) fn insert_attribute(&mut self, index: usize, v: AttributeValue) {
+        self.attributes.insert(index, v);
+        if index < self.array_reprs.len() {
+            self.array_reprs.insert(index, None);
+        }
+    }
+
+    /// Removes the attribute at the given index, and returns it.
+    pub(crate) fn remove_attribute(&mut self, index: usize) -> AttributeValue {
+        let v = self.attributes.remove(index);
+        if index < self.array_reprs.len() {
+            self.array_reprs.remove(index);
+        }
+        v
+    }
+
+    /// Removes all attributes.
+    pub(crate) fn clear_attributes(&mut self) {
+        self.attributes.clear();
+        self.array_reprs.clear();
+    }
+
+    /// Returns the array representation of the attribute at the given index.
+    pub(crate) fn array_repr(&self, index: usize) -> Option<&ArrayRepr> {
+        self.array_reprs.get(index).and_then(Option::as_ref)
+    }
+
+    /// Sets the array representation of the attribute at the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of range.
+    pub(crate) fn set_array_repr(&mut self, index: usize, repr: Option<ArrayRepr>) {
+        assert!(
+            index < self.attributes.len(),
+            "Attribute index out of range: index={:?}, len={:?}",
+            index,
+            self.attributes.len()
+        );
+        if index >= self.array_reprs.len() {
+            if repr.is_none() {
+                return;
+            }
+            self.array_reprs.resize(index + 1, None);
+        }
+        self.array_reprs[index] = repr;
+    }
+
+    /// Copies the attributes and their array representations from the given
+    /// node data.
+    pub(crate) fn extend_attributes_from(&mut self, other: &Self) {
+        let len = self.attributes.len();
+        self.attributes.extend_from_slice(&other.attributes);
+        if !other.array_reprs.is_empty() {
+            self.array_reprs.resize(len, None);
+            self.array_reprs.extend_from_slice(&other.array_reprs);
+        }
+    }
+
     /// Creates a new `NodeData`.
     pub(crate) fn new(name_sym: NodeNameSym, attributes: Vec<AttributeValue>) -> Self {
+        Self::with_array_reprs(name_sym, attributes, Vec::new())
+    }
+
+    /// Creates a new `NodeData` with the array representations.
+    pub(crate) fn with_array_reprs(
+        name_sym: NodeNameSym,
+        attributes: Vec<AttributeValue>,
+        array_reprs: Vec<Option<ArrayRepr>>,
+    ) -> Self {
         Self {
             name_sym,
             attributes,
+            array_reprs,
         }
     }
 }
@@ -3,7 +3,7 @@
 use std::fmt;
 
 use crate::{
-    low::v7400::AttributeValue,
+    low::v7400::{ArrayAttributeEncoding, AttributeValue},
     tree::v7400::{NodeData, NodeId, NodeNameSym, Tree},
 };
 
@@ -66,6 +66,30 @@ impl<'a> NodeHandle<'a> {
         self.node().get().attributes()
     }
 
+    /// Returns the encoding of the array attribute at the given index, if
+    /// preserved.
+    ///
+    /// The encoding is preserved by [`Loader`][`crate::tree::v7400::Loader`]
+    /// with [`ArrayPreservation`][`crate::tree::v7400::ArrayPreservation`], or
+    /// set by [`Tree::set_array_encoding`].
+    pub fn array_encoding(&self, index: usize) -> Option<ArrayAttributeEncoding> {
+        self.node()
+            .get()
+            .array_repr(index)
+            .map(|repr| repr.encoding)
+    }
+
+    /// Returns the raw (possibly compressed) elements of the array attribute
+    /// at the given index, if kept by the loader.
+    ///
+    /// The raw elements are discarded when the attribute is modified.
+    pub fn raw_array(&self, index: usize) -> Option<&'a [u8]> {
+        self.node()
+            .get()
+            .array_repr(index)
+            .and_then(|repr| repr.raw.as_deref())
+    }
+
     /// Returns an iterator of children with the given name.
     #[inline]
     #[must_use]
@@ -105,6 +129,125 @@ impl<'a> NodeHandle<'a> {
     }
 }
 
+/// Mutable node handle.
+///
+/// Node names are always interned in the tree, and structural changes are
+/// done through the methods of [`Tree`].
+#[derive(Debug)]
+pub struct NodeHandleMut<'a> {
+    /// The tree the node belongs to.
+    tree: &'a mut Tree,
+    /// Node ID.
+    node_id: NodeId,
+}
+
+impl<'a> NodeHandleMut<'a> {
+    /// Creates a new `NodeHandleMut`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given node ID is not used in the given tree.
+    pub(crate) fn new(tree: &'a mut Tree, node_id: NodeId) -> Self {
+        assert!(
+            tree.contains_node(node_id),
+            "The node ID is not used in the given tree: node_id={:?}",
+            node_id
+        );
+
+        Self { tree, node_id }
+    }
+
+    /// Returns the node ID.
+    pub fn node_id(&self) -> NodeId {
+        self.node_id
+    }
+
+    /// Returns an immutable handle of the node.
+    pub fn as_handle(&self) -> NodeHandle<'_> {
+        NodeHandle::new(self.tree, self.node_id)
+    }
+
+    /// Returns the node name.
+    pub fn name(&self) -> &str {
+        self.as_handle().name()
+    }
+
+    /// Returns the node attributes.
+    pub fn attributes(&self) -> &[AttributeValue] {
+        self.as_handle().attributes()
+    }
+
+    /// Renames the node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is the root node.
+    pub fn set_name(&mut self, name: &str) {
+        self.tree.rename(self.node_id, name);
+    }
+
+    /// Sets the encoding of the array attribute at the given index.
+    ///
+    /// See [`Tree::set_array_encoding`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is the root node, the index is out of range, or the
+    /// attribute is not an array.
+    pub fn set_array_encoding(&mut self, index: usize, encoding: Option<ArrayAttributeEncoding>) {
+        self.tree.set_array_encoding(self.node_id, index, encoding);
+    }
+
+    /// Returns the mutable reference to the node attributes.
+    ///
+    /// Preserved array encodings of the node are discarded, since the
+    /// attributes can be modified arbitrarily.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is the root node.
+    pub fn attributes_mut(&mut self) -> &mut Vec<AttributeValue> {
+        self.tree
+            .non_root_node_data_mut(self.node_id)
+            .attributes_mut()
+    }
+
+    /// Creates a new node, appends it to the node, and returns the handle of
+    /// the new node.
+    pub fn append_new(&mut self, name: &str) -> NodeHandleMut<'_> {
+        let child = self.tree.append_new(self.node_id, name);
+        NodeHandleMut::new(self.tree, child)
+    }
+
+    /// Returns the mutable handle of the parent node if available.
+    pub fn parent(self) -> Option<NodeHandleMut<'a>> {
+        let parent = self.as_handle().parent()?.node_id();
+        Some(NodeHandleMut::new(self.tree, parent))
+    }
+
+    /// Detaches the node and its descendants from the parent.
+    ///
+    /// See [`Tree::detach`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is the root node.
+    pub fn detach(&mut self) {
+        self.tree.detach(self.node_id);
+    }
+
+    /// Removes the node and its descendants from the tree.
+    ///
+    /// See [`Tree::remove`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node is the root node.
+    pub fn remove(self) {
+        self.tree.remove(self.node_id);
+    }
+}
+
 /// Implement accessors to neighbor nodes.
 macro_rules! impl_related_node_accessor {
     (
@@ -0,0 +1,74 @@
+//! `serde` support for the tree.
+//!
+//! A tree is serialized as a sequence of top-level nodes, and a node is
+//! serialized as a struct with `name`, `attributes`, and `children` fields.
+
+use serde::{
+    de::{Deserialize, Deserializer},
+    ser::{Serialize, SerializeStruct, Serializer},
+};
+
+use crate::{
+    low::v7400::AttributeValue,
+    tree::v7400::{NodeHandle, NodeId, Tree},
+};
+
+impl Serialize for Tree {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.root().children())
+    }
+}
+
+impl Serialize for NodeHandle<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Node", 3)?;
+        state.serialize_field("name", self.name())?;
+        state.serialize_field("attributes", self.attributes())?;
+        state.serialize_field("children", &SerializeChildren(*self))?;
+        state.end()
+    }
+}
+
+/// Serializable children of a node.
+struct SerializeChildren<'a>(NodeHandle<'a>);
+
+impl Serialize for SerializeChildren<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.children())
+    }
+}
+
+impl<'de> Deserialize<'de> for Tree {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let toplevels = Vec::<OwnedNode>::deserialize(deserializer)?;
+        let mut tree = Tree::default();
+        let root_id = tree.root().node_id();
+        for node in toplevels {
+            node.append_to(&mut tree, root_id);
+        }
+        Ok(tree)
+    }
+}
+
+/// Deserialized node.
+#[derive(serde::Deserialize)]
+#[serde(rename = "Node")]
+struct OwnedNode {
+    /// Node name.
+    name: String,
+    /// Node attributes.
+    attributes: Vec<AttributeValue>,
+    /// Child nodes.
+    children: Vec<OwnedNode>,
+}
+
+impl OwnedNode {
+    /// Appends the node and its descendants to the given parent.
+    fn append_to(self, tree: &mut Tree, parent: NodeId) {
+        let node_id = tree.append_new(parent, &self.name);
+        *tree.non_root_node_data_mut(node_id).attributes_mut() = self.attributes;
+        for child in self.children {
+            child.append_to(tree, node_id);
+        }
+    }
+}
@@ -1,3 +1,4 @@
 //! Writer for FBX 7.4 or later.
 
+pub mod ascii;
 pub mod binary;
@@ -0,0 +1,262 @@
+//! ASCII writer for FBX 7.4 or later.
+//!
+//! The writer has almost the same interface as [binary writer][`super::binary`],
+//! but does not require [`std::io::Seek`] for the sink.
+//!
+//! # Using writer
+//!
+//! ```
+//! use fbxcel::{low::FbxVersion, writer::v7400::ascii::Writer};
+//! # let sink = Vec::new();
+//! let mut writer = Writer::new(sink, FbxVersion::V7_4)?;
+//!
+//! // Create a node with name `NodeName`.
+//! let mut attrs_writer = writer.new_node("NodeName")?;
+//! // Add attributes to the node.
+//! attrs_writer.append_bool(true)?;
+//! attrs_writer.append_arr_i32_from_iter([1, 2, 4, 8, 16].iter().cloned())?;
+//! attrs_writer.append_string_direct("Hello, world")?;
+//! // Close the node.
+//! writer.close_node()?;
+//!
+//! let sink = writer.finalize_and_flush()?;
+//! # Ok::<_, fbxcel::writer::v7400::ascii::Error>(())
+//! ```
+//!
+//! # Attribute representation
+//!
+//! FBX ASCII does not have type information of the attributes, so some values
+//! cannot be read as the same type as written.
+//!
+//! * Floating point values are written so that they can be read exactly
+//!   (except for NaN payloads).
+//!     + `f32` values are written with the `f64` representation of the same
+//!       value.
+//! * Strings are escaped (`"` as `&quot;`, CR as `&cr;`, LF as `&lf;`).
+//!     + Object names in the form of `"Name\u{0}\u{1}Class"` are written as
+//!       `"Class::Name"`.
+//!       Object names are the first string attributes of the nodes directly
+//!       under the top-level `Objects` node, and other strings are written
+//!       as is.
+//! * Binaries are written as base64 strings.
+
+use std::io::Write;
+
+use log::trace;
+
+use crate::low::FbxVersion;
+
+pub use self::{
+    attributes::AttributesWriter,
+    error::{Error, Result},
+};
+
+mod attributes;
+mod error;
+
+/// ASCII writer.
+///
+/// See [module documentation][`self`] for usage.
+#[derive(Debug, Clone)]
+pub struct Writer<W: Write> {
+    /// Writer destination.
+    sink: W,
+    /// FBX version.
+    fbx_version: FbxVersion,
+    /// Nodes not yet closed.
+    open_nodes: Vec<OpenNode>,
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new `Writer` and writes FBX ASCII header comment.
+    pub fn new(mut sink: W, fbx_version: FbxVersion) -> Result<Self> {
+        // Check if the given version is supported.
+        if fbx_version.major() != 7 {
+            return Err(Error::UnsupportedFbxVersion(fbx_version));
+        }
+
+        writeln!(
+            sink,
+            "; FBX {}.{}.0 project file",
+            fbx_version.major(),
+            fbx_version.minor()
+        )?;
+        writeln!(
+            sink,
+            "; ----------------------------------------------------"
+        )?;
+
+        Ok(Self {
+            sink,
+            fbx_version,
+            open_nodes: Vec::new(),
+        })
+    }
+
+    /// Returns FBX version.
+    pub fn fbx_version(&self) -> FbxVersion {
+        self.fbx_version
+    }
+
+    /// Returns a mutable reference to the sink.
+    fn sink(&mut self) -> &mut W {
+        &mut self.sink
+    }
+
+    /// Returns the current depth (0 for implicit root node).
+    fn depth(&self) -> usize {
+        self.open_nodes.len()
+    }
+
+    /// Writes the indent for the given depth.
+    fn write_indent(&mut self, depth: usize) -> Result<()> {
+        for _ in 0..depth {
+            self.sink.write_all(b"\t")?;
+        }
+        Ok(())
+    }
+
+    /// Starts the children block of the current node, if not yet started.
+    fn start_children_block(&mut self) -> Result<()> {
+        let current_node = match self.open_nodes.last_mut() {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        if current_node.has_child {
+            return Ok(());
+        }
+        current_node.has_child = true;
+        self.sink.write_all(b" {\n")?;
+
+        Ok(())
+    }
+
+    /// Creates a new node and returns node attributes writer.
+    pub fn new_node(&mut self, name: &str) -> Result<AttributesWriter<'_, W>> {
+        trace!("New node: name={:?}, depth={:?}", name, self.depth());
+
+        // Check if the node name is representable.
+        let is_valid_name = name
+            .bytes()
+            .enumerate()
+            .all(|(i, b)| b.is_ascii_alphabetic() || b == b'_' || (i != 0 && b.is_ascii_digit()));
+        if name.is_empty() || !is_valid_name {
+            return Err(Error::InvalidNodeName(name.to_owned()));
+        }
+
+        self.start_children_block()?;
+        if self.depth() == 0 {
+            // Separate top-level nodes with an empty line.
+            self.sink.write_all(b"\n")?;
+        }
+        self.write_indent(self.depth())?;
+        write!(self.sink, "{}:", name)?;
+
+        let is_object = self.open_nodes.len() == 1 && self.open_nodes[0].is_objects;
+        self.open_nodes.push(OpenNode {
+            num_attributes: 0,
+            has_child: false,
+            is_objects: self.open_nodes.is_empty() && name == "Objects",
+            object_name_pending: is_object,
+        });
+
+        Ok(AttributesWriter::new(self))
+    }
+
+    /// Closes an open node.
+    pub fn close_node(&mut self) -> Result<()> {
+        trace!("Close node: depth={:?}", self.depth());
+
+        let current_node = match self.open_nodes.pop() {
+            Some(v) => v,
+            None => return Err(Error::NoNodesToClose),
+        };
+
+        // Write empty children block for nodes without attributes, as the
+        // binary writer writes node end marker for them.
+        if !current_node.has_child && current_node.num_attributes == 0 {
+            self.sink.write_all(b" {\n")?;
+        } else if !current_node.has_child {
+            self.sink.write_all(b"\n")?;
+            return Ok(());
+        }
+        self.write_indent(self.depth())?;
+        self.sink.write_all(b"}\n")?;
+
+        Ok(())
+    }
+
+    /// Writes the given tree.
+    #[cfg(feature = "tree")]
+    #[cfg_attr(feature = "docsrs", doc(cfg(feature = "tree")))]
+    pub fn write_tree(&mut self, tree: &crate::tree::v7400::Tree) -> Result<()> {
+        let mut current = match tree.root().first_child() {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+
+        'all: loop {
+            let mut attrs_writer = self.new_node(current.name())?;
+            for attr in current.attributes() {
+                attrs_writer.append_value(attr)?;
+            }
+
+            let mut visit_child = true;
+            current = 'next: loop {
+                if visit_child {
+                    if let Some(child) = current.first_child() {
+                        break 'next child;
+                    }
+                    // No children.
+                    visit_child = false;
+                }
+                self.close_node()?;
+                if let Some(sib) = current.next_sibling() {
+                    break 'next sib;
+                }
+                let parent = current
+                    .parent()
+                    .expect("Should never fail: `current` must not be the root note");
+                if parent.node_id() == tree.root().node_id() {
+                    break 'all;
+                }
+                current = parent;
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Finalizes the FBX ASCII and returns the inner sink.
+    ///
+    /// You may want to use [`finalize_and_flush()`][`Self::finalize_and_flush()`].
+    pub fn finalize(self) -> Result<W> {
+        if !self.open_nodes.is_empty() {
+            return Err(Error::UnclosedNode(self.open_nodes.len()));
+        }
+
+        Ok(self.sink)
+    }
+
+    /// Finalizes the FBX ASCII, and returns the inner sink after flushing.
+    pub fn finalize_and_flush(self) -> Result<W> {
+        let mut sink = self.finalize()?;
+        sink.flush()?;
+
+        Ok(sink)
+    }
+}
+
+/// Open node state.
+#[derive(Debug, Clone, Copy)]
+struct OpenNode {
+    /// Number of attributes.
+    num_attributes: u64,
+    /// Whether the node has child.
+    has_child: bool,
+    /// Whether the node is the top-level `Objects` node.
+    is_objects: bool,
+    /// Whether the node is an object and its name (i.e. the first string
+    /// attribute) is not written yet.
+    object_name_pending: bool,
+}
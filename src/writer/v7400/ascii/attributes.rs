@@ -0,0 +1,327 @@
+//! Node attributes writer.
+
+use std::io::{self, Write};
+
+use crate::writer::v7400::ascii::{Error, Result, Writer};
+
+/// A trait for types which can be written as FBX ASCII array elements or
+/// single attributes.
+pub(crate) trait WriteAscii: Copy {
+    /// Writes the value to the given sink.
+    fn write_ascii(self, sink: impl Write) -> io::Result<()>;
+}
+
+impl WriteAscii for bool {
+    fn write_ascii(self, mut sink: impl Write) -> io::Result<()> {
+        // Same representation as FBX binary.
+        sink.write_all(if self { b"Y" } else { b"T" })
+    }
+}
+
+/// Implements `WriteAscii` for integer types.
+macro_rules! impl_write_ascii_for_int {
+    ($($ty:ty),*) => {$(
+        impl WriteAscii for $ty {
+            fn write_ascii(self, mut sink: impl Write) -> io::Result<()> {
+                write!(sink, "{}", self)
+            }
+        }
+    )*};
+}
+
+impl_write_ascii_for_int!(i16, i32, i64);
+
+impl WriteAscii for f32 {
+    fn write_ascii(self, sink: impl Write) -> io::Result<()> {
+        // The shortest representation of `f32` may be rounded to another `f32`
+        // value when it is read as `f64` and then converted to `f32`.
+        // Use the representation of the exactly same `f64` value.
+        f64::from(self).write_ascii(sink)
+    }
+}
+
+impl WriteAscii for f64 {
+    fn write_ascii(self, mut sink: impl Write) -> io::Result<()> {
+        // `Debug` emits the shortest representation which can be read as the
+        // same value, and always emits a fractional part or an exponent.
+        write!(sink, "{:?}", self)
+    }
+}
+
+/// Writes the given string as a quoted and escaped string.
+///
+/// If `is_object_name` is true, `Name\u{0}\u{1}Class` is written as
+/// `Class::Name`.
+fn write_string(mut sink: impl Write, s: &str, is_object_name: bool) -> io::Result<()> {
+    let converted;
+    let s = match s.find("\u{0}\u{1}") {
+        Some(sep) if is_object_name => {
+            converted = format!("{}::{}", &s[(sep + 2)..], &s[..sep]);
+            &converted
+        }
+        _ => s,
+    };
+
+    sink.write_all(b"\"")?;
+    let mut rest = s;
+    while let Some(pos) = rest.find(&['"', '\r', '\n'][..]) {
+        sink.write_all(&rest.as_bytes()[..pos])?;
+        let escaped: &[u8] = match rest.as_bytes()[pos] {
+            b'"' => b"&quot;",
+            b'\r' => b"&cr;",
+            _ => b"&lf;",
+        };
+        sink.write_all(escaped)?;
+        rest = &rest[(pos + 1)..];
+    }
+    sink.write_all(rest.as_bytes())?;
+    sink.write_all(b"\"")
+}
+
+/// Node attributes writer.
+///
+/// See [module documentation](index.html) for usage.
+pub struct AttributesWriter<'a, W: Write> {
+    /// Inner writer.
+    writer: &'a mut Writer<W>,
+}
+
+/// Implement `append_*` methods for single value.
+macro_rules! impl_single_attr_append {
+    ($(
+        $(#[$meta:meta])*
+        $method:ident($ty:ty);
+    )*) => {
+        $(
+            $(#[$meta])*
+            pub fn $method(&mut self, v: $ty) -> Result<()> {
+                self.start_attribute()?;
+                v.write_ascii(self.writer.sink()).map_err(Into::into)
+            }
+        )*
+    }
+}
+
+/// Implement `append_*` methods for array values.
+macro_rules! impl_arr_from_iter {
+    ($(
+        $(#[$meta:meta])*
+        $name:ident: $ty_elem:ty {
+            from_result_iter: $name_from_result_iter:ident,
+        },
+    )*) => {$(
+        $(#[$meta])*
+        pub fn $name(&mut self, iter: impl IntoIterator<Item = $ty_elem>) -> Result<()> {
+            let elements = iter.into_iter().collect::<Vec<_>>();
+            self.write_array(&elements)
+        }
+
+        $(#[$meta])*
+        pub fn $name_from_result_iter<E>(
+            &mut self,
+            iter: impl IntoIterator<Item = std::result::Result<$ty_elem, E>>,
+        ) -> Result<()>
+        where
+            E: Into<Box<dyn std::error::Error + 'static>>,
+        {
+            let elements = iter
+                .into_iter()
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|e| Error::UserDefined(e.into()))?;
+            self.write_array(&elements)
+        }
+    )*}
+}
+
+impl<'a, W: Write> AttributesWriter<'a, W> {
+    /// Creates a new `AttributesWriter`.
+    pub(crate) fn new(writer: &'a mut Writer<W>) -> Self {
+        Self { writer }
+    }
+
+    /// Writes the separator before the attribute, and updates the node state.
+    fn start_attribute(&mut self) -> Result<()> {
+        let current_node = self
+            .writer
+            .open_nodes
+            .last_mut()
+            .expect("Should never fail: some nodes must be open if `AttributesWriter` exists");
+        let separator: &[u8] = if current_node.num_attributes == 0 {
+            b" "
+        } else {
+            b", "
+        };
+        current_node.num_attributes += 1;
+        self.writer.sink().write_all(separator)?;
+
+        Ok(())
+    }
+
+    impl_single_attr_append! {
+        /// Writes a single boolean attribute.
+        append_bool(bool);
+        /// Writes a single `i16` attribute.
+        append_i16(i16);
+        /// Writes a single `i32` attribute.
+        append_i32(i32);
+        /// Writes a single `i64` attribute.
+        append_i64(i64);
+        /// Writes a single `f32` attribute.
+        append_f32(f32);
+        /// Writes a single `f64` attribute.
+        append_f64(f64);
+    }
+
+    /// Writes an array attribute.
+    fn write_array<T: WriteAscii>(&mut self, elements: &[T]) -> Result<()> {
+        self.start_attribute()?;
+        let depth = self.writer.depth();
+        writeln!(self.writer.sink(), "*{} {{", elements.len())?;
+        self.writer.write_indent(depth)?;
+        self.writer.sink().write_all(b"a: ")?;
+        for (i, elem) in elements.iter().enumerate() {
+            if i != 0 {
+                self.writer.sink().write_all(b",")?;
+            }
+            elem.write_ascii(self.writer.sink())?;
+        }
+        self.writer.sink().write_all(b"\n")?;
+        self.writer.write_indent(depth - 1)?;
+        self.writer.sink().write_all(b"}")?;
+
+        Ok(())
+    }
+
+    impl_arr_from_iter! {
+        /// Writes a boolean array attribute.
+        append_arr_bool_from_iter: bool {
+            from_result_iter: append_arr_bool_from_result_iter,
+        },
+
+        /// Writes an `i32` array attribute.
+        append_arr_i32_from_iter: i32 {
+            from_result_iter: append_arr_i32_from_result_iter,
+        },
+
+        /// Writes an `i64` array attribute.
+        append_arr_i64_from_iter: i64 {
+            from_result_iter: append_arr_i64_from_result_iter,
+        },
+
+        /// Writes an `f32` array attribute.
+        append_arr_f32_from_iter: f32 {
+            from_result_iter: append_arr_f32_from_result_iter,
+        },
+
+        /// Writes an `f64` array attribute.
+        append_arr_f64_from_iter: f64 {
+            from_result_iter: append_arr_f64_from_result_iter,
+        },
+    }
+
+    /// Writes a binary attribute.
+    ///
+    /// The binary is written as a base64 string.
+    pub fn append_binary_direct(&mut self, binary: &[u8]) -> Result<()> {
+        self.start_attribute()?;
+        write_string(self.writer.sink(), &crate::base64::encode(binary), false)?;
+
+        Ok(())
+    }
+
+    /// Writes a string attribute.
+    ///
+    /// If this is the first string attribute of a node directly under the
+    /// top-level `Objects` node, the string is considered as an object name,
+    /// and `Name\u{0}\u{1}Class` is written as `Class::Name`.
+    pub fn append_string_direct(&mut self, string: &str) -> Result<()> {
+        self.start_attribute()?;
+        let current_node = self
+            .writer
+            .open_nodes
+            .last_mut()
+            .expect("Should never fail: some nodes must be open if `AttributesWriter` exists");
+        let is_object_name = current_node.object_name_pending;
+        current_node.object_name_pending = false;
+        write_string(self.writer.sink(), string, is_object_name)?;
+
+        Ok(())
+    }
+
+    /// Writes a binary attribute read from the given reader.
+    ///
+    /// The binary is written as a base64 string.
+    pub fn append_binary_from_reader(&mut self, mut reader: impl io::Read) -> Result<()> {
+        let mut binary = Vec::new();
+        reader.read_to_end(&mut binary)?;
+        self.append_binary_direct(&binary)
+    }
+
+    /// Writes a binary attribute from the given iterator.
+    ///
+    /// The binary is written as a base64 string.
+    pub fn append_binary_from_iter(&mut self, iter: impl IntoIterator<Item = u8>) -> Result<()> {
+        let binary = iter.into_iter().collect::<Vec<_>>();
+        self.append_binary_direct(&binary)
+    }
+
+    /// Writes a binary attribute from the given iterator.
+    ///
+    /// The binary is written as a base64 string.
+    pub fn append_binary_from_result_iter<E>(
+        &mut self,
+        iter: impl IntoIterator<Item = std::result::Result<u8, E>>,
+    ) -> Result<()>
+    where
+        E: Into<Box<dyn std::error::Error + 'static>>,
+    {
+        let binary = iter
+            .into_iter()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| Error::UserDefined(e.into()))?;
+        self.append_binary_direct(&binary)
+    }
+
+    /// Writes a string attribute from the given iterator.
+    pub fn append_string_from_iter(&mut self, iter: impl IntoIterator<Item = char>) -> Result<()> {
+        let string = iter.into_iter().collect::<String>();
+        self.append_string_direct(&string)
+    }
+
+    /// Writes a string attribute from the given iterator.
+    pub fn append_string_from_result_iter<E>(
+        &mut self,
+        iter: impl IntoIterator<Item = std::result::Result<char, E>>,
+    ) -> Result<()>
+    where
+        E: Into<Box<dyn std::error::Error + 'static>>,
+    {
+        let string = iter
+            .into_iter()
+            .collect::<std::result::Result<String, _>>()
+            .map_err(|e| Error::UserDefined(e.into()))?;
+        self.append_string_direct(&string)
+    }
+
+    /// Writes the given attribute value.
+    #[cfg(feature = "tree")]
+    pub(crate) fn append_value(&mut self, value: &crate::low::v7400::AttributeValue) -> Result<()> {
+        use crate::low::v7400::AttributeValue;
+
+        match value {
+            AttributeValue::Bool(v) => self.append_bool(*v),
+            AttributeValue::I16(v) => self.append_i16(*v),
+            AttributeValue::I32(v) => self.append_i32(*v),
+            AttributeValue::I64(v) => self.append_i64(*v),
+            AttributeValue::F32(v) => self.append_f32(*v),
+            AttributeValue::F64(v) => self.append_f64(*v),
+            AttributeValue::ArrBool(v) => self.write_array(v),
+            AttributeValue::ArrI32(v) => self.write_array(v),
+            AttributeValue::ArrI64(v) => self.write_array(v),
+            AttributeValue::ArrF32(v) => self.write_array(v),
+            AttributeValue::ArrF64(v) => self.write_array(v),
+            AttributeValue::Binary(v) => self.append_binary_direct(v),
+            AttributeValue::String(v) => self.append_string_direct(v),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! ASCII writer error.
+
+use std::{error, fmt, io};
+
+use crate::low::FbxVersion;
+
+/// Write result.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Write error.
+#[derive(Debug)]
+pub enum Error {
+    /// Node name cannot be represented in FBX ASCII.
+    ///
+    /// Node names should be non-empty, consist of ASCII alphanumerics and
+    /// underscores, and should not start with a digit.
+    InvalidNodeName(String),
+    /// I/O error.
+    Io(io::Error),
+    /// There are no nodes to close.
+    NoNodesToClose,
+    /// There remains unclosed nodes.
+    UnclosedNode(usize),
+    /// Unsupported FBX version.
+    UnsupportedFbxVersion(FbxVersion),
+    /// User-defined error.
+    UserDefined(Box<dyn std::error::Error + 'static>),
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::UserDefined(e) => Some(&**e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidNodeName(v) => {
+                write!(f, "Node name cannot be represented in FBX ASCII: {:?}", v)
+            }
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::NoNodesToClose => write!(f, "There are no nodes to close"),
+            Error::UnclosedNode(v) => write!(f, "There remains unclosed nodes: depth={}", v),
+            Error::UnsupportedFbxVersion(v) => write!(f, "Unsupported FBX version: {:?}", v),
+            Error::UserDefined(e) => write!(f, "User-defined error: {}", e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -26,6 +26,15 @@
 //! # Ok::<_, fbxcel::writer::v7400::binary::Error>(())
 //! ```
 //!
+//! ## Non-seekable sinks
+//!
+//! [`Writer`] requires [`std::io::Seek`] for the sink, because it updates node
+//! headers after writing the node contents.
+//! To write FBX binary into non-seekable sinks such as pipes, use
+//! [`StreamWriter`] instead.
+//! It buffers each top-level node in memory, and emits exactly the same bytes
+//! as `Writer`.
+//!
 //! ## Create node and add node attributes
 //!
 //! To create node, use [`Writer::new_node`].
@@ -49,6 +58,7 @@
 //! // Add attributes to the node.
 //! attrs_writer.append_bool(true)?;
 //! // If you don't care about compression, pass `None`.
+//! // The compression policy of the writer is used in this case.
 //! attrs_writer.append_arr_i32_from_iter(None, [1, 2, 4, 8, 16].iter().cloned())?;
 //! // If you want to use specific compression, pass `Some(_)`.
 //! attrs_writer.append_arr_f32_from_iter(
@@ -60,6 +70,57 @@
 //! # Ok::<_, fbxcel::writer::v7400::binary::Error>(())
 //! ```
 //!
+//! ## Compress array attributes
+//!
+//! To compress array attributes automatically, set [`CompressionPolicy`] by
+//! [`Writer::set_compression_policy`].
+//! The policy is used for arrays written with `None` encoding, and for arrays
+//! written by [`Writer::write_tree`].
+//! Explicitly specified encoding always takes precedence.
+//!
+//! ```
+//! use fbxcel::{
+//!     low::{v7400::ArrayAttributeEncoding, FbxVersion},
+//!     writer::v7400::binary::{
+//!         CompressionLevel, CompressionPolicy, CompressionThreshold, Writer,
+//!     },
+//! };
+//! # let mut sink = std::io::Cursor::new(Vec::new());
+//! let mut writer = Writer::new(sink, FbxVersion::V7_4)?;
+//! // Compress arrays with 64 or more elements.
+//! writer.set_compression_policy(CompressionPolicy::new(
+//!     CompressionThreshold::ElementsCount(64),
+//!     CompressionLevel::Default,
+//! ));
+//!
+//! let mut attrs_writer = writer.new_node("NodeName")?;
+//! // This will be compressed.
+//! attrs_writer.append_arr_i32_from_iter(None, 0..64)?;
+//! // This will not be compressed.
+//! attrs_writer.append_arr_i32_from_iter(None, 0..4)?;
+//! // This will not be compressed, since the encoding is explicitly specified.
+//! attrs_writer.append_arr_i32_from_iter(Some(ArrayAttributeEncoding::Direct), 0..64)?;
+//!
+//! # Ok::<_, fbxcel::writer::v7400::binary::Error>(())
+//! ```
+//!
+//! ## Copy from a parser
+//!
+//! [`copy_from_parser`] copies FBX data from a pull parser to a writer without
+//! loading the whole tree.
+//! The given callback can skip, rename, or replace nodes and attributes.
+//!
+//! [`convert_version`] copies the whole FBX data with the FBX version of the
+//! writer, for example to convert FBX 7.4 into FBX 7.5.
+//!
+//! ## Large FBX data
+//!
+//! FBX 7.4 cannot hold data larger than 4 GiB, and [`Error::FileTooLarge`] is
+//! returned in the middle of writing by default.
+//! With [`Writer::set_version_overflow`], [`Writer::write_tree`] and
+//! [`convert_version`] check the size before writing, and return the error or
+//! upgrade the FBX version to 7.5 (see [`VersionOverflow`]).
+//!
 //! ## Close current node
 //!
 //! Simply call [`Writer::close_node`].
@@ -101,15 +162,26 @@ use crate::low::{v7400::NodeHeader, FbxVersion, MAGIC};
 
 pub use self::{
     attributes::AttributesWriter,
+    compression::{CompressionLevel, CompressionPolicy, CompressionThreshold},
+    copy::{convert_version, copy_from_parser, CopyAction, CopyAttribute, CopyError, CopyEvent},
     error::{CompressionError, Error, Result},
     footer::{FbxFooter, FbxFooterPaddingLength},
+    overflow::VersionOverflow,
+    stream::{StreamSink, StreamWriter},
 };
 
 mod macros;
 
 mod attributes;
+mod compression;
+mod copy;
 mod error;
 mod footer;
+mod overflow;
+mod stream;
+
+/// FBX header length.
+const HEADER_LEN: u64 = MAGIC.len() as u64 + 4;
 
 /// Binary writer.
 ///
@@ -122,6 +194,10 @@ pub struct Writer<W: Write> {
     fbx_version: FbxVersion,
     /// Node header positions not yet closed.
     open_nodes: Vec<OpenNode>,
+    /// Compression policy for array attributes.
+    compression_policy: CompressionPolicy,
+    /// Behavior when the data does not fit in the FBX version.
+    version_overflow: VersionOverflow,
 }
 
 impl<W: Write + Seek> Writer<W> {
@@ -141,9 +217,43 @@ impl<W: Write + Seek> Writer<W> {
             sink,
             fbx_version,
             open_nodes: Vec::new(),
+            compression_policy: CompressionPolicy::default(),
+            version_overflow: VersionOverflow::default(),
         })
     }
 
+    /// Returns the FBX version.
+    ///
+    /// This can differ from the version passed to [`Writer::new`] if the
+    /// version is upgraded (see [`VersionOverflow::Upgrade`]).
+    pub fn fbx_version(&self) -> FbxVersion {
+        self.fbx_version
+    }
+
+    /// Returns the behavior when the data does not fit in the FBX version.
+    pub fn version_overflow(&self) -> VersionOverflow {
+        self.version_overflow
+    }
+
+    /// Sets the behavior when the data does not fit in the FBX version.
+    ///
+    /// See [`VersionOverflow`] for detail.
+    pub fn set_version_overflow(&mut self, version_overflow: VersionOverflow) {
+        self.version_overflow = version_overflow;
+    }
+
+    /// Returns the compression policy for array attributes.
+    pub fn compression_policy(&self) -> CompressionPolicy {
+        self.compression_policy
+    }
+
+    /// Sets the compression policy for array attributes.
+    ///
+    /// The policy is applied to the arrays written after this call.
+    pub fn set_compression_policy(&mut self, policy: CompressionPolicy) {
+        self.compression_policy = policy;
+    }
+
     /// Returns a mutable reference to the sink.
     fn sink(&mut self) -> &mut W {
         &mut self.sink
@@ -299,40 +409,80 @@ impl<W: Write + Seek> Writer<W> {
     #[cfg(feature = "tree")]
     #[cfg_attr(feature = "docsrs", doc(cfg(feature = "tree")))]
     pub fn write_tree(&mut self, tree: &crate::tree::v7400::Tree) -> Result<()> {
-        use crate::low::v7400::AttributeValue;
+        self.prepare_fbx_version(tree, u64::from(u32::MAX))?;
+        tree.root()
+            .children()
+            .try_for_each(|toplevel| self.write_subtree(toplevel))
+    }
 
-        let mut current = match tree.root().first_child() {
-            Some(v) => v,
-            None => return Ok(()),
-        };
+    /// Checks whether the tree fits in the FBX version, and upgrades the
+    /// version if necessary and possible.
+    ///
+    /// `limit` is the maximum offset which can be written in FBX 7.4.
+    #[cfg(feature = "tree")]
+    fn prepare_fbx_version(&mut self, tree: &crate::tree::v7400::Tree, limit: u64) -> Result<()> {
+        if self.version_overflow == VersionOverflow::Unchecked || self.fbx_version.raw() >= 7500 {
+            return Ok(());
+        }
+
+        let bytelen = overflow::tree_bytelen(tree, self.fbx_version, &self.compression_policy)?;
+        self.prepare_fbx_version_for_bytelen(bytelen, limit)
+    }
+
+    /// Checks whether the data of the given length fits in the FBX version,
+    /// and upgrades the version if necessary and possible.
+    ///
+    /// `limit` is the maximum offset which can be written in FBX 7.4.
+    pub(crate) fn prepare_fbx_version_for_bytelen(
+        &mut self,
+        bytelen: u64,
+        limit: u64,
+    ) -> Result<()> {
+        if self.version_overflow == VersionOverflow::Unchecked || self.fbx_version.raw() >= 7500 {
+            return Ok(());
+        }
+
+        let current_pos = self.sink.stream_position()?;
+        let end_pos = current_pos.saturating_add(bytelen);
+        if end_pos <= limit {
+            return Ok(());
+        }
+
+        let is_header_only = self.open_nodes.is_empty() && current_pos == HEADER_LEN;
+        if self.version_overflow == VersionOverflow::Upgrade && is_header_only {
+            debug!(
+                "Upgrading FBX version from {:?} to {:?}: expected size={:?}",
+                self.fbx_version,
+                FbxVersion::V7_5,
+                end_pos
+            );
+            self.fbx_version = FbxVersion::V7_5;
+            self.sink.seek(SeekFrom::Start(HEADER_LEN - 4))?;
+            self.write_fbx_verison()?;
+            self.sink.seek(SeekFrom::Start(current_pos))?;
+            return Ok(());
+        }
+
+        Err(Error::FileTooLarge(end_pos))
+    }
+
+    /// Writes the given node and its descendants.
+    #[cfg(feature = "tree")]
+    fn write_subtree(&mut self, top: crate::tree::v7400::NodeHandle<'_>) -> Result<()> {
+        let mut current = top;
 
         'all: loop {
             let mut attrs_writer = self.new_node(current.name())?;
-            for attr in current.attributes() {
-                match attr {
-                    AttributeValue::Bool(v) => attrs_writer.append_bool(*v)?,
-                    AttributeValue::I16(v) => attrs_writer.append_i16(*v)?,
-                    AttributeValue::I32(v) => attrs_writer.append_i32(*v)?,
-                    AttributeValue::I64(v) => attrs_writer.append_i64(*v)?,
-                    AttributeValue::F32(v) => attrs_writer.append_f32(*v)?,
-                    AttributeValue::F64(v) => attrs_writer.append_f64(*v)?,
-                    AttributeValue::ArrBool(v) => {
-                        attrs_writer.append_arr_bool_from_iter(None, v.iter().cloned())?
-                    }
-                    AttributeValue::ArrI32(v) => {
-                        attrs_writer.append_arr_i32_from_iter(None, v.iter().cloned())?
-                    }
-                    AttributeValue::ArrI64(v) => {
-                        attrs_writer.append_arr_i64_from_iter(None, v.iter().cloned())?
-                    }
-                    AttributeValue::ArrF32(v) => {
-                        attrs_writer.append_arr_f32_from_iter(None, v.iter().cloned())?
-                    }
-                    AttributeValue::ArrF64(v) => {
-                        attrs_writer.append_arr_f64_from_iter(None, v.iter().cloned())?
-                    }
-                    AttributeValue::Binary(v) => attrs_writer.append_binary_direct(v)?,
-                    AttributeValue::String(v) => attrs_writer.append_string_direct(v)?,
+            for (index, attr) in current.attributes().iter().enumerate() {
+                let encoding = current.array_encoding(index);
+                match (encoding, current.raw_array(index)) {
+                    (Some(encoding), Some(raw)) => attrs_writer.append_raw_array(
+                        attr.type_(),
+                        array_len(attr),
+                        encoding,
+                        raw,
+                    )?,
+                    _ => attrs_writer.append_attribute_value(attr, encoding)?,
                 }
             }
 
@@ -346,16 +496,15 @@ impl<W: Write + Seek> Writer<W> {
                     visit_child = false;
                 }
                 self.close_node()?;
+                if current.node_id() == top.node_id() {
+                    break 'all;
+                }
                 if let Some(sib) = current.next_sibling() {
                     break 'next sib;
                 }
-                let parent = current
+                current = current
                     .parent()
-                    .expect("Should never fail: `current` must not be the root note");
-                if parent.node_id() == tree.root().node_id() {
-                    break 'all;
-                }
-                current = parent;
+                    .expect("Should never fail: `current` must be a descendant of `top`");
             };
         }
 
@@ -427,3 +576,22 @@ struct OpenNode {
     /// Whether the attributes are finalized.
     is_attrs_finalized: bool,
 }
+
+/// Returns the number of elements of the array attribute.
+///
+/// # Panics
+///
+/// Panics if the attribute is not an array.
+#[cfg(feature = "tree")]
+fn array_len(attr: &crate::low::v7400::AttributeValue) -> usize {
+    use crate::low::v7400::AttributeValue;
+
+    match attr {
+        AttributeValue::ArrBool(v) => v.len(),
+        AttributeValue::ArrI32(v) => v.len(),
+        AttributeValue::ArrI64(v) => v.len(),
+        AttributeValue::ArrF32(v) => v.len(),
+        AttributeValue::ArrF64(v) => v.len(),
+        _ => panic!("The attribute is not an array: type={:?}", attr.type_()),
+    }
+}
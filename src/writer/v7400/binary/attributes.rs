@@ -6,11 +6,11 @@ use std::{
 };
 
 use crate::{
-    low::v7400::{ArrayAttributeEncoding, ArrayAttributeHeader, AttributeType},
-    writer::v7400::binary::{Error, Result, Writer},
+    low::v7400::{ArrayAttributeEncoding, ArrayAttributeHeader, AttributeType, AttributeValue},
+    writer::v7400::binary::{CompressionPolicy, Error, Result, Writer},
 };
 
-mod array;
+pub(crate) mod array;
 
 /// A dummy type for impossible error.
 pub(crate) enum Never {}
@@ -23,11 +23,16 @@ impl From<Never> for Error {
 
 /// A trait for types which can be represented as single bytes array.
 pub(crate) trait IntoBytes: Sized {
+    /// Byte length of a value.
+    const BYTELEN: usize;
+
     /// Calls the given function with the bytes array.
     fn call_with_le_bytes<R>(self, f: impl FnOnce(&[u8]) -> R) -> R;
 }
 
 impl IntoBytes for bool {
+    const BYTELEN: usize = 1;
+
     fn call_with_le_bytes<R>(self, f: impl FnOnce(&[u8]) -> R) -> R {
         let v = if self { b'Y' } else { b'T' };
         f(&v.to_le_bytes())
@@ -35,30 +40,40 @@ impl IntoBytes for bool {
 }
 
 impl IntoBytes for i16 {
+    const BYTELEN: usize = 2;
+
     fn call_with_le_bytes<R>(self, f: impl FnOnce(&[u8]) -> R) -> R {
         f(&self.to_le_bytes())
     }
 }
 
 impl IntoBytes for i32 {
+    const BYTELEN: usize = 4;
+
     fn call_with_le_bytes<R>(self, f: impl FnOnce(&[u8]) -> R) -> R {
         f(&self.to_le_bytes())
     }
 }
 
 impl IntoBytes for i64 {
+    const BYTELEN: usize = 8;
+
     fn call_with_le_bytes<R>(self, f: impl FnOnce(&[u8]) -> R) -> R {
         f(&self.to_le_bytes())
     }
 }
 
 impl IntoBytes for f32 {
+    const BYTELEN: usize = 4;
+
     fn call_with_le_bytes<R>(self, f: impl FnOnce(&[u8]) -> R) -> R {
         f(&self.to_bits().to_le_bytes())
     }
 }
 
 impl IntoBytes for f64 {
+    const BYTELEN: usize = 8;
+
     fn call_with_le_bytes<R>(self, f: impl FnOnce(&[u8]) -> R) -> R {
         f(&self.to_bits().to_le_bytes())
     }
@@ -143,6 +158,11 @@ impl<'a, W: Write + Seek> AttributesWriter<'a, W> {
         self.writer.sink()
     }
 
+    /// Returns the compression policy for array attributes.
+    pub(crate) fn compression_policy(&self) -> CompressionPolicy {
+        self.writer.compression_policy()
+    }
+
     /// Writes the given attribute type as type code.
     fn write_type_code(&mut self, ty: AttributeType) -> Result<()> {
         self.writer
@@ -245,16 +265,93 @@ impl<'a, W: Write + Seek> AttributesWriter<'a, W> {
         /// Writes an `f32` array attribute.
         append_arr_f32_from_iter: f32 {
             from_result_iter: append_arr_f32_from_result_iter,
-            tyval: ArrI32,
+            tyval: ArrF32,
         },
 
         /// Writes an `f64` array attribute.
         append_arr_f64_from_iter: f64 {
             from_result_iter: append_arr_f64_from_result_iter,
-            tyval: ArrI64,
+            tyval: ArrF64,
         },
     }
 
+    /// Writes the given attribute value.
+    ///
+    /// Arrays are written with the given encoding, or with the compression
+    /// policy of the writer if the encoding is `None`.
+    pub(crate) fn append_attribute_value(
+        &mut self,
+        value: &AttributeValue,
+        encoding: Option<ArrayAttributeEncoding>,
+    ) -> Result<()> {
+        match value {
+            AttributeValue::Bool(v) => self.append_bool(*v),
+            AttributeValue::I16(v) => self.append_i16(*v),
+            AttributeValue::I32(v) => self.append_i32(*v),
+            AttributeValue::I64(v) => self.append_i64(*v),
+            AttributeValue::F32(v) => self.append_f32(*v),
+            AttributeValue::F64(v) => self.append_f64(*v),
+            AttributeValue::ArrBool(v) => {
+                self.append_arr_bool_from_iter(encoding, v.iter().cloned())
+            }
+            AttributeValue::ArrI32(v) => self.append_arr_i32_from_iter(encoding, v.iter().cloned()),
+            AttributeValue::ArrI64(v) => self.append_arr_i64_from_iter(encoding, v.iter().cloned()),
+            AttributeValue::ArrF32(v) => self.append_arr_f32_from_iter(encoding, v.iter().cloned()),
+            AttributeValue::ArrF64(v) => self.append_arr_f64_from_iter(encoding, v.iter().cloned()),
+            AttributeValue::Binary(v) => self.append_binary_direct(v),
+            AttributeValue::String(v) => self.append_string_direct(v),
+        }
+    }
+
+    /// Writes an array attribute with the raw (possibly compressed) elements.
+    ///
+    /// The raw elements are written as is, so they should be consistent with
+    /// the given type, number of elements, and encoding.
+    #[cfg(feature = "tree")]
+    pub(crate) fn append_raw_array(
+        &mut self,
+        ty: AttributeType,
+        elements_count: usize,
+        encoding: ArrayAttributeEncoding,
+        raw: &[u8],
+    ) -> Result<()> {
+        let bytelen = u32::try_from(raw.len()).map_err(|_| Error::AttributeTooLong(raw.len()))?;
+        self.append_raw_array_from_reader(ty, elements_count, encoding, bytelen, raw)
+    }
+
+    /// Writes an array attribute with the raw (possibly compressed) elements
+    /// read from the given reader.
+    ///
+    /// Exactly `bytelen` bytes are copied from the reader without loading
+    /// them into memory at once.
+    /// The raw elements are written as is, so they should be consistent with
+    /// the given type, number of elements, and encoding.
+    pub(crate) fn append_raw_array_from_reader(
+        &mut self,
+        ty: AttributeType,
+        elements_count: usize,
+        encoding: ArrayAttributeEncoding,
+        bytelen: u32,
+        raw: impl io::Read,
+    ) -> Result<()> {
+        let elements_count = u32::try_from(elements_count)
+            .map_err(|_| Error::TooManyArrayAttributeElements(elements_count))?;
+
+        self.update_node_header()?;
+        self.write_type_code(ty)?;
+        self.write_array_header(&ArrayAttributeHeader {
+            elements_count,
+            encoding,
+            bytelen,
+        })?;
+        let copied = io::copy(&mut raw.take(u64::from(bytelen)), self.writer.sink())?;
+        if copied != u64::from(bytelen) {
+            return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+        }
+
+        Ok(())
+    }
+
     /// Writes some headers for a special attribute, and returns the special
     /// header position.
     fn initialize_special(&mut self, ty: AttributeType) -> Result<u64> {
@@ -329,7 +426,7 @@ impl<'a, W: Write + Seek> AttributesWriter<'a, W> {
             self.writer.sink().write_all(&[v])?;
             len = len
                 .checked_add(1)
-                .ok_or(Error::AttributeTooLong(std::usize::MAX))?;
+                .ok_or(Error::AttributeTooLong(usize::MAX))?;
 
             Ok(())
         })?;
@@ -355,7 +452,7 @@ impl<'a, W: Write + Seek> AttributesWriter<'a, W> {
             self.writer.sink().write_all(&[v])?;
             len = len
                 .checked_add(1)
-                .ok_or(Error::AttributeTooLong(std::usize::MAX))?;
+                .ok_or(Error::AttributeTooLong(usize::MAX))?;
 
             Ok(())
         })?;
@@ -376,7 +473,7 @@ impl<'a, W: Write + Seek> AttributesWriter<'a, W> {
             self.writer.sink().write_all(buf)?;
             len = len
                 .checked_add(char_len)
-                .ok_or(Error::AttributeTooLong(std::usize::MAX))?;
+                .ok_or(Error::AttributeTooLong(usize::MAX))?;
 
             Ok(())
         })?;
@@ -404,7 +501,7 @@ impl<'a, W: Write + Seek> AttributesWriter<'a, W> {
             self.writer.sink().write_all(buf)?;
             len = len
                 .checked_add(char_len)
-                .ok_or(Error::AttributeTooLong(std::usize::MAX))?;
+                .ok_or(Error::AttributeTooLong(usize::MAX))?;
 
             Ok(())
         })?;
@@ -8,7 +8,7 @@ use std::{
 use crate::{
     low::v7400::{ArrayAttributeEncoding, ArrayAttributeHeader, AttributeType},
     writer::v7400::binary::{
-        attributes::IntoBytes, AttributesWriter, CompressionError, Error, Result,
+        attributes::IntoBytes, AttributesWriter, CompressionError, CompressionPolicy, Error, Result,
     },
 };
 
@@ -66,29 +66,94 @@ pub(crate) fn write_array_header(
     Ok(())
 }
 
+/// Writes array elements into the given writer, and compresses them if the
+/// uncompressed byte length reaches the threshold.
+///
+/// Elements are buffered until the threshold is reached, so the encoding is
+/// decided without knowing the number of elements in advance.
+fn write_elements_with_policy<T, E>(
+    sink: &mut impl Write,
+    policy: &CompressionPolicy,
+    threshold: usize,
+    iter: impl IntoIterator<Item = std::result::Result<T, E>>,
+) -> Result<(ArrayAttributeEncoding, u32)>
+where
+    T: IntoBytes,
+    E: Into<Error>,
+{
+    let mut iter = iter.into_iter().map(|res| res.map_err(Into::into));
+    let mut buf = Vec::new();
+    let mut buffered_count = 0usize;
+    while buf.len() < threshold {
+        match iter.next() {
+            Some(elem) => {
+                elem?.call_with_le_bytes(|bytes| buf.extend_from_slice(bytes));
+                buffered_count += 1;
+            }
+            None => break,
+        }
+    }
+    let buffered_count = u32::try_from(buffered_count)
+        .map_err(|_| Error::TooManyArrayAttributeElements(buffered_count))?;
+
+    if buf.len() < threshold {
+        // All elements are buffered and the array is small enough.
+        sink.write_all(&buf)?;
+        return Ok((ArrayAttributeEncoding::Direct, buffered_count));
+    }
+
+    let mut encoder = policy.zlib_encoder(sink)?;
+    encoder.write_all(&buf)?;
+    drop(buf);
+    let rest_count = write_elements_result_iter(&mut encoder, iter)?;
+    encoder
+        .finish()
+        .into_result()
+        .map_err(CompressionError::Zlib)?;
+    let elements_count = buffered_count.checked_add(rest_count).ok_or_else(|| {
+        Error::TooManyArrayAttributeElements(buffered_count as usize + rest_count as usize)
+    })?;
+
+    Ok((ArrayAttributeEncoding::Zlib, elements_count))
+}
+
 /// Writes the given array attribute.
+///
+/// If `encoding` is `None`, the compression policy of the writer is used.
 pub(crate) fn write_array_attr_result_iter<W: Write + Seek, T: IntoBytes, E: Into<Error>>(
     writer: &mut AttributesWriter<'_, W>,
     ty: AttributeType,
     encoding: Option<ArrayAttributeEncoding>,
     iter: impl IntoIterator<Item = std::result::Result<T, E>>,
 ) -> Result<()> {
-    let encoding = encoding.unwrap_or(ArrayAttributeEncoding::Direct);
+    let policy = writer.compression_policy();
 
-    let header_pos = writer.initialize_array(ty, encoding)?;
+    // The encoding in the placeholder header is overwritten on finalization.
+    let header_pos =
+        writer.initialize_array(ty, encoding.unwrap_or(ArrayAttributeEncoding::Direct))?;
 
     // Write elements.
     let start_pos = writer.sink().stream_position()?;
-    let elements_count = match encoding {
-        ArrayAttributeEncoding::Direct => write_elements_result_iter(writer.sink(), iter)?,
-        ArrayAttributeEncoding::Zlib => {
-            let mut sink = libflate::zlib::Encoder::new(writer.sink())?;
+    let (encoding, elements_count) = match encoding {
+        Some(ArrayAttributeEncoding::Direct) => (
+            ArrayAttributeEncoding::Direct,
+            write_elements_result_iter(writer.sink(), iter)?,
+        ),
+        Some(ArrayAttributeEncoding::Zlib) => {
+            let mut sink = policy.zlib_encoder(writer.sink())?;
             let count = write_elements_result_iter(&mut sink, iter)?;
             sink.finish()
                 .into_result()
                 .map_err(CompressionError::Zlib)?;
-            count
+            (ArrayAttributeEncoding::Zlib, count)
         }
+        None => match policy.threshold_bytelen(T::BYTELEN) {
+            Some(threshold) => write_elements_with_policy(writer.sink(), &policy, threshold, iter)?,
+            None => (
+                ArrayAttributeEncoding::Direct,
+                write_elements_result_iter(writer.sink(), iter)?,
+            ),
+        },
     };
     let end_pos = writer.sink().stream_position()?;
     let bytelen = end_pos - start_pos;
@@ -0,0 +1,112 @@
+//! Array attribute compression.
+
+use std::io::{self, Write};
+
+/// Condition to compress array attributes automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum CompressionThreshold {
+    /// Never compress arrays automatically.
+    Never,
+    /// Compress arrays with the given number of elements or more.
+    ElementsCount(usize),
+    /// Compress arrays with the given uncompressed byte length or more.
+    ByteLength(usize),
+}
+
+impl Default for CompressionThreshold {
+    fn default() -> Self {
+        CompressionThreshold::Never
+    }
+}
+
+impl CompressionThreshold {
+    /// Returns the threshold in uncompressed bytes for arrays with the given
+    /// element size.
+    fn bytelen(self, elem_size: usize) -> Option<usize> {
+        match self {
+            CompressionThreshold::Never => None,
+            CompressionThreshold::ElementsCount(count) => Some(count.saturating_mul(elem_size)),
+            CompressionThreshold::ByteLength(bytelen) => Some(bytelen),
+        }
+    }
+}
+
+/// Compression level for zlib-compressed arrays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum CompressionLevel {
+    /// No compression (stored blocks in zlib stream).
+    ///
+    /// The output is larger than the uncompressed array, so this is only
+    /// useful for compatibility testing.
+    Stored,
+    /// Fast compression with fixed Huffman codes.
+    Fast,
+    /// Default compression with dynamic Huffman codes.
+    Default,
+}
+
+impl Default for CompressionLevel {
+    fn default() -> Self {
+        CompressionLevel::Default
+    }
+}
+
+/// Compression policy for array attributes.
+///
+/// The policy is used when the encoding passed to `append_arr_*` methods is
+/// `None`, and when the tree is written by [`Writer::write_tree`].
+/// The compression level is also used for arrays explicitly specified to be
+/// compressed.
+///
+/// By default, arrays are not compressed automatically.
+///
+/// [`Writer::write_tree`]: super::Writer::write_tree
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompressionPolicy {
+    /// Threshold.
+    threshold: CompressionThreshold,
+    /// Compression level.
+    level: CompressionLevel,
+}
+
+impl CompressionPolicy {
+    /// Creates a new `CompressionPolicy`.
+    pub fn new(threshold: CompressionThreshold, level: CompressionLevel) -> Self {
+        Self { threshold, level }
+    }
+
+    /// Creates a new `CompressionPolicy` which never compresses arrays
+    /// automatically.
+    pub fn never() -> Self {
+        Self::default()
+    }
+
+    /// Returns the threshold.
+    pub fn threshold(&self) -> CompressionThreshold {
+        self.threshold
+    }
+
+    /// Returns the compression level.
+    pub fn level(&self) -> CompressionLevel {
+        self.level
+    }
+
+    /// Returns the threshold in uncompressed bytes for arrays with the given
+    /// element size.
+    pub(crate) fn threshold_bytelen(&self, elem_size: usize) -> Option<usize> {
+        self.threshold.bytelen(elem_size)
+    }
+
+    /// Creates a zlib encoder with the compression level.
+    pub(crate) fn zlib_encoder<W: Write>(&self, sink: W) -> io::Result<libflate::zlib::Encoder<W>> {
+        let options = libflate::zlib::EncodeOptions::new();
+        let options = match self.level {
+            CompressionLevel::Stored => options.no_compression(),
+            CompressionLevel::Fast => options.fixed_huffman_codes(),
+            CompressionLevel::Default => options,
+        };
+        libflate::zlib::Encoder::with_options(sink, options)
+    }
+}
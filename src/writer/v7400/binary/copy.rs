@@ -0,0 +1,626 @@
+//! Streaming copy from a parser to a writer.
+
+use std::{
+    error, fmt,
+    io::{self, Seek, Write},
+};
+
+use log::warn;
+
+use crate::{
+    low::v7400::{ArrayAttributeHeader, AttributeType, AttributeValue, FbxFooter},
+    pull_parser::{
+        self,
+        v7400::{
+            attribute::loaders::{BinaryLoader, StringLoader},
+            Event, LoadAttribute, Parser,
+        },
+        ParserSource,
+    },
+    writer::v7400::binary::{AttributesWriter, Error, FbxFooter as WriterFooter, Writer},
+};
+
+/// Event passed to the callback of [`copy_from_parser`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum CopyEvent<'a> {
+    /// Start of a node.
+    StartNode {
+        /// Names of the ancestor nodes, from the top-level node.
+        ancestors: &'a [String],
+        /// Node name.
+        name: &'a str,
+    },
+    /// Node attribute.
+    Attribute {
+        /// Names of the ancestor nodes of the node, from the top-level node.
+        ancestors: &'a [String],
+        /// Name of the node which has the attribute.
+        node_name: &'a str,
+        /// Index of the attribute.
+        index: usize,
+        /// Attribute.
+        attribute: CopyAttribute<'a>,
+    },
+}
+
+/// Attribute passed to the callback of [`copy_from_parser`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CopyAttribute<'a> {
+    /// Single value, string, or binary attribute.
+    Value(&'a AttributeValue),
+    /// Array attribute.
+    ///
+    /// Arrays are not decoded, so that they can be copied to the writer as
+    /// is.
+    Array {
+        /// Attribute type.
+        type_: AttributeType,
+        /// Number of elements.
+        len: usize,
+    },
+}
+
+/// Action for an event, decided by the callback of [`copy_from_parser`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum CopyAction {
+    /// Copies the node or the attribute as is.
+    Keep,
+    /// Skips the node and its descendants, or the attribute.
+    ///
+    /// Skipped nodes and attributes are not loaded at all.
+    Skip,
+    /// Copies the node with the given name.
+    ///
+    /// This is not applicable to attributes.
+    Rename(String),
+    /// Writes the given attribute instead of the attribute.
+    ///
+    /// This is not applicable to nodes.
+    ReplaceAttribute(AttributeValue),
+    /// Writes the top-level nodes of the given tree instead of the node and
+    /// its descendants.
+    ///
+    /// This is not applicable to attributes.
+    #[cfg(feature = "tree")]
+    #[cfg_attr(feature = "docsrs", doc(cfg(feature = "tree")))]
+    ReplaceNode(crate::tree::v7400::Tree),
+}
+
+impl CopyAction {
+    /// Returns the action name.
+    fn name(&self) -> &'static str {
+        match self {
+            CopyAction::Keep => "Keep",
+            CopyAction::Skip => "Skip",
+            CopyAction::Rename(_) => "Rename",
+            CopyAction::ReplaceAttribute(_) => "ReplaceAttribute",
+            #[cfg(feature = "tree")]
+            CopyAction::ReplaceNode(_) => "ReplaceNode",
+        }
+    }
+}
+
+/// Error of [`copy_from_parser`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CopyError {
+    /// Parser error.
+    Parser(pull_parser::Error),
+    /// Writer error.
+    Writer(Error),
+    /// The callback returned an action not applicable to the event.
+    InvalidAction {
+        /// Name of the action.
+        action: &'static str,
+        /// Whether the event was for an attribute.
+        for_attribute: bool,
+    },
+}
+
+impl error::Error for CopyError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CopyError::Parser(e) => Some(e),
+            CopyError::Writer(e) => Some(e),
+            CopyError::InvalidAction { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for CopyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CopyError::Parser(e) => write!(f, "Parser error: {}", e),
+            CopyError::Writer(e) => write!(f, "Writer error: {}", e),
+            CopyError::InvalidAction {
+                action,
+                for_attribute,
+            } => write!(
+                f,
+                "Action `{}` is not applicable to {}",
+                action,
+                if *for_attribute {
+                    "attributes"
+                } else {
+                    "nodes"
+                }
+            ),
+        }
+    }
+}
+
+impl From<pull_parser::Error> for CopyError {
+    fn from(e: pull_parser::Error) -> Self {
+        CopyError::Parser(e)
+    }
+}
+
+impl From<Error> for CopyError {
+    fn from(e: Error) -> Self {
+        match e {
+            // Errors from the array iterators of the parser.
+            Error::UserDefined(e) => match e.downcast::<pull_parser::Error>() {
+                Ok(e) => CopyError::Parser(*e),
+                Err(e) => CopyError::Writer(Error::UserDefined(e)),
+            },
+            e => CopyError::Writer(e),
+        }
+    }
+}
+
+impl From<io::Error> for CopyError {
+    fn from(e: io::Error) -> Self {
+        CopyError::Writer(e.into())
+    }
+}
+
+/// Copies the FBX data from the parser to the writer.
+///
+/// The given callback is called for each node and attribute, and decides how
+/// to copy it (see [`CopyAction`]).
+/// Skipped nodes are not passed to the callback, and their descendants are
+/// neither.
+///
+/// Array attributes of FBX binary are copied with the raw (possibly
+/// compressed) elements, so that the encodings are preserved and the elements
+/// are neither decompressed nor recompressed.
+/// Arrays of FBX ASCII are streamed to the writer with the compression policy
+/// of the writer.
+/// Other attributes are loaded before passed to the callback.
+///
+/// This function reads the parser until the end of the FBX data, and returns
+/// the FBX footer read by the parser.
+/// Note that the writer is not finalized, so the caller should finalize it
+/// after this function returns.
+///
+/// # Examples
+///
+/// ```
+/// use fbxcel::{
+///     low::FbxVersion,
+///     writer::v7400::binary::{copy_from_parser, CopyAction, CopyEvent, Writer},
+/// };
+/// # let mut source = Writer::new(std::io::Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+/// # source.new_node("Creator")?;
+/// # source.close_node()?;
+/// # let source = source.finalize(&Default::default())?.into_inner();
+/// # let mut parser = match fbxcel::pull_parser::any::from_seekable_reader(
+/// #     std::io::Cursor::new(source),
+/// # )? {
+/// #     fbxcel::pull_parser::any::AnyParser::V7400(parser) => parser,
+/// #     _ => unreachable!(),
+/// # };
+///
+/// let mut writer = Writer::new(std::io::Cursor::new(Vec::new()), parser.fbx_version())?;
+/// // Drop `Creator` nodes, and rename `Thumbnail` nodes.
+/// let _footer = copy_from_parser(&mut parser, &mut writer, |event| match event {
+///     CopyEvent::StartNode { name: "Creator", .. } => CopyAction::Skip,
+///     CopyEvent::StartNode { name: "Thumbnail", .. } => {
+///         CopyAction::Rename("OldThumbnail".to_owned())
+///     }
+///     _ => CopyAction::Keep,
+/// })?;
+/// let _bin = writer.finalize_and_flush(&Default::default())?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn copy_from_parser<R, W, F>(
+    parser: &mut Parser<R>,
+    writer: &mut Writer<W>,
+    callback: F,
+) -> Result<Result<Box<FbxFooter>, pull_parser::Error>, CopyError>
+where
+    R: ParserSource,
+    W: Write + Seek,
+    F: FnMut(CopyEvent<'_>) -> CopyAction,
+{
+    copy_impl(parser, writer, callback, None)
+}
+
+/// Internal implementation of [`copy_from_parser`].
+///
+/// If `size_limit` is given, the size of each top-level node is checked by
+/// the writer before the node is written, assuming that the node is copied
+/// as is.
+/// `size_limit` is the maximum offset which can be written in FBX 7.4.
+fn copy_impl<R, W, F>(
+    parser: &mut Parser<R>,
+    writer: &mut Writer<W>,
+    mut callback: F,
+    size_limit: Option<u64>,
+) -> Result<Result<Box<FbxFooter>, pull_parser::Error>, CopyError>
+where
+    R: ParserSource,
+    W: Write + Seek,
+    F: FnMut(CopyEvent<'_>) -> CopyAction,
+{
+    let mut ancestors = Vec::new();
+    // Errors recovered before the copy are not the copy's business.
+    let diagnostics_count = parser.diagnostics().len();
+
+    loop {
+        if let Some(e) = parser.take_diagnostic_since(diagnostics_count) {
+            return Err(CopyError::Parser(e));
+        }
+        match parser.next_event()? {
+            Event::StartNode(start) => {
+                if let (Some(limit), true, Some(header)) =
+                    (size_limit, ancestors.is_empty(), start.header())
+                {
+                    // Attributes are copied as is, so the node does not get
+                    // larger unless node headers get larger (i.e. unless the
+                    // output is FBX 7.5, which needs no checks).
+                    let bytelen = header.end_offset().saturating_sub(start.start_offset());
+                    writer.prepare_fbx_version_for_bytelen(bytelen, limit)?;
+                }
+                let name = start.name().to_owned();
+                let new_name = match callback(CopyEvent::StartNode {
+                    ancestors: &ancestors,
+                    name: &name,
+                }) {
+                    CopyAction::Keep => None,
+                    CopyAction::Rename(new_name) => Some(new_name),
+                    CopyAction::Skip => {
+                        parser.skip_current_node()?;
+                        continue;
+                    }
+                    #[cfg(feature = "tree")]
+                    CopyAction::ReplaceNode(tree) => {
+                        writer.write_tree(&tree)?;
+                        parser.skip_current_node()?;
+                        continue;
+                    }
+                    action => {
+                        return Err(CopyError::InvalidAction {
+                            action: action.name(),
+                            for_attribute: false,
+                        })
+                    }
+                };
+
+                let mut attrs_writer = writer.new_node(new_name.as_deref().unwrap_or(&name))?;
+                let mut attrs = start.attributes();
+                let mut index = 0;
+                // Raw elements of arrays in FBX binary are streamed to the
+                // writer as is, without being loaded into memory.
+                while let Some(res) = attrs.load_next_or_raw_array(
+                    CopyLoader {
+                        writer: &mut attrs_writer,
+                        callback: &mut callback,
+                        ancestors: &ancestors,
+                        node_name: &name,
+                        index,
+                    },
+                    CopyLoader::copy_raw_array,
+                )? {
+                    res?;
+                    index += 1;
+                }
+                ancestors.push(name);
+            }
+            Event::EndNode => {
+                ancestors.pop();
+                writer.close_node()?;
+            }
+            Event::EndFbx(footer) => {
+                if let Some(e) = parser.take_diagnostic_since(diagnostics_count) {
+                    return Err(CopyError::Parser(e));
+                }
+                return Ok(footer);
+            }
+        }
+    }
+}
+
+/// Copies the FBX data from the parser to the writer, and finalizes the
+/// writer.
+///
+/// This rewrites the FBX data with the FBX version of the writer, for example
+/// from FBX 7.4 to FBX 7.5 or vice versa.
+/// Node header fields and the footer are written for the new version, and the
+/// unknown fields of the original footer are kept.
+///
+/// Array attributes are copied with the raw elements, so the encodings are
+/// preserved (see [`copy_from_parser`]).
+///
+/// If the parser fails to read the footer, the default footer is written and
+/// a warning is logged.
+///
+/// With [`VersionOverflow::Error`] or [`VersionOverflow::Upgrade`] set to the
+/// writer, the size of each top-level node of FBX binary is checked with the
+/// source node header before the node is written.
+/// Note that the version can be upgraded only before the first top-level node
+/// is written, so [`Error::FileTooLarge`] can still be returned after some
+/// top-level nodes are written.
+/// With [`VersionOverflow::Unchecked`] (default), the error can be returned in
+/// the middle of writing a node.
+///
+/// [`VersionOverflow::Error`]: super::VersionOverflow::Error
+/// [`VersionOverflow::Upgrade`]: super::VersionOverflow::Upgrade
+/// [`VersionOverflow::Unchecked`]: super::VersionOverflow::Unchecked
+///
+/// # Examples
+///
+/// ```
+/// use fbxcel::{
+///     low::FbxVersion,
+///     writer::v7400::binary::{convert_version, Writer},
+/// };
+/// # let mut source = Writer::new(std::io::Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+/// # source.new_node("Creator")?;
+/// # source.close_node()?;
+/// # let source = source.finalize(&Default::default())?.into_inner();
+/// # let mut parser = match fbxcel::pull_parser::any::from_seekable_reader(
+/// #     std::io::Cursor::new(source),
+/// # )? {
+/// #     fbxcel::pull_parser::any::AnyParser::V7400(parser) => parser,
+/// #     _ => unreachable!(),
+/// # };
+///
+/// let writer = Writer::new(std::io::Cursor::new(Vec::new()), FbxVersion::V7_5)?;
+/// let _bin = convert_version(&mut parser, writer)?.into_inner();
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn convert_version<R, W>(parser: &mut Parser<R>, mut writer: Writer<W>) -> Result<W, CopyError>
+where
+    R: ParserSource,
+    W: Write + Seek,
+{
+    let footer = copy_impl(
+        parser,
+        &mut writer,
+        |_| CopyAction::Keep,
+        Some(u64::from(u32::MAX)),
+    )?;
+    let footer = match &footer {
+        Ok(footer) => WriterFooter {
+            unknown1: Some(&footer.unknown1),
+            unknown2: Some(footer.unknown2),
+            unknown3: Some(&footer.unknown3),
+            ..Default::default()
+        },
+        Err(e) => {
+            warn!("Failed to read the FBX footer, using default footer: {}", e);
+            Default::default()
+        }
+    };
+
+    Ok(writer.finalize_and_flush(&footer)?)
+}
+
+/// Attribute loader which writes the attribute to the writer.
+struct CopyLoader<'a, 'w, W: Write, F> {
+    /// Attributes writer.
+    writer: &'a mut AttributesWriter<'w, W>,
+    /// Callback.
+    callback: &'a mut F,
+    /// Names of the ancestor nodes.
+    ancestors: &'a [String],
+    /// Node name.
+    node_name: &'a str,
+    /// Attribute index.
+    index: usize,
+}
+
+impl<W: Write, F> fmt::Debug for CopyLoader<'_, '_, W, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CopyLoader")
+            .field("ancestors", &self.ancestors)
+            .field("node_name", &self.node_name)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+/// Implements `load_*` methods for single values.
+macro_rules! impl_load_single {
+    ($($method:ident($ty:ty): $variant:ident;)*) => {$(
+        fn $method(self, v: $ty) -> pull_parser::Result<Self::Output> {
+            Ok(self.copy_value(AttributeValue::$variant(v)))
+        }
+    )*}
+}
+
+/// Implements `load_seq_*` methods.
+macro_rules! impl_load_seq {
+    ($($method:ident($ty:ty): $variant:ident, $append:ident;)*) => {$(
+        fn $method(
+            self,
+            iter: impl Iterator<Item = pull_parser::Result<$ty>>,
+            len: usize,
+        ) -> pull_parser::Result<Self::Output> {
+            let type_ = AttributeType::$variant;
+            Ok(match self.call(CopyAttribute::Array { type_, len }) {
+                Ok(Some(writer)) => writer.$append(None, iter).map_err(Into::into),
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            })
+        }
+    )*}
+}
+
+impl<'a, 'w, W: Write + Seek, F> CopyLoader<'a, 'w, W, F>
+where
+    F: FnMut(CopyEvent<'_>) -> CopyAction,
+{
+    /// Calls the callback, and writes the replacement attribute if necessary.
+    ///
+    /// Returns the writer if the attribute should be copied as is.
+    fn call(
+        self,
+        attribute: CopyAttribute<'_>,
+    ) -> Result<Option<&'a mut AttributesWriter<'w, W>>, CopyError> {
+        let writer = self.writer;
+        let action = (self.callback)(CopyEvent::Attribute {
+            ancestors: self.ancestors,
+            node_name: self.node_name,
+            index: self.index,
+            attribute,
+        });
+        match action {
+            CopyAction::Keep => Ok(Some(writer)),
+            CopyAction::Skip => Ok(None),
+            CopyAction::ReplaceAttribute(value) => {
+                writer.append_attribute_value(&value, None)?;
+                Ok(None)
+            }
+            action => Err(CopyError::InvalidAction {
+                action: action.name(),
+                for_attribute: true,
+            }),
+        }
+    }
+
+    /// Copies the raw (possibly compressed) elements of the array attribute.
+    ///
+    /// The elements are neither loaded into memory nor decompressed.
+    fn copy_raw_array(
+        self,
+        type_: AttributeType,
+        header: &ArrayAttributeHeader,
+        raw: &mut dyn io::Read,
+    ) -> Result<(), CopyError> {
+        let len = header.elements_count() as usize;
+        match self.call(CopyAttribute::Array { type_, len })? {
+            Some(writer) => writer
+                .append_raw_array_from_reader(type_, len, header.encoding(), header.bytelen(), raw)
+                .map_err(Into::into),
+            None => Ok(()),
+        }
+    }
+
+    /// Copies the loaded value.
+    fn copy_value(self, value: AttributeValue) -> Result<(), CopyError> {
+        match self.call(CopyAttribute::Value(&value))? {
+            Some(writer) => writer
+                .append_attribute_value(&value, None)
+                .map_err(Into::into),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write + Seek, F> LoadAttribute for CopyLoader<'_, '_, W, F>
+where
+    F: FnMut(CopyEvent<'_>) -> CopyAction,
+{
+    type Output = Result<(), CopyError>;
+
+    fn expecting(&self) -> String {
+        "any type".into()
+    }
+
+    impl_load_single! {
+        load_bool(bool): Bool;
+        load_i16(i16): I16;
+        load_i32(i32): I32;
+        load_i64(i64): I64;
+        load_f32(f32): F32;
+        load_f64(f64): F64;
+    }
+
+    impl_load_seq! {
+        load_seq_bool(bool): ArrBool, append_arr_bool_from_result_iter;
+        load_seq_i32(i32): ArrI32, append_arr_i32_from_result_iter;
+        load_seq_i64(i64): ArrI64, append_arr_i64_from_result_iter;
+        load_seq_f32(f32): ArrF32, append_arr_f32_from_result_iter;
+        load_seq_f64(f64): ArrF64, append_arr_f64_from_result_iter;
+    }
+
+    fn load_binary(self, reader: impl io::Read, len: u64) -> pull_parser::Result<Self::Output> {
+        let value = BinaryLoader.load_binary(reader, len)?;
+        Ok(self.copy_value(AttributeValue::Binary(value)))
+    }
+
+    fn load_string(self, reader: impl io::Read, len: u64) -> pull_parser::Result<Self::Output> {
+        let value = StringLoader.load_string(reader, len)?;
+        Ok(self.copy_value(AttributeValue::String(value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        low::FbxVersion,
+        pull_parser::any::{from_seekable_reader, AnyParser},
+        writer::v7400::binary::{VersionOverflow, HEADER_LEN},
+    };
+
+    /// Returns the FBX 7.5 binary with three top-level nodes of 35 bytes.
+    fn sample() -> Vec<u8> {
+        let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_5).unwrap();
+        for name in &["Node0", "Node1", "Node2"] {
+            writer.new_node(name).unwrap().append_i32(42).unwrap();
+            writer.close_node().unwrap();
+        }
+        writer
+            .finalize_and_flush(&Default::default())
+            .unwrap()
+            .into_inner()
+    }
+
+    /// Copies the FBX binary to the FBX 7.4 writer with the given limit.
+    fn copy(
+        overflow: VersionOverflow,
+        limit: u64,
+    ) -> (Result<(), CopyError>, Writer<Cursor<Vec<u8>>>) {
+        let bin = sample();
+        let mut parser = match from_seekable_reader(Cursor::new(&bin[..])).unwrap() {
+            AnyParser::V7400(parser) => parser,
+            _ => unreachable!(),
+        };
+        let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4).unwrap();
+        writer.set_version_overflow(overflow);
+        let res = copy_impl(&mut parser, &mut writer, |_| CopyAction::Keep, Some(limit));
+        (res.map(drop), writer)
+    }
+
+    #[test]
+    fn size_check() {
+        // The nodes in FBX 7.4 are 23 bytes, but are estimated to 35 bytes.
+        let limit = HEADER_LEN + 35 * 2;
+
+        let (res, mut writer) = copy(VersionOverflow::Unchecked, limit);
+        res.expect("Size should not be checked");
+        assert_eq!(writer.sink().position(), HEADER_LEN + 23 * 3);
+
+        let (res, mut writer) = copy(VersionOverflow::Error, limit);
+        match res {
+            Err(CopyError::Writer(Error::FileTooLarge(end))) => {
+                assert_eq!(end, HEADER_LEN + 23 * 2 + 35)
+            }
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        // The first two nodes are written.
+        assert_eq!(writer.sink().position(), HEADER_LEN + 23 * 2);
+        assert_eq!(writer.fbx_version(), FbxVersion::V7_4);
+
+        let (res, writer) = copy(VersionOverflow::Upgrade, 34);
+        res.expect("Version should be upgraded");
+        assert_eq!(writer.fbx_version(), FbxVersion::V7_5);
+    }
+}
@@ -72,3 +72,24 @@ impl<'a> FbxFooter<'a> {
         self.unknown3.unwrap_or(&DEFAULT)
     }
 }
+
+/// Converts the footer read by the parser into the footer for the writer.
+///
+/// The padding length is kept as is (by [`FbxFooterPaddingLength::Forced`]), so
+/// that the footer is written byte-identically.
+/// If the content of the FBX data is modified, the kept padding length can
+/// be wrong, and you may want to reset `padding_len` to
+/// [`FbxFooterPaddingLength::Default`].
+///
+/// Note that the FBX version of the footer is not kept, since it is always
+/// the version of the writer.
+impl<'a> From<&'a crate::low::v7400::FbxFooter> for FbxFooter<'a> {
+    fn from(footer: &'a crate::low::v7400::FbxFooter) -> Self {
+        Self {
+            unknown1: Some(&footer.unknown1),
+            padding_len: FbxFooterPaddingLength::Forced(footer.padding_len),
+            unknown2: Some(footer.unknown2),
+            unknown3: Some(&footer.unknown3),
+        }
+    }
+}
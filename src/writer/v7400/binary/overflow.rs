@@ -0,0 +1,343 @@
+//! Overflow of node header fields in FBX 7.4 or earlier.
+
+#[cfg(feature = "tree")]
+use std::io;
+
+#[cfg(feature = "tree")]
+use crate::{
+    low::{
+        v7400::{ArrayAttributeEncoding, AttributeValue},
+        FbxVersion,
+    },
+    tree::v7400::{NodeHandle, Tree},
+    writer::v7400::binary::{
+        attributes::{array::write_elements_result_iter, IntoBytes, Never},
+        CompressionError, CompressionPolicy, Result,
+    },
+};
+
+/// Behavior when the FBX data does not fit in FBX 7.4.
+///
+/// FBX 7.4 (and earlier) uses 32-bit node header fields, so a file larger
+/// than 4 GiB cannot be written as FBX 7.4.
+/// FBX 7.5 uses 64-bit fields and has no such limitation.
+///
+/// Note that the size can be checked in advance only when the data is written
+/// by [`Writer::write_tree`] or [`convert_version`].
+/// When nodes are written one by one, [`Error::FileTooLarge`] may be returned
+/// in the middle of writing regardless of this setting.
+///
+/// [`Error::FileTooLarge`]: super::Error::FileTooLarge
+/// [`Writer::write_tree`]: super::Writer::write_tree
+/// [`convert_version`]: super::convert_version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum VersionOverflow {
+    /// Does not check the size in advance.
+    ///
+    /// `Error::FileTooLarge` can be returned after some nodes are written.
+    Unchecked,
+    /// Checks the size before writing a tree, and returns
+    /// `Error::FileTooLarge` without writing anything if it is too large.
+    Error,
+    /// Checks the size before writing a tree, and upgrades the FBX version to
+    /// 7.5 if it is too large.
+    ///
+    /// The version can be upgraded only if no nodes are written yet.
+    /// Otherwise, `Error::FileTooLarge` is returned without writing anything.
+    Upgrade,
+}
+
+impl Default for VersionOverflow {
+    fn default() -> Self {
+        VersionOverflow::Unchecked
+    }
+}
+
+/// Returns the node header length for the FBX version.
+#[cfg(feature = "tree")]
+fn node_header_len(fbx_version: FbxVersion) -> u64 {
+    if fbx_version.raw() < 7500 {
+        // `end_offset`, `num_attributes`, `bytelen_attributes`, and
+        // `bytelen_name`.
+        4 + 4 + 4 + 1
+    } else {
+        8 + 8 + 8 + 1
+    }
+}
+
+/// Returns the byte length of the top-level nodes of the tree written with
+/// the given FBX version and the given compression policy.
+///
+/// This returns exactly the same length as written by `Writer::write_tree`.
+/// Arrays to be compressed are actually compressed to measure the length.
+#[cfg(feature = "tree")]
+pub(crate) fn tree_bytelen(
+    tree: &Tree,
+    fbx_version: FbxVersion,
+    policy: &CompressionPolicy,
+) -> Result<u64> {
+    let header_len = node_header_len(fbx_version);
+    tree.root().children().try_fold(0u64, |sum, node| {
+        Ok(sum.saturating_add(node_bytelen(node, header_len, policy)?))
+    })
+}
+
+/// Returns the byte length of the node and its descendants.
+#[cfg(feature = "tree")]
+fn node_bytelen(node: NodeHandle<'_>, header_len: u64, policy: &CompressionPolicy) -> Result<u64> {
+    let mut len = header_len + node.name().len() as u64;
+    for (index, attr) in node.attributes().iter().enumerate() {
+        let attr_len = match node.raw_array(index) {
+            // Type code, array header, and raw elements.
+            Some(raw) if node.array_encoding(index).is_some() => 1 + 4 * 3 + raw.len() as u64,
+            _ => attribute_bytelen(attr, node.array_encoding(index), policy)?,
+        };
+        len = len.saturating_add(attr_len);
+    }
+    let mut has_child = false;
+    for child in node.children() {
+        has_child = true;
+        len = len.saturating_add(node_bytelen(child, header_len, policy)?);
+    }
+    if has_child || node.attributes().is_empty() {
+        // Node end marker.
+        len = len.saturating_add(header_len);
+    }
+
+    Ok(len)
+}
+
+/// Returns the byte length of the attribute, including the type code.
+///
+/// Arrays are assumed to be written with the given encoding, or with the
+/// policy if the encoding is `None`.
+#[cfg(feature = "tree")]
+fn attribute_bytelen(
+    attr: &AttributeValue,
+    encoding: Option<ArrayAttributeEncoding>,
+    policy: &CompressionPolicy,
+) -> Result<u64> {
+    /// Array attribute header length.
+    const ARRAY_HEADER_LEN: u64 = 4 * 3;
+    /// Special attribute header length.
+    const SPECIAL_HEADER_LEN: u64 = 4;
+
+    let value_len = match attr {
+        AttributeValue::Bool(_) => 1,
+        AttributeValue::I16(_) => 2,
+        AttributeValue::I32(_) | AttributeValue::F32(_) => 4,
+        AttributeValue::I64(_) | AttributeValue::F64(_) => 8,
+        AttributeValue::ArrBool(v) => ARRAY_HEADER_LEN + array_bytelen(v, encoding, policy)?,
+        AttributeValue::ArrI32(v) => ARRAY_HEADER_LEN + array_bytelen(v, encoding, policy)?,
+        AttributeValue::ArrI64(v) => ARRAY_HEADER_LEN + array_bytelen(v, encoding, policy)?,
+        AttributeValue::ArrF32(v) => ARRAY_HEADER_LEN + array_bytelen(v, encoding, policy)?,
+        AttributeValue::ArrF64(v) => ARRAY_HEADER_LEN + array_bytelen(v, encoding, policy)?,
+        AttributeValue::Binary(v) => SPECIAL_HEADER_LEN + v.len() as u64,
+        AttributeValue::String(v) => SPECIAL_HEADER_LEN + v.len() as u64,
+    };
+
+    // Type code is 1 byte.
+    Ok(1 + value_len)
+}
+
+/// Returns the byte length of the array elements written with the encoding or
+/// the policy.
+#[cfg(feature = "tree")]
+fn array_bytelen<T: IntoBytes + Copy>(
+    values: &[T],
+    encoding: Option<ArrayAttributeEncoding>,
+    policy: &CompressionPolicy,
+) -> Result<u64> {
+    let raw_len = (values.len() * T::BYTELEN) as u64;
+    let compress = match encoding {
+        Some(encoding) => encoding == ArrayAttributeEncoding::Zlib,
+        None => policy
+            .threshold_bytelen(T::BYTELEN)
+            .map_or(false, |threshold| raw_len >= threshold as u64),
+    };
+    if !compress {
+        return Ok(raw_len);
+    }
+
+    let mut counter = ByteCounter(0);
+    let mut encoder = policy.zlib_encoder(&mut counter)?;
+    write_elements_result_iter(&mut encoder, values.iter().map(|&v| Ok::<_, Never>(v)))?;
+    encoder
+        .finish()
+        .into_result()
+        .map_err(CompressionError::Zlib)?;
+    Ok(counter.0)
+}
+
+/// Sink which only counts the written bytes.
+#[cfg(feature = "tree")]
+#[derive(Debug)]
+struct ByteCounter(u64);
+
+#[cfg(feature = "tree")]
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "tree"))]
+mod tests {
+    use std::io::{Cursor, Seek};
+
+    use super::*;
+    use crate::writer::v7400::binary::{
+        CompressionLevel, CompressionThreshold, Error, Writer, HEADER_LEN,
+    };
+
+    /// Returns the tree for tests.
+    fn sample_tree() -> Tree {
+        let mut tree = Tree::default();
+        let root = tree.root().node_id();
+        let node0 = tree.append_new(root, "Node0");
+        tree.append_new(node0, "Node0_0");
+        let node0_1 = tree.append_new(node0, "Node0_1");
+        tree.append_attribute(node0_1, true);
+        tree.append_attribute(node0_1, 42i16);
+        let node1 = tree.append_new(root, "Node1");
+        tree.append_attribute(node1, (0..100).collect::<Vec<i32>>());
+        tree.append_attribute(node1, vec![0.5f64; 30]);
+        let node1_0 = tree.append_new(node1, "Node1_0");
+        tree.append_attribute(node1_0, &[1u8, 2, 4, 8][..]);
+        tree.append_attribute(node1_0, "Hello, world");
+        let node1_1 = tree.append_new(node1, "Node1_1");
+        tree.append_attribute(node1_1, vec![true, false, true]);
+        tree.append_attribute(node1_1, vec![1i64, 2, 3]);
+        tree.append_attribute(node1_1, vec![1.5f32; 4]);
+
+        tree
+    }
+
+    #[test]
+    fn tree_bytelen_is_exact() -> Result<()> {
+        let tree = sample_tree();
+        let policies = [
+            CompressionPolicy::never(),
+            CompressionPolicy::new(
+                CompressionThreshold::ElementsCount(3),
+                CompressionLevel::Default,
+            ),
+            CompressionPolicy::new(CompressionThreshold::ByteLength(0), CompressionLevel::Fast),
+        ];
+        for &version in &[FbxVersion::V7_4, FbxVersion::V7_5] {
+            for policy in &policies {
+                let mut writer = Writer::new(Cursor::new(Vec::new()), version)?;
+                writer.set_compression_policy(*policy);
+                let start = writer.sink().stream_position()?;
+                writer.write_tree(&tree)?;
+                let written = writer.sink().stream_position()? - start;
+
+                assert_eq!(
+                    tree_bytelen(&tree, version, policy)?,
+                    written,
+                    "version={:?}, policy={:?}",
+                    version,
+                    policy
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn tree_bytelen_with_preserved_arrays() -> Result<()> {
+        use crate::{
+            low::v7400::ArrayAttributeEncoding,
+            pull_parser::any::{from_seekable_reader, AnyParser},
+            tree::v7400::{ArrayPreservation, Loader},
+        };
+
+        let mut tree = sample_tree();
+        let node1 = tree.root().first_child_by_name("Node1").unwrap().node_id();
+        tree.set_array_encoding(node1, 0, Some(ArrayAttributeEncoding::Zlib));
+        tree.set_array_encoding(node1, 1, Some(ArrayAttributeEncoding::Direct));
+
+        // Write with the encodings, and load with the raw elements.
+        let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+        writer.write_tree(&tree)?;
+        let bin = writer.finalize_and_flush(&Default::default())?.into_inner();
+        let mut parser = match from_seekable_reader(Cursor::new(bin)).unwrap() {
+            AnyParser::V7400(parser) => parser,
+            _ => unreachable!(),
+        };
+        let (loaded, _) = Loader::new()
+            .with_array_preservation(ArrayPreservation::RawBytes)
+            .load(&mut parser)
+            .unwrap();
+
+        let policy = CompressionPolicy::new(
+            CompressionThreshold::ByteLength(0),
+            CompressionLevel::Stored,
+        );
+        for tree in &[tree, loaded] {
+            let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+            writer.set_compression_policy(policy);
+            writer.write_tree(tree)?;
+            let written = writer.sink().stream_position()? - HEADER_LEN;
+            assert_eq!(tree_bytelen(tree, FbxVersion::V7_4, &policy)?, written);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn overflow_error() -> Result<()> {
+        let tree = sample_tree();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+        writer.set_version_overflow(VersionOverflow::Error);
+        assert!(matches!(
+            writer.prepare_fbx_version(&tree, 100),
+            Err(Error::FileTooLarge(_))
+        ));
+        assert_eq!(writer.fbx_version(), FbxVersion::V7_4);
+        assert_eq!(writer.sink().stream_position()?, HEADER_LEN);
+
+        Ok(())
+    }
+
+    #[test]
+    fn overflow_upgrade() -> Result<()> {
+        let tree = sample_tree();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+        writer.set_version_overflow(VersionOverflow::Upgrade);
+        writer.prepare_fbx_version(&tree, 100)?;
+        assert_eq!(writer.fbx_version(), FbxVersion::V7_5);
+        assert_eq!(writer.sink().stream_position()?, HEADER_LEN);
+        assert_eq!(&writer.sink().get_ref()[23..27], &7500u32.to_le_bytes());
+
+        // The version cannot be upgraded after some nodes are written.
+        let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+        writer.set_version_overflow(VersionOverflow::Upgrade);
+        writer.new_node("Node")?;
+        writer.close_node()?;
+        assert!(matches!(
+            writer.prepare_fbx_version(&tree, 100),
+            Err(Error::FileTooLarge(_))
+        ));
+        assert_eq!(writer.fbx_version(), FbxVersion::V7_4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unchecked_never_fails() -> Result<()> {
+        let tree = sample_tree();
+        let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+        writer.prepare_fbx_version(&tree, 0)?;
+        assert_eq!(writer.fbx_version(), FbxVersion::V7_4);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+//! Binary writer for non-seekable sinks.
+
+use std::{
+    convert::TryFrom,
+    io::{self, Cursor, Seek, SeekFrom, Write},
+};
+
+use crate::{
+    low::FbxVersion,
+    writer::v7400::binary::{
+        AttributesWriter, CompressionPolicy, FbxFooter, Result, VersionOverflow, Writer,
+    },
+};
+
+/// Sink adapter which buffers the data not yet committed.
+///
+/// The bytes are written to the inner sink only when they are committed, and
+/// seeking is allowed only in the uncommitted (buffered) region.
+/// This is used as the sink of the writer inside [`StreamWriter`].
+#[derive(Debug, Clone)]
+pub struct StreamSink<W> {
+    /// Inner sink.
+    inner: W,
+    /// Length of the bytes already written to the inner sink.
+    committed: u64,
+    /// Uncommitted bytes.
+    buf: Cursor<Vec<u8>>,
+}
+
+impl<W: Write> StreamSink<W> {
+    /// Creates a new `StreamSink`.
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            committed: 0,
+            buf: Cursor::new(Vec::new()),
+        }
+    }
+
+    /// Writes the buffered bytes to the inner sink.
+    fn commit(&mut self) -> io::Result<()> {
+        let buf = self.buf.get_mut();
+        self.inner.write_all(buf)?;
+        self.committed += buf.len() as u64;
+        buf.clear();
+        self.buf.set_position(0);
+
+        Ok(())
+    }
+
+    /// Returns the inner sink.
+    fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for StreamSink<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // Uncommitted bytes can be modified later, so they are not written.
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Seek for StreamSink<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let buf_len = self.buf.get_ref().len() as u64;
+        let target = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::Current(offset) => {
+                checked_add_signed(self.committed + self.buf.position(), offset)
+            }
+            SeekFrom::End(offset) => checked_add_signed(self.committed + buf_len, offset),
+        };
+        let target = match target {
+            Some(v) if v >= self.committed => v,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "attempt to seek to the position already written to the non-seekable sink",
+                ))
+            }
+        };
+        self.buf.set_position(target - self.committed);
+
+        Ok(target)
+    }
+}
+
+/// Adds the signed offset to the position.
+fn checked_add_signed(pos: u64, offset: i64) -> Option<u64> {
+    if offset >= 0 {
+        pos.checked_add(offset as u64)
+    } else {
+        pos.checked_sub(u64::try_from(offset.checked_neg()?).ok()?)
+    }
+}
+
+/// Binary writer for non-seekable sinks.
+///
+/// This has the same interface as [`Writer`], but requires only
+/// [`std::io::Write`] for the sink.
+/// Each top-level node is buffered in memory until it is closed, and then
+/// written to the sink.
+/// The FBX header is written to the sink together with the first top-level
+/// node.
+/// The output is identical to the output of [`Writer`].
+///
+/// ```
+/// use fbxcel::{low::FbxVersion, writer::v7400::binary::StreamWriter};
+///
+/// // `Vec<u8>` does not implement `std::io::Seek`.
+/// let mut writer = StreamWriter::new(Vec::new(), FbxVersion::V7_4)?;
+///
+/// let mut attrs_writer = writer.new_node("NodeName")?;
+/// attrs_writer.append_string_direct("Hello, world")?;
+/// writer.close_node()?;
+///
+/// let bin: Vec<u8> = writer.finalize_and_flush(&Default::default())?;
+/// # Ok::<_, fbxcel::writer::v7400::binary::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct StreamWriter<W: Write> {
+    /// Inner writer.
+    writer: Writer<StreamSink<W>>,
+}
+
+impl<W: Write> StreamWriter<W> {
+    /// Creates a new `StreamWriter` and writes FBX file header.
+    pub fn new(sink: W, fbx_version: FbxVersion) -> Result<Self> {
+        // The header is committed together with the first top-level node, so
+        // that the version can be upgraded (see `VersionOverflow::Upgrade`).
+        let writer = Writer::new(StreamSink::new(sink), fbx_version)?;
+
+        Ok(Self { writer })
+    }
+
+    /// Returns the FBX version.
+    ///
+    /// See [`Writer::fbx_version`].
+    pub fn fbx_version(&self) -> FbxVersion {
+        self.writer.fbx_version()
+    }
+
+    /// Returns the behavior when the data does not fit in the FBX version.
+    pub fn version_overflow(&self) -> VersionOverflow {
+        self.writer.version_overflow()
+    }
+
+    /// Sets the behavior when the data does not fit in the FBX version.
+    ///
+    /// See [`VersionOverflow`] for detail.
+    pub fn set_version_overflow(&mut self, version_overflow: VersionOverflow) {
+        self.writer.set_version_overflow(version_overflow)
+    }
+
+    /// Returns the compression policy for array attributes.
+    pub fn compression_policy(&self) -> CompressionPolicy {
+        self.writer.compression_policy()
+    }
+
+    /// Sets the compression policy for array attributes.
+    ///
+    /// See [`Writer::set_compression_policy`].
+    pub fn set_compression_policy(&mut self, policy: CompressionPolicy) {
+        self.writer.set_compression_policy(policy)
+    }
+
+    /// Creates a new node and returns node attributes writer.
+    pub fn new_node(&mut self, name: &str) -> Result<AttributesWriter<'_, StreamSink<W>>> {
+        self.writer.new_node(name)
+    }
+
+    /// Closes an open node.
+    ///
+    /// If the node is a top-level node, the buffered node is written to the
+    /// sink.
+    pub fn close_node(&mut self) -> Result<()> {
+        self.writer.close_node()?;
+        if self.writer.open_nodes.is_empty() {
+            self.writer.sink().commit()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the given tree.
+    ///
+    /// Top-level nodes are written to the sink one by one.
+    #[cfg(feature = "tree")]
+    #[cfg_attr(feature = "docsrs", doc(cfg(feature = "tree")))]
+    pub fn write_tree(&mut self, tree: &crate::tree::v7400::Tree) -> Result<()> {
+        self.writer.prepare_fbx_version(tree, u64::from(u32::MAX))?;
+        tree.root().children().try_for_each(|toplevel| {
+            self.writer.write_subtree(toplevel)?;
+            if self.writer.open_nodes.is_empty() {
+                self.writer.sink().commit()?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Finalizes the FBX binary and returns the inner sink.
+    ///
+    /// You may want to use [`finalize_and_flush()`][`Self::finalize_and_flush()`].
+    pub fn finalize(self, footer: &FbxFooter<'_>) -> Result<W> {
+        let mut sink = self.writer.finalize(footer)?;
+        sink.commit()?;
+
+        Ok(sink.into_inner())
+    }
+
+    /// Finalizes the FBX binary, and returns the inner sink after flushing.
+    pub fn finalize_and_flush(self, footer: &FbxFooter<'_>) -> Result<W> {
+        let mut sink = self.writer.finalize(footer)?;
+        sink.commit()?;
+        sink.flush()?;
+
+        Ok(sink.into_inner())
+    }
+}
@@ -0,0 +1,248 @@
+//! Tests for raw node headers and attribute layouts.
+#![cfg(feature = "writer")]
+
+use std::io::Cursor;
+
+use fbxcel::{
+    low::{
+        v7400::{ArrayAttributeEncoding, AttributeType},
+        FbxVersion,
+    },
+    pull_parser::{
+        error::{ErrorKind, OperationError},
+        v7400::{
+            ascii, attribute::loaders::DirectLoader, index::NodeIndex, Event, Parser, StartNode,
+        },
+        ParserSource,
+    },
+    writer::v7400::binary::Writer,
+};
+
+use self::common::parser;
+
+mod common;
+
+/// Writes the sample FBX 7.4 binary.
+fn sample() -> Vec<u8> {
+    let mut writer =
+        Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4).expect("Failed to create writer");
+    (|| {
+        {
+            let mut attrs = writer.new_node("Container")?;
+            attrs.append_bool(true)?;
+            attrs.append_i16(16)?;
+            attrs.append_f64(64.0)?;
+            attrs.append_string_direct("Hello")?;
+            attrs.append_arr_i32_from_iter(Some(ArrayAttributeEncoding::Direct), 0..4)?;
+            attrs.append_arr_f64_from_iter(
+                Some(ArrayAttributeEncoding::Zlib),
+                (0..64).map(|_| 0.0),
+            )?;
+        }
+        {
+            let mut attrs = writer.new_node("Child")?;
+            attrs.append_i32(42)?;
+            attrs.append_binary_direct(&[1, 2, 3])?;
+        }
+        writer.close_node()?;
+        writer.close_node()?;
+        Ok::<_, fbxcel::writer::v7400::binary::Error>(())
+    })()
+    .expect("Failed to write nodes");
+    writer
+        .finalize_and_flush(&Default::default())
+        .expect("Failed to finalize")
+        .into_inner()
+}
+
+/// Returns the next node start event.
+fn next_start<'a, R: ParserSource>(parser: &'a mut Parser<R>) -> StartNode<'a, R> {
+    match parser.next_event().expect("Failed to read event") {
+        Event::StartNode(start) => start,
+        _ => panic!("Expected node start event"),
+    }
+}
+
+#[test]
+fn node_headers() {
+    let bin = sample();
+    let index = NodeIndex::build(Cursor::new(&bin)).expect("Failed to build index");
+    let mut parser = parser(&bin);
+
+    for entry in index.entries() {
+        let start = next_start(&mut parser);
+        assert_eq!(start.name(), entry.name());
+        assert_eq!(start.start_offset(), entry.start_offset());
+        let header = start.header().expect("FBX binary should have node headers");
+        assert_eq!(header.end_offset(), entry.end_offset());
+        assert_eq!(header.num_attributes(), entry.num_attributes());
+        assert_eq!(header.bytelen_attributes(), entry.bytelen_attributes());
+        assert_eq!(usize::from(header.bytelen_name()), entry.name().len());
+    }
+}
+
+#[test]
+fn attribute_layouts() {
+    let bin = sample();
+    let index = NodeIndex::build(Cursor::new(&bin)).expect("Failed to build index");
+    let container = index.get(0).expect("The node should exist");
+    let mut parser = parser(&bin);
+
+    let mut attrs = next_start(&mut parser).attributes();
+    let mut layouts = Vec::new();
+    while let Some(layout) = attrs.next_layout().expect("Failed to read layout") {
+        layouts.push(layout);
+    }
+    assert_eq!(layouts.len() as u64, container.num_attributes());
+
+    // Attributes are contiguous.
+    let range = container.attributes_range();
+    assert_eq!(layouts[0].start_offset(), range.start);
+    assert_eq!(layouts[layouts.len() - 1].end_offset(), range.end);
+    for pair in layouts.windows(2) {
+        assert_eq!(pair[0].end_offset(), pair[1].start_offset());
+    }
+
+    let types = layouts.iter().map(|v| v.type_()).collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec![
+            AttributeType::Bool,
+            AttributeType::I16,
+            AttributeType::F64,
+            AttributeType::String,
+            AttributeType::ArrI32,
+            AttributeType::ArrF64
+        ]
+    );
+    let codes = layouts.iter().map(|v| v.type_code()).collect::<Vec<_>>();
+    assert_eq!(codes, b"CYDSid");
+    for layout in &layouts {
+        assert_eq!(bin[layout.start_offset() as usize], layout.type_code());
+    }
+
+    // Scalar attributes.
+    assert_eq!(layouts[0].end_offset() - layouts[0].start_offset(), 1 + 1);
+    assert_eq!(layouts[1].end_offset() - layouts[1].start_offset(), 1 + 2);
+    assert_eq!(layouts[2].end_offset() - layouts[2].start_offset(), 1 + 8);
+    assert_eq!(
+        layouts[3].end_offset() - layouts[3].start_offset(),
+        1 + 4 + 5
+    );
+    assert!(layouts[..4].iter().all(|v| v.array_header().is_none()));
+
+    // Array attributes.
+    let direct = layouts[4].array_header().expect("Should be an array");
+    assert_eq!(direct.elements_count(), 4);
+    assert_eq!(direct.encoding(), ArrayAttributeEncoding::Direct);
+    assert_eq!(direct.bytelen(), 4 * 4);
+    assert_eq!(
+        layouts[4].end_offset() - layouts[4].start_offset(),
+        1 + 12 + u64::from(direct.bytelen())
+    );
+    let zlib = layouts[5].array_header().expect("Should be an array");
+    assert_eq!(zlib.elements_count(), 64);
+    assert_eq!(zlib.encoding(), ArrayAttributeEncoding::Zlib);
+    assert!(zlib.bytelen() < 64 * 8);
+    assert_eq!(
+        layouts[5].end_offset() - layouts[5].start_offset(),
+        1 + 12 + u64::from(zlib.bytelen())
+    );
+
+    // The parser continues after the layouts are read.
+    let start = next_start(&mut parser);
+    assert_eq!(start.name(), "Child");
+    let mut attrs = start.attributes();
+    assert_eq!(attrs.load_next(DirectLoader).unwrap(), Some(42i32.into()));
+    let layout = attrs
+        .next_layout()
+        .expect("Failed to read layout")
+        .expect("The attribute should exist");
+    assert_eq!(layout.type_(), AttributeType::Binary);
+    assert_eq!(layout.end_offset() - layout.start_offset(), 1 + 4 + 3);
+    assert_eq!(attrs.next_layout().expect("Failed to read layout"), None);
+    assert!(matches!(parser.next_event(), Ok(Event::EndNode)));
+    assert!(matches!(parser.next_event(), Ok(Event::EndNode)));
+    match parser.next_event().expect("Failed to read event") {
+        Event::EndFbx(footer) => {
+            footer.expect("Failed to read footer");
+        }
+        _ => panic!("Expected FBX end event"),
+    }
+}
+
+#[test]
+fn skip_value_and_load_next() {
+    let bin = sample();
+    let mut parser = parser(&bin);
+
+    let mut attrs = next_start(&mut parser).attributes();
+    for _ in 0..4 {
+        attrs.next_layout().expect("Failed to read layout");
+    }
+    assert_eq!(
+        attrs.load_next(DirectLoader).unwrap(),
+        Some(vec![0i32, 1, 2, 3].into())
+    );
+    assert_eq!(attrs.rest_count(), 1);
+}
+
+#[test]
+fn load_with_layout() {
+    let bin = sample();
+    let mut layout_parser = parser(&bin);
+    let mut load_parser = parser(&bin);
+
+    for _ in 0..2 {
+        let mut layout_attrs = next_start(&mut layout_parser).attributes();
+        let mut load_attrs = next_start(&mut load_parser).attributes();
+        while let Some(expected) = layout_attrs.next_layout().expect("Failed to read layout") {
+            let (value, layout) = load_attrs
+                .load_next_with_layout(DirectLoader)
+                .expect("Failed to load attribute")
+                .expect("The attribute should exist");
+            assert_eq!(layout, expected);
+            assert_eq!(value.type_(), layout.type_());
+        }
+        assert_eq!(
+            load_attrs
+                .load_next_with_layout(DirectLoader)
+                .expect("Failed to load attribute"),
+            None
+        );
+    }
+}
+
+#[test]
+fn ascii() {
+    const DOCUMENT: &str = r#"; FBX 7.4.0 project file
+Container: 1, "Hello" {
+}
+"#;
+    let mut parser = ascii::from_reader(DOCUMENT.as_bytes()).expect("Failed to create parser");
+    let start = next_start(&mut parser);
+    assert_eq!(
+        start.start_offset(),
+        DOCUMENT.find("Container").unwrap() as u64
+    );
+    assert_eq!(start.header(), None);
+
+    let mut attrs = start.attributes();
+    let err = attrs
+        .next_layout()
+        .expect_err("FBX ASCII should have no attribute layouts");
+    assert_eq!(err.kind(), ErrorKind::Operation);
+    assert!(matches!(
+        err.downcast_ref::<OperationError>(),
+        Some(OperationError::AttributeLayoutUnavailable)
+    ));
+    let err = attrs
+        .load_next_with_layout(DirectLoader)
+        .expect_err("FBX ASCII should have no attribute layouts");
+    assert!(matches!(
+        err.downcast_ref::<OperationError>(),
+        Some(OperationError::AttributeLayoutUnavailable)
+    ));
+    // The parser is still available.
+    assert_eq!(attrs.load_next(DirectLoader).unwrap(), Some(1i32.into()));
+}
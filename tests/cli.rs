@@ -0,0 +1,345 @@
+//! Tests for `fbxcel` command-line tool.
+#![cfg(feature = "cli")]
+
+use std::{
+    env, fs,
+    io::Cursor,
+    path::{Path, PathBuf},
+    process::{self, Command, Output},
+};
+
+use fbxcel::{
+    low::{v7400::ArrayAttributeEncoding, FbxVersion},
+    pull_parser::any::{from_seekable_reader, AnyParser},
+    tree::{
+        any::AnyTree,
+        v7400::{ArrayPreservation, Loader, Tree},
+    },
+    tree_v7400,
+    writer::v7400::binary::Writer,
+};
+
+/// Returns a tree for tests.
+fn sample_tree() -> Tree {
+    tree_v7400! {
+        Header: {
+            Version: [7400i32] {},
+        },
+        Objects: {
+            Model: [1i64, "Cube\u{0}\u{1}Model", "Mesh"] {
+                Indices: [vec![0i32, 1, 2, 3, 4, 5, 6, 7, 8, 9]] {},
+            },
+            Video: [2i64, "Texture"] {
+                Content: [&[0xde_u8, 0xad, 0xbe, 0xef][..]] {},
+            },
+        },
+    }
+}
+
+/// Creates a clean working directory for the test.
+fn work_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir()
+        .join(format!("fbxcel-cli-{}", process::id()))
+        .join(name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).expect("Failed to clean the working directory");
+    }
+    fs::create_dir_all(&dir).expect("Failed to create the working directory");
+    dir
+}
+
+/// Writes the sample tree as an FBX binary.
+fn write_sample(path: &Path) {
+    write_tree(path, &sample_tree());
+}
+
+/// Writes the tree as an FBX binary.
+fn write_tree(path: &Path, tree: &Tree) {
+    let mut writer =
+        Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4).expect("Failed to create a writer");
+    writer.write_tree(tree).expect("Failed to write the tree");
+    let bin = writer
+        .finalize_and_flush(&Default::default())
+        .expect("Failed to finalize")
+        .into_inner();
+    fs::write(path, bin).expect("Failed to write the file");
+}
+
+/// Runs `fbxcel` with the arguments.
+fn fbxcel(args: &[&dyn AsRef<std::ffi::OsStr>]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fbxcel"))
+        .args(args)
+        .env_remove("RUST_LOG")
+        .output()
+        .expect("Failed to run fbxcel")
+}
+
+/// Returns the standard output as a string.
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).expect("Output should be UTF-8")
+}
+
+/// Loads the FBX binary file into a tree.
+fn load_binary(path: &Path) -> (FbxVersion, Tree) {
+    let file = fs::File::open(path).expect("Failed to open the file");
+    match AnyTree::from_seekable_reader(std::io::BufReader::new(file)).expect("Failed to load") {
+        AnyTree::V7400(version, tree, footer) => {
+            assert!(footer.is_ok());
+            (version, tree)
+        }
+        _ => panic!("Should be FBX 7.x"),
+    }
+}
+
+/// Returns the encoding of the `Indices` array in the FBX binary file.
+fn indices_encoding(path: &Path) -> Option<ArrayAttributeEncoding> {
+    let file = fs::File::open(path).expect("Failed to open the file");
+    let mut parser = match from_seekable_reader(std::io::BufReader::new(file)) {
+        Ok(AnyParser::V7400(parser)) => parser,
+        _ => panic!("Should be FBX 7.x"),
+    };
+    let (tree, _) = Loader::new()
+        .with_array_preservation(ArrayPreservation::Encoding)
+        .load(&mut parser)
+        .expect("Failed to load");
+    tree.root()
+        .first_child_by_name("Objects")
+        .and_then(|node| node.first_child_by_name("Model"))
+        .and_then(|node| node.first_child_by_name("Indices"))
+        .expect("The node should exist")
+        .array_encoding(0)
+}
+
+#[test]
+fn info() {
+    let dir = work_dir("info");
+    let input = dir.join("input.fbx");
+    write_sample(&input);
+
+    let output = fbxcel(&[&"info", &input]);
+    assert!(output.status.success(), "{:?}", output);
+    let out = stdout(&output);
+    assert!(out.contains("Format: binary\n"), "{}", out);
+    assert!(out.contains("FBX version: 7.4\n"), "{}", out);
+    assert!(out.contains("Nodes: 7 (max depth: 3)\n"), "{}", out);
+    assert!(
+        out.contains("Top-level nodes: Header, Objects\n"),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn dump() {
+    let dir = work_dir("dump");
+    let input = dir.join("input.fbx");
+    write_sample(&input);
+
+    let output = fbxcel(&[&"dump", &"--max-array-len=2", &input]);
+    assert!(output.status.success(), "{:?}", output);
+    let out = stdout(&output);
+    assert!(out.starts_with("; FBX 7.4 (binary)\n"), "{}", out);
+    assert!(
+        out.contains("\n        Indices: arr_i32(10) [0, 1, ... (8 more)]\n"),
+        "{}",
+        out
+    );
+    assert!(
+        out.contains("\n        Content: binary(4) [de, ad, ... (2 more)]\n"),
+        "{}",
+        out
+    );
+
+    let output = fbxcel(&[&"dump", &"--max-depth", &"1", &input]);
+    assert!(output.status.success(), "{:?}", output);
+    let out = stdout(&output);
+    assert!(out.contains("\n    ... (2 children)\n"), "{}", out);
+    assert!(!out.contains("Model"), "{}", out);
+}
+
+#[test]
+fn convert_round_trip() {
+    let dir = work_dir("convert");
+    let input = dir.join("input.fbx");
+    write_sample(&input);
+    let json = dir.join("tree.json");
+    let ascii = dir.join("tree.fbx.txt");
+    let binary = dir.join("output.fbx");
+
+    // JSON is lossless.
+    let output = fbxcel(&[&"convert", &input, &json]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = fbxcel(&[&"convert", &"--fbx-version", &"7.5", &json, &binary]);
+    assert!(output.status.success(), "{:?}", output);
+    let (version, tree) = load_binary(&binary);
+    assert_eq!(version, FbxVersion::V7_5);
+
+    // FBX binary can be written to stdout.
+    let output = fbxcel(&[
+        &"convert",
+        &"--to=binary",
+        &"--fbx-version=7.5",
+        &json,
+        &"-",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        output.stdout,
+        fs::read(&binary).expect("Failed to read the file")
+    );
+    assert!(tree.strict_eq(&sample_tree()));
+
+    // FBX ASCII does not preserve attribute types, but it should be readable.
+    let output = fbxcel(&[&"convert", &"--to=ascii", &input, &ascii]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = fbxcel(&[&"dump", &ascii]);
+    assert!(output.status.success(), "{:?}", output);
+    let out = stdout(&output);
+    assert!(out.starts_with("; FBX 7.4 (ascii)\n"), "{}", out);
+    assert!(
+        out.contains("\n    Model: i32 1, string \"Cube\\0\\u{1}Model\", "),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn convert_compressed_arrays() {
+    let dir = work_dir("convert-compressed");
+    let input = dir.join("input.fbx");
+    let mut tree = sample_tree();
+    let indices = tree
+        .root()
+        .first_child_by_name("Objects")
+        .and_then(|node| node.first_child_by_name("Model"))
+        .and_then(|node| node.first_child_by_name("Indices"))
+        .expect("The node should exist")
+        .node_id();
+    tree.set_array_encoding(indices, 0, Some(ArrayAttributeEncoding::Zlib));
+    write_tree(&input, &tree);
+    assert_eq!(indices_encoding(&input), Some(ArrayAttributeEncoding::Zlib));
+    let json = dir.join("tree.json");
+    let output_v7400 = dir.join("output-7400.fbx");
+    let output_v7500 = dir.join("output-7500.fbx");
+
+    // Compressed arrays are kept compressed.
+    let output = fbxcel(&[&"convert", &input, &output_v7400]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        fs::read(&output_v7400).expect("Failed to read the file"),
+        fs::read(&input).expect("Failed to read the file")
+    );
+    let output = fbxcel(&[&"convert", &"--fbx-version=7.5", &input, &output_v7500]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        indices_encoding(&output_v7500),
+        Some(ArrayAttributeEncoding::Zlib)
+    );
+    let (_, converted) = load_binary(&output_v7500);
+    assert!(converted.strict_eq(&tree));
+
+    // Arrays without encodings are compressed with `--compress`.
+    let output = fbxcel(&[&"convert", &input, &json]);
+    assert!(output.status.success(), "{:?}", output);
+    let output = fbxcel(&[&"convert", &json, &output_v7400]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        indices_encoding(&output_v7400),
+        Some(ArrayAttributeEncoding::Direct)
+    );
+    let output = fbxcel(&[&"convert", &"--compress=10", &json, &output_v7400]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        indices_encoding(&output_v7400),
+        Some(ArrayAttributeEncoding::Zlib)
+    );
+    let output = fbxcel(&[&"convert", &"--compress=11", &json, &output_v7400]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        indices_encoding(&output_v7400),
+        Some(ArrayAttributeEncoding::Direct)
+    );
+}
+
+#[test]
+fn validate() {
+    let dir = work_dir("validate");
+    let input = dir.join("input.fbx");
+    write_sample(&input);
+
+    let output = fbxcel(&[&"validate", &input]);
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    assert!(stdout(&output).ends_with(": ok\n"));
+
+    // Truncated file.
+    let broken = dir.join("broken.fbx");
+    let bin = fs::read(&input).expect("Failed to read the file");
+    fs::write(&broken, &bin[..bin.len() / 2]).expect("Failed to write the file");
+    let output = fbxcel(&[&"validate", &input, &broken]);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert!(stdout(&output).contains("broken.fbx: error: "));
+}
+
+#[test]
+fn extract() {
+    let dir = work_dir("extract");
+    let input = dir.join("input.fbx");
+    write_sample(&input);
+    let output_dir = dir.join("out");
+
+    let output = fbxcel(&[
+        &"extract",
+        &"--node=Content",
+        &"--output-dir",
+        &output_dir,
+        &input,
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        fs::read(output_dir.join("6-Content-0.bin")).expect("Failed to read the file"),
+        [0xde, 0xad, 0xbe, 0xef]
+    );
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 1);
+}
+
+#[test]
+fn extract_from_ascii() {
+    let dir = work_dir("extract-ascii");
+    let input = dir.join("input.fbx");
+    write_sample(&input);
+    let ascii = dir.join("input.fbx.txt");
+    let output = fbxcel(&[&"convert", &"--to=ascii", &input, &ascii]);
+    assert!(output.status.success(), "{:?}", output);
+    let output_dir = dir.join("out");
+
+    // Binaries are written as base64 strings in FBX ASCII.
+    let output = fbxcel(&[
+        &"extract",
+        &"--node=Content",
+        &"--output-dir",
+        &output_dir,
+        &ascii,
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        fs::read(output_dir.join("6-Content-0.bin")).expect("Failed to read the file"),
+        [0xde, 0xad, 0xbe, 0xef]
+    );
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 1);
+
+    // Conversion from FBX ASCII loses the binary type.
+    let binary = dir.join("output.fbx");
+    let output = fbxcel(&[&"convert", &ascii, &binary]);
+    assert!(output.status.success(), "{:?}", output);
+    let err = String::from_utf8_lossy(&output.stderr);
+    assert!(err.contains("converted as strings"), "{}", err);
+}
+
+#[test]
+fn usage_error() {
+    let output = fbxcel(&[&"dump", &"--no-such-option"]);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let err = String::from_utf8_lossy(&output.stderr);
+    assert!(err.contains("Unknown option `--no-such-option`"), "{}", err);
+    assert!(err.contains("Usage: fbxcel"), "{}", err);
+}
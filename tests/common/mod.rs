@@ -0,0 +1,135 @@
+//! Helpers shared by the tests.
+// Each test crate uses only some of the helpers.
+#![allow(dead_code)]
+
+use std::io::Cursor;
+
+use fbxcel::pull_parser::{
+    any::{from_seekable_reader, AnyParser},
+    v7400::Parser,
+    ParserSource,
+};
+#[cfg(feature = "writer")]
+use fbxcel::{
+    low::{v7400::ArrayAttributeEncoding, FbxVersion},
+    pull_parser::v7400::index::NodeIndex,
+    writer::v7400::binary::{FbxFooter, Writer},
+};
+#[cfg(all(feature = "tree", feature = "writer"))]
+use fbxcel::{tree::v7400::Tree, tree_v7400};
+
+/// Creates a parser for the FBX binary.
+pub fn parser(bin: &[u8]) -> Parser<impl ParserSource + '_> {
+    match from_seekable_reader(Cursor::new(bin)).expect("Failed to create parser") {
+        AnyParser::V7400(parser) => parser,
+        _ => panic!("Generated data should be parsable with v7400 parser"),
+    }
+}
+
+/// Writes the sample FBX binary with the given footer.
+///
+/// The nodes are:
+///
+/// * `Container` without attributes.
+///     + `Flag` with a boolean.
+///     + `Values` with an `i32` and a direct `i32` array of 8 elements.
+///     + `Compressed` with a zlib-compressed `f64` array of 16 elements.
+///     + `Name` with a string `"Hello, world"`.
+/// * `Second` with an `i64`.
+#[cfg(feature = "writer")]
+pub fn sample_with_footer(fbx_version: FbxVersion, footer: &FbxFooter<'_>) -> Vec<u8> {
+    let mut writer =
+        Writer::new(Cursor::new(Vec::new()), fbx_version).expect("Failed to create writer");
+    (|| {
+        writer.new_node("Container")?;
+        {
+            let mut attrs = writer.new_node("Flag")?;
+            attrs.append_bool(true)?;
+        }
+        writer.close_node()?;
+        {
+            let mut attrs = writer.new_node("Values")?;
+            attrs.append_i32(1)?;
+            attrs.append_arr_i32_from_iter(Some(ArrayAttributeEncoding::Direct), 0..8)?;
+        }
+        writer.close_node()?;
+        {
+            let mut attrs = writer.new_node("Compressed")?;
+            attrs.append_arr_f64_from_iter(
+                Some(ArrayAttributeEncoding::Zlib),
+                (0..16).map(f64::from),
+            )?;
+        }
+        writer.close_node()?;
+        {
+            let mut attrs = writer.new_node("Name")?;
+            attrs.append_string_direct("Hello, world")?;
+        }
+        writer.close_node()?;
+        writer.close_node()?;
+        {
+            let mut attrs = writer.new_node("Second")?;
+            attrs.append_i64(42)?;
+        }
+        writer.close_node()?;
+        Ok::<_, fbxcel::writer::v7400::binary::Error>(())
+    })()
+    .expect("Failed to write nodes");
+    writer
+        .finalize_and_flush(footer)
+        .expect("Failed to finalize")
+        .into_inner()
+}
+
+/// Writes the sample FBX 7.4 binary.
+///
+/// See [`sample_with_footer`] for the nodes.
+#[cfg(feature = "writer")]
+pub fn sample() -> Vec<u8> {
+    sample_with_footer(FbxVersion::V7_4, &Default::default())
+}
+
+/// Returns the start offset and the attributes start offset of the node.
+#[cfg(feature = "writer")]
+pub fn offsets(bin: &[u8], name: &str) -> (usize, usize) {
+    let index = NodeIndex::build(Cursor::new(bin)).expect("Failed to build index");
+    let entry = index
+        .entries()
+        .iter()
+        .find(|entry| entry.name() == name)
+        .expect("The node should exist");
+    (
+        entry.start_offset() as usize,
+        entry.attributes_range().start as usize,
+    )
+}
+
+/// Returns a tree for tests.
+#[cfg(all(feature = "tree", feature = "writer"))]
+pub fn source_tree() -> Tree {
+    tree_v7400! {
+        Creator: ["fbxcel"] {},
+        Objects: {
+            Geometry: [1i64, "Mesh"] {
+                Vertices: [vec![0.0f64, 1.0, 2.0, 3.0, 4.0, 5.0]] {},
+                PolygonVertexIndex: [vec![0i32, 1, -2]] {},
+                Thumbnail: [&[1u8, 2, 3][..]] {},
+            },
+            Model: [2i64, "Cube", "Mesh"] {
+                Visibility: [true] {},
+            },
+        },
+    }
+}
+
+/// Writes the tree as FBX binary with the given version and footer.
+#[cfg(all(feature = "tree", feature = "writer"))]
+pub fn write_tree(tree: &Tree, fbx_version: FbxVersion, footer: &FbxFooter<'_>) -> Vec<u8> {
+    let mut writer =
+        Writer::new(Cursor::new(Vec::new()), fbx_version).expect("Failed to create writer");
+    writer.write_tree(tree).expect("Failed to write tree");
+    writer
+        .finalize_and_flush(footer)
+        .expect("Failed to finalize")
+        .into_inner()
+}
@@ -0,0 +1,245 @@
+//! Tests for array compression of the binary writer.
+#![cfg(feature = "writer")]
+
+use std::io::Cursor;
+
+use fbxcel::{
+    low::{
+        v7400::{ArrayAttributeEncoding, AttributeValue},
+        FbxVersion,
+    },
+    pull_parser::v7400::{from_slice, slice::SliceAttribute, Event},
+    writer::v7400::binary::{
+        CompressionLevel, CompressionPolicy, CompressionThreshold, Error, Writer,
+    },
+};
+
+/// Returns the encodings and values of all array attributes in the FBX binary.
+fn arrays(bin: &[u8]) -> Vec<(ArrayAttributeEncoding, AttributeValue)> {
+    let mut parser = from_slice(bin).expect("Failed to create a parser");
+    let mut arrays = Vec::new();
+    loop {
+        match parser.next_event().expect("Failed to parse") {
+            Event::StartNode(start) => {
+                let mut attrs = start.attributes();
+                while let Some(attr) = attrs.next_borrowed().expect("Failed to load an attribute") {
+                    let encoding = match attr {
+                        SliceAttribute::ArrBool(v) => v.encoding(),
+                        SliceAttribute::ArrI32(v) => v.encoding(),
+                        SliceAttribute::ArrI64(v) => v.encoding(),
+                        SliceAttribute::ArrF32(v) => v.encoding(),
+                        SliceAttribute::ArrF64(v) => v.encoding(),
+                        _ => continue,
+                    };
+                    let value = attr.to_value().expect("Failed to decode an array");
+                    arrays.push((encoding, value));
+                }
+            }
+            Event::EndNode => {}
+            Event::EndFbx(footer) => {
+                assert!(footer.is_ok());
+                break;
+            }
+        }
+    }
+    arrays
+}
+
+/// Writes arrays of every type with the given policy.
+fn write_arrays(policy: CompressionPolicy, len: usize) -> Result<Vec<u8>, Error> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+    writer.set_compression_policy(policy);
+    let mut attrs = writer.new_node("Arrays")?;
+    attrs.append_arr_bool_from_iter(None, (0..len).map(|i| i % 3 == 0))?;
+    attrs.append_arr_i32_from_iter(None, (0..len).map(|i| i as i32))?;
+    attrs.append_arr_i64_from_iter(None, (0..len).map(|i| i as i64))?;
+    attrs.append_arr_f32_from_iter(None, (0..len).map(|i| i as f32 * 0.5))?;
+    attrs.append_arr_f64_from_iter(None, (0..len).map(|i| i as f64 * 0.25))?;
+    writer.close_node()?;
+    Ok(writer.finalize_and_flush(&Default::default())?.into_inner())
+}
+
+#[test]
+fn never_compress_by_default() -> Result<(), Error> {
+    let bin = write_arrays(CompressionPolicy::default(), 1000)?;
+    let arrays = arrays(&bin);
+    assert_eq!(arrays.len(), 5);
+    assert!(arrays
+        .iter()
+        .all(|(encoding, _)| *encoding == ArrayAttributeEncoding::Direct));
+    Ok(())
+}
+
+#[test]
+fn elements_count_threshold() -> Result<(), Error> {
+    let policy = CompressionPolicy::new(
+        CompressionThreshold::ElementsCount(16),
+        CompressionLevel::Default,
+    );
+    let expected = arrays(&write_arrays(CompressionPolicy::never(), 16)?);
+
+    // Below the threshold.
+    let bin = write_arrays(policy, 15)?;
+    assert!(arrays(&bin)
+        .iter()
+        .all(|(encoding, _)| *encoding == ArrayAttributeEncoding::Direct));
+
+    // Reaches the threshold.
+    let bin = write_arrays(policy, 16)?;
+    let compressed = arrays(&bin);
+    assert!(compressed
+        .iter()
+        .all(|(encoding, _)| *encoding == ArrayAttributeEncoding::Zlib));
+    assert_eq!(
+        compressed.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+        expected.iter().map(|(_, v)| v).collect::<Vec<_>>()
+    );
+    assert!(matches!(compressed[3].1, AttributeValue::ArrF32(_)));
+    assert!(matches!(compressed[4].1, AttributeValue::ArrF64(_)));
+
+    Ok(())
+}
+
+#[test]
+fn byte_length_threshold() -> Result<(), Error> {
+    let policy = CompressionPolicy::new(
+        CompressionThreshold::ByteLength(64),
+        CompressionLevel::Default,
+    );
+    // 16 elements: bool and i32 arrays are smaller than 64 bytes.
+    let bin = write_arrays(policy, 16)?;
+    let encodings = arrays(&bin)
+        .into_iter()
+        .map(|(encoding, _)| encoding)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        encodings,
+        [
+            ArrayAttributeEncoding::Direct,
+            ArrayAttributeEncoding::Zlib,
+            ArrayAttributeEncoding::Zlib,
+            ArrayAttributeEncoding::Zlib,
+            ArrayAttributeEncoding::Zlib,
+        ]
+    );
+
+    // Empty arrays are compressed only if the threshold is zero.
+    let bin = write_arrays(
+        CompressionPolicy::new(CompressionThreshold::ByteLength(0), CompressionLevel::Fast),
+        0,
+    )?;
+    assert!(arrays(&bin)
+        .iter()
+        .all(|(encoding, _)| *encoding == ArrayAttributeEncoding::Zlib));
+
+    Ok(())
+}
+
+#[test]
+fn explicit_encoding_wins() -> Result<(), Error> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+    writer.set_compression_policy(CompressionPolicy::new(
+        CompressionThreshold::ElementsCount(4),
+        CompressionLevel::Default,
+    ));
+    let mut attrs = writer.new_node("Arrays")?;
+    attrs.append_arr_i32_from_iter(Some(ArrayAttributeEncoding::Direct), 0..100)?;
+    attrs.append_arr_i32_from_iter(Some(ArrayAttributeEncoding::Zlib), 0..1)?;
+    attrs.append_arr_i32_from_iter(None, 0..100)?;
+    writer.close_node()?;
+    let bin = writer.finalize_and_flush(&Default::default())?.into_inner();
+
+    assert_eq!(
+        arrays(&bin),
+        [
+            (
+                ArrayAttributeEncoding::Direct,
+                AttributeValue::ArrI32((0..100).collect())
+            ),
+            (
+                ArrayAttributeEncoding::Zlib,
+                AttributeValue::ArrI32(vec![0])
+            ),
+            (
+                ArrayAttributeEncoding::Zlib,
+                AttributeValue::ArrI32((0..100).collect())
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn compression_levels() -> Result<(), Error> {
+    let expected = arrays(&write_arrays(CompressionPolicy::never(), 1000)?);
+    let mut sizes = Vec::new();
+    for &level in &[
+        CompressionLevel::Stored,
+        CompressionLevel::Fast,
+        CompressionLevel::Default,
+    ] {
+        let policy = CompressionPolicy::new(CompressionThreshold::ElementsCount(0), level);
+        let bin = write_arrays(policy, 1000)?;
+        let arrays = arrays(&bin);
+        assert_eq!(
+            arrays.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            expected.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            "level={:?}",
+            level
+        );
+        sizes.push(bin.len());
+    }
+    assert!(sizes[0] > sizes[1], "sizes={:?}", sizes);
+    assert!(sizes[1] >= sizes[2], "sizes={:?}", sizes);
+
+    Ok(())
+}
+
+#[cfg(feature = "tree")]
+#[test]
+fn write_tree_with_policy() -> Result<(), Box<dyn std::error::Error>> {
+    use fbxcel::{
+        pull_parser::any::{from_seekable_reader, AnyParser},
+        tree::v7400::Loader,
+        tree_v7400,
+    };
+
+    let tree = tree_v7400! {
+        Geometry: {
+            Vertices: [(0..300).map(|i| i as f64 * 0.5).collect::<Vec<_>>()] {},
+            PolygonVertexIndex: [(0..100).collect::<Vec<i32>>()] {},
+            Small: [vec![1.0f32, 2.0]] {},
+        },
+    };
+    let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+    writer.set_compression_policy(CompressionPolicy::new(
+        CompressionThreshold::ElementsCount(64),
+        CompressionLevel::Default,
+    ));
+    writer.write_tree(&tree)?;
+    let bin = writer.finalize_and_flush(&Default::default())?.into_inner();
+
+    let encodings = arrays(&bin)
+        .into_iter()
+        .map(|(encoding, _)| encoding)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        encodings,
+        [
+            ArrayAttributeEncoding::Zlib,
+            ArrayAttributeEncoding::Zlib,
+            ArrayAttributeEncoding::Direct,
+        ]
+    );
+
+    let mut parser = match from_seekable_reader(Cursor::new(bin))? {
+        AnyParser::V7400(parser) => parser,
+        _ => panic!("Generated data should be parsable with v7400 parser"),
+    };
+    let (loaded, footer) = Loader::new().load(&mut parser)?;
+    assert!(footer.is_ok());
+    assert!(loaded.strict_eq(&tree));
+
+    Ok(())
+}
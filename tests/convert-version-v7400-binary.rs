@@ -0,0 +1,164 @@
+//! Tests for FBX version conversion of the binary writer.
+#![cfg(all(feature = "tree", feature = "writer"))]
+
+use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+use fbxcel::{
+    low::{
+        v7400::{ArrayAttributeEncoding, FbxFooter},
+        FbxVersion,
+    },
+    tree::v7400::{ArrayPreservation, Loader, Tree},
+    writer::v7400::binary::{
+        convert_version, FbxFooter as WriterFooter, StreamWriter, VersionOverflow, Writer,
+    },
+};
+
+use self::common::{parser, source_tree, write_tree};
+
+mod common;
+
+/// Unknown field 1 of the footer for tests.
+const UNKNOWN1: [u8; 16] = [
+    0xf1, 0xb2, 0xa3, 0x04, 0xd5, 0xc6, 0xd7, 0x68, 0xb9, 0x7a, 0xfb, 0x8c, 0x1d, 0xfe, 0x2f, 0x70,
+];
+
+/// Writes the tree as FBX binary with the given version.
+fn to_binary(tree: &Tree, fbx_version: FbxVersion) -> Vec<u8> {
+    let footer = WriterFooter {
+        unknown1: Some(&UNKNOWN1),
+        ..Default::default()
+    };
+    write_tree(tree, fbx_version, &footer)
+}
+
+/// Converts the FBX binary into the given version.
+fn convert(bin: &[u8], fbx_version: FbxVersion) -> Vec<u8> {
+    let writer =
+        Writer::new(Cursor::new(Vec::new()), fbx_version).expect("Failed to create writer");
+    convert_version(&mut parser(bin), writer)
+        .expect("Failed to convert")
+        .into_inner()
+}
+
+/// Loads the FBX binary, and checks that there are no warnings.
+fn load(bin: &[u8]) -> (FbxVersion, Tree, Box<FbxFooter>) {
+    let mut parser = parser(bin);
+    let warnings = Rc::new(RefCell::new(Vec::new()));
+    parser.set_warning_handler({
+        let warnings = warnings.clone();
+        move |warning, _pos| {
+            warnings.borrow_mut().push(warning);
+            Ok(())
+        }
+    });
+    let (tree, footer) = Loader::new()
+        .load(&mut parser)
+        .expect("Failed to load tree");
+    assert!(warnings.borrow().is_empty(), "{:?}", warnings.borrow());
+
+    (
+        parser.fbx_version(),
+        tree,
+        footer.expect("Failed to read footer"),
+    )
+}
+
+#[test]
+fn convert_v7400_to_v7500() {
+    let tree = source_tree();
+    let bin = convert(&to_binary(&tree, FbxVersion::V7_4), FbxVersion::V7_5);
+
+    let (version, converted, footer) = load(&bin);
+    assert_eq!(version, FbxVersion::V7_5);
+    assert_eq!(footer.fbx_version, FbxVersion::V7_5);
+    assert_eq!(footer.unknown1, UNKNOWN1);
+    assert!(converted.strict_eq(&tree));
+
+    assert_eq!(bin, to_binary(&tree, FbxVersion::V7_5));
+}
+
+#[test]
+fn convert_round_trip() {
+    let tree = source_tree();
+    let v7400 = to_binary(&tree, FbxVersion::V7_4);
+    let v7500 = convert(&v7400, FbxVersion::V7_5);
+    let back = convert(&v7500, FbxVersion::V7_4);
+
+    let (version, converted, footer) = load(&back);
+    assert_eq!(version, FbxVersion::V7_4);
+    assert_eq!(footer.fbx_version, FbxVersion::V7_4);
+    assert!(converted.strict_eq(&tree));
+
+    assert_eq!(back, v7400);
+}
+
+#[test]
+fn checked_write_of_small_tree() -> Result<(), Box<dyn std::error::Error>> {
+    let tree = source_tree();
+    let expected = to_binary(&tree, FbxVersion::V7_4);
+    let footer = WriterFooter {
+        unknown1: Some(&UNKNOWN1),
+        ..Default::default()
+    };
+
+    for &mode in &[VersionOverflow::Error, VersionOverflow::Upgrade] {
+        let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+        writer.set_version_overflow(mode);
+        writer.write_tree(&tree)?;
+        assert_eq!(writer.fbx_version(), FbxVersion::V7_4);
+        let bin = writer.finalize_and_flush(&footer)?.into_inner();
+        assert_eq!(bin, expected, "mode={:?}", mode);
+
+        let mut writer = StreamWriter::new(Vec::new(), FbxVersion::V7_4)?;
+        writer.set_version_overflow(mode);
+        writer.write_tree(&tree)?;
+        assert_eq!(writer.fbx_version(), FbxVersion::V7_4);
+        let bin = writer.finalize_and_flush(&footer)?;
+        assert_eq!(bin, expected, "mode={:?}", mode);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn convert_compressed_arrays() {
+    let mut tree = source_tree();
+    let objects = tree.root().first_child_by_name("Objects").unwrap();
+    let geometry = objects.first_child_by_name("Geometry").unwrap();
+    let vertices = geometry.first_child_by_name("Vertices").unwrap().node_id();
+    let indices = geometry
+        .first_child_by_name("PolygonVertexIndex")
+        .unwrap()
+        .node_id();
+    tree.set_array_encoding(vertices, 0, Some(ArrayAttributeEncoding::Zlib));
+    tree.set_array_encoding(indices, 0, Some(ArrayAttributeEncoding::Direct));
+
+    let v7400 = to_binary(&tree, FbxVersion::V7_4);
+    let v7500 = convert(&v7400, FbxVersion::V7_5);
+    // The encodings are preserved by the writer.
+    assert_eq!(v7500, to_binary(&tree, FbxVersion::V7_5));
+    assert_eq!(convert(&v7500, FbxVersion::V7_4), v7400);
+
+    let (converted, _) = Loader::new()
+        .with_array_preservation(ArrayPreservation::Encoding)
+        .load(&mut parser(&v7500))
+        .expect("Failed to load tree");
+    let geometry = converted
+        .root()
+        .first_child_by_name("Objects")
+        .and_then(|node| node.first_child_by_name("Geometry"))
+        .expect("The node should exist");
+    let encoding = |name| {
+        geometry
+            .first_child_by_name(name)
+            .expect("The node should exist")
+            .array_encoding(0)
+    };
+    assert_eq!(encoding("Vertices"), Some(ArrayAttributeEncoding::Zlib));
+    assert_eq!(
+        encoding("PolygonVertexIndex"),
+        Some(ArrayAttributeEncoding::Direct)
+    );
+    assert!(converted.strict_eq(&tree));
+}
@@ -0,0 +1,273 @@
+//! Tests for streaming copy from parser to writer.
+#![cfg(all(feature = "tree", feature = "writer"))]
+
+use std::io::{self, Cursor, Seek, SeekFrom, Write};
+
+use fbxcel::{
+    low::{
+        v7400::{ArrayAttributeEncoding, AttributeType, AttributeValue},
+        FbxVersion,
+    },
+    tree::v7400::{Loader, Tree},
+    tree_v7400,
+    writer::v7400::binary::{
+        copy_from_parser, CopyAction, CopyAttribute, CopyError, CopyEvent, Writer,
+    },
+};
+
+use self::common::{parser, source_tree, write_tree};
+
+mod common;
+
+/// Writes the tree as FBX 7.4 binary.
+fn to_binary(tree: &Tree) -> Vec<u8> {
+    write_tree(tree, FbxVersion::V7_4, &Default::default())
+}
+
+/// Copies the FBX binary with the callback, and returns the result.
+fn copy<F>(bin: &[u8], callback: F) -> Result<Vec<u8>, CopyError>
+where
+    F: FnMut(CopyEvent<'_>) -> CopyAction,
+{
+    let mut parser = parser(bin);
+    let mut writer = Writer::new(Cursor::new(Vec::new()), parser.fbx_version())?;
+    let footer = copy_from_parser(&mut parser, &mut writer, callback)?;
+    assert!(footer.is_ok());
+    Ok(writer.finalize_and_flush(&Default::default())?.into_inner())
+}
+
+/// Loads the FBX binary into a tree.
+fn load(bin: &[u8]) -> Tree {
+    let (tree, footer) = Loader::new()
+        .load(&mut parser(bin))
+        .expect("Failed to load tree");
+    assert!(footer.is_ok());
+    tree
+}
+
+#[test]
+fn keep_all() -> Result<(), CopyError> {
+    let bin = to_binary(&source_tree());
+    let mut events = Vec::new();
+    let copied = copy(&bin, |event| {
+        events.push(format!("{:?}", event));
+        CopyAction::Keep
+    })?;
+    assert_eq!(copied, bin);
+    // 8 nodes and 10 attributes.
+    assert_eq!(events.len(), 8 + 10);
+    assert_eq!(events[0], r#"StartNode { ancestors: [], name: "Creator" }"#);
+
+    Ok(())
+}
+
+#[test]
+fn keep_compressed_arrays() -> Result<(), CopyError> {
+    let mut tree = source_tree();
+    let vertices = tree
+        .root()
+        .first_child_by_name("Objects")
+        .and_then(|node| node.first_child_by_name("Geometry"))
+        .and_then(|node| node.first_child_by_name("Vertices"))
+        .expect("The node should exist")
+        .node_id();
+    tree.set_array_encoding(vertices, 0, Some(ArrayAttributeEncoding::Zlib));
+    let bin = to_binary(&tree);
+
+    // Compressed arrays are copied as is, even though the writer does not
+    // compress arrays by default.
+    let copied = copy(&bin, |_| CopyAction::Keep)?;
+    assert_eq!(copied, bin);
+
+    Ok(())
+}
+
+#[test]
+fn arrays_are_not_loaded() -> Result<(), CopyError> {
+    let bin = to_binary(&source_tree());
+    let mut arrays = Vec::new();
+    copy(&bin, |event| {
+        if let CopyEvent::Attribute {
+            ancestors,
+            node_name,
+            index,
+            attribute: CopyAttribute::Array { type_, len },
+        } = event
+        {
+            arrays.push((ancestors.to_vec(), node_name.to_owned(), index, type_, len));
+        }
+        CopyAction::Keep
+    })?;
+    let geometry = vec!["Objects".to_owned(), "Geometry".to_owned()];
+    assert_eq!(
+        arrays,
+        [
+            (
+                geometry.clone(),
+                "Vertices".to_owned(),
+                0,
+                AttributeType::ArrF64,
+                6
+            ),
+            (
+                geometry,
+                "PolygonVertexIndex".to_owned(),
+                0,
+                AttributeType::ArrI32,
+                3
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
+/// Sink which records the largest buffer passed to a single `write()` call.
+#[derive(Default)]
+struct MaxWriteSink {
+    /// Written data.
+    inner: Cursor<Vec<u8>>,
+    /// Largest buffer length passed to `write()`.
+    max_write: usize,
+}
+
+impl Write for MaxWriteSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.max_write = self.max_write.max(buf.len());
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Seek for MaxWriteSink {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn raw_arrays_are_streamed() -> Result<(), CopyError> {
+    const LEN: usize = 1024 * 1024;
+    let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+    writer
+        .new_node("Large")?
+        .append_arr_i32_from_iter(ArrayAttributeEncoding::Direct, (0..LEN).map(|v| v as i32))?;
+    writer.close_node()?;
+    let bin = writer.finalize_and_flush(&Default::default())?.into_inner();
+
+    let mut parser = parser(&bin);
+    let mut writer = Writer::new(MaxWriteSink::default(), parser.fbx_version())?;
+    let footer = copy_from_parser(&mut parser, &mut writer, |_| CopyAction::Keep)?;
+    assert!(footer.is_ok());
+    let sink = writer.finalize_and_flush(&Default::default())?;
+    assert_eq!(sink.inner.into_inner(), bin);
+    // The 4 MiB array is not written at once.
+    assert!(sink.max_write <= 64 * 1024, "max_write={}", sink.max_write);
+
+    Ok(())
+}
+
+#[test]
+fn skip_rename_and_replace() -> Result<(), CopyError> {
+    let bin = to_binary(&source_tree());
+    let copied = copy(&bin, |event| match event {
+        CopyEvent::StartNode {
+            name: "Creator", ..
+        } => CopyAction::Skip,
+        CopyEvent::StartNode {
+            name: "Geometry", ..
+        } => CopyAction::Rename("Mesh".to_owned()),
+        CopyEvent::Attribute {
+            node_name: "Thumbnail",
+            ..
+        } => CopyAction::Skip,
+        CopyEvent::Attribute {
+            node_name: "Model",
+            attribute: CopyAttribute::Value(AttributeValue::String(name)),
+            ..
+        } if name == "Cube" => CopyAction::ReplaceAttribute("Box".into()),
+        CopyEvent::Attribute {
+            node_name: "PolygonVertexIndex",
+            ..
+        } => CopyAction::ReplaceAttribute(vec![3i32, 4, -6].into()),
+        _ => CopyAction::Keep,
+    })?;
+
+    let expected = tree_v7400! {
+        Objects: {
+            Mesh: [1i64, "Mesh"] {
+                Vertices: [vec![0.0f64, 1.0, 2.0, 3.0, 4.0, 5.0]] {},
+                PolygonVertexIndex: [vec![3i32, 4, -6]] {},
+                Thumbnail: {},
+            },
+            Model: [2i64, "Box", "Mesh"] {
+                Visibility: [true] {},
+            },
+        },
+    };
+    let tree = load(&copied);
+    assert!(tree.strict_eq(&expected), "{:?}", tree.debug_tree());
+
+    Ok(())
+}
+
+#[test]
+fn replace_node() -> Result<(), CopyError> {
+    let bin = to_binary(&source_tree());
+    let copied = copy(&bin, |event| match event {
+        CopyEvent::StartNode { name: "Model", .. } => CopyAction::ReplaceNode(tree_v7400! {
+            Model: [3i64] {},
+            Model: [4i64] {
+                Visibility: [false] {},
+            },
+        }),
+        _ => CopyAction::Keep,
+    })?;
+
+    let expected = tree_v7400! {
+        Creator: ["fbxcel"] {},
+        Objects: {
+            Geometry: [1i64, "Mesh"] {
+                Vertices: [vec![0.0f64, 1.0, 2.0, 3.0, 4.0, 5.0]] {},
+                PolygonVertexIndex: [vec![0i32, 1, -2]] {},
+                Thumbnail: [&[1u8, 2, 3][..]] {},
+            },
+            Model: [3i64] {},
+            Model: [4i64] {
+                Visibility: [false] {},
+            },
+        },
+    };
+    let tree = load(&copied);
+    assert!(tree.strict_eq(&expected), "{:?}", tree.debug_tree());
+
+    Ok(())
+}
+
+#[test]
+fn invalid_action() {
+    let bin = to_binary(&source_tree());
+    let res = copy(&bin, |event| match event {
+        CopyEvent::Attribute { .. } => CopyAction::Rename("Foo".to_owned()),
+        _ => CopyAction::Keep,
+    });
+    assert!(matches!(
+        res,
+        Err(CopyError::InvalidAction {
+            action: "Rename",
+            for_attribute: true,
+        })
+    ));
+
+    let res = copy(&bin, |_| CopyAction::ReplaceAttribute(true.into()));
+    assert!(matches!(
+        res,
+        Err(CopyError::InvalidAction {
+            action: "ReplaceAttribute",
+            for_attribute: false,
+        })
+    ));
+}
@@ -0,0 +1,287 @@
+//! Tests for deserialization of nodes into user types.
+#![cfg(all(feature = "tree", feature = "serde"))]
+
+use std::collections::BTreeMap;
+
+use fbxcel::{tree::v7400::de::from_node, tree_v7400};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[allow(non_snake_case)]
+struct Model {
+    #[serde(rename = "$name")]
+    name: String,
+    #[serde(rename = "$attributes")]
+    attributes: (i64, String, String),
+    Version: i32,
+    Properties70: Properties,
+    Shading: Option<bool>,
+    Culling: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Properties {
+    #[serde(rename = "P", default)]
+    props: Vec<Property>,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Property(String, String, String, String, f64);
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Culling {
+    CullingOff,
+    CullingOn,
+}
+
+#[test]
+fn deserialize_struct() -> Result<(), Box<dyn std::error::Error>> {
+    let tree = tree_v7400! {
+        Model: [1i64, "Model::Cube", "Mesh"] {
+            Version: [232i32] {},
+            Properties70: {
+                P: ["Lcl Translation", "Lcl Translation", "", "A", 1.5f64] {},
+                P: ["Visibility", "Visibility", "", "A", 1.0f64] {},
+            },
+            Shading: [true] {},
+        },
+    };
+    let node = tree.root().first_child().expect("Should have a child");
+
+    let model: Model = from_node(node)?;
+    assert_eq!(
+        model,
+        Model {
+            name: "Model".to_owned(),
+            attributes: (1, "Model::Cube".to_owned(), "Mesh".to_owned()),
+            Version: 232,
+            Properties70: Properties {
+                props: vec![
+                    Property(
+                        "Lcl Translation".to_owned(),
+                        "Lcl Translation".to_owned(),
+                        String::new(),
+                        "A".to_owned(),
+                        1.5
+                    ),
+                    Property(
+                        "Visibility".to_owned(),
+                        "Visibility".to_owned(),
+                        String::new(),
+                        "A".to_owned(),
+                        1.0
+                    ),
+                ],
+            },
+            Shading: Some(true),
+            Culling: None,
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn deserialize_arrays_maps_and_enums() -> Result<(), Box<dyn std::error::Error>> {
+    let tree = tree_v7400! {
+        Geometry: {
+            Vertices: [vec![0.0f64, 1.0, 2.0]] {},
+            PolygonVertexIndex: [vec![0i32, 1, -3]] {},
+            Culling: ["CullingOff"] {},
+        },
+    };
+    let node = tree.root().first_child().expect("Should have a child");
+
+    #[derive(Debug, Deserialize)]
+    #[allow(non_snake_case)]
+    struct Geometry {
+        Vertices: (Vec<f64>,),
+        PolygonVertexIndex: (Vec<i32>,),
+        Culling: Culling,
+    }
+    let geometry: Geometry = from_node(node)?;
+    assert_eq!(geometry.Vertices.0, [0.0, 1.0, 2.0]);
+    assert_eq!(geometry.PolygonVertexIndex.0, [0, 1, -3]);
+    assert_eq!(geometry.Culling, Culling::CullingOff);
+
+    let map: BTreeMap<String, serde_json::Value> = from_node(node)?;
+    assert_eq!(
+        map.keys().collect::<Vec<_>>(),
+        ["Culling", "PolygonVertexIndex", "Vertices"]
+    );
+    assert_eq!(map["Vertices"], serde_json::json!([0.0, 1.0, 2.0]));
+    assert_eq!(map["Culling"], serde_json::json!("CullingOff"));
+
+    Ok(())
+}
+
+#[test]
+fn error_position() {
+    let tree = tree_v7400! {
+        Objects: {
+            Model: [1i64, "Model::Cube", "Mesh"] {},
+            Model: [2i64, "Model::Sphere", 3i32] {},
+        },
+    };
+    let node = tree.root().first_child().expect("Should have a child");
+
+    #[derive(Debug, Deserialize)]
+    #[allow(non_snake_case, dead_code)]
+    struct Objects {
+        Model: Vec<(i64, String, String)>,
+    }
+    let err = from_node::<Objects>(node).expect_err("Should fail");
+    assert_eq!(
+        err.node_path(),
+        Some(&[(1, "Model".to_owned())][..]),
+        "err={}",
+        err
+    );
+    assert_eq!(err.attribute_index(), Some(2));
+    assert!(
+        err.to_string().ends_with("(at /Model[1], attribute #2)"),
+        "err={}",
+        err
+    );
+}
+
+#[test]
+fn missing_children() {
+    let tree = tree_v7400! {
+        Geometry: [1i64] {
+            Vertices: [vec![0.0f64, 1.0]] {},
+        },
+    };
+    let node = tree.root().first_child().expect("Should have a child");
+
+    #[derive(Debug, Deserialize)]
+    #[allow(non_snake_case)]
+    struct Geometry {
+        Vertices: (Vec<f64>,),
+        #[serde(default)]
+        Layer: Vec<(i32,)>,
+        Version: Option<(i32,)>,
+        #[serde(default = "default_smoothing")]
+        Smoothing: (i32,),
+    }
+    fn default_smoothing() -> (i32,) {
+        (1,)
+    }
+    let geometry = from_node::<Geometry>(node).expect("Failed to deserialize");
+    assert_eq!(geometry.Vertices.0, [0.0, 1.0]);
+    assert!(geometry.Layer.is_empty());
+    assert_eq!(geometry.Version, None);
+    assert_eq!(geometry.Smoothing, (1,));
+
+    #[derive(Debug, Deserialize)]
+    #[allow(non_snake_case, dead_code)]
+    struct LayerWithoutDefault {
+        Layer: Vec<(i32,)>,
+    }
+    let err = from_node::<LayerWithoutDefault>(node).expect_err("Should fail");
+    assert!(
+        err.to_string().contains("missing field `Layer`"),
+        "err={}",
+        err
+    );
+
+    #[derive(Debug, Deserialize)]
+    #[allow(non_snake_case, dead_code)]
+    struct RequiredLayer {
+        Layer: (i32,),
+    }
+    let err = from_node::<RequiredLayer>(node).expect_err("Should fail");
+    assert!(
+        err.to_string().contains("missing field `Layer`"),
+        "err={}",
+        err
+    );
+    assert_eq!(err.node_path(), Some(&[][..]));
+}
+
+#[cfg(feature = "writer")]
+#[test]
+fn deserialize_from_parser() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Cursor;
+
+    use fbxcel::{
+        low::FbxVersion,
+        pull_parser::{
+            any::{from_seekable_reader, AnyParser},
+            v7400::Event,
+        },
+        tree::v7400::de::from_start_node,
+        writer::v7400::binary::Writer,
+    };
+
+    let tree = tree_v7400! {
+        Header: [1i32] {},
+        Objects: {
+            Model: [1i64, "Model::Cube", "Mesh"] {
+                Version: [232i32] {},
+                Properties70: {
+                    P: ["Lcl Translation", "Lcl Translation", "", "A", 1.5f64] {},
+                },
+            },
+            Model: [2i64, "Model::Sphere", "Mesh"] {
+                Version: [232i32] {},
+                Properties70: {},
+            },
+        },
+    };
+    let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+    writer.write_tree(&tree)?;
+    let bin = writer.finalize_and_flush(&Default::default())?.into_inner();
+
+    let mut parser = match from_seekable_reader(Cursor::new(&bin[..]))? {
+        AnyParser::V7400(parser) => parser,
+        _ => panic!("Generated data should be parsable with v7400 parser"),
+    };
+
+    #[derive(Debug, Deserialize)]
+    #[allow(non_snake_case)]
+    struct SimpleModel {
+        #[serde(rename = "$attributes")]
+        attributes: (i64, String, String),
+        Properties70: Properties,
+    }
+
+    let mut models = Vec::new();
+    loop {
+        match parser.next_event()? {
+            Event::StartNode(start) if start.name() == "Model" => {
+                models.push(from_start_node::<SimpleModel, _>(start)?);
+            }
+            Event::StartNode(_) | Event::EndNode => {}
+            Event::EndFbx(_) => break,
+        }
+    }
+    assert_eq!(models.len(), 2);
+    assert_eq!(models[0].attributes.1, "Model::Cube");
+    assert_eq!(models[0].Properties70.props.len(), 1);
+    assert_eq!(models[0].Properties70.props[0].4, 1.5);
+    assert_eq!(models[1].attributes.0, 2);
+    assert!(models[1].Properties70.props.is_empty());
+
+    // Errors have absolute node paths.
+    let mut parser = match from_seekable_reader(Cursor::new(&bin[..]))? {
+        AnyParser::V7400(parser) => parser,
+        _ => unreachable!(),
+    };
+    let err = loop {
+        match parser.next_event()? {
+            Event::StartNode(start) if start.name() == "Model" => {
+                break from_start_node::<(i64, String, i32), _>(start).expect_err("Should fail");
+            }
+            Event::StartNode(_) | Event::EndNode => {}
+            Event::EndFbx(_) => panic!("Model node should exist"),
+        }
+    };
+    assert_eq!(
+        err.node_path(),
+        Some(&[(1, "Objects".to_owned()), (0, "Model".to_owned())][..])
+    );
+    assert_eq!(err.attribute_index(), Some(2));
+
+    Ok(())
+}
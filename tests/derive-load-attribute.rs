@@ -0,0 +1,110 @@
+//! Tests for `#[derive(LoadAttribute)]`.
+#![cfg(feature = "derive")]
+
+use fbxcel::pull_parser::{
+    error::{DataError, ErrorKind},
+    v7400::LoadAttribute,
+    Error,
+};
+
+#[derive(Debug, Clone, PartialEq, LoadAttribute)]
+enum Mode {
+    #[fbxcel(string = "ByPolygonVertex")]
+    ByPolygonVertex,
+    #[fbxcel(string = "ByVertex", string = "ByVertice")]
+    ByVertex,
+    #[fbxcel(i32 = 0, i16 = 0)]
+    Zero,
+    #[fbxcel(i32 = -1)]
+    Negative,
+    #[fbxcel(i32, i64)]
+    Index(i64),
+    #[fbxcel(seq_f64)]
+    Values(Vec<f64>),
+    #[allow(dead_code)]
+    Unloadable,
+}
+
+#[derive(Debug, Clone, PartialEq, LoadAttribute)]
+#[fbxcel(loader = "BlobReader", expecting = "blob data")]
+#[fbxcel(binary, string)]
+struct Blob(Vec<u8>);
+
+#[derive(Debug, Clone, PartialEq, LoadAttribute)]
+#[fbxcel(binary)]
+struct Content(Vec<u8>);
+
+/// Returns the error message of `DataError::UnexpectedAttribute`.
+fn unexpected(e: Error) -> (String, String) {
+    assert_eq!(e.kind(), ErrorKind::Data);
+    match e
+        .downcast_ref::<DataError>()
+        .expect("Should be a data error")
+    {
+        DataError::UnexpectedAttribute(expected, got) => (expected.clone(), got.clone()),
+        e => panic!("Unexpected data error: {:?}", e),
+    }
+}
+
+#[test]
+fn enum_loader() -> Result<(), Error> {
+    assert_eq!(
+        ModeLoader.load_string(&b"ByPolygonVertex"[..], 15)?,
+        Mode::ByPolygonVertex
+    );
+    assert_eq!(
+        ModeLoader.load_string(&b"ByVertice"[..], 9)?,
+        Mode::ByVertex
+    );
+    assert_eq!(ModeLoader.load_i16(0)?, Mode::Zero);
+    assert_eq!(ModeLoader.load_i32(0)?, Mode::Zero);
+    assert_eq!(ModeLoader.load_i32(-1)?, Mode::Negative);
+    assert_eq!(ModeLoader.load_i32(42)?, Mode::Index(42));
+    assert_eq!(ModeLoader.load_i64(0)?, Mode::Index(0));
+    assert_eq!(
+        ModeLoader.load_seq_f64(vec![Ok(1.0), Ok(2.0)].into_iter(), 2)?,
+        Mode::Values(vec![1.0, 2.0])
+    );
+
+    assert_eq!(
+        ModeLoader.expecting(),
+        "i16 0, i32, i64, f64 array, string \"ByPolygonVertex\", string \"ByVertex\", \
+         or string \"ByVertice\""
+    );
+    let (_, got) = unexpected(
+        ModeLoader
+            .load_string(&b"AllSame"[..], 7)
+            .expect_err("Should fail"),
+    );
+    assert_eq!(got, "string \"AllSame\"");
+    let (_, got) = unexpected(ModeLoader.load_i16(1).expect_err("Should fail"));
+    assert_eq!(got, "i16 1");
+    let (_, got) = unexpected(ModeLoader.load_f32(1.0).expect_err("Should fail"));
+    assert_eq!(got, "f32");
+
+    Ok(())
+}
+
+#[test]
+fn newtype_loader() -> Result<(), Error> {
+    assert_eq!(
+        BlobReader.load_binary(&b"\x00\x01"[..], 2)?,
+        Blob(vec![0, 1])
+    );
+    assert_eq!(
+        BlobReader.load_string(&b"abc"[..], 3)?,
+        Blob(b"abc".to_vec())
+    );
+    let (expected, _) = unexpected(BlobReader.load_i32(0).expect_err("Should fail"));
+    assert_eq!(expected, "blob data");
+
+    Ok(())
+}
+
+#[test]
+fn expects_binary() {
+    assert!(ContentLoader.expects_binary());
+    // Strings are also accepted, so they should not be decoded.
+    assert!(!BlobReader.expects_binary());
+    assert!(!ModeLoader.expects_binary());
+}
@@ -0,0 +1,144 @@
+//! Tests for tree diff.
+#![cfg(feature = "tree")]
+
+use fbxcel::{
+    low::v7400::{AttributeType, AttributeValue},
+    tree::v7400::diff::{diff_trees, Differ, DifferenceKind},
+    tree_v7400,
+};
+
+#[test]
+fn same_trees() {
+    let tree = tree_v7400! {
+        Node: [1i32, vec![0.5f32, f32::NAN], "foo"] {
+            Child: {},
+        },
+    };
+    assert!(diff_trees(&tree, &tree.clone()).is_empty());
+}
+
+#[test]
+fn report_differences() {
+    let left = tree_v7400! {
+        Header: [1i32] {},
+        Objects: {
+            Model: ["Model::Cube", 1i64, vec![1i32, 2, 3]] {
+                Removed: {},
+            },
+            Geometry: [vec![0.0f64, 1.0], 2.0f32] {},
+        },
+    };
+    let right = tree_v7400! {
+        Header: [2i32] {},
+        Objects: {
+            Model: ["Model::Cube", 1i32, vec![1i32, 5, 3, 4], true] {},
+            Mesh: [vec![0.0f64, 1.5], 2.0f32] {},
+            Added: {},
+        },
+    };
+
+    let diffs = diff_trees(&left, &right)
+        .into_iter()
+        .map(|diff| (diff.path().to_string(), diff.kind().clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diffs,
+        [
+            (
+                "/Header[0]".to_owned(),
+                DifferenceKind::ValueChanged {
+                    index: 0,
+                    left: AttributeValue::I32(1),
+                    right: AttributeValue::I32(2),
+                }
+            ),
+            (
+                "/Objects[1]/Model[0]".to_owned(),
+                DifferenceKind::AttributeTypeChanged {
+                    index: 1,
+                    left: AttributeType::I64,
+                    right: AttributeType::I32,
+                }
+            ),
+            (
+                "/Objects[1]/Model[0]".to_owned(),
+                DifferenceKind::ArrayLengthChanged {
+                    index: 2,
+                    left: 3,
+                    right: 4,
+                }
+            ),
+            (
+                "/Objects[1]/Model[0]".to_owned(),
+                DifferenceKind::ArrayElementChanged {
+                    index: 2,
+                    element: 1,
+                    left: AttributeValue::I32(2),
+                    right: AttributeValue::I32(5),
+                }
+            ),
+            (
+                "/Objects[1]/Model[0]".to_owned(),
+                DifferenceKind::AttributeAdded {
+                    index: 3,
+                    type_: AttributeType::Bool,
+                }
+            ),
+            (
+                "/Objects[1]/Model[0]/Removed[0]".to_owned(),
+                DifferenceKind::NodeRemoved
+            ),
+            (
+                "/Objects[1]/Geometry[1]".to_owned(),
+                DifferenceKind::NodeRenamed {
+                    left: "Geometry".to_owned(),
+                    right: "Mesh".to_owned(),
+                }
+            ),
+            (
+                "/Objects[1]/Geometry[1]".to_owned(),
+                DifferenceKind::ArrayElementChanged {
+                    index: 0,
+                    element: 1,
+                    left: AttributeValue::F64(1.0),
+                    right: AttributeValue::F64(1.5),
+                }
+            ),
+            ("/Objects[1]/Added[2]".to_owned(), DifferenceKind::NodeAdded),
+        ]
+    );
+
+    let diffs = diff_trees(&left, &right);
+    assert_eq!(diffs[5].path().components()[1].name(), "Model");
+    assert_eq!(diffs[5].path().components()[1].index(), 0);
+    assert_eq!(
+        diffs[5].to_string(),
+        "/Objects[1]/Model[0]/Removed[0]: Node removed"
+    );
+}
+
+#[test]
+fn float_tolerance() {
+    let left = tree_v7400! {
+        Node: [1.0f32, vec![0.0f64, 1.0, f64::NAN], 0.0f64] {},
+    };
+    let right = tree_v7400! {
+        Node: [1.001f32, vec![0.0005f64, 1.1, f64::NAN], -0.0f64] {},
+    };
+
+    // Bitwise comparison by default.
+    assert_eq!(diff_trees(&left, &right).len(), 4);
+
+    let diffs = Differ::new()
+        .float_tolerance(0.01)
+        .diff_trees(&left, &right);
+    assert_eq!(diffs.len(), 1);
+    assert!(matches!(
+        diffs[0].kind(),
+        DifferenceKind::ArrayElementChanged {
+            index: 1,
+            element: 1,
+            ..
+        }
+    ));
+}
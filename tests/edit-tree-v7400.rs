@@ -0,0 +1,215 @@
+//! Tests for tree mutation.
+#![cfg(all(feature = "tree", feature = "writer"))]
+
+use std::io::Cursor;
+
+use fbxcel::{
+    low::{v7400::AttributeValue, FbxVersion},
+    pull_parser::any::{from_seekable_reader, AnyParser},
+    tree::v7400::Loader as TreeLoader,
+    tree_v7400,
+    writer::v7400::binary::Writer,
+};
+
+#[test]
+fn edit_structure() {
+    let mut tree = tree_v7400! {
+        A: {
+            A0: {},
+            A1: {
+                A1_0: {},
+            },
+        },
+        B: {},
+        C: {},
+    };
+    let a = tree
+        .root()
+        .first_child_by_name("A")
+        .expect("`A` exists")
+        .node_id();
+    let a1 = tree
+        .root()
+        .first_child_by_name("A")
+        .and_then(|a| a.first_child_by_name("A1"))
+        .expect("`A1` exists")
+        .node_id();
+    let b = tree
+        .root()
+        .first_child_by_name("B")
+        .expect("`B` exists")
+        .node_id();
+    let c = tree
+        .root()
+        .first_child_by_name("C")
+        .expect("`C` exists")
+        .node_id();
+
+    // Move `A1` under `B`.
+    tree.append_existing(b, a1);
+    // Move `C` before `A`.
+    tree.insert_existing_before(a, c);
+    tree.rename(c, "C2");
+    // Detach `A`.
+    tree.detach(a);
+    assert!(tree.node_mut(a).as_handle().parent().is_none());
+    let expected = tree_v7400! {
+        C2: {},
+        B: {
+            A1: {
+                A1_0: {},
+            },
+        },
+    };
+    assert!(tree.strict_eq(&expected), "tree={:?}", tree.debug_tree());
+
+    // Attach `A` again, and remove `B`.
+    tree.prepend_existing(c, a);
+    tree.remove(b);
+    assert!(std::panic::catch_unwind(|| tree.clone().node_mut(b).node_id()).is_err());
+    let expected = tree_v7400! {
+        C2: {
+            A: {
+                A0: {},
+            },
+        },
+    };
+    assert!(tree.strict_eq(&expected), "tree={:?}", tree.debug_tree());
+}
+
+#[test]
+#[should_panic]
+fn move_to_descendant() {
+    let mut tree = tree_v7400! {
+        A: {
+            B: {},
+        },
+    };
+    let a = tree.root().first_child().expect("`A` exists").node_id();
+    let b = tree
+        .root()
+        .first_child()
+        .and_then(|a| a.first_child())
+        .expect("`B` exists")
+        .node_id();
+    tree.append_existing(b, a);
+}
+
+#[test]
+fn edit_attributes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut tree = tree_v7400! {
+        Node: [1i32, "two", 3i64] {
+            Child: [true] {},
+        },
+    };
+    let node = tree.root().first_child().expect("`Node` exists").node_id();
+
+    assert_eq!(tree.set_attribute(node, 0, 10i32), AttributeValue::I32(1));
+    tree.insert_attribute(node, 1, 1.5f64);
+    assert_eq!(tree.remove_attribute(node, 2), AttributeValue::from("two"));
+    {
+        let mut node = tree.node_mut(node);
+        node.set_name("Renamed");
+        node.attributes_mut().push("four".into());
+        let mut child = node.append_new("NewChild");
+        child.attributes_mut().push(5i16.into());
+    }
+    let child = tree
+        .root()
+        .first_child()
+        .and_then(|node| node.first_child_by_name("Child"))
+        .expect("`Child` exists")
+        .node_id();
+    tree.clear_attributes(child);
+
+    let expected = tree_v7400! {
+        Renamed: [10i32, 1.5f64, 3i64, "four"] {
+            Child: {},
+            NewChild: [5i16] {},
+        },
+    };
+    assert!(tree.strict_eq(&expected), "tree={:?}", tree.debug_tree());
+
+    // Write the edited tree and load it again.
+    let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+    writer.write_tree(&tree)?;
+    let bin = writer.finalize_and_flush(&Default::default())?.into_inner();
+    let mut parser = match from_seekable_reader(Cursor::new(bin))? {
+        AnyParser::V7400(parser) => parser,
+        _ => panic!("Generated data should be parsable with v7400 parser"),
+    };
+    let (loaded, _) = TreeLoader::new().load(&mut parser)?;
+    assert!(loaded.strict_eq(&expected));
+
+    Ok(())
+}
+
+#[test]
+fn import_subtree() {
+    let src = tree_v7400! {
+        Objects: {
+            Model: ["Model::Cube"] {
+                Version: [232i32] {}
+                Properties70: {
+                    P: ["Lcl Translation"] {}
+                },
+            },
+            Geometry: [vec![1i32, 2, 3]] {},
+        },
+    };
+    let mut dest = tree_v7400! {
+        Objects: {
+            Existing: {},
+        },
+    };
+    let src_model = src
+        .root()
+        .first_child_by_name("Objects")
+        .and_then(|objects| objects.first_child_by_name("Model"))
+        .expect("`Model` exists");
+    let src_geometry = src_model.next_sibling().expect("`Geometry` exists");
+    let dest_objects = dest
+        .root()
+        .first_child()
+        .expect("`Objects` exists")
+        .node_id();
+
+    let new_model = dest.import_subtree(dest_objects, src_model);
+    assert_eq!(new_model.to_handle(&dest).name(), "Model");
+    let (new_geometry, mapping) = dest.import_subtree_with_mapping(dest_objects, src_geometry);
+    assert_eq!(mapping.len(), 1);
+    assert_eq!(mapping[&src_geometry.node_id()], new_geometry);
+
+    let expected = tree_v7400! {
+        Objects: {
+            Existing: {},
+            Model: ["Model::Cube"] {
+                Version: [232i32] {}
+                Properties70: {
+                    P: ["Lcl Translation"] {}
+                },
+            },
+            Geometry: [vec![1i32, 2, 3]] {},
+        },
+    };
+    assert!(dest.strict_eq(&expected), "tree={:?}", dest.debug_tree());
+
+    // Import the whole document.
+    let mut merged = tree_v7400! {};
+    let merged_root = merged.root().node_id();
+    let (_, mapping) = merged.import_subtree_with_mapping(
+        merged_root,
+        src.root().first_child().expect("`Objects` exists"),
+    );
+    assert_eq!(mapping.len(), 6);
+    for (old, new) in mapping {
+        let old = old.to_handle(&src);
+        let new = new.to_handle(&merged);
+        assert_eq!(old.name(), new.name());
+        assert_eq!(
+            old.parent().map(|p| p.name()),
+            new.parent().map(|p| p.name())
+        );
+    }
+    assert!(merged.strict_eq(&src));
+}
@@ -0,0 +1,92 @@
+//! Tests for tree loader with node filter.
+#![cfg(all(feature = "tree", feature = "writer"))]
+
+use std::sync::{Arc, Mutex};
+
+use fbxcel::{
+    low::FbxVersion,
+    tree::v7400::{FilterAction, Loader as TreeLoader},
+    tree_v7400,
+};
+
+use self::common::{parser, write_tree};
+
+mod common;
+
+/// Load only the nodes accepted by the filter.
+#[test]
+fn tree_load_with_filter() -> Result<(), Box<dyn std::error::Error>> {
+    let tree = tree_v7400! {
+        Header: [1i32] {
+            Child: [2i32] {},
+        },
+        Objects: [vec![1i32, 2, 3]] {
+            Model: ["Model::Cube"] {
+                Version: [232i32] {}
+            },
+            Geometry: [vec![0i64; 100]] {
+                Vertices: [vec![1i32; 100]] {}
+            },
+            Model: ["Model::Sphere"] {},
+        },
+        Connections: {
+            C: ["OO", 1i64, 0i64] {}
+        },
+    };
+    let bin = write_tree(&tree, FbxVersion::V7_4, &Default::default());
+
+    let mut parser = parser(&bin);
+    let visited = Arc::new(Mutex::new(Vec::new()));
+    let loader = TreeLoader::new().with_filter({
+        let visited = visited.clone();
+        move |path, name| {
+            visited
+                .lock()
+                .expect("Should not be poisoned")
+                .push(format!("{}/{}", path.join("/"), name));
+            match (path, name) {
+                ([], "Objects") => FilterAction::KeepWithoutAttributes,
+                ([], "Connections") => FilterAction::Keep,
+                ([], _) => FilterAction::Skip,
+                ([objects], "Model") if objects == "Objects" => FilterAction::Keep,
+                ([objects], _) if objects == "Objects" => FilterAction::Skip,
+                _ => FilterAction::Keep,
+            }
+        }
+    });
+    let (loaded, footer_res) = loader.load(&mut parser)?;
+    assert!(footer_res.is_ok());
+
+    let expected = tree_v7400! {
+        Objects: {
+            Model: ["Model::Cube"] {
+                Version: [232i32] {}
+            },
+            Model: ["Model::Sphere"] {},
+        },
+        Connections: {
+            C: ["OO", 1i64, 0i64] {}
+        },
+    };
+    assert!(
+        loaded.strict_eq(&expected),
+        "loaded={:?}",
+        loaded.debug_tree()
+    );
+    // Descendants of skipped nodes are not passed to the filter.
+    assert_eq!(
+        *visited.lock().expect("Should not be poisoned"),
+        [
+            "/Header",
+            "/Objects",
+            "Objects/Model",
+            "Objects/Model/Version",
+            "Objects/Geometry",
+            "Objects/Model",
+            "/Connections",
+            "Connections/C",
+        ]
+    );
+
+    Ok(())
+}
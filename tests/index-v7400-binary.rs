@@ -0,0 +1,131 @@
+//! Tests for node offset index.
+#![cfg(all(feature = "tree", feature = "writer"))]
+
+use std::io::{Cursor, Seek, SeekFrom};
+
+use fbxcel::{
+    low::FbxVersion,
+    pull_parser::{
+        error::OperationError,
+        v7400::{index::NodeIndex, Event},
+    },
+    tree::v7400::Loader as TreeLoader,
+    tree_v7400,
+};
+
+use self::common::write_tree;
+
+mod common;
+
+#[test]
+fn build_index() -> Result<(), Box<dyn std::error::Error>> {
+    let tree = tree_v7400! {
+        Header: [1i32] {},
+        Objects: {
+            Model: ["Model::Cube", "Mesh"] {
+                Version: [232i32] {}
+                Vertices: [vec![1i32, 2, 3]] {}
+            },
+            Empty: {},
+        },
+        Connections: ["C"] {
+            C: ["OO", 1i64, 0i64] {}
+        },
+    };
+    for &version in &[FbxVersion::V7_4, FbxVersion::V7_5] {
+        let bin = write_tree(&tree, version, &Default::default());
+        // Put some data before the FBX binary.
+        let mut data = vec![0xff; 7];
+        data.extend(&bin);
+        let mut reader = Cursor::new(data);
+        reader.seek(SeekFrom::Start(7))?;
+
+        let index = NodeIndex::build(&mut reader)?;
+        assert_eq!(index.fbx_version(), version);
+        let summary = index
+            .entries()
+            .iter()
+            .map(|entry| (entry.name(), entry.depth(), entry.parent()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("Header", 1, None),
+                ("Objects", 1, None),
+                ("Model", 2, Some(1)),
+                ("Version", 3, Some(2)),
+                ("Vertices", 3, Some(2)),
+                ("Empty", 2, Some(1)),
+                ("Connections", 1, None),
+                ("C", 2, Some(6)),
+            ]
+        );
+        assert_eq!(index.children(None).collect::<Vec<_>>(), [0, 1, 6]);
+        assert_eq!(index.children(Some(1)).collect::<Vec<_>>(), [2, 5]);
+        assert_eq!(index.children(Some(3)).count(), 0);
+
+        let model = &index.entries()[2];
+        assert_eq!(model.num_attributes(), 2);
+        // Type code (1 byte), length (4 bytes), and the content for each.
+        assert_eq!(
+            model.bytelen_attributes(),
+            (5 + "Model::Cube".len() + 5 + "Mesh".len()) as u64
+        );
+        let attrs_range = model.attributes_range();
+        assert_eq!(
+            attrs_range.end - attrs_range.start,
+            model.bytelen_attributes()
+        );
+        let attrs = &bin[(attrs_range.start as usize)..(attrs_range.end as usize)];
+        assert_eq!(&attrs[..5], b"S\x0b\x00\x00\x00");
+        // Descendants are inside the range of the parent.
+        for entry in &index.entries()[3..5] {
+            assert!(model.byte_range().start < entry.start_offset());
+            assert!(entry.end_offset() <= model.byte_range().end);
+        }
+        // `Connections` has a node end marker after `C`.
+        assert!(index.entries()[7].end_offset() < index.entries()[6].end_offset());
+
+        // Parse `Objects` subtree.
+        let mut parser = index.parser_at(&mut reader, 1)?;
+        let (subtree, footer) = TreeLoader::new().load(&mut parser)?;
+        match footer {
+            Err(e) => assert!(matches!(
+                e.downcast_ref::<OperationError>(),
+                Some(OperationError::FooterUnavailable)
+            )),
+            Ok(_) => panic!("Subtree parser should not read the footer"),
+        }
+        let expected = tree_v7400! {
+            Objects: {
+                Model: ["Model::Cube", "Mesh"] {
+                    Version: [232i32] {}
+                    Vertices: [vec![1i32, 2, 3]] {}
+                },
+                Empty: {},
+            },
+        };
+        assert!(subtree.strict_eq(&expected));
+
+        // Parse a leaf node without node end marker.
+        let mut parser = index.parser_at(&mut reader, 7)?;
+        match parser.next_event()? {
+            Event::StartNode(start) => assert_eq!(start.name(), "C"),
+            _ => panic!("Expected `StartNode` event"),
+        }
+        assert!(matches!(parser.next_event()?, Event::EndNode));
+        assert!(matches!(parser.next_event()?, Event::EndFbx(Err(_))));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn empty_document() -> Result<(), Box<dyn std::error::Error>> {
+    let bin = write_tree(&tree_v7400! {}, FbxVersion::V7_4, &Default::default());
+    let index = NodeIndex::build(Cursor::new(bin))?;
+    assert!(index.is_empty());
+    assert_eq!(index.children(None).count(), 0);
+
+    Ok(())
+}
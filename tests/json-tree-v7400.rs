@@ -0,0 +1,219 @@
+//! Tests for JSON import and export of trees.
+#![cfg(feature = "tree")]
+
+use fbxcel::{
+    low::v7400::AttributeValue,
+    tree::v7400::json::{self, Error},
+    tree_v7400,
+};
+
+#[test]
+fn round_trip_all_types() -> Result<(), Error> {
+    let mut tree = tree_v7400! {
+        Scalars: [true, -3i16, i32::MIN, i64::MAX, -0.0f32, 0.1f64] {
+            Floats: [
+                f32::from_bits(0x7fc0_1234),
+                f64::from_bits(0xfff8_0000_0000_abcd),
+                f32::INFINITY,
+                f64::MIN_POSITIVE
+            ] {},
+        },
+        Arrays: [
+            vec![true, false],
+            vec![1i32, -2],
+            vec![i64::MIN],
+            vec![0.5f32, f32::NAN],
+            vec![-0.0f64],
+            Vec::<i32>::new()
+        ] {},
+        Strings: ["", "quote\" backslash\\ newline\n tab\t nul\u{0}\u{1} unicode\u{3042}\u{1f600}"] {},
+        Binaries: [vec![0u8, 255, 128], Vec::<u8>::new()] {},
+    };
+    let root_id = tree.root().node_id();
+    tree.append_new(root_id, "Node name with \"quotes\"");
+
+    let json = json::to_string(&tree);
+    let imported = json::from_str(&json)?;
+    assert!(
+        imported.strict_eq(&tree),
+        "json={}, imported={:?}",
+        json,
+        imported.debug_tree()
+    );
+    assert!(json.contains(r#"{"i16": -3}"#));
+    assert!(json.contains(r#"{"f32": "0x80000000"}"#));
+    assert!(json.contains(r#"{"f32": "0x7fc01234"}"#));
+    assert!(json.contains(r#"{"arr_i64": [-9223372036854775808]}"#));
+    assert!(json.contains(r#"{"binary": "AP+A"}"#));
+    assert!(json.contains(r#"nul\u0000\u0001"#));
+
+    let mut bytes = Vec::new();
+    json::to_writer(&tree, &mut bytes).expect("Should never fail");
+    assert_eq!(bytes, json.as_bytes());
+    assert!(json::from_reader(&bytes[..])?.strict_eq(&tree));
+
+    Ok(())
+}
+
+#[test]
+fn import_handwritten_json() -> Result<(), Error> {
+    let json = r#"
+        [
+            {
+                "children": [{"name": "Child"}],
+                "name": "Node",
+                "attributes": [
+                    {"f32": 1.5}, {"f64": -2e3}, {"arr_f32": [1, "0x40000000"]},
+                    {"string": "あ😀\/"}
+                ]
+            }
+        ]
+    "#;
+    let imported = json::from_str(json)?;
+    let expected = tree_v7400! {
+        Node: [1.5f32, -2000.0f64, vec![1.0f32, 2.0], "\u{3042}\u{1f600}/"] {
+            Child: {},
+        },
+    };
+    assert!(
+        imported.strict_eq(&expected),
+        "imported={:?}",
+        imported.debug_tree()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn import_errors() {
+    /// Returns the line, the column, and the message of the error.
+    fn error_of(json: &str) -> (usize, usize, String) {
+        match json::from_str(json).expect_err("Should fail") {
+            Error::Syntax {
+                line,
+                column,
+                message,
+            } => (line, column, message),
+            e => panic!("Unexpected error: {}", e),
+        }
+    }
+
+    assert_eq!(
+        error_of("[\n  {\"name\": \"A\", \"attributes\": [{\"u8\": 1}]}\n]"),
+        (2, 33, "Unknown attribute type `u8`".to_owned())
+    );
+    assert_eq!(
+        error_of(r#"[{"name": "A", "attributes": [{"i16": 40000}]}]"#),
+        (1, 39, "Invalid integer `40000`".to_owned())
+    );
+    assert_eq!(
+        error_of(r#"[{"name": "A", "attributes": [{"i32": 1, "i64": 2}]}]"#),
+        (1, 42, "Attribute should have only one field".to_owned())
+    );
+    assert_eq!(
+        error_of(r#"[{"name": "A", "attributes": [{"binary": "AAA"}]}]"#),
+        (1, 42, "Invalid base64 string".to_owned())
+    );
+    assert_eq!(
+        error_of(r#"[{"attributes": []}]"#),
+        (1, 2, "Missing field `name`".to_owned())
+    );
+    assert_eq!(error_of("[] []").2, "Trailing characters after the tree");
+    assert_eq!(error_of(r#"[{"name": "A"#).2, "Unterminated string");
+
+    // Exact types are required.
+    let tree =
+        json::from_str(r#"[{"name": "A", "attributes": [{"i64": 1}]}]"#).expect("Should be valid");
+    let node = tree.root().first_child().expect("Should have a child");
+    assert!(node.attributes()[0].strict_eq(&AttributeValue::I64(1)));
+}
+
+#[test]
+fn deep_nesting() -> Result<(), Error> {
+    const DEPTH: usize = 10_000;
+
+    let json = format!(
+        "[{}{}{}]",
+        r#"{"name": "Node", "children": ["#.repeat(DEPTH),
+        r#"{"name": "Leaf", "attributes": [{"i32": 42}]}"#,
+        "]}".repeat(DEPTH)
+    );
+    let tree = json::from_str(&json)?;
+    let mut depth = 0;
+    let mut node = tree.root();
+    while let Some(child) = node.first_child() {
+        depth += 1;
+        node = child;
+    }
+    assert_eq!(depth, DEPTH + 1);
+    assert_eq!(node.name(), "Leaf");
+    assert!(node.attributes()[0].strict_eq(&AttributeValue::I32(42)));
+
+    // Deeply nested trees can be exported.
+    let exported = json::to_string(&tree);
+    let leaf = exported
+        .lines()
+        .find(|line| line.contains("Leaf"))
+        .expect("Leaf should be exported");
+    assert_eq!(
+        leaf,
+        format!(
+            r#"{}{{"name": "Leaf", "attributes": [{{"i32": 42}}], "children": []}}"#,
+            "  ".repeat(DEPTH + 1)
+        )
+    );
+
+    // Errors are detected in deeply nested nodes.
+    let broken = json.replace(r#""name": "Leaf", "#, "");
+    match json::from_str(&broken) {
+        Err(Error::Syntax { message, .. }) => assert_eq!(message, "Missing field `name`"),
+        Err(e) => panic!("Unexpected error: {}", e),
+        Ok(_) => panic!("Should fail"),
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "writer")]
+#[test]
+fn fbx_json_fbx() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Cursor;
+
+    use fbxcel::{
+        low::FbxVersion,
+        pull_parser::any::{from_seekable_reader, AnyParser},
+        tree::v7400::{Loader, Tree},
+        writer::v7400::binary::Writer,
+    };
+
+    /// Writes the tree as FBX binary.
+    fn to_fbx(tree: &Tree) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+        writer.write_tree(tree)?;
+        Ok(writer.finalize_and_flush(&Default::default())?.into_inner())
+    }
+
+    let tree = tree_v7400! {
+        Header: [1i32, 0.25f64, "name\u{0}\u{1}class"] {
+            Child: [true, 2i16] {},
+        },
+        Objects: {
+            Geometry: [vec![1i32, -2], vec![3i64], vec![false, true]] {
+                Blob: [vec![0u8, 255]] {},
+            },
+        },
+    };
+    let fbx = to_fbx(&tree)?;
+
+    let mut parser = match from_seekable_reader(Cursor::new(&fbx[..]))? {
+        AnyParser::V7400(parser) => parser,
+        _ => panic!("Generated data should be parsable with v7400 parser"),
+    };
+    let (loaded, _) = Loader::new().load(&mut parser)?;
+
+    let imported = json::from_str(&json::to_string(&loaded))?;
+    assert!(imported.strict_eq(&tree));
+    assert_eq!(to_fbx(&imported)?, fbx);
+
+    Ok(())
+}
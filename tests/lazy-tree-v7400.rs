@@ -0,0 +1,168 @@
+//! Tests for lazily loaded tree.
+#![cfg(all(feature = "tree", feature = "writer"))]
+
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use fbxcel::{
+    low::{
+        v7400::{ArrayAttributeEncoding, AttributeValue},
+        FbxVersion,
+    },
+    tree::v7400::{LazyNodeHandle, LazyTree, NodeHandle},
+    tree_v7400,
+    writer::v7400::binary::Writer,
+};
+
+use self::common::{offsets, write_tree};
+
+mod common;
+
+/// Checks the lazy node has the same data as the eager node, recursively.
+fn assert_same_node<R: Read + Seek>(
+    lazy: &LazyNodeHandle<'_, R>,
+    eager: &NodeHandle<'_>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    assert_eq!(lazy.name(), eager.name());
+    assert_eq!(lazy.num_attributes(), eager.attributes().len() as u64);
+    let attrs = lazy.attributes()?;
+    assert_eq!(attrs.len(), eager.attributes().len());
+    for (lazy_attr, eager_attr) in attrs.iter().zip(eager.attributes()) {
+        assert!(lazy_attr.strict_eq(eager_attr), "node={:?}", eager.name());
+    }
+
+    let lazy_children = lazy.children()?;
+    assert_eq!(lazy_children.len(), eager.children().count());
+    for (lazy_child, eager_child) in lazy_children.zip(eager.children()) {
+        assert_eq!(
+            lazy_child.parent().map(|p| p.name().to_owned()).as_deref(),
+            Some(lazy.name())
+        );
+        assert_same_node(&lazy_child, &eager_child)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn same_as_eager_tree() -> Result<(), Box<dyn std::error::Error>> {
+    let tree = tree_v7400! {
+        Header: [1i32, "header"] {},
+        Objects: {
+            Model: ["Model::Cube", "Mesh"] {
+                Version: [232i32] {}
+                Vertices: [vec![1i32, 2, 3], vec![true, false], 1.5f64] {}
+                Vertices: [vec![4i64, 5]] {}
+            },
+            Empty: {},
+        },
+        Connections: ["C"] {
+            C: ["OO", 1i64, 0i64] {}
+            Blob: [vec![0u8, 1, 2]] {}
+        },
+    };
+    for &version in &[FbxVersion::V7_4, FbxVersion::V7_5] {
+        let bin = write_tree(&tree, version, &Default::default());
+        // Put some data before the FBX binary.
+        let mut data = vec![0xff; 5];
+        data.extend(&bin);
+        let mut reader = Cursor::new(data);
+        reader.seek(SeekFrom::Start(5))?;
+
+        let lazy = LazyTree::from_seekable_reader(reader)?;
+        assert_eq!(lazy.fbx_version(), version);
+        assert!(lazy.root().parent().is_none());
+        assert_same_node(&lazy.root(), &tree.root())?;
+
+        // Navigation by name.
+        let objects = lazy
+            .root()
+            .first_child_by_name("Objects")?
+            .expect("`Objects` should exist");
+        let model = objects
+            .first_child_by_name("Model")?
+            .expect("`Model` should exist");
+        assert_eq!(model.children_by_name("Vertices")?.count(), 2);
+        assert!(model.first_child_by_name("Unknown")?.is_none());
+        assert_eq!(
+            *model.attribute(1)?.expect("Should have the attribute"),
+            AttributeValue::String("Mesh".to_owned())
+        );
+        assert!(model.attribute(2)?.is_none());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn array_cache() -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+    for i in 0..4 {
+        let mut attrs = writer.new_node("Array")?;
+        attrs.append_i32(i)?;
+        // 400 bytes for each array.
+        attrs.append_arr_i32_from_iter(ArrayAttributeEncoding::Zlib, (0..100).map(|v| v * i))?;
+        writer.close_node()?;
+    }
+    let bin = writer.finalize_and_flush(&Default::default())?.into_inner();
+
+    let mut lazy = LazyTree::from_seekable_reader(Cursor::new(bin))?;
+    lazy.set_array_cache_capacity(1000);
+    assert_eq!(lazy.array_cache_capacity(), 1000);
+    let nodes = lazy.root().children()?.collect::<Vec<_>>();
+    assert_eq!(nodes.len(), 4);
+
+    // Scalar attributes do not use the cache.
+    assert_eq!(
+        *nodes[0].attribute(0)?.expect("Should have the attribute"),
+        AttributeValue::I32(0)
+    );
+    assert_eq!(lazy.cached_array_bytes(), 0);
+
+    for (i, node) in nodes.iter().enumerate() {
+        let arr = node.attribute(1)?.expect("Should have the attribute");
+        let expected = (0..100).map(|v| v * i as i32).collect::<Vec<_>>();
+        assert_eq!(*arr, AttributeValue::ArrI32(expected));
+        assert_eq!(lazy.cached_array_bytes(), 400 * (i + 1).min(2));
+    }
+    // Evicted arrays are loaded again.
+    assert_eq!(nodes[0].attributes()?.len(), 2);
+    assert_eq!(lazy.cached_array_bytes(), 800);
+
+    lazy.clear_array_cache();
+    assert_eq!(lazy.cached_array_bytes(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn corrupt_node_length() -> Result<(), Box<dyn std::error::Error>> {
+    let tree = tree_v7400! {
+        Parent: {
+            Child: [1i32] {},
+        },
+    };
+
+    // Child node ends before its start.
+    let mut bin = write_tree(&tree, FbxVersion::V7_4, &Default::default());
+    let (child_start, _) = offsets(&bin, "Child");
+    bin[child_start..(child_start + 4)].copy_from_slice(&0u32.to_le_bytes());
+    let lazy = LazyTree::from_seekable_reader(Cursor::new(bin))?;
+    let parent = lazy
+        .root()
+        .first_child_by_name("Parent")?
+        .expect("`Parent` should exist");
+    assert!(parent.children().is_err());
+
+    // Attributes end overflows.
+    let mut bin = write_tree(&tree, FbxVersion::V7_5, &Default::default());
+    let (child_start, _) = offsets(&bin, "Child");
+    bin[(child_start + 16)..(child_start + 24)].copy_from_slice(&u64::MAX.to_le_bytes());
+    let lazy = LazyTree::from_seekable_reader(Cursor::new(bin))?;
+    let parent = lazy
+        .root()
+        .first_child_by_name("Parent")?
+        .expect("`Parent` should exist");
+    assert!(parent.children().is_err());
+
+    Ok(())
+}
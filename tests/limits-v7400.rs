@@ -0,0 +1,359 @@
+//! Tests for resource limits of the parser.
+#![cfg(feature = "writer")]
+
+use std::io::Cursor;
+
+use fbxcel::{
+    low::{v7400::ArrayAttributeEncoding, FbxVersion},
+    pull_parser::{
+        error::{DataError, Limit},
+        v7400::{ascii, attribute::loaders::DirectLoader, Event, ParserLimits},
+        Error, ParserSource,
+    },
+    writer::v7400::binary::Writer,
+};
+
+use self::common::{offsets, parser, sample};
+
+mod common;
+
+/// Reads all events and attributes with the given limits.
+///
+/// Returns the number of nodes read before the error, and the error.
+fn parse(bin: &[u8], limits: ParserLimits) -> (usize, Result<(), Error>) {
+    let mut parser = parser(bin);
+    parser.set_limits(limits);
+    assert_eq!(parser.limits(), &limits);
+    parse_events(&mut parser)
+}
+
+/// Reads all events and attributes from the parser.
+fn parse_events<R: ParserSource>(
+    parser: &mut fbxcel::pull_parser::v7400::Parser<R>,
+) -> (usize, Result<(), Error>) {
+    let mut count = 0;
+    let res = (|| loop {
+        match parser.next_event()? {
+            Event::StartNode(start) => {
+                count += 1;
+                let mut attrs = start.attributes();
+                while attrs.load_next(DirectLoader)?.is_some() {}
+            }
+            Event::EndNode => {}
+            Event::EndFbx(footer) => return footer.map(drop),
+        }
+    })();
+    (count, res)
+}
+
+/// Returns the exceeded limit and the value.
+fn limit_exceeded(res: Result<(), Error>) -> (Limit, u64, Error) {
+    let err = res.expect_err("Limit should be exceeded");
+    match err.downcast_ref::<DataError>() {
+        Some(DataError::LimitExceeded(limit, value)) => (*limit, *value, err),
+        _ => panic!("Unexpected error: {:?}", err),
+    }
+}
+
+/// Returns the node names in the path of the error position.
+fn node_path(err: &Error) -> Vec<&str> {
+    err.position()
+        .expect("Error should have a position")
+        .node_path()
+        .iter()
+        .map(|(_, name)| name.as_str())
+        .collect()
+}
+
+#[test]
+fn unlimited_by_default() {
+    let bin = sample();
+    let (count, res) = parse(&bin, ParserLimits::default());
+    res.expect("Should be parsed successfully");
+    assert_eq!(count, 6);
+    assert_eq!(ParserLimits::unlimited(), ParserLimits::default());
+}
+
+#[test]
+fn exactly_at_limits() {
+    let bin = sample();
+    let limits = ParserLimits {
+        max_depth: Some(2),
+        max_attributes_per_node: Some(2),
+        max_array_elements: Some(16),
+        max_decompressed_bytes: Some(16 * 8),
+        max_string_len: Some(12),
+        max_node_count: Some(6),
+    };
+    parse(&bin, limits)
+        .1
+        .expect("Should be parsed successfully");
+}
+
+#[test]
+fn max_depth() {
+    let bin = sample();
+    let (count, res) = parse(
+        &bin,
+        ParserLimits {
+            max_depth: Some(1),
+            ..Default::default()
+        },
+    );
+    assert_eq!(count, 1);
+    let (limit, value, err) = limit_exceeded(res);
+    assert_eq!((limit, value), (Limit::Depth, 2));
+    assert_eq!(node_path(&err), vec!["Container"]);
+}
+
+#[test]
+fn max_attributes_per_node() {
+    let bin = sample();
+    let (count, res) = parse(
+        &bin,
+        ParserLimits {
+            max_attributes_per_node: Some(1),
+            ..Default::default()
+        },
+    );
+    assert_eq!(count, 2);
+    let (limit, value, _) = limit_exceeded(res);
+    assert_eq!((limit, value), (Limit::AttributesPerNode, 2));
+}
+
+#[test]
+fn max_array_elements() {
+    let bin = sample();
+    let (_, res) = parse(
+        &bin,
+        ParserLimits {
+            max_array_elements: Some(15),
+            ..Default::default()
+        },
+    );
+    let (limit, value, err) = limit_exceeded(res);
+    assert_eq!((limit, value), (Limit::ArrayElements, 16));
+    assert_eq!(node_path(&err), vec!["Container", "Compressed"]);
+    assert_eq!(err.position().unwrap().attribute_index(), Some(0));
+}
+
+#[test]
+fn max_decompressed_bytes() {
+    let bin = sample();
+    let (count, res) = parse(
+        &bin,
+        ParserLimits {
+            max_decompressed_bytes: Some(16 * 8 - 1),
+            ..Default::default()
+        },
+    );
+    assert_eq!(count, 4);
+    let (limit, value, err) = limit_exceeded(res);
+    assert_eq!((limit, value), (Limit::DecompressedBytes, 16 * 8));
+    assert_eq!(node_path(&err), vec!["Container", "Compressed"]);
+}
+
+#[test]
+fn max_decompressed_bytes_is_cumulative() {
+    let mut writer =
+        Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4).expect("Failed to create writer");
+    for _ in 0..2 {
+        writer
+            .new_node("Compressed")
+            .expect("Failed to create node")
+            .append_arr_f64_from_iter(Some(ArrayAttributeEncoding::Zlib), (0..8).map(f64::from))
+            .expect("Failed to append attribute");
+        writer.close_node().expect("Failed to close node");
+    }
+    let bin = writer
+        .finalize_and_flush(&Default::default())
+        .expect("Failed to finalize")
+        .into_inner();
+
+    // The first compressed array fits the limit, but the second does not.
+    let (count, res) = parse(
+        &bin,
+        ParserLimits {
+            max_decompressed_bytes: Some(8 * 8 + 1),
+            ..Default::default()
+        },
+    );
+    assert_eq!(count, 2);
+    let (limit, value, err) = limit_exceeded(res);
+    assert_eq!((limit, value), (Limit::DecompressedBytes, 2 * 8 * 8));
+    assert_eq!(node_path(&err), vec!["Compressed"]);
+    assert_eq!(err.position().unwrap().node_path()[0].0, 1);
+}
+
+#[test]
+fn decompression_bomb() {
+    let mut bin = sample();
+    let (_, attrs_start) = offsets(&bin, "Compressed");
+    // Declare `u32::MAX` elements.
+    bin[(attrs_start + 1)..(attrs_start + 5)].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let (_, res) = parse(
+        &bin,
+        ParserLimits {
+            max_decompressed_bytes: Some(64 * 1024 * 1024),
+            ..Default::default()
+        },
+    );
+    let (limit, value, _) = limit_exceeded(res);
+    assert_eq!(
+        (limit, value),
+        (Limit::DecompressedBytes, u64::from(u32::MAX) * 8)
+    );
+}
+
+#[test]
+fn max_string_len() {
+    let bin = sample();
+    let (_, res) = parse(
+        &bin,
+        ParserLimits {
+            max_string_len: Some(11),
+            ..Default::default()
+        },
+    );
+    let (limit, value, err) = limit_exceeded(res);
+    assert_eq!((limit, value), (Limit::StringLength, 12));
+    assert_eq!(node_path(&err), vec!["Container", "Name"]);
+}
+
+#[test]
+fn max_node_count() {
+    let bin = sample();
+    let (count, res) = parse(
+        &bin,
+        ParserLimits {
+            max_node_count: Some(4),
+            ..Default::default()
+        },
+    );
+    assert_eq!(count, 4);
+    let (limit, value, _) = limit_exceeded(res);
+    assert_eq!((limit, value), (Limit::NodeCount, 5));
+}
+
+#[test]
+fn limits_for_ascii() {
+    const DOCUMENT: &str = r#"; FBX 7.4.0 project file
+Container:  {
+    Child: "Hello", *3 {
+        a: 1,2,3
+    }
+}
+"#;
+    let cases = [
+        (
+            ParserLimits {
+                max_depth: Some(1),
+                ..Default::default()
+            },
+            Limit::Depth,
+            2,
+        ),
+        (
+            ParserLimits {
+                max_attributes_per_node: Some(1),
+                ..Default::default()
+            },
+            Limit::AttributesPerNode,
+            2,
+        ),
+        (
+            ParserLimits {
+                max_array_elements: Some(2),
+                ..Default::default()
+            },
+            Limit::ArrayElements,
+            3,
+        ),
+        (
+            ParserLimits {
+                max_string_len: Some(4),
+                ..Default::default()
+            },
+            Limit::StringLength,
+            5,
+        ),
+    ];
+    for (limits, expected_limit, expected_value) in cases.iter().copied() {
+        let mut parser = ascii::from_reader(DOCUMENT.as_bytes()).expect("Failed to create parser");
+        parser.set_limits(limits);
+        let (_, res) = parse_events(&mut parser);
+        let (limit, value, _) = limit_exceeded(res);
+        assert_eq!((limit, value), (expected_limit, expected_value));
+    }
+}
+
+#[test]
+fn limits_for_ascii_are_checked_while_reading() {
+    // Each document is truncated, so the parser fails with an I/O error if
+    // it tries to read the whole data before checking the limits.
+    let cases = [
+        // Huge declared array length.
+        (
+            "Node: *4294967295 {\n    a: 0,0,0",
+            Limit::ArrayElements,
+            4294967295,
+        ),
+        // More elements than declared.
+        ("Node: *2 {\n    a: 0,0,0,0,0,0", Limit::ArrayElements, 5),
+        // Long string without the closing quote.
+        (
+            "Node: \"01234567890123456789012345678",
+            Limit::StringLength,
+            4 * 6 + 1,
+        ),
+        // Escape sequences are counted as unescaped characters.
+        (
+            "Node: \"&quot;&quot;&quot;&quot;\", \"&quot;&quot;abc\"",
+            Limit::StringLength,
+            5,
+        ),
+        // Too many attributes.
+        ("Node: 0, 1, 2, 3, 4, 5", Limit::AttributesPerNode, 5),
+    ];
+    let limits = ParserLimits {
+        max_attributes_per_node: Some(4),
+        max_array_elements: Some(4),
+        max_string_len: Some(4),
+        ..Default::default()
+    };
+    for (body, expected_limit, expected_value) in cases.iter().copied() {
+        let document = format!("; FBX 7.4.0 project file\n{}", body);
+        let mut parser = ascii::from_reader(document.as_bytes()).expect("Failed to create parser");
+        parser.set_limits(limits);
+        let (count, res) = parse_events(&mut parser);
+        assert_eq!(count, 0, "{:?}", body);
+        let (limit, value, _) = limit_exceeded(res);
+        assert_eq!(
+            (limit, value),
+            (expected_limit, expected_value),
+            "{:?}",
+            body
+        );
+    }
+}
+
+#[cfg(feature = "tree")]
+#[test]
+fn tree_loader() {
+    use fbxcel::tree::v7400::{LoadError, Loader};
+
+    let bin = sample();
+    let mut parser = parser(&bin);
+    parser.set_limits(ParserLimits {
+        max_node_count: Some(2),
+        ..Default::default()
+    });
+    match Loader::new().load(&mut parser) {
+        Err(LoadError::Parser(e)) => assert!(matches!(
+            e.downcast_ref::<DataError>(),
+            Some(DataError::LimitExceeded(Limit::NodeCount, 3))
+        )),
+        res => panic!("Unexpected result: {:?}", res.map(drop)),
+    }
+}
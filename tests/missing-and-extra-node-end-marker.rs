@@ -179,7 +179,7 @@ fn extra_node_end_marker() -> Result<(), Box<dyn std::error::Error>> {
                     // Node name.
                     vec.extend(INVALID_NODE);
                     // An attribute.
-                    vec.extend(&[b'C', b'T']);
+                    vec.extend(b"CT");
                     // Extra node end marker.
                     vec.extend(&[0; 13]);
                     let end_pos = (vec.len() as u32).to_le_bytes();
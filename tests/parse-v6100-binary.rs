@@ -0,0 +1,75 @@
+//! Tests for FBX 6.x binary parser.
+#![cfg(all(feature = "tree", feature = "writer"))]
+
+use std::io::Cursor;
+
+use fbxcel::{
+    low::FbxVersion,
+    pull_parser::{
+        any::{from_seekable_reader, AnyParser},
+        ParserVersion,
+    },
+    tree::any::AnyTree,
+    tree_v7400,
+    writer::v7400::binary::Writer,
+};
+
+/// Creates FBX 6.1 binary with the same node structure as the given tree.
+///
+/// The binary writer does not support FBX 6.x, so this rewrites the versions
+/// in the header and footer of FBX 7.4 binary.
+fn write_v6100(tree: &fbxcel::tree::v7400::Tree) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+    writer.write_tree(tree)?;
+    let mut bin = writer.finalize_and_flush(&Default::default())?.into_inner();
+
+    let version = 6100_u32.to_le_bytes();
+    // Header: magic (23 bytes) and version.
+    bin[23..27].copy_from_slice(&version);
+    // Footer: version, 120 zeroes, and unknown3 (16 bytes).
+    let footer_version_pos = bin.len() - 16 - 120 - 4;
+    assert_eq!(
+        bin[footer_version_pos..(footer_version_pos + 4)],
+        7400_u32.to_le_bytes()
+    );
+    bin[footer_version_pos..(footer_version_pos + 4)].copy_from_slice(&version);
+
+    Ok(bin)
+}
+
+#[test]
+fn parse_v6100() -> Result<(), Box<dyn std::error::Error>> {
+    let tree1 = tree_v7400! {
+        Objects: {
+            Model: ["Model::Cube", "Mesh"] {
+                Version: [232i32] {}
+                PolygonVertexIndex: [vec![0i32, 1, -3]] {}
+            }
+        },
+        Connections: {
+            Connect: ["OO", "Model::Cube", "Model::Scene"] {}
+        },
+    };
+    let bin = write_v6100(&tree1)?;
+
+    let parser = from_seekable_reader(Cursor::new(bin.clone()))?;
+    assert_eq!(parser.parser_version(), ParserVersion::V6100);
+    match parser {
+        AnyParser::V6100(parser) => {
+            assert_eq!(parser.parser_version(), ParserVersion::V6100);
+            assert_eq!(parser.fbx_version().major_minor(), (6, 1));
+        }
+        _ => panic!("FBX 6.1 binary should be parsable with v6100 parser"),
+    }
+
+    match AnyTree::from_seekable_reader(Cursor::new(bin))? {
+        AnyTree::V6100(fbx_version, tree2, footer) => {
+            assert_eq!(fbx_version.major_minor(), (6, 1));
+            assert!(tree1.strict_eq(&tree2));
+            assert_eq!(footer?.fbx_version, fbx_version);
+        }
+        _ => panic!("FBX 6.1 binary should be loaded as v6100 tree"),
+    }
+
+    Ok(())
+}
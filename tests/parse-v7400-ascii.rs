@@ -0,0 +1,222 @@
+//! Tests for FBX ASCII parser.
+
+use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+use fbxcel::{
+    low::{v7400::AttributeValue, FbxVersion},
+    pull_parser::{
+        error::{ErrorContainer, OperationError},
+        v7400::{
+            ascii,
+            attribute::loaders::{ArrayLoader, DirectLoader, PrimitiveLoader, StringLoader},
+            Attributes, Event, Parser,
+        },
+        ParserSource,
+    },
+};
+
+/// Sample FBX ASCII document.
+const SAMPLE: &str = r#"; FBX 7.5.0 project file
+; ----------------------------------------------------
+
+FBXHeaderExtension:  {
+	FBXHeaderVersion: 1003
+	Creator: "test &quot;quoted&quot;"
+}
+
+; Object definitions
+;------------------------------------------------------------------
+
+Objects:  {
+	Geometry: 2035541511296, "Geometry::Cube", "Mesh" {
+		Vertices: *6 {
+			a: 1,-1.5,2e3,-0.0,nan,4
+		}
+		PolygonVertexIndex: *3 {
+			a: 0,1,-3
+		}
+		Edges: *0 {
+			a:
+		}
+		Flags: *2 {
+			a: Y,T
+		}
+	}
+	Empty:
+}
+Connections:  {
+	C: "OO",2035541511296,0
+}
+"#;
+
+/// Returns the start event or panics.
+fn expect_node_start<'a, R: ParserSource + std::fmt::Debug>(
+    parser: &'a mut Parser<R>,
+    name: &str,
+) -> Attributes<'a, R> {
+    match parser.next_event().expect("Failed to read event") {
+        Event::StartNode(start) => {
+            assert_eq!(start.name(), name);
+            start.attributes()
+        }
+        ev => panic!("Unexpected event: {:?}", ev),
+    }
+}
+
+/// Reads an end event or panics.
+fn expect_node_end<R: ParserSource + std::fmt::Debug>(parser: &mut Parser<R>) {
+    match parser.next_event().expect("Failed to read event") {
+        Event::EndNode => {}
+        ev => panic!("Unexpected event: {:?}", ev),
+    }
+}
+
+#[test]
+fn parse_ascii_events() -> Result<(), Box<dyn std::error::Error>> {
+    let mut parser = ascii::from_seekable_reader(Cursor::new(SAMPLE))?;
+    let warnings = Rc::new(RefCell::new(Vec::new()));
+    parser.set_warning_handler({
+        let warnings = warnings.clone();
+        move |warning, _pos| {
+            warnings.borrow_mut().push(warning);
+            Ok(())
+        }
+    });
+    assert!(parser.is_ascii());
+    assert_eq!(parser.fbx_version(), FbxVersion::V7_5);
+
+    {
+        let attrs = expect_node_start(&mut parser, "FBXHeaderExtension");
+        assert_eq!(attrs.total_count(), 0);
+    }
+    {
+        let mut attrs = expect_node_start(&mut parser, "FBXHeaderVersion");
+        assert_eq!(
+            attrs.load_next(PrimitiveLoader::<i32>::default())?,
+            Some(1003)
+        );
+        assert_eq!(attrs.load_next(DirectLoader)?, None);
+    }
+    expect_node_end(&mut parser);
+    {
+        let mut attrs = expect_node_start(&mut parser, "Creator");
+        assert_eq!(
+            attrs.load_next(StringLoader)?.as_deref(),
+            Some("test \"quoted\"")
+        );
+    }
+    expect_node_end(&mut parser);
+    expect_node_end(&mut parser);
+
+    expect_node_start(&mut parser, "Objects");
+    {
+        let mut attrs = expect_node_start(&mut parser, "Geometry");
+        assert_eq!(attrs.total_count(), 3);
+        assert_eq!(
+            attrs.load_next(PrimitiveLoader::<i64>::default())?,
+            Some(2_035_541_511_296)
+        );
+        assert_eq!(
+            attrs.load_next(StringLoader)?.as_deref(),
+            Some("Cube\u{0}\u{1}Geometry")
+        );
+        // Leave the last attribute unread.
+    }
+    {
+        let mut attrs = expect_node_start(&mut parser, "Vertices");
+        let vertices = attrs
+            .load_next(ArrayLoader::<Vec<f64>>::default())?
+            .expect("Should have an attribute");
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(vertices[..4], [1.0, -1.5, 2000.0, 0.0]);
+        assert!(vertices[3].is_sign_negative());
+        assert!(vertices[4].is_nan());
+    }
+    expect_node_end(&mut parser);
+    {
+        let mut attrs = expect_node_start(&mut parser, "PolygonVertexIndex");
+        assert_eq!(
+            attrs.load_next(ArrayLoader::<Vec<i32>>::default())?,
+            Some(vec![0, 1, -3])
+        );
+    }
+    expect_node_end(&mut parser);
+    // Skip a node with an empty array.
+    expect_node_start(&mut parser, "Edges");
+    parser.skip_current_node()?;
+    {
+        let mut attrs = expect_node_start(&mut parser, "Flags");
+        assert_eq!(
+            attrs.load_next(DirectLoader)?,
+            Some(AttributeValue::ArrBool(vec![true, false]))
+        );
+    }
+    expect_node_end(&mut parser);
+    expect_node_end(&mut parser);
+    {
+        let attrs = expect_node_start(&mut parser, "Empty");
+        assert_eq!(attrs.total_count(), 0);
+    }
+    expect_node_end(&mut parser);
+    expect_node_end(&mut parser);
+
+    // Skip the whole `Connections` node.
+    expect_node_start(&mut parser, "Connections");
+    parser.skip_current_node()?;
+    assert_eq!(parser.current_depth(), 0);
+
+    match parser.next_event()? {
+        Event::EndFbx(Err(e)) => match e.get_ref() {
+            ErrorContainer::Operation(OperationError::FooterUnavailable) => {}
+            e => panic!("Unexpected error: {:?}", e),
+        },
+        ev => panic!("Unexpected event: {:?}", ev),
+    }
+
+    assert_eq!(warnings.borrow().len(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn broken_ascii() -> Result<(), Box<dyn std::error::Error>> {
+    let mut parser = ascii::from_reader("Node: 1, {\n}".as_bytes())?;
+    assert_eq!(parser.fbx_version(), FbxVersion::V7_4);
+    assert!(parser.next_event().is_err());
+
+    let mut parser = ascii::from_reader("Node: *3 { a: 1, 2 }".as_bytes())?;
+    assert!(parser.next_event().is_err());
+
+    let mut parser = ascii::from_reader("Node: {\n\tChild: 1\n".as_bytes())?;
+    expect_node_start(&mut parser, "Node");
+    expect_node_start(&mut parser, "Child");
+    expect_node_end(&mut parser);
+    assert!(parser.next_event().is_err());
+
+    Ok(())
+}
+
+#[cfg(feature = "tree")]
+#[test]
+fn load_ascii_tree() -> Result<(), Box<dyn std::error::Error>> {
+    use fbxcel::{tree::v7400::Loader as TreeLoader, tree_v7400};
+
+    let src = "; FBX 7.4.0 project file\nNode0: {\n\tNode0_0: {\n\t}\n\tNode0_1: \n}\nNode1: Y {\n\tNode1_0: 42, 1.234\n\tNode1_1: \"Hello, world\"\n}\n";
+    let mut parser = ascii::from_reader(src.as_bytes())?;
+    let (tree, footer_res) = TreeLoader::new().load(&mut parser)?;
+    assert!(footer_res.is_err());
+
+    let expected = tree_v7400! {
+        Node0: {
+            Node0_0: {},
+            Node0_1: {},
+        },
+        Node1: [true] {
+            Node1_0: (vec![42i32.into(), 1.234f64.into()]) {}
+            Node1_1: ["Hello, world"] {}
+        },
+    };
+    assert!(tree.strict_eq(&expected));
+
+    Ok(())
+}
@@ -0,0 +1,423 @@
+//! Tests for zero-copy slice parser.
+#![cfg(feature = "writer")]
+
+use std::io::Cursor;
+
+use fbxcel::{
+    low::{
+        v7400::{ArrayAttributeEncoding, AttributeValue},
+        FbxVersion,
+    },
+    pull_parser::{
+        any::{from_seekable_reader, AnyParser},
+        error::{DataError, Limit},
+        v7400::{
+            attribute::loaders::DirectLoader,
+            from_slice,
+            slice::{SliceAttribute, SliceParser},
+            Event, ParserLimits,
+        },
+    },
+    writer::v7400::binary::Writer,
+};
+
+use self::common::{offsets, sample};
+
+mod common;
+
+/// Parser event with owned data.
+#[derive(Debug, Clone, PartialEq)]
+enum OwnedEvent {
+    /// Node start.
+    Start(String, Vec<AttributeValue>),
+    /// Node end.
+    End,
+}
+
+/// Writes FBX binary with various attributes.
+fn write_fbx(version: FbxVersion) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()), version)?;
+    {
+        let mut attrs = writer.new_node("Scalars")?;
+        attrs.append_bool(true)?;
+        attrs.append_i16(-16)?;
+        attrs.append_i32(32)?;
+        attrs.append_i64(-64)?;
+        attrs.append_f32(0.5)?;
+        attrs.append_f64(-0.25)?;
+    }
+    writer.close_node()?;
+    {
+        let mut attrs = writer.new_node("Specials")?;
+        attrs.append_string_direct("Cube\u{0}\u{1}Model")?;
+        attrs.append_binary_direct(&[0, 1, 2, 255])?;
+    }
+    writer.close_node()?;
+    {
+        let mut attrs = writer.new_node("Arrays")?;
+        attrs.append_arr_bool_from_iter(ArrayAttributeEncoding::Direct, vec![true, false])?;
+        attrs.append_arr_i32_from_iter(ArrayAttributeEncoding::Direct, vec![1, -2, 3])?;
+        attrs.append_arr_i32_from_iter(ArrayAttributeEncoding::Zlib, (0..100).map(|v| v * 7))?;
+        attrs.append_arr_i64_from_iter(ArrayAttributeEncoding::Zlib, vec![1 << 40, -1])?;
+        attrs.append_arr_i64_from_iter(ArrayAttributeEncoding::Direct, vec![])?;
+    }
+    writer.new_node("Child")?;
+    writer.close_node()?;
+    writer.close_node()?;
+    writer.new_node("Skipped")?;
+    writer.new_node("Grandchild")?.append_i32(1)?;
+    writer.close_node()?;
+    writer.close_node()?;
+    writer.new_node("Last")?;
+    writer.close_node()?;
+    let bin = writer.finalize_and_flush(&Default::default())?.into_inner();
+
+    Ok(bin)
+}
+
+/// Reads all the rest attributes of the current node without copying them.
+fn borrowed_attributes<'a>(
+    parser: &mut SliceParser<'a>,
+) -> Result<Vec<SliceAttribute<'a>>, fbxcel::pull_parser::Error> {
+    let mut attrs = match parser.next_event()? {
+        Event::StartNode(node) => node.attributes(),
+        ev => panic!("Unexpected event: {:?}", ev),
+    };
+    let mut values = Vec::new();
+    while let Some(value) = attrs.next_borrowed()? {
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// Reads all events by the slice parser.
+fn slice_events(data: &[u8]) -> Result<Vec<OwnedEvent>, Box<dyn std::error::Error>> {
+    let mut parser = from_slice(data)?;
+    let mut events = Vec::new();
+    loop {
+        match parser.next_event()? {
+            Event::StartNode(node) => {
+                let name = node.borrowed_name().to_owned();
+                let mut attrs = node.attributes();
+                let mut values = Vec::new();
+                while let Some(value) = attrs.next_borrowed()? {
+                    values.push(value.to_value()?);
+                }
+                events.push(OwnedEvent::Start(name, values));
+            }
+            Event::EndNode => events.push(OwnedEvent::End),
+            Event::EndFbx(footer) => {
+                assert_eq!(footer?.fbx_version, parser.fbx_version());
+                break;
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// Reads all events by the reader-based parser.
+fn reader_events(data: &[u8]) -> Result<Vec<OwnedEvent>, Box<dyn std::error::Error>> {
+    let mut parser = match from_seekable_reader(Cursor::new(data))? {
+        AnyParser::V7400(parser) => parser,
+        _ => panic!("Generated data should be parsable with v7400 parser"),
+    };
+    let mut events = Vec::new();
+    loop {
+        match parser.next_event()? {
+            Event::StartNode(node) => {
+                let name = node.name().to_owned();
+                let mut attrs = node.attributes();
+                let mut values = Vec::new();
+                while let Some(value) = attrs.load_next(DirectLoader)? {
+                    values.push(value);
+                }
+                events.push(OwnedEvent::Start(name, values));
+            }
+            Event::EndNode => events.push(OwnedEvent::End),
+            Event::EndFbx(footer) => {
+                footer?;
+                break;
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// Returns `true` if `part` is a subslice of `whole`.
+fn is_borrowed_from(part: &[u8], whole: &[u8]) -> bool {
+    let range = whole.as_ptr_range();
+    range.start <= part.as_ptr() && part.as_ptr_range().end <= range.end
+}
+
+#[test]
+fn same_events_as_reader_parser() -> Result<(), Box<dyn std::error::Error>> {
+    for &version in &[FbxVersion::V7_4, FbxVersion::V7_5] {
+        let data = write_fbx(version)?;
+        let slice_events = slice_events(&data)?;
+        assert_eq!(slice_events, reader_events(&data)?);
+        assert_eq!(
+            slice_events[0],
+            OwnedEvent::Start(
+                "Scalars".to_owned(),
+                vec![
+                    true.into(),
+                    (-16i16).into(),
+                    32i32.into(),
+                    (-64i64).into(),
+                    0.5f32.into(),
+                    (-0.25f64).into(),
+                ]
+            )
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn borrowed_data() -> Result<(), Box<dyn std::error::Error>> {
+    let data = write_fbx(FbxVersion::V7_4)?;
+    let mut parser = from_slice(&data)?;
+
+    // `Scalars`.
+    match parser.next_event()? {
+        Event::StartNode(node) => assert_eq!(node.borrowed_name(), "Scalars"),
+        ev => panic!("Unexpected event: {:?}", ev),
+    }
+    assert!(matches!(parser.next_event()?, Event::EndNode));
+
+    // `Specials`.
+    let (name, string, binary) = match parser.next_event()? {
+        Event::StartNode(node) => {
+            let name = node.borrowed_name();
+            let mut attrs = node.attributes();
+            assert_eq!(attrs.total_count(), 2);
+            let string = match attrs.next_borrowed()? {
+                Some(SliceAttribute::String(v)) => v,
+                v => panic!("Unexpected attribute: {:?}", v),
+            };
+            let binary = match attrs.next_borrowed()? {
+                Some(SliceAttribute::Binary(v)) => v,
+                v => panic!("Unexpected attribute: {:?}", v),
+            };
+            assert!(attrs.next_borrowed()?.is_none());
+            (name, string, binary)
+        }
+        ev => panic!("Unexpected event: {:?}", ev),
+    };
+    assert!(matches!(parser.next_event()?, Event::EndNode));
+    // They are still available after the parser proceeds.
+    assert_eq!(name, "Specials");
+    assert_eq!(string, "Cube\u{0}\u{1}Model");
+    assert_eq!(binary, [0, 1, 2, 255]);
+    assert!(is_borrowed_from(name.as_bytes(), &data));
+    assert!(is_borrowed_from(string.as_bytes(), &data));
+    assert!(is_borrowed_from(binary, &data));
+
+    // `Arrays`.
+    {
+        let attrs = borrowed_attributes(&mut parser)?;
+        match attrs[..] {
+            [SliceAttribute::ArrBool(bools), SliceAttribute::ArrI32(direct), SliceAttribute::ArrI32(zlib), SliceAttribute::ArrI64(_), SliceAttribute::ArrI64(empty)] =>
+            {
+                assert_eq!(bools.as_le_bytes(), Some(&b"YT"[..]));
+                assert_eq!(bools.to_vec()?, [true, false]);
+
+                assert_eq!(direct.encoding(), ArrayAttributeEncoding::Direct);
+                assert_eq!(direct.len(), 3);
+                let bytes = direct.as_le_bytes().expect("Should not be compressed");
+                assert!(is_borrowed_from(bytes, &data));
+                assert_eq!(&bytes[4..8], (-2i32).to_le_bytes());
+                assert_eq!(direct.to_vec()?, [1, -2, 3]);
+
+                assert_eq!(zlib.encoding(), ArrayAttributeEncoding::Zlib);
+                assert_eq!(zlib.len(), 100);
+                assert!(zlib.as_le_bytes().is_none());
+                assert_eq!(zlib.le_bytes()?.len(), 400);
+                assert_eq!(zlib.to_vec()?, (0..100).map(|v| v * 7).collect::<Vec<_>>());
+
+                assert!(empty.is_empty());
+                assert_eq!(empty.as_le_bytes(), Some(&[][..]));
+            }
+            _ => panic!("Unexpected attributes: {:?}", attrs),
+        }
+    }
+    // Skip the rest of `Arrays` including `Child`.
+    parser.skip_current_node()?;
+    assert_eq!(parser.current_depth(), 0);
+
+    // `Skipped`.
+    match parser.next_event()? {
+        Event::StartNode(node) => assert_eq!(node.borrowed_name(), "Skipped"),
+        ev => panic!("Unexpected event: {:?}", ev),
+    }
+    parser.skip_current_node()?;
+    assert_eq!(parser.current_depth(), 0);
+
+    // `Last`.
+    match parser.next_event()? {
+        Event::StartNode(node) => assert_eq!(node.borrowed_name(), "Last"),
+        ev => panic!("Unexpected event: {:?}", ev),
+    }
+    assert!(matches!(parser.next_event()?, Event::EndNode));
+    assert!(matches!(parser.next_event()?, Event::EndFbx(Ok(_))));
+
+    Ok(())
+}
+
+#[test]
+fn broken_input() -> Result<(), Box<dyn std::error::Error>> {
+    let err = from_slice(b"Not an FBX").expect_err("Should fail");
+    assert!(matches!(
+        err.downcast_ref::<DataError>(),
+        Some(DataError::InvalidFbxHeader(_))
+    ));
+
+    let data = write_fbx(FbxVersion::V7_4)?;
+    let truncated = &data[..100];
+    let mut parser = from_slice(truncated)?;
+    let err = 'events: loop {
+        match parser.next_event() {
+            Ok(Event::StartNode(node)) => {
+                let mut attrs = node.attributes();
+                while let Some(attr) = attrs.next_borrowed().transpose() {
+                    if let Err(e) = attr {
+                        break 'events e;
+                    }
+                }
+            }
+            Ok(Event::EndFbx(_)) => panic!("Truncated data should not be parsed"),
+            Ok(_) => {}
+            Err(e) => break e,
+        }
+    };
+    assert!(err.position().is_some());
+    assert!(parser.next_event().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn broken_elements_count() -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+    writer
+        .new_node("Array")?
+        .append_arr_i32_from_iter(ArrayAttributeEncoding::Zlib, 0..4)?;
+    writer.close_node()?;
+    let mut data = writer.finalize_and_flush(&Default::default())?.into_inner();
+    let (_, attrs_start) = offsets(&data, "Array");
+    // Skip the type code.
+    let count_pos = attrs_start + 1;
+    data[count_pos..(count_pos + 4)].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let mut parser = from_slice(&data)?;
+    match parser.next_event()? {
+        Event::StartNode(node) => match node.attributes().next_borrowed()? {
+            Some(SliceAttribute::ArrI32(arr)) => {
+                assert_eq!(arr.len(), u32::MAX as usize);
+                // This should not allocate 16 GiB for the declared count.
+                let err = arr.le_bytes().expect_err("Elements should be insufficient");
+                assert!(matches!(
+                    err.downcast_ref::<DataError>(),
+                    Some(DataError::BrokenCompression(..))
+                ));
+            }
+            v => panic!("Unexpected attribute: {:?}", v),
+        },
+        ev => panic!("Unexpected event: {:?}", ev),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn parser_features() -> Result<(), Box<dyn std::error::Error>> {
+    let mut bin = sample();
+
+    // Resource limits.
+    let mut parser = from_slice(&bin)?;
+    parser.set_limits(ParserLimits {
+        max_string_len: Some(11),
+        ..Default::default()
+    });
+    let err = 'events: loop {
+        match parser.next_event()? {
+            Event::StartNode(node) => {
+                let mut attrs = node.attributes();
+                while let Some(attr) = attrs.next_borrowed().transpose() {
+                    if let Err(e) = attr {
+                        break 'events e;
+                    }
+                }
+            }
+            Event::EndNode => {}
+            Event::EndFbx(_) => panic!("The limit should be exceeded"),
+        }
+    };
+    assert!(matches!(
+        err.downcast_ref::<DataError>(),
+        Some(DataError::LimitExceeded(Limit::StringLength, 12))
+    ));
+
+    // Attribute layouts.
+    let (second, second_attrs) = offsets(&bin, "Second");
+    let mut parser = from_slice(&bin)?;
+    match parser.next_event()? {
+        Event::StartNode(node) => assert_eq!(node.borrowed_name(), "Container"),
+        ev => panic!("Unexpected event: {:?}", ev),
+    }
+    parser.skip_current_node()?;
+    match parser.next_event()? {
+        Event::StartNode(node) => {
+            assert_eq!(node.start_offset(), second as u64);
+            let layout = node
+                .attributes()
+                .next_layout()?
+                .expect("`Second` should have an attribute");
+            assert_eq!(layout.start_offset(), second_attrs as u64);
+            assert_eq!(layout.end_offset(), second_attrs as u64 + 9);
+        }
+        ev => panic!("Unexpected event: {:?}", ev),
+    }
+
+    // Recovery mode.
+    let (_, values) = offsets(&bin, "Values");
+    // Invalid array encoding of the second attribute.
+    bin[values + 5 + 5] = 7;
+    let mut parser = from_slice(&bin)?;
+    parser.set_recovery_mode(true);
+    let mut names = Vec::new();
+    let mut errors = 0;
+    loop {
+        match parser.next_event()? {
+            Event::StartNode(node) => {
+                names.push(node.borrowed_name());
+                let mut attrs = node.attributes();
+                while let Some(attr) = attrs.next_borrowed().transpose() {
+                    if attr.is_err() {
+                        errors += 1;
+                    }
+                }
+            }
+            Event::EndNode => {}
+            Event::EndFbx(footer) => {
+                footer?;
+                break;
+            }
+        }
+    }
+    assert_eq!(errors, 1);
+    assert_eq!(
+        names,
+        [
+            "Container",
+            "Flag",
+            "Values",
+            "Compressed",
+            "Name",
+            "Second"
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,409 @@
+//! Tests for the recovery mode of FBX binary parser.
+#![cfg(feature = "writer")]
+
+use fbxcel::pull_parser::{
+    error::{DataError, ErrorKind},
+    v7400::{attribute::loaders::DirectLoader, Event, Parser},
+    ParserSource,
+};
+
+use self::common::{offsets, sample};
+
+mod common;
+
+/// Creates a parser in the recovery mode.
+fn parser(bin: &[u8]) -> Parser<impl ParserSource + '_> {
+    let mut parser = common::parser(bin);
+    parser.set_recovery_mode(true);
+    parser
+}
+
+/// Simplified parser event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Ev {
+    /// Node start with the name and the number of loaded attributes.
+    Start(String, usize),
+    /// Node end.
+    End,
+    /// Error on loading attributes.
+    AttributeError(ErrorKind),
+    /// Recovered error, taken by `Parser::take_diagnostics()`.
+    Diagnostic(ErrorKind),
+    /// FBX end, with whether the footer is successfully read.
+    EndFbx(bool),
+}
+
+/// Takes the recovered errors from the parser.
+fn diagnostics<R: ParserSource>(parser: &mut Parser<R>) -> impl Iterator<Item = Ev> {
+    parser
+        .take_diagnostics()
+        .into_iter()
+        .map(|e| Ev::Diagnostic(e.kind()))
+}
+
+/// Reads all events from the parser.
+///
+/// Recovered errors are put before the event emitted after the recovery.
+fn events<R: ParserSource>(parser: &mut Parser<R>) -> Vec<Ev> {
+    let mut events = Vec::new();
+    loop {
+        match parser.next_event().expect("Should recover from the error") {
+            Event::StartNode(start) => {
+                let name = start.name().to_owned();
+                let mut attrs = start.attributes();
+                let mut count = 0;
+                let err = loop {
+                    match attrs.load_next(DirectLoader) {
+                        Ok(Some(_)) => count += 1,
+                        Ok(None) => break None,
+                        Err(e) => break Some(e.kind()),
+                    }
+                };
+                events.push(Ev::Start(name, count));
+                events.extend(err.map(Ev::AttributeError));
+            }
+            Event::EndNode => {
+                events.extend(diagnostics(parser));
+                events.push(Ev::End);
+            }
+            Event::EndFbx(footer) => {
+                events.extend(diagnostics(parser));
+                events.push(Ev::EndFbx(footer.is_ok()));
+                return events;
+            }
+        }
+    }
+}
+
+/// Creates a node start event.
+fn start(name: &str, count: usize) -> Ev {
+    Ev::Start(name.to_owned(), count)
+}
+
+#[test]
+fn valid_file() {
+    let bin = sample();
+    assert_eq!(
+        events(&mut parser(&bin)),
+        vec![
+            start("Container", 0),
+            start("Flag", 1),
+            Ev::End,
+            start("Values", 2),
+            Ev::End,
+            start("Compressed", 1),
+            Ev::End,
+            start("Name", 1),
+            Ev::End,
+            Ev::End,
+            start("Second", 1),
+            Ev::End,
+            Ev::EndFbx(true),
+        ]
+    );
+}
+
+#[test]
+fn truncated_in_attributes() {
+    let bin = sample();
+    let (_, name) = offsets(&bin, "Name");
+    // Truncated in the string length of `Name`.
+    let bin = &bin[..(name + 3)];
+
+    assert_eq!(
+        events(&mut parser(bin)),
+        vec![
+            start("Container", 0),
+            start("Flag", 1),
+            Ev::End,
+            start("Values", 2),
+            Ev::End,
+            start("Compressed", 1),
+            Ev::End,
+            start("Name", 0),
+            Ev::AttributeError(ErrorKind::Io),
+            Ev::End,
+            Ev::End,
+            Ev::EndFbx(false),
+        ]
+    );
+}
+
+#[test]
+fn truncated_between_nodes() {
+    let bin = sample();
+    let (second, _) = offsets(&bin, "Second");
+    let bin = &bin[..(second + 3)];
+
+    assert_eq!(
+        events(&mut parser(bin)),
+        vec![
+            start("Container", 0),
+            start("Flag", 1),
+            Ev::End,
+            start("Values", 2),
+            Ev::End,
+            start("Compressed", 1),
+            Ev::End,
+            start("Name", 1),
+            Ev::End,
+            Ev::End,
+            Ev::Diagnostic(ErrorKind::Io),
+            Ev::EndFbx(false),
+        ]
+    );
+}
+
+#[test]
+fn truncated_in_skipped_node() {
+    let bin = sample();
+    let (_, name) = offsets(&bin, "Name");
+    // Truncated in the string length of `Name`.
+    let bin = &bin[..(name + 3)];
+
+    let mut parser = parser(bin);
+    let mut events = Vec::new();
+    loop {
+        match parser.next_event().expect("Should recover from the error") {
+            Event::StartNode(start) => {
+                events.push(start.name().to_owned());
+                if start.name() == "Container" {
+                    parser
+                        .skip_current_node()
+                        .expect("Should recover from the error");
+                }
+            }
+            Event::EndNode => events.push("End".to_owned()),
+            Event::EndFbx(footer) => {
+                assert!(footer.is_err());
+                break;
+            }
+        }
+    }
+    assert_eq!(events, vec!["Container"]);
+    let errors = parser.take_diagnostics();
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert_eq!(errors[0].kind(), ErrorKind::Io);
+}
+
+#[test]
+fn broken_attribute() {
+    let mut bin = sample();
+    let (_, values) = offsets(&bin, "Values");
+    // Invalid type code of the second attribute.
+    bin[values + 5] = b'?';
+
+    let mut parser = parser(&bin);
+    assert_eq!(
+        events(&mut parser),
+        vec![
+            start("Container", 0),
+            start("Flag", 1),
+            Ev::End,
+            start("Values", 1),
+            Ev::AttributeError(ErrorKind::Data),
+            Ev::End,
+            start("Compressed", 1),
+            Ev::End,
+            start("Name", 1),
+            Ev::End,
+            Ev::End,
+            start("Second", 1),
+            Ev::End,
+            Ev::EndFbx(true),
+        ]
+    );
+}
+
+#[test]
+fn broken_node_name() {
+    let mut bin = sample();
+    let (_, values) = offsets(&bin, "Values");
+    // Invalid UTF-8 sequence in the name.
+    bin[values - 1] = 0xff;
+
+    let mut parser = parser(&bin);
+    assert_eq!(
+        events(&mut parser),
+        vec![
+            start("Container", 0),
+            start("Flag", 1),
+            Ev::End,
+            Ev::Diagnostic(ErrorKind::Data),
+            // The rest of the parent is skipped.
+            Ev::End,
+            start("Second", 1),
+            Ev::End,
+            Ev::EndFbx(true),
+        ]
+    );
+    // Taken diagnostics are not returned again.
+    assert!(parser.diagnostics().is_empty());
+    assert!(parser.take_diagnostics().is_empty());
+}
+
+#[test]
+fn node_length_mismatch() {
+    let mut bin = sample();
+    let (name, _) = offsets(&bin, "Name");
+    let (_, second) = offsets(&bin, "Second");
+    // Make `Name` node end before its end.
+    let end = u32::from_le_bytes([bin[name], bin[name + 1], bin[name + 2], bin[name + 3]]);
+    bin[name..(name + 4)].copy_from_slice(&(end - 4).to_le_bytes());
+    // Broken attribute in `Second` node.
+    bin[second] = b'?';
+
+    let mut parser = parser(&bin);
+    let mut errors = Vec::new();
+    loop {
+        match parser.next_event().expect("Should recover from the error") {
+            Event::StartNode(start) => {
+                let mut attrs = start.attributes();
+                while let Some(res) = attrs.load_next(DirectLoader).transpose() {
+                    if let Err(e) = res {
+                        errors.push(e);
+                    }
+                }
+            }
+            Event::EndNode => errors.extend(parser.take_diagnostics()),
+            Event::EndFbx(footer) => {
+                assert!(footer.is_ok());
+                break;
+            }
+        }
+    }
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert!(matches!(
+        errors[0].downcast_ref::<DataError>(),
+        Some(DataError::NodeLengthMismatch(..))
+    ));
+    assert_eq!(
+        errors[0]
+            .position()
+            .expect("Error should have a position")
+            .node_path()
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Container", "Name"]
+    );
+    assert!(matches!(
+        errors[1].downcast_ref::<DataError>(),
+        Some(DataError::InvalidAttributeTypeCode(..))
+    ));
+}
+
+#[test]
+fn errors_abort_without_recovery_mode() {
+    let mut bin = sample();
+    let (_, values) = offsets(&bin, "Values");
+    bin[values - 1] = 0xff;
+
+    let mut parser = parser(&bin);
+    parser.set_recovery_mode(false);
+    assert!(!parser.is_recovery_mode());
+    let err = loop {
+        match parser.next_event() {
+            Ok(Event::EndFbx(_)) => panic!("Parser should fail"),
+            Ok(_) => assert!(parser.diagnostics().is_empty()),
+            Err(e) => break e,
+        }
+    };
+    assert_eq!(err.kind(), ErrorKind::Data);
+    assert!(parser.next_event().is_err());
+}
+
+#[cfg(feature = "tree")]
+mod tree {
+    use fbxcel::{
+        low::v7400::ArrayAttributeEncoding,
+        tree::v7400::{ArrayPreservation, LoadError, Loader, NodeHandle, Tree},
+    };
+
+    use super::*;
+
+    /// Returns the node names of the tree in depth-first order, with the
+    /// number of attributes.
+    fn nodes(tree: &Tree) -> Vec<(String, usize)> {
+        fn visit(node: NodeHandle<'_>, nodes: &mut Vec<(String, usize)>) {
+            for child in node.children() {
+                nodes.push((child.name().to_owned(), child.attributes().len()));
+                visit(child, nodes);
+            }
+        }
+        let mut nodes = Vec::new();
+        visit(tree.root(), &mut nodes);
+        nodes
+    }
+
+    #[test]
+    fn load_truncated_file() {
+        let bin = sample();
+        let (_, name) = offsets(&bin, "Name");
+        // Truncated in the string length of `Name`.
+        let bin = &bin[..(name + 3)];
+
+        let (tree, footer, errors) = Loader::new()
+            .with_array_preservation(ArrayPreservation::Encoding)
+            .load_with_recovery(&mut parser(bin))
+            .expect("Should recover from the error");
+        assert!(footer.is_err());
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert_eq!(errors[0].kind(), ErrorKind::Io);
+        assert_eq!(
+            nodes(&tree),
+            vec![
+                ("Container".to_owned(), 0),
+                ("Flag".to_owned(), 1),
+                ("Values".to_owned(), 2),
+                ("Compressed".to_owned(), 1),
+                ("Name".to_owned(), 0),
+            ]
+        );
+        let values = tree
+            .root()
+            .first_child_by_name("Container")
+            .and_then(|container| container.first_child_by_name("Values"))
+            .unwrap();
+        assert_eq!(values.array_encoding(0), None);
+        assert_eq!(
+            values.array_encoding(1),
+            Some(ArrayAttributeEncoding::Direct)
+        );
+    }
+
+    #[test]
+    fn load_broken_file() {
+        let mut bin = sample();
+        let (_, values) = offsets(&bin, "Values");
+        bin[values + 5] = b'?';
+        let (second, _) = offsets(&bin, "Second");
+        // Invalid UTF-8 sequence in the name.
+        bin[second + 13] = 0xff;
+
+        let (tree, footer, errors) = Loader::new()
+            .load_with_recovery(&mut parser(&bin))
+            .expect("Should recover from the error");
+        assert!(footer.is_err());
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors.iter().all(|e| e.kind() == ErrorKind::Data));
+        assert_eq!(
+            nodes(&tree),
+            vec![
+                ("Container".to_owned(), 0),
+                ("Flag".to_owned(), 1),
+                ("Values".to_owned(), 1),
+                ("Compressed".to_owned(), 1),
+                ("Name".to_owned(), 1),
+            ]
+        );
+
+        // Without the recovery mode, the loader fails.
+        let mut parser = parser(&bin);
+        parser.set_recovery_mode(false);
+        assert!(matches!(
+            Loader::new().load(&mut parser),
+            Err(LoadError::Parser(_))
+        ));
+    }
+}
@@ -0,0 +1,194 @@
+//! Tests for byte-identical round trip of FBX binary through a tree.
+#![cfg(all(feature = "tree", feature = "writer"))]
+
+use std::io::Cursor;
+
+use fbxcel::{
+    low::{
+        v7400::{ArrayAttributeEncoding, FbxFooter},
+        FbxVersion,
+    },
+    tree::v7400::{ArrayPreservation, Loader, Tree},
+    writer::v7400::binary::{
+        CompressionLevel, CompressionPolicy, CompressionThreshold, FbxFooter as WriterFooter,
+        FbxFooterPaddingLength, Writer,
+    },
+};
+
+use self::common::{parser, write_tree};
+
+mod common;
+
+/// Writes the source FBX binary with various array encodings.
+fn source_binary(fbx_version: FbxVersion) -> Vec<u8> {
+    let mut writer =
+        Writer::new(Cursor::new(Vec::new()), fbx_version).expect("Failed to create writer");
+    // Use a compression level different from the default, to check that
+    // the raw elements are written as is.
+    writer.set_compression_policy(CompressionPolicy::new(
+        CompressionThreshold::Never,
+        CompressionLevel::Stored,
+    ));
+    (|| {
+        let mut attrs = writer.new_node("Geometry")?;
+        attrs.append_i64(1)?;
+        attrs.append_string_direct("Mesh")?;
+        {
+            let mut attrs = writer.new_node("Vertices")?;
+            attrs.append_arr_f64_from_iter(
+                Some(ArrayAttributeEncoding::Zlib),
+                (0..64).map(f64::from),
+            )?;
+        }
+        writer.close_node()?;
+        {
+            let mut attrs = writer.new_node("PolygonVertexIndex")?;
+            attrs.append_arr_i32_from_iter(Some(ArrayAttributeEncoding::Direct), 0..64)?;
+            attrs.append_arr_bool_from_iter(
+                Some(ArrayAttributeEncoding::Zlib),
+                (0..16).map(|i| i % 3 == 0),
+            )?;
+        }
+        writer.close_node()?;
+        writer.close_node()?;
+        Ok::<_, fbxcel::writer::v7400::binary::Error>(())
+    })()
+    .expect("Failed to write nodes");
+    let footer = WriterFooter {
+        unknown1: Some(&[0x42; 16]),
+        // Wrong padding length.
+        padding_len: FbxFooterPaddingLength::Forced(3),
+        ..Default::default()
+    };
+    writer
+        .finalize_and_flush(&footer)
+        .expect("Failed to finalize")
+        .into_inner()
+}
+
+/// Loads the FBX binary into a tree.
+fn load(bin: &[u8], preservation: ArrayPreservation) -> (Tree, Box<FbxFooter>) {
+    let mut parser = parser(bin);
+    parser.set_warning_handler(|_, _| Ok(()));
+    let (tree, footer) = Loader::new()
+        .with_array_preservation(preservation)
+        .load(&mut parser)
+        .expect("Failed to load tree");
+    (tree, footer.expect("Failed to read footer"))
+}
+
+#[test]
+fn byte_identical_with_raw_bytes() {
+    for &version in &[FbxVersion::V7_4, FbxVersion::V7_5] {
+        let source = source_binary(version);
+        let (tree, footer) = load(&source, ArrayPreservation::RawBytes);
+        assert_eq!(footer.padding_len, 3);
+
+        assert_eq!(
+            write_tree(&tree, version, &WriterFooter::from(&*footer)),
+            source,
+            "version={:?}",
+            version
+        );
+    }
+}
+
+#[test]
+fn encodings_are_preserved() {
+    let source = source_binary(FbxVersion::V7_4);
+    let (tree, footer) = load(&source, ArrayPreservation::Encoding);
+    let geometry = tree.root().first_child_by_name("Geometry").unwrap();
+    let vertices = geometry.first_child_by_name("Vertices").unwrap();
+    let indices = geometry.first_child_by_name("PolygonVertexIndex").unwrap();
+    assert_eq!(
+        vertices.array_encoding(0),
+        Some(ArrayAttributeEncoding::Zlib)
+    );
+    assert_eq!(
+        indices.array_encoding(0),
+        Some(ArrayAttributeEncoding::Direct)
+    );
+    assert_eq!(
+        indices.array_encoding(1),
+        Some(ArrayAttributeEncoding::Zlib)
+    );
+    assert_eq!(geometry.array_encoding(0), None);
+    assert_eq!(vertices.raw_array(0), None);
+
+    // Arrays are compressed again with the writer's compression level, so
+    // only the encodings are checked.
+    let (written, _) = load(
+        &write_tree(&tree, FbxVersion::V7_4, &WriterFooter::from(&*footer)),
+        ArrayPreservation::Encoding,
+    );
+    assert!(written.strict_eq(&tree));
+    let vertices = written
+        .root()
+        .first_child_by_name("Geometry")
+        .and_then(|geometry| geometry.first_child_by_name("Vertices"))
+        .unwrap();
+    assert_eq!(
+        vertices.array_encoding(0),
+        Some(ArrayAttributeEncoding::Zlib)
+    );
+}
+
+#[test]
+fn encodings_are_discarded_by_default() {
+    let source = source_binary(FbxVersion::V7_4);
+    let (tree, _) = load(&source, ArrayPreservation::Discard);
+    let vertices = tree
+        .root()
+        .first_child_by_name("Geometry")
+        .and_then(|geometry| geometry.first_child_by_name("Vertices"))
+        .unwrap();
+    assert_eq!(vertices.array_encoding(0), None);
+}
+
+#[test]
+fn modified_attributes_drop_raw_bytes() {
+    let source = source_binary(FbxVersion::V7_4);
+    let (mut tree, footer) = load(&source, ArrayPreservation::RawBytes);
+    let indices = tree
+        .root()
+        .first_child_by_name("Geometry")
+        .and_then(|geometry| geometry.first_child_by_name("PolygonVertexIndex"))
+        .unwrap()
+        .node_id();
+
+    // Replaced attribute has no preserved encoding.
+    tree.set_attribute(indices, 1, vec![true, false]);
+    let handle = indices.to_handle(&tree);
+    assert_eq!(
+        handle.array_encoding(0),
+        Some(ArrayAttributeEncoding::Direct)
+    );
+    assert!(handle.raw_array(0).is_some());
+    assert_eq!(handle.array_encoding(1), None);
+
+    // Preserved encodings follow the attributes.
+    tree.insert_attribute(indices, 0, 42i32);
+    let handle = indices.to_handle(&tree);
+    assert_eq!(handle.array_encoding(0), None);
+    assert_eq!(
+        handle.array_encoding(1),
+        Some(ArrayAttributeEncoding::Direct)
+    );
+    tree.remove_attribute(indices, 0);
+
+    // Changing the encoding drops the raw elements.
+    tree.set_array_encoding(indices, 0, Some(ArrayAttributeEncoding::Zlib));
+    let handle = indices.to_handle(&tree);
+    assert_eq!(handle.array_encoding(0), Some(ArrayAttributeEncoding::Zlib));
+    assert_eq!(handle.raw_array(0), None);
+
+    // Mutable access to the attributes drops everything.
+    tree.node_mut(indices).attributes_mut();
+    assert_eq!(indices.to_handle(&tree).array_encoding(0), None);
+
+    let (written, _) = load(
+        &write_tree(&tree, FbxVersion::V7_4, &WriterFooter::from(&*footer)),
+        ArrayPreservation::Discard,
+    );
+    assert!(written.strict_eq(&tree));
+}
@@ -0,0 +1,132 @@
+//! Tests for serde support of tree.
+#![cfg(all(feature = "tree", feature = "serde"))]
+
+use fbxcel::{low::v7400::AttributeValue, tree::v7400::Tree, tree_v7400};
+
+#[test]
+fn attribute_value_type_tags() -> Result<(), Box<dyn std::error::Error>> {
+    let values: Vec<AttributeValue> = vec![
+        3i16.into(),
+        3i32.into(),
+        1.5f32.into(),
+        1.5f64.into(),
+        vec![true, false].into(),
+        AttributeValue::Binary(b"abc".to_vec()),
+        "abc".into(),
+    ];
+    let json = serde_json::to_string(&values)?;
+    assert_eq!(
+        json,
+        r#"[{"I16":3},{"I32":3},{"F32":"0x3fc00000"},{"F64":"0x3ff8000000000000"},{"ArrBool":[true,false]},{"Binary":[97,98,99]},{"String":"abc"}]"#
+    );
+    let de: Vec<AttributeValue> = serde_json::from_str(&json)?;
+    assert_eq!(de.len(), values.len());
+    assert!(de.iter().zip(&values).all(|(l, r)| l.strict_eq(r)));
+
+    Ok(())
+}
+
+#[test]
+fn non_finite_floats() -> Result<(), Box<dyn std::error::Error>> {
+    let nan_with_payload = f32::from_bits(0x7fc0_1234);
+    let values: Vec<AttributeValue> = vec![
+        f32::NAN.into(),
+        nan_with_payload.into(),
+        f64::INFINITY.into(),
+        f64::NEG_INFINITY.into(),
+        vec![1.5f32, f32::INFINITY].into(),
+        vec![-0.0f64, f64::NAN].into(),
+    ];
+    let json = serde_json::to_string(&values)?;
+    assert_eq!(
+        json,
+        format!(
+            r#"[{{"F32":"0x{:08x}"}},{{"F32":"0x7fc01234"}},{{"F64":"0x7ff0000000000000"}},{{"F64":"0xfff0000000000000"}},{{"ArrF32":["0x3fc00000","0x7f800000"]}},{{"ArrF64":["0x8000000000000000","0x{:016x}"]}}]"#,
+            f32::NAN.to_bits(),
+            f64::NAN.to_bits()
+        )
+    );
+    let de: Vec<AttributeValue> = serde_json::from_str(&json)?;
+    assert_eq!(de.len(), values.len());
+    assert!(
+        de.iter().zip(&values).all(|(l, r)| l.strict_eq(r)),
+        "de={:?}",
+        de
+    );
+
+    // Plain numbers are also accepted.
+    let de: AttributeValue = serde_json::from_str(r#"{"F32":1.5}"#)?;
+    assert!(de.strict_eq(&1.5f32.into()));
+
+    // Malformed bit patterns are rejected.
+    assert!(serde_json::from_str::<AttributeValue>(r#"{"F32":"0x7fc0"}"#).is_err());
+    assert!(serde_json::from_str::<AttributeValue>(r#"{"F64":"nan"}"#).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn tree_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let tree = tree_v7400! {
+        Header: [true, 1i16, 2i32, 3i64, 0.1f32, -0.2f64] {
+            Child: ["name\u{0}\u{1}class"] {},
+        },
+        Objects: {
+            Geometry: [vec![true], vec![1i32, -2], vec![3i64], vec![0.5f32, 1e-10], vec![0.25f64]] {
+                Blob: [vec![0u8, 255]] {}
+            },
+            Geometry: {},
+        },
+        Empty: {},
+    };
+
+    let json = serde_json::to_string(&tree)?;
+    assert!(json.starts_with(r#"[{"name":"Header","attributes":[{"Bool":true},"#));
+    let de: Tree = serde_json::from_str(&json)?;
+    assert!(tree.strict_eq(&de), "tree={:?}", de.debug_tree());
+
+    // Node handles are serialized as nodes.
+    let objects = tree
+        .root()
+        .first_child_by_name("Objects")
+        .expect("`Objects` exists");
+    let value = serde_json::to_value(objects)?;
+    assert_eq!(value["name"], "Objects");
+    assert_eq!(value["children"].as_array().map(Vec::len), Some(2));
+
+    Ok(())
+}
+
+#[test]
+fn random_floats() -> Result<(), Box<dyn std::error::Error>> {
+    // xorshift64*, to avoid depending on random number generator crates.
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = || {
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    };
+    let f64_values = (0..1000)
+        .map(|_| f64::from_bits(next()))
+        .collect::<Vec<_>>();
+    let f32_values = (0..1000)
+        .map(|_| f32::from_bits(next() as u32))
+        .collect::<Vec<_>>();
+    let mut values = f64_values
+        .iter()
+        .map(|&v| AttributeValue::from(v))
+        .chain(f32_values.iter().map(|&v| AttributeValue::from(v)))
+        .collect::<Vec<_>>();
+    values.push(f64_values.into());
+    values.push(f32_values.into());
+
+    let json = serde_json::to_string(&values)?;
+    let de: Vec<AttributeValue> = serde_json::from_str(&json)?;
+    assert_eq!(de.len(), values.len());
+    for (l, r) in de.iter().zip(&values) {
+        assert!(l.strict_eq(r), "expected {:?}, got {:?}", r, l);
+    }
+
+    Ok(())
+}
@@ -1,4 +1,6 @@
 #![cfg(feature = "writer")]
+// Each test crate uses only some of the helpers.
+#![allow(dead_code)]
 
 use fbxcel::pull_parser::{
     v7400::{Attributes, Event, Parser},
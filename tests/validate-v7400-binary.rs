@@ -0,0 +1,413 @@
+//! Tests for FBX binary validator.
+#![cfg(feature = "writer")]
+
+use std::{io::Cursor, iter};
+
+use fbxcel::{
+    low::{v7400::ArrayAttributeEncoding, FbxVersion},
+    pull_parser::v7400::validate::{validate, IssueKind, Report, Severity},
+    writer::v7400::binary::{FbxFooter, FbxFooterPaddingLength, Writer},
+};
+
+use self::{
+    common::{offsets, sample, sample_with_footer},
+    v7400::writer::{CUSTOM_UNKNOWN1, MAGIC, UNKNOWN3},
+};
+
+mod common;
+mod v7400;
+
+/// Overwrites the `u32` value at the given offset.
+fn patch_u32(bin: &mut [u8], offset: usize, value: u32) {
+    bin[offset..(offset + 4)].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Reads the `u32` value at the given offset.
+fn read_u32(bin: &[u8], offset: usize) -> u32 {
+    let mut buf = [0; 4];
+    buf.copy_from_slice(&bin[offset..(offset + 4)]);
+    u32::from_le_bytes(buf)
+}
+
+/// Returns the kinds, node names, and attribute indices of the issues.
+fn summarize(report: &Report) -> Vec<(IssueKind, Vec<&str>, Option<usize>)> {
+    report
+        .issues()
+        .iter()
+        .map(|issue| {
+            let names = issue
+                .position()
+                .node_path()
+                .iter()
+                .map(|(_, name)| name.as_str())
+                .collect();
+            (
+                issue.kind().clone(),
+                names,
+                issue.position().attribute_index(),
+            )
+        })
+        .collect()
+}
+
+/// Builds an FBX 7.4 binary with the nodes written by the given function.
+fn raw_fbx(write_nodes: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    let raw_ver = 7400_u32;
+    let mut vec = Vec::new();
+    vec.extend(MAGIC);
+    vec.extend(&raw_ver.to_le_bytes());
+    write_nodes(&mut vec);
+    // End of implicit root.
+    vec.extend(iter::repeat(0).take(4 * 3 + 1));
+    // Footer.
+    vec.extend(&CUSTOM_UNKNOWN1);
+    let padding_len = vec.len().wrapping_neg() % 16;
+    vec.extend(iter::repeat(0).take(padding_len));
+    vec.extend(&[0; 4]);
+    vec.extend(&raw_ver.to_le_bytes());
+    vec.extend(iter::repeat(0).take(120));
+    vec.extend(&UNKNOWN3);
+    vec
+}
+
+/// Writes the raw node record, and its children (and node end marker if
+/// necessary) by the given function.
+fn raw_node(
+    vec: &mut Vec<u8>,
+    name: &[u8],
+    num_attrs: u32,
+    attrs: &[u8],
+    write_children: impl FnOnce(&mut Vec<u8>),
+) {
+    let start = vec.len();
+    // End offset, number of attributes, and attributes length.
+    vec.extend(&[0; 4]);
+    vec.extend(&num_attrs.to_le_bytes());
+    vec.extend(&(attrs.len() as u32).to_le_bytes());
+    vec.push(name.len() as u8);
+    vec.extend(name);
+    vec.extend(attrs);
+    write_children(vec);
+    let end = vec.len() as u32;
+    patch_u32(vec, start, end);
+}
+
+#[test]
+fn valid_file() -> Result<(), Box<dyn std::error::Error>> {
+    for &version in &[FbxVersion::V7_4, FbxVersion::V7_5] {
+        let bin = sample_with_footer(version, &Default::default());
+        let report = validate(Cursor::new(&bin))?;
+        assert_eq!(report.fbx_version(), version);
+        assert!(report.is_clean(), "{:?}", report);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn all_problems_are_reported() -> Result<(), Box<dyn std::error::Error>> {
+    let mut bin = sample();
+    let (_, flag) = offsets(&bin, "Flag");
+    let (_, values) = offsets(&bin, "Values");
+    let (_, compressed) = offsets(&bin, "Compressed");
+    let (second, _) = offsets(&bin, "Second");
+    // Incorrect boolean representation.
+    bin[flag + 1] = 1;
+    // Invalid array encoding of the second attribute.
+    patch_u32(&mut bin, values + 5 + 5, 7);
+    // Elements count inconsistent with the decompressed length.
+    patch_u32(&mut bin, compressed + 1, 15);
+    // Wrong attributes length.
+    patch_u32(&mut bin, second + 8, 10);
+
+    let report = validate(Cursor::new(&bin))?;
+    assert_eq!(
+        summarize(&report),
+        vec![
+            (
+                IssueKind::IncorrectBooleanRepresentation,
+                vec!["Container", "Flag"],
+                Some(0)
+            ),
+            (
+                IssueKind::InvalidArrayAttributeEncoding(7),
+                vec!["Container", "Values"],
+                Some(1)
+            ),
+            (
+                IssueKind::ArrayLengthMismatch {
+                    expected: 15 * 8,
+                    actual: 15 * 8 + 1
+                },
+                vec!["Container", "Compressed"],
+                Some(0)
+            ),
+            (
+                IssueKind::AttributesLengthMismatch {
+                    expected: 10,
+                    actual: 9
+                },
+                vec!["Second"],
+                None
+            ),
+        ]
+    );
+    assert_eq!(report.errors().count(), 3);
+    assert_eq!(report.warnings().count(), 1);
+    assert!(report.has_errors());
+
+    Ok(())
+}
+
+#[test]
+fn invalid_end_offsets() -> Result<(), Box<dyn std::error::Error>> {
+    let mut bin = sample();
+    let (container, _) = offsets(&bin, "Container");
+    let container_end = read_u32(&bin, container);
+    let (flag, _) = offsets(&bin, "Flag");
+    let (second, _) = offsets(&bin, "Second");
+    patch_u32(&mut bin, flag, container_end + 1);
+    patch_u32(&mut bin, second, 0xffff);
+
+    let report = validate(Cursor::new(&bin))?;
+    assert_eq!(
+        summarize(&report),
+        vec![
+            (
+                IssueKind::EndOffsetPastParent {
+                    end_offset: u64::from(container_end) + 1,
+                    parent_end_offset: u64::from(container_end),
+                },
+                vec!["Container", "Flag"],
+                None
+            ),
+            (
+                IssueKind::EndOffsetPastEof {
+                    end_offset: 0xffff,
+                    file_len: bin.len() as u64,
+                },
+                vec!["Second"],
+                None
+            ),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn node_length_mismatch() -> Result<(), Box<dyn std::error::Error>> {
+    let mut bin = sample();
+    let (container, _) = offsets(&bin, "Container");
+    let container_end = read_u32(&bin, container);
+    patch_u32(&mut bin, container, container_end - 1);
+
+    let report = validate(Cursor::new(&bin))?;
+    assert_eq!(
+        summarize(&report),
+        vec![(
+            IssueKind::NodeLengthMismatch {
+                expected: u64::from(container_end) - 1,
+                actual: Some(u64::from(container_end)),
+            },
+            vec!["Container"],
+            None
+        )]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn broken_compression() -> Result<(), Box<dyn std::error::Error>> {
+    let mut bin = sample();
+    let (_, compressed) = offsets(&bin, "Compressed");
+    let (_, flag) = offsets(&bin, "Flag");
+    // Break the zlib header.
+    bin[compressed + 13] = 0;
+    bin[flag + 1] = 1;
+
+    let report = validate(Cursor::new(&bin))?;
+    let issues = report.issues();
+    assert_eq!(issues.len(), 2, "{:?}", issues);
+    assert_eq!(issues[0].kind(), &IssueKind::IncorrectBooleanRepresentation);
+    assert!(matches!(issues[1].kind(), IssueKind::BrokenCompression(_)));
+    assert_eq!(issues[1].severity(), Severity::Error);
+    assert_eq!(
+        issues[1].position().byte_pos(),
+        compressed as u64 + 13,
+        "The position should point the compressed data"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn truncated_file() -> Result<(), Box<dyn std::error::Error>> {
+    let bin = sample();
+    let (_, compressed) = offsets(&bin, "Compressed");
+    let bin = &bin[..(compressed + 20)];
+
+    let report = validate(Cursor::new(bin))?;
+    let kinds = report
+        .issues()
+        .iter()
+        .map(|issue| issue.kind().clone())
+        .collect::<Vec<_>>();
+    assert!(
+        matches!(
+            &kinds[..],
+            [
+                IssueKind::EndOffsetPastEof { .. },
+                IssueKind::EndOffsetPastEof { .. },
+                IssueKind::AttributePastNodeEnd,
+                IssueKind::UnexpectedEof,
+            ]
+        ),
+        "{:?}",
+        kinds
+    );
+
+    Ok(())
+}
+
+#[test]
+fn footer_warnings() -> Result<(), Box<dyn std::error::Error>> {
+    let footer = FbxFooter {
+        unknown1: Some(&[0x42; 16]),
+        padding_len: FbxFooterPaddingLength::Forced(3),
+        ..Default::default()
+    };
+    let mut bin = sample_with_footer(FbxVersion::V7_4, &footer);
+    // Padding (3) + unknown2 (4) + version (4) + zeroes (120) + unknown3 (16)
+    // follows the unknown field 1.
+    let expected_padding_len = (bin.len() - 147).wrapping_neg() & 0x0f;
+    bin.extend(&[0xff; 5]);
+
+    let report = validate(Cursor::new(&bin))?;
+    assert_eq!(
+        report
+            .issues()
+            .iter()
+            .map(|issue| issue.kind().clone())
+            .collect::<Vec<_>>(),
+        vec![
+            IssueKind::UnexpectedFooterFieldValue,
+            IssueKind::InvalidFooterPaddingLength {
+                expected: expected_padding_len,
+                actual: 3
+            },
+            IssueKind::TrailingData { len: 5 },
+        ]
+    );
+    assert!(!report.has_errors());
+
+    Ok(())
+}
+
+#[test]
+fn broken_footer() -> Result<(), Box<dyn std::error::Error>> {
+    let mut bin = sample();
+    // Change FBX version in the footer.
+    let version_pos = bin.len() - 16 - 120 - 4;
+    let (_, flag) = offsets(&bin, "Flag");
+    patch_u32(&mut bin, version_pos, 7500);
+    bin[flag + 1] = 1;
+
+    let report = validate(Cursor::new(&bin))?;
+    let kinds = report
+        .issues()
+        .iter()
+        .map(|issue| issue.kind().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            IssueKind::IncorrectBooleanRepresentation,
+            IssueKind::BrokenFbxFooter
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn compressed_array_is_inflated_up_to_expected_length() -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer =
+        Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4).expect("Failed to create writer");
+    {
+        let mut attrs = writer.new_node("Zeros")?;
+        attrs.append_arr_f64_from_iter(
+            Some(ArrayAttributeEncoding::Zlib),
+            (0..(1 << 20)).map(|_| 0.0),
+        )?;
+    }
+    writer.close_node()?;
+    let mut bin = writer.finalize_and_flush(&Default::default())?.into_inner();
+    let (_, zeros) = offsets(&bin, "Zeros");
+    // 8 MiB of zeros are compressed into a few KiB.
+    assert!(read_u32(&bin, zeros + 9) < 64 * 1024);
+    patch_u32(&mut bin, zeros + 1, 1);
+
+    let report = validate(Cursor::new(&bin))?;
+    assert_eq!(
+        summarize(&report),
+        vec![(
+            IssueKind::ArrayLengthMismatch {
+                expected: 8,
+                actual: 9
+            },
+            vec!["Zeros"],
+            Some(0)
+        )]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn missing_node_end_marker() -> Result<(), Box<dyn std::error::Error>> {
+    let bin = raw_fbx(|vec| {
+        raw_node(vec, b"Container", 0, &[], |vec| {
+            // A node without attributes should have a node end marker.
+            raw_node(vec, b"InvalidNode", 0, &[], |_| {});
+            vec.extend(&[0; 13]);
+        });
+    });
+
+    let report = validate(Cursor::new(&bin))?;
+    assert_eq!(
+        summarize(&report),
+        vec![(
+            IssueKind::MissingNodeEndMarker,
+            vec!["Container", "InvalidNode"],
+            None
+        )]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn extra_node_end_marker() -> Result<(), Box<dyn std::error::Error>> {
+    let bin = raw_fbx(|vec| {
+        raw_node(vec, b"Container", 0, &[], |vec| {
+            // A node with attributes and without children should not have a
+            // node end marker.
+            raw_node(vec, b"InvalidNode", 1, b"CT", |vec| vec.extend(&[0; 13]));
+            vec.extend(&[0; 13]);
+        });
+    });
+
+    let report = validate(Cursor::new(&bin))?;
+    assert_eq!(
+        summarize(&report),
+        vec![(
+            IssueKind::ExtraNodeEndMarker,
+            vec!["Container", "InvalidNode"],
+            None
+        )]
+    );
+
+    Ok(())
+}
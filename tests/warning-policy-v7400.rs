@@ -0,0 +1,219 @@
+//! Tests for warning policies.
+#![cfg(feature = "writer")]
+
+use fbxcel::{
+    low::FbxVersion,
+    pull_parser::{
+        error::{ErrorKind, WarningKind},
+        v7400::{attribute::loaders::DirectLoader, Event, Parser},
+        Error, ParserSource, Warning, WarningAction, WarningCollector, WarningPolicy,
+    },
+    writer::v7400::binary::{FbxFooter, FbxFooterPaddingLength},
+};
+
+use self::common::{offsets, parser, sample_with_footer};
+
+mod common;
+
+/// Writes the sample FBX 7.4 binary which causes warnings.
+///
+/// The warnings are `IncorrectBooleanRepresentation` for the `Flag` node,
+/// `UnexpectedFooterFieldValue`, and `InvalidFooterPaddingLength`.
+fn sample() -> Vec<u8> {
+    let footer = FbxFooter {
+        unknown1: Some(&[0x42; 16]),
+        padding_len: FbxFooterPaddingLength::Forced(3),
+        ..Default::default()
+    };
+    let mut bin = sample_with_footer(FbxVersion::V7_4, &footer);
+    let (_, flag) = offsets(&bin, "Flag");
+    // Incorrect boolean representation.
+    bin[flag + 1] = 1;
+
+    bin
+}
+
+/// Reads all events and attributes from the parser.
+fn parse<R: ParserSource>(parser: &mut Parser<R>) -> Result<(), Error> {
+    loop {
+        match parser.next_event()? {
+            Event::StartNode(start) => {
+                let mut attrs = start.attributes();
+                while attrs.load_next(DirectLoader)?.is_some() {}
+            }
+            Event::EndNode => {}
+            Event::EndFbx(footer) => return footer.map(drop),
+        }
+    }
+}
+
+/// Parses the sample with the given policy.
+fn parse_with_policy(policy: &WarningPolicy) -> Result<(), Error> {
+    let bin = sample();
+    let mut parser = parser(&bin);
+    parser.set_warning_policy(policy.clone());
+    parse(&mut parser)
+}
+
+/// Returns the kinds of the collected warnings.
+fn collected_kinds(collector: &WarningCollector) -> Vec<WarningKind> {
+    collector
+        .take()
+        .iter()
+        .map(|(warning, _)| warning.kind())
+        .collect()
+}
+
+#[test]
+fn lenient() {
+    let policy = WarningPolicy::lenient();
+    parse_with_policy(&policy).expect("Warnings should be ignored");
+    assert!(policy.collector().is_empty());
+    assert_eq!(
+        policy.action(WarningKind::EmptyNodeName),
+        WarningAction::Ignore
+    );
+}
+
+#[test]
+fn strict() {
+    let policy = WarningPolicy::strict();
+    let err = parse_with_policy(&policy).expect_err("Warnings should be fatal");
+    assert_eq!(err.kind(), ErrorKind::Warning);
+    assert!(matches!(
+        err.downcast_ref::<Warning>(),
+        Some(Warning::IncorrectBooleanRepresentation)
+    ));
+    assert_eq!(
+        err.position()
+            .expect("Error should have a position")
+            .node_path()
+            .iter()
+            .map(|(_, name)| name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Container", "Flag"]
+    );
+}
+
+#[test]
+fn collecting() {
+    let policy = WarningPolicy::collecting();
+    parse_with_policy(&policy).expect("Warnings should be collected");
+
+    let collector = policy.collector();
+    assert_eq!(collector.len(), 3);
+    let warnings = collector.take();
+    assert!(collector.is_empty());
+    assert!(matches!(
+        warnings[0].0,
+        Warning::IncorrectBooleanRepresentation
+    ));
+    assert_eq!(warnings[0].1.attribute_index(), Some(0));
+    assert!(matches!(warnings[1].0, Warning::UnexpectedFooterFieldValue));
+    assert!(matches!(
+        warnings[2].0,
+        Warning::InvalidFooterPaddingLength(_, 3)
+    ));
+}
+
+#[test]
+fn per_kind_actions() {
+    // Collect the footer warnings only.
+    let policy = WarningPolicy::lenient()
+        .with_action(
+            WarningKind::UnexpectedFooterFieldValue,
+            WarningAction::Collect,
+        )
+        .with_action(
+            WarningKind::InvalidFooterPaddingLength,
+            WarningAction::Collect,
+        );
+    parse_with_policy(&policy).expect("Warnings should not be fatal");
+    assert_eq!(
+        collected_kinds(policy.collector()),
+        vec![
+            WarningKind::UnexpectedFooterFieldValue,
+            WarningKind::InvalidFooterPaddingLength
+        ]
+    );
+
+    // Reject the footer padding length only.
+    let policy = WarningPolicy::collecting().with_action(
+        WarningKind::InvalidFooterPaddingLength,
+        WarningAction::Fatal,
+    );
+    let err = parse_with_policy(&policy).expect_err("The warning should be fatal");
+    assert!(matches!(
+        err.downcast_ref::<Warning>(),
+        Some(Warning::InvalidFooterPaddingLength(..))
+    ));
+    assert_eq!(
+        collected_kinds(policy.collector()),
+        vec![
+            WarningKind::IncorrectBooleanRepresentation,
+            WarningKind::UnexpectedFooterFieldValue
+        ]
+    );
+}
+
+#[cfg(feature = "tree")]
+mod tree {
+    use std::io::Cursor;
+
+    use fbxcel::tree::{
+        any::{AnyTree, Error as AnyTreeError},
+        v7400::{LoadError, Loader},
+    };
+
+    use super::*;
+
+    #[test]
+    fn loader() {
+        let bin = sample();
+        let policy = WarningPolicy::collecting();
+        let (_, footer) = Loader::new()
+            .with_warning_policy(policy.clone())
+            .load(&mut parser(&bin))
+            .expect("Failed to load tree");
+        footer.expect("Failed to read footer");
+        assert_eq!(policy.collector().len(), 3);
+
+        let res = Loader::new()
+            .with_warning_policy(WarningPolicy::strict())
+            .load(&mut parser(&bin));
+        match res {
+            Err(LoadError::Parser(e)) => assert_eq!(e.kind(), ErrorKind::Warning),
+            res => panic!("Unexpected result: {:?}", res.map(drop)),
+        }
+    }
+
+    #[test]
+    fn any_tree() {
+        let bin = sample();
+        let policy = WarningPolicy::collecting();
+        let tree = AnyTree::from_seekable_reader_with_warning_policy(
+            Cursor::new(&bin[..]),
+            policy.clone(),
+        )
+        .expect("Failed to load tree");
+        assert_eq!(tree.fbx_version(), FbxVersion::V7_4);
+        assert_eq!(
+            collected_kinds(policy.collector()),
+            vec![
+                WarningKind::IncorrectBooleanRepresentation,
+                WarningKind::UnexpectedFooterFieldValue,
+                WarningKind::InvalidFooterPaddingLength
+            ]
+        );
+
+        let res = AnyTree::from_reader_with_warning_policy(&bin[..], WarningPolicy::strict());
+        match res {
+            Err(AnyTreeError::Tree(e)) => match e.downcast_ref::<LoadError>() {
+                Some(LoadError::Parser(e)) => assert_eq!(e.kind(), ErrorKind::Warning),
+                _ => panic!("Unexpected error: {:?}", e),
+            },
+            Err(e) => panic!("Unexpected error: {:?}", e),
+            Ok(_) => panic!("Warnings should be fatal"),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Tests for ASCII writer and parser.
+#![cfg(all(feature = "tree", feature = "writer"))]
+
+use fbxcel::{
+    low::{v7400::AttributeValue, FbxVersion},
+    pull_parser::v7400::{
+        ascii,
+        attribute::loaders::{BinaryLoader, DirectLoader},
+        Event,
+    },
+    tree::v7400::Loader as TreeLoader,
+    tree_v7400,
+    writer::v7400::ascii::{Error, Writer},
+};
+
+/// Writes the tree as FBX ASCII, and returns the ASCII document.
+fn write_tree(tree: &fbxcel::tree::v7400::Tree) -> Result<String, Box<dyn std::error::Error>> {
+    let mut writer = Writer::new(Vec::new(), FbxVersion::V7_5)?;
+    writer.write_tree(tree)?;
+    let bytes = writer.finalize_and_flush()?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// Construct tree, export it to ASCII, parse it and construct tree, and
+/// compare them.
+#[test]
+fn tree_write_parse_idempotence_ascii() -> Result<(), Box<dyn std::error::Error>> {
+    let tree1 = tree_v7400! {
+        Node0: {
+            Node0_0: {},
+            Node0_1: {},
+        },
+        Node1: [true, false] {
+            Node1_0: (vec![42i32.into(), 1.234f64.into(), (-0.0f64).into()]) {}
+            Node1_1: ["Hello, \"world\"\r\n", "Cube\u{0}\u{1}Model"] {}
+            Node1_2: [
+                vec![0.1f64, 1e300, -1e-300, f64::INFINITY, f64::MIN_POSITIVE],
+                vec![1i32, -3],
+                vec![1i64 << 40, -1],
+                vec![true, false],
+            ] {}
+        },
+    };
+
+    let text = write_tree(&tree1)?;
+    let mut parser = ascii::from_reader(text.as_bytes())?;
+    assert_eq!(parser.fbx_version(), FbxVersion::V7_5);
+    let (tree2, _) = TreeLoader::new().load(&mut parser)?;
+
+    assert!(tree1.strict_eq(&tree2), "text:\n{}", text);
+
+    Ok(())
+}
+
+/// Checks that values whose types are not representable in FBX ASCII are
+/// read as the exactly same values.
+#[test]
+fn exact_float_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let f32_values = [0.1f32, 16_777_217.0, f32::MAX, f32::MIN_POSITIVE, 1e-45];
+    let tree1 = tree_v7400! {
+        Node: [f32_values[0], f32_values[1], f32_values[2], f32_values[3], f32_values[4]] {
+            Arr: [f32_values.to_vec(), &b"\x00\xff binary"[..]] {}
+        },
+    };
+
+    let text = write_tree(&tree1)?;
+    let mut parser = ascii::from_reader(text.as_bytes())?;
+    let (tree2, _) = TreeLoader::new().load(&mut parser)?;
+
+    let node = tree2.root().first_child().expect("Should have a child");
+    for (attr, expected) in node.attributes().iter().zip(&f32_values) {
+        let v = attr.get_f64().expect("Should be read as `f64`") as f32;
+        assert_eq!(v.to_bits(), expected.to_bits());
+    }
+    let arr = node.first_child().expect("Should have a child");
+    match arr.attributes() {
+        [AttributeValue::ArrF64(values), AttributeValue::String(binary)] => {
+            let values = values.iter().map(|&v| v as f32).collect::<Vec<_>>();
+            assert_eq!(values, f32_values);
+            // The tree loader does not know the binary is expected.
+            assert_eq!(binary, "AP8gYmluYXJ5");
+        }
+        attrs => panic!("Unexpected attributes: {:?}", attrs),
+    }
+
+    Ok(())
+}
+
+/// Checks that only object names are converted between `Class::Name` and
+/// `Name\u{0}\u{1}Class`.
+#[test]
+fn object_names() -> Result<(), Box<dyn std::error::Error>> {
+    let tree1 = tree_v7400! {
+        Objects: {
+            Model: [2035541511296i64, "Cube\u{0}\u{1}Model", "Mesh\u{0}\u{1}Other"] {
+                Name: ["Video::tex"] {}
+            },
+        },
+        Video: ["Video::tex", "Cube\u{0}\u{1}Model"] {},
+    };
+
+    let text = write_tree(&tree1)?;
+    assert!(
+        text.contains(r#"Model: 2035541511296, "Model::Cube", "Mesh"#),
+        "text:\n{}",
+        text
+    );
+    assert!(
+        text.contains(r#"Video: "Video::tex", "Cube"#),
+        "text:\n{}",
+        text
+    );
+
+    let mut parser = ascii::from_reader(text.as_bytes())?;
+    let (tree2, _) = TreeLoader::new().load(&mut parser)?;
+    assert!(tree1.strict_eq(&tree2), "text:\n{}", text);
+
+    Ok(())
+}
+
+#[test]
+fn base64_binary() -> Result<(), Box<dyn std::error::Error>> {
+    let tree = tree_v7400! {
+        Video: {
+            Content: [&b"\x00\xff binary"[..], "AP8=", "not base64"] {}
+        },
+    };
+    let text = write_tree(&tree)?;
+    let mut parser = ascii::from_reader(text.as_bytes())?;
+
+    match parser.next_event()? {
+        Event::StartNode(start) => assert_eq!(start.name(), "Video"),
+        _ => panic!("Expected node start event"),
+    }
+    match parser.next_event()? {
+        Event::StartNode(start) => {
+            assert_eq!(start.name(), "Content");
+            let mut attrs = start.attributes();
+            assert_eq!(
+                attrs.load_next(BinaryLoader)?,
+                Some(b"\x00\xff binary".to_vec())
+            );
+            // Loaders which do not expect binaries get strings.
+            assert_eq!(attrs.load_next(DirectLoader)?, Some("AP8=".into()));
+            // Strings which are not valid base64 are not decoded.
+            assert!(attrs.load_next(BinaryLoader).is_err());
+        }
+        _ => panic!("Expected node start event"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn invalid_node_name() -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = Writer::new(Vec::new(), FbxVersion::V7_4)?;
+    assert!(matches!(
+        writer.new_node("0Node"),
+        Err(Error::InvalidNodeName(_))
+    ));
+    assert!(matches!(
+        writer.new_node("Node name"),
+        Err(Error::InvalidNodeName(_))
+    ));
+    writer.new_node("Node")?;
+    assert!(matches!(writer.finalize(), Err(Error::UnclosedNode(1))));
+
+    Ok(())
+}
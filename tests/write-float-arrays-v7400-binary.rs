@@ -0,0 +1,41 @@
+//! Tests for type codes of floating point number arrays written by the binary
+//! writer.
+#![cfg(feature = "writer")]
+
+use std::io::Cursor;
+
+use fbxcel::{low::FbxVersion, writer::v7400::binary::Writer};
+
+#[test]
+fn float_array_type_codes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_4)?;
+    {
+        let mut attrs = writer.new_node("Node")?;
+        attrs.append_arr_f32_from_iter(None, vec![1.0f32, 2.0])?;
+        attrs.append_arr_f64_from_iter(None, vec![1.0f64, 2.0])?;
+        attrs.append_arr_f32_from_result_iter(
+            None,
+            vec![Ok::<_, std::convert::Infallible>(1.0f32)],
+        )?;
+        attrs.append_arr_f64_from_result_iter(
+            None,
+            vec![Ok::<_, std::convert::Infallible>(1.0f64)],
+        )?;
+    }
+    writer.close_node()?;
+    let bin = writer.finalize_and_flush(&Default::default())?.into_inner();
+
+    // FBX header (27 bytes), node header (13 bytes for FBX 7.4), and node name.
+    let mut offset = 27 + 13 + "Node".len();
+    // Type code (1 byte), array header (12 bytes), and elements.
+    for &(type_code, bytelen) in &[(b'f', 4 * 2), (b'd', 8 * 2), (b'f', 4), (b'd', 8)] {
+        assert_eq!(
+            bin[offset], type_code,
+            "Unexpected type code at offset {}",
+            offset
+        );
+        offset += 1 + 12 + bytelen;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! Tests for the binary writer for non-seekable sinks.
+#![cfg(feature = "writer")]
+
+use std::io::{self, Cursor, Seek, Write};
+
+use fbxcel::{
+    low::{v7400::ArrayAttributeEncoding, FbxVersion},
+    writer::v7400::binary::{
+        AttributesWriter, CompressionLevel, CompressionPolicy, CompressionThreshold, Error,
+        FbxFooter, FbxFooterPaddingLength, StreamWriter, Writer,
+    },
+};
+
+/// Sink which records sizes of the chunks written to it.
+#[derive(Default)]
+struct RecordingSink {
+    /// Written data.
+    data: Vec<u8>,
+    /// Total length of the data on each `write()` call.
+    written_lens: Vec<usize>,
+}
+
+impl Write for RecordingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.extend_from_slice(buf);
+        self.written_lens.push(self.data.len());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes attributes of various types.
+fn append_attributes<W: Write + Seek>(attrs: &mut AttributesWriter<'_, W>) -> Result<(), Error> {
+    attrs.append_bool(true)?;
+    attrs.append_i16(-16)?;
+    attrs.append_i64(64)?;
+    attrs.append_f64(1.5)?;
+    attrs.append_arr_i32_from_iter(None, 0..100)?;
+    attrs.append_arr_f32_from_iter(
+        Some(ArrayAttributeEncoding::Zlib),
+        (0..10).map(|i| i as f32),
+    )?;
+    attrs.append_arr_bool_from_iter(Some(ArrayAttributeEncoding::Direct), vec![true, false])?;
+    attrs.append_binary_direct(&[0, 1, 2, 3])?;
+    attrs.append_string_direct("Hello, world")?;
+    Ok(())
+}
+
+/// Generates the same FBX data using the given functions.
+macro_rules! write_nodes {
+    ($writer:expr) => {{
+        let writer = &mut $writer;
+        writer.set_compression_policy(CompressionPolicy::new(
+            CompressionThreshold::ElementsCount(50),
+            CompressionLevel::Fast,
+        ));
+        append_attributes(&mut writer.new_node("Node0")?)?;
+        writer.new_node("Node0_0")?;
+        writer.close_node()?;
+        append_attributes(&mut writer.new_node("Node0_1")?)?;
+        writer.new_node("Node0_1_0")?;
+        writer.close_node()?;
+        writer.close_node()?;
+        writer.close_node()?;
+        writer.new_node("Node1")?;
+        writer.close_node()?;
+        append_attributes(&mut writer.new_node("Node2")?)?;
+        writer.close_node()?;
+    }};
+}
+
+#[test]
+fn identical_to_seekable_writer() -> Result<(), Error> {
+    let unknown1 = [0x42; 16];
+    for &(version, padding_len) in &[
+        (FbxVersion::V7_4, FbxFooterPaddingLength::Default),
+        (FbxVersion::V7_5, FbxFooterPaddingLength::Default),
+        (FbxVersion::V7_4, FbxFooterPaddingLength::Forced(3)),
+    ] {
+        let footer = FbxFooter {
+            unknown1: Some(&unknown1),
+            padding_len,
+            ..Default::default()
+        };
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()), version)?;
+        write_nodes!(writer);
+        let expected = writer.finalize_and_flush(&footer)?.into_inner();
+
+        let mut writer = StreamWriter::new(RecordingSink::default(), version)?;
+        write_nodes!(writer);
+        let sink = writer.finalize_and_flush(&footer)?;
+
+        assert_eq!(sink.data, expected, "version={:?}", version);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn top_level_nodes_are_written_on_close() -> Result<(), Error> {
+    let mut writer = StreamWriter::new(RecordingSink::default(), FbxVersion::V7_4)?;
+    writer.new_node("Node0")?;
+    writer.new_node("Node0_0")?;
+    writer.close_node()?;
+    writer.close_node()?;
+    writer.new_node("Node1")?.append_i32(42)?;
+    writer.close_node()?;
+    let sink = writer.finalize(&Default::default())?;
+
+    // Header with `Node0`, `Node1`, and footer.
+    let node0_end = 27 + (13 + 5) + (13 + 7) + 13 + 13;
+    let node1_end = node0_end + (13 + 5) + 5;
+    assert_eq!(sink.written_lens, [node0_end, node1_end, sink.data.len()]);
+
+    Ok(())
+}
+
+#[test]
+fn unclosed_node() -> Result<(), Error> {
+    let mut writer = StreamWriter::new(Vec::new(), FbxVersion::V7_4)?;
+    writer.new_node("Node0")?;
+    assert!(matches!(
+        writer.finalize(&Default::default()),
+        Err(Error::UnclosedNode(1))
+    ));
+
+    Ok(())
+}
+
+#[cfg(feature = "tree")]
+#[test]
+fn write_tree() -> Result<(), Error> {
+    use fbxcel::tree_v7400;
+
+    let tree = tree_v7400! {
+        Node0: {
+            Node0_0: {},
+            Node0_1: [vec![1.0f64, 2.0, 3.0]] {},
+        },
+        Node1: [true, "Hello"] {
+            Node1_0: [&[1u8, 2, 3][..]] {}
+        },
+    };
+
+    let mut writer = Writer::new(Cursor::new(Vec::new()), FbxVersion::V7_5)?;
+    writer.write_tree(&tree)?;
+    let expected = writer.finalize_and_flush(&Default::default())?.into_inner();
+
+    let mut writer = StreamWriter::new(Vec::new(), FbxVersion::V7_5)?;
+    writer.write_tree(&tree)?;
+    let bin = writer.finalize_and_flush(&Default::default())?;
+
+    assert_eq!(bin, expected);
+
+    Ok(())
+}